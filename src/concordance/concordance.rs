@@ -1,5 +1,11 @@
 #![allow(clippy::explicit_counter_loop)]
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 #[allow(dead_code)]
@@ -7,85 +13,96 @@ struct ConcordanceResult {
     count: Vec<f64>,
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn concordance(
-    y: Vec<f64>,
-    x: Vec<i32>,
-    wt: Vec<f64>,
-    timewt: Vec<f64>,
-    sortstart: Option<Vec<usize>>,
-    sortstop: Vec<usize>,
+    py: Python<'_>,
+    y: VectorArg<'_, f64>,
+    x: VectorArg<'_, i32>,
+    wt: VectorArg<'_, f64>,
+    timewt: VectorArg<'_, f64>,
+    sortstart: Option<IndexArg>,
+    sortstop: IndexArg,
 ) -> PyResult<Py<PyDict>> {
-    let n = y.len();
-    let mut ntree = 0;
-    let mut nwt = vec![0.0; n];
-    let mut twt = vec![0.0; n];
-    let mut count = vec![0.0; 5];
+    let y = y.as_slice();
+    let x = x.as_slice();
+    let wt = wt.as_slice();
+    let timewt = timewt.as_slice();
+    let sortstart = sortstart.map(IndexArg::into_vec);
+    let sortstop = sortstop.into_vec();
+    let count = parallel::detach(py, || {
+        let n = y.len();
+        let mut ntree = 0;
+        let mut nwt = vec![0.0; n];
+        let mut twt = vec![0.0; n];
+        let mut count = vec![0.0; 5];
 
-    for val in &x {
-        ntree = ntree.max(*val as usize + 1);
-    }
+        for val in x.iter() {
+            ntree = ntree.max(*val as usize + 1);
+        }
 
-    let mut utime = 0;
-    let i2 = 0;
-    let mut i = 0;
+        let mut utime = 0;
+        let i2 = 0;
+        let mut i = 0;
 
-    while i < n {
-        let ii = sortstop[i];
-        let current_time = y[ii];
+        while i < n {
+            let ii = sortstop[i];
+            let current_time = y[ii];
 
-        if (sortstart.as_ref().is_some()
-            && i2 < n
-            && y[sortstart.as_ref().unwrap()[i2]] >= current_time)
-            || y[ii] == 0.0
-        {
-            addin(&mut nwt, &mut twt, x[ii] as usize, wt[ii]);
-            i += 1;
-        } else {
-            let mut ndeath = 0;
-            let mut _dwt = 0.0;
-            let mut _dwt2 = 0.0;
-            let xsave = x[ii];
-            let adjtimewt = timewt[utime];
-            utime += 1;
+            if (sortstart.as_ref().is_some()
+                && i2 < n
+                && y[sortstart.as_ref().unwrap()[i2]] >= current_time)
+                || y[ii] == 0.0
+            {
+                addin(&mut nwt, &mut twt, x[ii] as usize, wt[ii]);
+                i += 1;
+            } else {
+                let mut ndeath = 0;
+                let mut _dwt = 0.0;
+                let mut _dwt2 = 0.0;
+                let xsave = x[ii];
+                let adjtimewt = timewt[utime];
+                utime += 1;
 
-            while i + ndeath < n && y[sortstop[i + ndeath]] == current_time {
-                let jj = sortstop[i + ndeath];
-                if x[jj] == xsave {
-                    count[2] += 1.0;
-                } else {
-                    #[allow(clippy::needless_range_loop)]
-                    for k in 0..i {
-                        let kk = sortstop[k];
-                        if x[kk] != x[jj] {
-                            if (x[kk] < x[jj] && y[kk] > current_time)
-                                || (x[kk] > x[jj] && y[kk] < current_time)
-                            {
-                                count[0] += 1.0;
-                            } else {
-                                count[1] += 1.0;
+                while i + ndeath < n && y[sortstop[i + ndeath]] == current_time {
+                    let jj = sortstop[i + ndeath];
+                    if x[jj] == xsave {
+                        count[2] += 1.0;
+                    } else {
+                        #[allow(clippy::needless_range_loop)]
+                        for k in 0..i {
+                            let kk = sortstop[k];
+                            if x[kk] != x[jj] {
+                                if (x[kk] < x[jj] && y[kk] > current_time)
+                                    || (x[kk] > x[jj] && y[kk] < current_time)
+                                {
+                                    count[0] += 1.0;
+                                } else {
+                                    count[1] += 1.0;
+                                }
                             }
                         }
                     }
+                    _dwt += wt[jj];
+                    _dwt2 += wt[jj] * adjtimewt;
+                    ndeath += 1;
                 }
-                _dwt += wt[jj];
-                _dwt2 += wt[jj] * adjtimewt;
-                ndeath += 1;
-            }
 
-            count[4] += (ndeath as f64) * (ndeath as f64 - 1.0) / 2.0;
+                count[4] += (ndeath as f64) * (ndeath as f64 - 1.0) / 2.0;
 
-            #[allow(clippy::needless_range_loop)]
-            for j in i..(i + ndeath) {
-                let jj = sortstop[j];
-                addin(&mut nwt, &mut twt, x[jj] as usize, wt[jj]);
-            }
+                #[allow(clippy::needless_range_loop)]
+                for j in i..(i + ndeath) {
+                    let jj = sortstop[j];
+                    addin(&mut nwt, &mut twt, x[jj] as usize, wt[jj]);
+                }
 
-            i += ndeath;
+                i += ndeath;
+            }
         }
-    }
 
-    count[3] -= count[4];
+        count[3] -= count[4];
+        count
+    });
 
     Python::attach(|py| {
         let dict = PyDict::new(py);
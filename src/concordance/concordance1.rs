@@ -1,5 +1,12 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 #[allow(dead_code)]
@@ -156,13 +163,18 @@ pub fn concordance1(y: &[f64], wt: &[f64], indx: &[i32], ntree: i32) -> Vec<f64>
     count
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn perform_concordance1_calculation(
-    time_data: Vec<f64>,
-    weights: Vec<f64>,
-    indices: Vec<i32>,
+    py: Python<'_>,
+    time_data: VectorArg<'_, f64>,
+    weights: VectorArg<'_, f64>,
+    indices: VectorArg<'_, i32>,
     ntree: i32,
 ) -> PyResult<Py<PyAny>> {
+    let time_data = time_data.as_slice();
+    let weights = weights.as_slice();
+    let indices = indices.as_slice();
     let n = weights.len();
     if n == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
@@ -186,7 +198,7 @@ pub fn perform_concordance1_calculation(
         ));
     }
 
-    let count = concordance1(&time_data, &weights, &indices, ntree);
+    let count = parallel::detach(py, || concordance1(&time_data, &weights, &indices, ntree));
 
     let concordant = count[0];
     let discordant = count[1];
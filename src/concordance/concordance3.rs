@@ -1,6 +1,13 @@
 #![allow(clippy::explicit_counter_loop)]
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 fn walkup(nwt: &[f64], twt: &[f64], index: usize, ntree: usize) -> [f64; 3] {
@@ -183,15 +190,22 @@ pub fn concordance3(
     (count, imat, resid_opt)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn perform_concordance3_calculation(
-    time_data: Vec<f64>,
-    indices: Vec<i32>,
-    weights: Vec<f64>,
-    time_weights: Vec<f64>,
-    sort_stop: Vec<i32>,
+    py: Python<'_>,
+    time_data: VectorArg<'_, f64>,
+    indices: VectorArg<'_, i32>,
+    weights: VectorArg<'_, f64>,
+    time_weights: VectorArg<'_, f64>,
+    sort_stop: VectorArg<'_, i32>,
     do_residuals: bool,
 ) -> PyResult<Py<PyAny>> {
+    let time_data = time_data.as_slice();
+    let indices = indices.as_slice();
+    let weights = weights.as_slice();
+    let time_weights = time_weights.as_slice();
+    let sort_stop = sort_stop.as_slice();
     let n = weights.len();
     if n == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
@@ -227,14 +241,16 @@ pub fn perform_concordance3_calculation(
         ));
     }
 
-    let (count, imat, resid_opt) = concordance3(
-        &time_data,
-        &indices,
-        &weights,
-        &time_weights,
-        &sort_stop,
-        do_residuals,
-    );
+    let (count, imat, resid_opt) = parallel::detach(py, || {
+        concordance3(
+            &time_data,
+            &indices,
+            &weights,
+            &time_weights,
+            &sort_stop,
+            do_residuals,
+        )
+    });
 
     let concordant = count[0];
     let discordant = count[1];
@@ -1,19 +1,26 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
-struct FenwickTree {
+pub(crate) struct FenwickTree {
     tree: Vec<f64>,
 }
 
 impl FenwickTree {
-    fn new(size: usize) -> Self {
+    pub(crate) fn new(size: usize) -> Self {
         FenwickTree {
             tree: vec![0.0; size + 1],
         }
     }
 
-    fn update(&mut self, index: usize, value: f64) {
+    pub(crate) fn update(&mut self, index: usize, value: f64) {
         let mut idx = index + 1;
         while idx < self.tree.len() {
             self.tree[idx] += value;
@@ -21,7 +28,7 @@ impl FenwickTree {
         }
     }
 
-    fn prefix_sum(&self, index: usize) -> f64 {
+    pub(crate) fn prefix_sum(&self, index: usize) -> f64 {
         let mut sum = 0.0;
         let mut idx = index + 1;
         while idx > 0 {
@@ -31,18 +38,22 @@ impl FenwickTree {
         sum
     }
 
-    fn total(&self) -> f64 {
+    pub(crate) fn total(&self) -> f64 {
         self.prefix_sum(self.tree.len() - 2)
     }
 }
 
-fn addin(nwt: &mut [f64], fenwick: &mut FenwickTree, x: usize, weight: f64) {
+pub(crate) fn addin(nwt: &mut [f64], fenwick: &mut FenwickTree, x: usize, weight: f64) {
     nwt[x] += weight;
     fenwick.update(x, weight);
 }
 
-fn walkup(nwt: &[f64], fenwick: &FenwickTree, x: usize) -> [f64; 3] {
-    let sum_less = fenwick.prefix_sum(x.saturating_sub(1));
+pub(crate) fn walkup(nwt: &[f64], fenwick: &FenwickTree, x: usize) -> [f64; 3] {
+    let sum_less = if x == 0 {
+        0.0
+    } else {
+        fenwick.prefix_sum(x - 1)
+    };
     let sum_greater = fenwick.total() - fenwick.prefix_sum(x);
     let sum_equal = nwt[x];
     [sum_greater, sum_less, sum_equal]
@@ -156,17 +167,26 @@ fn compute_z2(wt: f64, wsum: &[f64]) -> f64 {
     wt * (observed - expected).powi(2) / expected
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time_data, predictor_values, weights, time_weights, sort_stop, sort_start=None, do_residuals=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn perform_concordance_calculation(
-    time_data: Vec<f64>,
-    predictor_values: Vec<i32>,
-    weights: Vec<f64>,
-    time_weights: Vec<f64>,
-    sort_stop: Vec<usize>,
-    sort_start: Option<Vec<usize>>,
+    py: Python<'_>,
+    time_data: VectorArg<'_, f64>,
+    predictor_values: VectorArg<'_, i32>,
+    weights: VectorArg<'_, f64>,
+    time_weights: VectorArg<'_, f64>,
+    sort_stop: IndexArg,
+    sort_start: Option<IndexArg>,
     do_residuals: Option<bool>,
 ) -> PyResult<Py<PyAny>> {
+    let time_data = time_data.as_slice();
+    let predictor_values = predictor_values.as_slice();
+    let weights = weights.as_slice();
+    let time_weights = time_weights.as_slice();
+    let sort_stop = sort_stop.into_vec();
+    let sort_start = sort_start.map(IndexArg::into_vec);
     let n = weights.len();
     if n == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
@@ -204,15 +224,17 @@ pub fn perform_concordance_calculation(
 
     let doresid = do_residuals.unwrap_or(false);
 
-    let (count, imat, resid) = concordance5(
-        &time_data,
-        &predictor_values,
-        &weights,
-        &time_weights,
-        sort_start.as_deref(),
-        &sort_stop,
-        doresid,
-    );
+    let (count, imat, resid) = parallel::detach(py, || {
+        concordance5(
+            &time_data,
+            &predictor_values,
+            &weights,
+            &time_weights,
+            sort_start.as_deref(),
+            &sort_stop,
+            doresid,
+        )
+    });
 
     let concordant = count[0];
     let discordant = count[1];
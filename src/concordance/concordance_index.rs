@@ -0,0 +1,262 @@
+use crate::concordance::concordance5::{FenwickTree, addin, walkup};
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::validation::calibration::CensoringDistribution;
+use ndarray::{ArrayView1, ArrayView2};
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use statrs::distribution::{ContinuousCDF, Normal};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConcordanceIndexError {
+    #[error("time, status and every risk score must have the same length")]
+    LengthMismatch,
+    #[error("at least one risk score is required")]
+    NoScores,
+    #[error("tau must be positive, got {0}")]
+    InvalidTau(f64),
+    #[error("no comparable pairs with an event before tau")]
+    NoComparablePairs,
+}
+
+#[cfg(feature = "python")]
+impl From<ConcordanceIndexError> for PyErr {
+    fn from(err: ConcordanceIndexError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct ConcordanceComparison {
+    pub first: usize,
+    pub second: usize,
+    pub difference: f64,
+    pub std_err: f64,
+    pub z_statistic: f64,
+    pub p_value: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct ConcordanceIndexResult {
+    pub concordance: Vec<f64>,
+    pub std_err: Vec<f64>,
+    pub covariance: Vec<Vec<f64>>,
+    pub comparisons: Vec<ConcordanceComparison>,
+    pub tau: Option<f64>,
+}
+
+fn score_ranks(score: ArrayView1<f64>) -> (Vec<usize>, usize) {
+    let mut unique = score.to_vec();
+    unique.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    unique.dedup();
+    let ranks = score
+        .iter()
+        .map(|&s| unique.partition_point(|&u| u < s))
+        .collect();
+    (ranks, unique.len())
+}
+
+/// Groups of `order` positions sharing a time value.
+fn time_groups(time: &[f64], order: &[usize]) -> Vec<(usize, usize)> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && time[order[end]] == time[order[start]] {
+            end += 1;
+        }
+        groups.push((start, end));
+        start = end;
+    }
+    groups
+}
+
+/// Weighted C statistic and the per-observation influence function for one
+/// risk score. `case_weight` is zero for observations that are not usable
+/// events; higher scores are taken to mean earlier failure.
+fn weighted_concordance(
+    time: &[f64],
+    score: ArrayView1<f64>,
+    case_weight: &[f64],
+    ascending: &[usize],
+    groups: &[(usize, usize)],
+) -> Result<(f64, Vec<f64>), ConcordanceIndexError> {
+    let n = time.len();
+    let (ranks, ntree) = score_ranks(score);
+
+    // Cases against everyone with a strictly later time.
+    let mut later = (vec![0.0; n], vec![0.0; n], vec![0.0; n]);
+    let mut nwt = vec![0.0; ntree];
+    let mut fenwick = FenwickTree::new(ntree);
+    for &(start, end) in groups.iter().rev() {
+        for &i in &ascending[start..end] {
+            if case_weight[i] > 0.0 {
+                let [greater, less, equal] = walkup(&nwt, &fenwick, ranks[i]);
+                later.0[i] = less;
+                later.1[i] = equal;
+                later.2[i] = greater + less + equal;
+            }
+        }
+        for &i in &ascending[start..end] {
+            addin(&mut nwt, &mut fenwick, ranks[i], 1.0);
+        }
+    }
+
+    let numerator: f64 = (0..n)
+        .map(|i| case_weight[i] * (later.0[i] + 0.5 * later.1[i]))
+        .sum();
+    let denominator: f64 = (0..n).map(|i| case_weight[i] * later.2[i]).sum();
+    if denominator <= 0.0 {
+        return Err(ConcordanceIndexError::NoComparablePairs);
+    }
+    let c = numerator / denominator;
+
+    let mut influence: Vec<f64> = (0..n)
+        .map(|i| case_weight[i] * (later.0[i] + 0.5 * later.1[i] - c * later.2[i]))
+        .collect();
+
+    // Everyone as the later member of a pair with an earlier case.
+    let mut nwt = vec![0.0; ntree];
+    let mut fenwick = FenwickTree::new(ntree);
+    for &(start, end) in groups {
+        for &k in &ascending[start..end] {
+            let [greater, less, equal] = walkup(&nwt, &fenwick, ranks[k]);
+            influence[k] += greater + 0.5 * equal - c * (greater + less + equal);
+        }
+        for &i in &ascending[start..end] {
+            if case_weight[i] > 0.0 {
+                addin(&mut nwt, &mut fenwick, ranks[i], case_weight[i]);
+            }
+        }
+    }
+
+    for value in &mut influence {
+        *value /= denominator;
+    }
+    Ok((c, influence))
+}
+
+/// Uno's IPCW concordance index truncated at `tau` for one or more risk
+/// scores, with influence-function variances and paired tests between scores.
+/// `risk_scores` has one row per observation and one column per score. The
+/// censoring weights are treated as fixed when computing the variance.
+pub fn concordance_index(
+    time: &[f64],
+    status: &[i32],
+    risk_scores: ArrayView2<f64>,
+    tau: Option<f64>,
+) -> Result<ConcordanceIndexResult, ConcordanceIndexError> {
+    let n = time.len();
+    if risk_scores.ncols() == 0 {
+        return Err(ConcordanceIndexError::NoScores);
+    }
+    if status.len() != n || risk_scores.nrows() != n {
+        return Err(ConcordanceIndexError::LengthMismatch);
+    }
+    if let Some(tau) = tau
+        && (tau.is_nan() || tau <= 0.0)
+    {
+        return Err(ConcordanceIndexError::InvalidTau(tau));
+    }
+
+    let censoring = CensoringDistribution::new(time, status);
+    let horizon = tau.unwrap_or(f64::INFINITY);
+    let case_weight: Vec<f64> = (0..n)
+        .map(|i| {
+            if status[i] == 1 && time[i] < horizon {
+                censoring.at(time[i], false).powi(-2)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let mut ascending: Vec<usize> = (0..n).collect();
+    ascending.sort_by(|&a, &b| {
+        time[a]
+            .partial_cmp(&time[b])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let groups = time_groups(time, &ascending);
+
+    let mut concordance = Vec::with_capacity(risk_scores.ncols());
+    let mut influence = Vec::with_capacity(risk_scores.ncols());
+    for score in risk_scores.columns() {
+        let (c, inf) = weighted_concordance(time, score, &case_weight, &ascending, &groups)?;
+        concordance.push(c);
+        influence.push(inf);
+    }
+
+    let m = risk_scores.ncols();
+    let covariance: Vec<Vec<f64>> = (0..m)
+        .map(|a| {
+            (0..m)
+                .map(|b| {
+                    influence[a]
+                        .iter()
+                        .zip(&influence[b])
+                        .map(|(x, y)| x * y)
+                        .sum()
+                })
+                .collect()
+        })
+        .collect();
+    let std_err = (0..m).map(|a| covariance[a][a].sqrt()).collect();
+
+    let normal = Normal::new(0.0, 1.0).expect("standard normal parameters are valid");
+    let mut comparisons = Vec::new();
+    for first in 0..m {
+        for second in (first + 1)..m {
+            let difference = concordance[first] - concordance[second];
+            let variance = covariance[first][first] + covariance[second][second]
+                - 2.0 * covariance[first][second];
+            let std_err = variance.max(0.0).sqrt();
+            let z_statistic = if std_err > 0.0 {
+                difference / std_err
+            } else {
+                0.0
+            };
+            comparisons.push(ConcordanceComparison {
+                first,
+                second,
+                difference,
+                std_err,
+                z_statistic,
+                p_value: 2.0 * normal.sf(z_statistic.abs()),
+            });
+        }
+    }
+
+    Ok(ConcordanceIndexResult {
+        concordance,
+        std_err,
+        covariance,
+        comparisons,
+        tau,
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "concordance_index", signature = (time, status, risk_scores, tau=None))]
+pub fn concordance_index_py(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    risk_scores: MatrixArg<'_, f64>,
+    tau: Option<f64>,
+) -> PyResult<ConcordanceIndexResult> {
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let risk_scores = risk_scores.view();
+    parallel::detach(py, || {
+        Ok(concordance_index(&time, &status, risk_scores, tau)?)
+    })
+}
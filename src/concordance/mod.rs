@@ -3,5 +3,6 @@ pub mod concordance;
 pub mod concordance1;
 pub mod concordance3;
 pub mod concordance5;
+pub mod concordance_index;
 #[allow(non_snake_case)]
 pub mod survConcordance;
@@ -1,23 +1,23 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-pub(crate) struct CoxSchoInput<'a> {
+pub struct CoxSchoInput<'a> {
     pub y: &'a [f64],
     pub score: &'a [f64],
     pub strata: &'a [i32],
 }
 
-pub(crate) struct CoxSchoParams {
+pub struct CoxSchoParams {
     pub nused: usize,
     pub nvar: usize,
     pub method: i32,
 }
 
-pub(crate) fn coxscho(
-    params: CoxSchoParams,
-    input: CoxSchoInput,
-    covar: &mut [f64],
-    work: &mut [f64],
-) {
+pub fn coxscho(params: CoxSchoParams, input: CoxSchoInput, covar: &mut [f64], work: &mut [f64]) {
     assert!(input.y.len() >= 3 * params.nused, "y array too short");
     assert!(
         covar.len() >= params.nvar * params.nused,
@@ -127,50 +127,58 @@ pub(crate) fn coxscho(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (y, score, strata, covar, nvar, method=0))]
 pub fn schoenfeld_residuals(
-    y: Vec<f64>,
-    score: Vec<f64>,
-    strata: Vec<i32>,
-    covar: Vec<f64>,
+    py: Python<'_>,
+    y: VectorArg<'_, f64>,
+    score: VectorArg<'_, f64>,
+    strata: VectorArg<'_, i32>,
+    covar: VectorArg<'_, f64>,
     nvar: usize,
     method: i32,
 ) -> PyResult<Vec<f64>> {
-    let nused = score.len();
-
-    if y.len() < 3 * nused {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "y array must have length >= 3 * n (start, stop, event)",
-        ));
-    }
-    if strata.len() < nused {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "strata array length must match score length",
-        ));
-    }
-    if covar.len() < nvar * nused {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "covar array must have length >= nvar * n",
-        ));
-    }
+    let y = y.as_slice();
+    let score = score.as_slice();
+    let strata = strata.as_slice();
+    let covar = covar.as_slice();
+    parallel::detach(py, || {
+        let nused = score.len();
+
+        if y.len() < 3 * nused {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "y array must have length >= 3 * n (start, stop, event)",
+            ));
+        }
+        if strata.len() < nused {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "strata array length must match score length",
+            ));
+        }
+        if covar.len() < nvar * nused {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "covar array must have length >= nvar * n",
+            ));
+        }
 
-    let mut covar_copy = covar.clone();
-    let mut work = vec![0.0; 3 * nvar];
+        let mut covar_copy = covar.into_owned();
+        let mut work = vec![0.0; 3 * nvar];
 
-    let params = CoxSchoParams {
-        nused,
-        nvar,
-        method,
-    };
+        let params = CoxSchoParams {
+            nused,
+            nvar,
+            method,
+        };
 
-    let input = CoxSchoInput {
-        y: &y,
-        score: &score,
-        strata: &strata,
-    };
+        let input = CoxSchoInput {
+            y: &y,
+            score: &score,
+            strata: &strata,
+        };
 
-    coxscho(params, input, &mut covar_copy, &mut work);
+        coxscho(params, input, &mut covar_copy, &mut work);
 
-    Ok(covar_copy)
+        Ok(covar_copy)
+    })
 }
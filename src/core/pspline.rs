@@ -1,6 +1,12 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use ndarray::{Array1, Array2};
 use ndarray_linalg::Solve;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use thiserror::Error;
 
@@ -16,15 +22,18 @@ pub enum PSplineError {
     },
     #[error("Failed to solve linear system: matrix may be singular or ill-conditioned")]
     LinearSolveError,
+    #[error("Model not fitted. Call fit() first.")]
+    NotFitted,
 }
 
+#[cfg(feature = "python")]
 impl From<PSplineError> for PyErr {
     fn from(err: PSplineError) -> PyErr {
         PyValueError::new_err(err.to_string())
     }
 }
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass)]
 pub struct PSpline {
     x: Vec<f64>,
     df: u32,
@@ -36,17 +45,13 @@ pub struct PSpline {
     boundary_knots: (f64, f64),
     intercept: bool,
     penalty: bool,
-    #[pyo3(get)]
     coefficients: Option<Vec<f64>>,
-    #[pyo3(get)]
     fitted: bool,
 }
 
-#[pymethods]
 impl PSpline {
-    #[new]
     #[allow(clippy::too_many_arguments)]
-    fn new(
+    pub fn new(
         x: Vec<f64>,
         df: u32,
         theta: f64,
@@ -74,27 +79,20 @@ impl PSpline {
         }
     }
 
-    pub fn fit(&mut self) -> PyResult<Vec<f64>> {
+    pub fn fit(&mut self) -> Result<Vec<f64>, PSplineError> {
         let basis = self.create_basis();
-        let penalized_basis = self
-            .apply_penalty(basis)
-            .map_err(|e| PyValueError::new_err(e.to_string()))?;
-        let coefficients = self
-            .optimize_fit(penalized_basis)
-            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let penalized_basis = self.apply_penalty(basis)?;
+        let coefficients = self.optimize_fit(penalized_basis)?;
         self.coefficients = Some(coefficients.clone());
         self.fitted = true;
         Ok(coefficients)
     }
 
-    pub fn predict(&self, new_x: Vec<f64>) -> PyResult<Vec<f64>> {
-        let coefficients = self
-            .coefficients
-            .as_ref()
-            .ok_or_else(|| PyValueError::new_err("Model not fitted. Call fit() first."))?;
+    pub fn predict(&self, new_x: &[f64]) -> Result<Vec<f64>, PSplineError> {
+        let coefficients = self.coefficients.as_ref().ok_or(PSplineError::NotFitted)?;
 
         let mut predictions = Vec::with_capacity(new_x.len());
-        for x_val in &new_x {
+        for x_val in new_x {
             let mut pred = 0.0;
             for (j, coef) in coefficients.iter().enumerate() {
                 pred += coef * self.basis_function(*x_val, j as u32);
@@ -104,13 +102,19 @@ impl PSpline {
         Ok(predictions)
     }
 
-    #[getter]
-    pub fn get_df(&self) -> u32 {
+    pub fn coefficients(&self) -> Option<&[f64]> {
+        self.coefficients.as_deref()
+    }
+
+    pub fn is_fitted(&self) -> bool {
+        self.fitted
+    }
+
+    pub fn df(&self) -> u32 {
         self.df
     }
 
-    #[getter]
-    pub fn get_eps(&self) -> f64 {
+    pub fn eps(&self) -> f64 {
         self.eps
     }
     fn create_basis(&self) -> Vec<Vec<f64>> {
@@ -368,3 +372,129 @@ impl PSpline {
         Ok(x.to_vec())
     }
 }
+
+/// Cubic B-spline basis of R's `pspline(x, df)`: `2.5 * df` intervals of equal
+/// width over the range of `x`, with the knots continued at the same spacing
+/// beyond it and the first basis function dropped. Returns one column per
+/// basis function, or `None` if `x` has no finite range.
+pub fn pspline_basis(x: &[f64], df: f64) -> Option<Vec<Vec<f64>>> {
+    const DEGREE: usize = 3;
+    let finite = x.iter().copied().filter(|v| v.is_finite());
+    let lo = finite.clone().fold(f64::INFINITY, f64::min);
+    let hi = finite.fold(f64::NEG_INFINITY, f64::max);
+    let nterm = 2.5 * df;
+    if hi <= lo || !nterm.is_finite() || nterm < 1.0 {
+        return None;
+    }
+    let dx = (hi - lo) / nterm;
+    let mut knots: Vec<f64> = (0..)
+        .map(|k| k as f64 - DEGREE as f64)
+        .take_while(|&k| k <= nterm - 1.0)
+        .map(|k| lo + dx * k)
+        .collect();
+    // The span ending at the upper boundary is closed on the right.
+    let closed = knots.len() - 1;
+    knots.extend((0..=DEGREE).map(|k| hi + dx * k as f64));
+    let nbasis = knots.len() - DEGREE - 1;
+
+    let mut columns = vec![vec![0.0; x.len()]; nbasis - 1];
+    for (i, &v) in x.iter().enumerate() {
+        if !v.is_finite() {
+            columns.iter_mut().for_each(|column| column[i] = f64::NAN);
+            continue;
+        }
+        let mut b: Vec<f64> = (0..knots.len() - 1)
+            .map(|s| {
+                let inside = if v == hi {
+                    s == closed
+                } else {
+                    knots[s] <= v && v < knots[s + 1]
+                };
+                f64::from(u8::from(inside))
+            })
+            .collect();
+        for degree in 1..=DEGREE {
+            for s in 0..knots.len() - degree - 1 {
+                let left = knots[s + degree] - knots[s];
+                let right = knots[s + degree + 1] - knots[s + 1];
+                b[s] =
+                    (v - knots[s]) / left * b[s] + (knots[s + degree + 1] - v) / right * b[s + 1];
+            }
+        }
+        for (j, column) in columns.iter_mut().enumerate() {
+            column[i] = b[j + 1];
+        }
+    }
+    Some(columns)
+}
+
+/// Penalty matrix `D'D` of the differences of order `order` between
+/// neighbouring coefficients of `m` basis functions.
+pub fn difference_penalty(m: usize, order: usize) -> Array2<f64> {
+    let mut d = Array2::<f64>::eye(m);
+    for _ in 0..order.min(m) {
+        let rows = d.nrows();
+        d = Array2::from_shape_fn((rows - 1, m), |(i, j)| d[[i + 1, j]] - d[[i, j]]);
+    }
+    d.t().dot(&d)
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PSpline {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        x: VectorArg<'_, f64>,
+        df: u32,
+        theta: f64,
+        eps: f64,
+        method: String,
+        boundary_knots: (f64, f64),
+        intercept: bool,
+        penalty: bool,
+    ) -> Self {
+        let x = x.into_vec();
+        Self::new(
+            x,
+            df,
+            theta,
+            eps,
+            method,
+            boundary_knots,
+            intercept,
+            penalty,
+        )
+    }
+
+    #[pyo3(name = "fit")]
+    fn py_fit(&mut self, py: Python<'_>) -> PyResult<Vec<f64>> {
+        Ok(parallel::detach(py, || self.fit())?)
+    }
+
+    #[pyo3(name = "predict")]
+    fn py_predict(&self, new_x: VectorArg<'_, f64>) -> PyResult<Vec<f64>> {
+        let new_x = new_x.as_slice();
+        Ok(self.predict(&new_x)?)
+    }
+
+    #[getter]
+    fn get_coefficients(&self) -> Option<Vec<f64>> {
+        self.coefficients.clone()
+    }
+
+    #[getter]
+    fn get_fitted(&self) -> bool {
+        self.fitted
+    }
+
+    #[getter]
+    fn get_df(&self) -> u32 {
+        self.df
+    }
+
+    #[getter]
+    fn get_eps(&self) -> f64 {
+        self.eps
+    }
+}
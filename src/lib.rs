@@ -1,192 +1,12 @@
-use pyo3::prelude::*;
-
-mod concordance;
-mod core;
-mod matrix;
-mod python;
-mod regression;
-mod residuals;
-mod scoring;
-mod specialized;
-mod surv_analysis;
+pub mod concordance;
+pub mod core;
+pub mod matrix;
+pub mod python;
+pub mod regression;
+pub mod residuals;
+pub mod scoring;
+pub mod specialized;
+pub mod surv_analysis;
 mod tests;
-mod utilities;
-mod validation;
-
-use concordance::concordance::concordance as concordance_fn;
-use concordance::concordance1::perform_concordance1_calculation;
-use concordance::concordance3::perform_concordance3_calculation;
-use concordance::concordance5::perform_concordance_calculation;
-use core::coxcount1::{CoxCountOutput, coxcount1, coxcount2};
-use core::coxscho::schoenfeld_residuals;
-use core::pspline::PSpline;
-use python::cox_py_callback::cox_callback;
-use python::pyears3b::perform_pyears_calculation;
-use python::pystep::{perform_pystep_calculation, perform_pystep_simple_calculation};
-use regression::aareg::{AaregOptions, aareg as aareg_function};
-use regression::agfit5::perform_cox_regression_frailty;
-use regression::blogit::LinkFunctionParams;
-use regression::clogit::{ClogitDataSet, ConditionalLogisticRegression};
-use regression::coxph::{CoxPHModel, Subject};
-use regression::survreg6::{DistributionType, SurvivalFit, survreg};
-use residuals::agmart::agmart;
-use residuals::coxmart::coxmart;
-use scoring::agscore2::perform_score_calculation;
-use scoring::agscore3::perform_agscore3_calculation;
-use scoring::coxscore2::cox_score_residuals;
-use specialized::brier::{brier, integrated_brier};
-use specialized::cch::{CchMethod, CohortData};
-use specialized::cipoisson::{cipoisson, cipoisson_anscombe, cipoisson_exact};
-use specialized::finegray::{FineGrayOutput, finegray};
-use specialized::norisk::norisk;
-use surv_analysis::agsurv4::agsurv4;
-use surv_analysis::agsurv5::agsurv5;
-use surv_analysis::nelson_aalen::{
-    NelsonAalenResult, StratifiedKMResult, nelson_aalen_estimator, stratified_kaplan_meier,
-};
-use surv_analysis::survdiff2::{SurvDiffResult, survdiff2};
-use surv_analysis::survfitaj::{SurvFitAJ, survfitaj};
-use surv_analysis::survfitkm::{SurvFitKMOutput, survfitkm};
-use utilities::agexact::agexact;
-use utilities::collapse::collapse;
-use utilities::survsplit::{SplitResult, survsplit};
-use utilities::tmerge::{tmerge, tmerge2, tmerge3};
-use validation::bootstrap::{BootstrapResult, bootstrap_cox_ci, bootstrap_survreg_ci};
-use validation::calibration::{
-    CalibrationResult, PredictionResult, RiskStratificationResult, TdAUCResult, calibration,
-    predict_cox, risk_stratification, td_auc,
-};
-use validation::crossval::{CVResult, cv_cox_concordance, cv_survreg_loglik};
-use validation::landmark::{
-    ConditionalSurvivalResult, HazardRatioResult, LandmarkResult, LifeTableResult,
-    SurvivalAtTimeResult, conditional_survival, hazard_ratio, landmark_analysis, life_table,
-    survival_at_times,
-};
-use validation::logrank::{
-    LogRankResult, TrendTestResult, fleming_harrington_test, logrank_test, logrank_trend,
-};
-use validation::power::{
-    AccrualResult, SampleSizeResult, expected_events, power_survival, sample_size_survival,
-    sample_size_survival_freedman,
-};
-use validation::rmst::{
-    CumulativeIncidenceResult, MedianSurvivalResult, NNTResult, RMSTComparisonResult, RMSTResult,
-    cumulative_incidence, number_needed_to_treat, rmst, rmst_comparison, survival_quantile,
-};
-use validation::tests::{
-    ProportionalityTest, TestResult, lrt_test, ph_test, score_test_py, wald_test_py,
-};
-
-#[pymodule]
-fn survival(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(perform_cox_regression_frailty, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_pyears_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_concordance1_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_concordance3_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_concordance_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_score_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_agscore3_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_pystep_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(perform_pystep_simple_calculation, &m)?)?;
-    m.add_function(wrap_pyfunction!(aareg_function, &m)?)?;
-    m.add_function(wrap_pyfunction!(collapse, &m)?)?;
-    m.add_function(wrap_pyfunction!(cox_callback, &m)?)?;
-    m.add_function(wrap_pyfunction!(coxcount1, &m)?)?;
-    m.add_function(wrap_pyfunction!(coxcount2, &m)?)?;
-    m.add_function(wrap_pyfunction!(norisk, &m)?)?;
-    m.add_function(wrap_pyfunction!(cipoisson, &m)?)?;
-    m.add_function(wrap_pyfunction!(cipoisson_exact, &m)?)?;
-    m.add_function(wrap_pyfunction!(cipoisson_anscombe, &m)?)?;
-    m.add_function(wrap_pyfunction!(concordance_fn, &m)?)?;
-    m.add_function(wrap_pyfunction!(agexact, &m)?)?;
-    m.add_function(wrap_pyfunction!(agsurv4, &m)?)?;
-    m.add_function(wrap_pyfunction!(agsurv5, &m)?)?;
-    m.add_function(wrap_pyfunction!(agmart, &m)?)?;
-    m.add_function(wrap_pyfunction!(coxmart, &m)?)?;
-    m.add_function(wrap_pyfunction!(survfitkm, &m)?)?;
-    m.add_function(wrap_pyfunction!(survfitaj, &m)?)?;
-    m.add_function(wrap_pyfunction!(survdiff2, &m)?)?;
-    m.add_function(wrap_pyfunction!(finegray, &m)?)?;
-    m.add_function(wrap_pyfunction!(survreg, &m)?)?;
-    m.add_function(wrap_pyfunction!(brier, &m)?)?;
-    m.add_function(wrap_pyfunction!(integrated_brier, &m)?)?;
-    m.add_function(wrap_pyfunction!(tmerge, &m)?)?;
-    m.add_function(wrap_pyfunction!(tmerge2, &m)?)?;
-    m.add_function(wrap_pyfunction!(tmerge3, &m)?)?;
-    m.add_function(wrap_pyfunction!(survsplit, &m)?)?;
-    m.add_function(wrap_pyfunction!(schoenfeld_residuals, &m)?)?;
-    m.add_function(wrap_pyfunction!(cox_score_residuals, &m)?)?;
-    m.add_function(wrap_pyfunction!(bootstrap_cox_ci, &m)?)?;
-    m.add_function(wrap_pyfunction!(bootstrap_survreg_ci, &m)?)?;
-    m.add_function(wrap_pyfunction!(cv_cox_concordance, &m)?)?;
-    m.add_function(wrap_pyfunction!(cv_survreg_loglik, &m)?)?;
-    m.add_function(wrap_pyfunction!(lrt_test, &m)?)?;
-    m.add_function(wrap_pyfunction!(wald_test_py, &m)?)?;
-    m.add_function(wrap_pyfunction!(score_test_py, &m)?)?;
-    m.add_function(wrap_pyfunction!(ph_test, &m)?)?;
-    m.add_function(wrap_pyfunction!(nelson_aalen_estimator, &m)?)?;
-    m.add_function(wrap_pyfunction!(stratified_kaplan_meier, &m)?)?;
-    m.add_function(wrap_pyfunction!(logrank_test, &m)?)?;
-    m.add_function(wrap_pyfunction!(fleming_harrington_test, &m)?)?;
-    m.add_function(wrap_pyfunction!(logrank_trend, &m)?)?;
-    m.add_function(wrap_pyfunction!(sample_size_survival, &m)?)?;
-    m.add_function(wrap_pyfunction!(sample_size_survival_freedman, &m)?)?;
-    m.add_function(wrap_pyfunction!(power_survival, &m)?)?;
-    m.add_function(wrap_pyfunction!(expected_events, &m)?)?;
-    m.add_function(wrap_pyfunction!(calibration, &m)?)?;
-    m.add_function(wrap_pyfunction!(predict_cox, &m)?)?;
-    m.add_function(wrap_pyfunction!(risk_stratification, &m)?)?;
-    m.add_function(wrap_pyfunction!(td_auc, &m)?)?;
-    m.add_function(wrap_pyfunction!(rmst, &m)?)?;
-    m.add_function(wrap_pyfunction!(rmst_comparison, &m)?)?;
-    m.add_function(wrap_pyfunction!(survival_quantile, &m)?)?;
-    m.add_function(wrap_pyfunction!(cumulative_incidence, &m)?)?;
-    m.add_function(wrap_pyfunction!(number_needed_to_treat, &m)?)?;
-    m.add_function(wrap_pyfunction!(landmark_analysis, &m)?)?;
-    m.add_function(wrap_pyfunction!(conditional_survival, &m)?)?;
-    m.add_function(wrap_pyfunction!(hazard_ratio, &m)?)?;
-    m.add_function(wrap_pyfunction!(survival_at_times, &m)?)?;
-    m.add_function(wrap_pyfunction!(life_table, &m)?)?;
-    m.add_class::<AaregOptions>()?;
-    m.add_class::<PSpline>()?;
-    m.add_class::<CoxCountOutput>()?;
-    m.add_class::<LinkFunctionParams>()?;
-    m.add_class::<CoxPHModel>()?;
-    m.add_class::<Subject>()?;
-    m.add_class::<SurvFitKMOutput>()?;
-    m.add_class::<SurvFitAJ>()?;
-    m.add_class::<FineGrayOutput>()?;
-    m.add_class::<SurvivalFit>()?;
-    m.add_class::<DistributionType>()?;
-    m.add_class::<SurvDiffResult>()?;
-    m.add_class::<CchMethod>()?;
-    m.add_class::<CohortData>()?;
-    m.add_class::<SplitResult>()?;
-    m.add_class::<ClogitDataSet>()?;
-    m.add_class::<ConditionalLogisticRegression>()?;
-    m.add_class::<BootstrapResult>()?;
-    m.add_class::<CVResult>()?;
-    m.add_class::<TestResult>()?;
-    m.add_class::<ProportionalityTest>()?;
-    m.add_class::<NelsonAalenResult>()?;
-    m.add_class::<StratifiedKMResult>()?;
-    m.add_class::<LogRankResult>()?;
-    m.add_class::<TrendTestResult>()?;
-    m.add_class::<SampleSizeResult>()?;
-    m.add_class::<AccrualResult>()?;
-    m.add_class::<CalibrationResult>()?;
-    m.add_class::<PredictionResult>()?;
-    m.add_class::<RiskStratificationResult>()?;
-    m.add_class::<TdAUCResult>()?;
-    m.add_class::<RMSTResult>()?;
-    m.add_class::<RMSTComparisonResult>()?;
-    m.add_class::<MedianSurvivalResult>()?;
-    m.add_class::<CumulativeIncidenceResult>()?;
-    m.add_class::<NNTResult>()?;
-    m.add_class::<LandmarkResult>()?;
-    m.add_class::<ConditionalSurvivalResult>()?;
-    m.add_class::<HazardRatioResult>()?;
-    m.add_class::<SurvivalAtTimeResult>()?;
-    m.add_class::<LifeTableResult>()?;
-    Ok(())
-}
+pub mod utilities;
+pub mod validation;
@@ -0,0 +1,142 @@
+use ndarray::{Array2, ArrayView1, ArrayView2};
+use numpy::{Element, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+
+/// One-dimensional numeric argument. A NumPy array of the matching dtype is
+/// borrowed without copying; any other sequence is converted element-wise.
+pub enum VectorArg<'py, T: Element> {
+    Array(PyReadonlyArray1<'py, T>),
+    Sequence(Vec<T>),
+}
+
+impl<'a, 'py, T> FromPyObject<'a, 'py> for VectorArg<'py, T>
+where
+    T: Element + FromPyObjectOwned<'py> + 'a,
+{
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = obj.cast::<PyArray1<T>>() {
+            return Ok(Self::Array(array.readonly()));
+        }
+        Ok(Self::Sequence(obj.extract()?))
+    }
+}
+
+impl<T: Element + Clone> VectorArg<'_, T> {
+    pub fn view(&self) -> ArrayView1<'_, T> {
+        match self {
+            Self::Array(array) => array.as_array(),
+            Self::Sequence(values) => ArrayView1::from(values.as_slice()),
+        }
+    }
+
+    /// Borrows the values when they are contiguous in memory.
+    pub fn as_slice(&self) -> Cow<'_, [T]> {
+        match self {
+            Self::Array(array) => match array.as_slice() {
+                Ok(values) => Cow::Borrowed(values),
+                Err(_) => Cow::Owned(array.as_array().to_vec()),
+            },
+            Self::Sequence(values) => Cow::Borrowed(values),
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::Array(array) => array.as_array().to_vec(),
+            Self::Sequence(values) => values,
+        }
+    }
+}
+
+/// Two-dimensional numeric argument with observations in rows. C- and
+/// F-contiguous (or strided) NumPy arrays are borrowed; a sequence of rows is
+/// packed into a single matrix and must be rectangular.
+pub enum MatrixArg<'py, T: Element> {
+    Array(PyReadonlyArray2<'py, T>),
+    Sequence(Array2<T>),
+}
+
+impl<'a, 'py, T> FromPyObject<'a, 'py> for MatrixArg<'py, T>
+where
+    T: Element + Clone + FromPyObjectOwned<'py> + 'a,
+{
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = obj.cast::<PyArray2<T>>() {
+            return Ok(Self::Array(array.readonly()));
+        }
+        let rows: Vec<Vec<T>> = obj.extract()?;
+        let ncols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != ncols) {
+            return Err(PyValueError::new_err("all rows must have the same length"));
+        }
+        let nrows = rows.len();
+        let values = rows.into_iter().flatten().collect();
+        Array2::from_shape_vec((nrows, ncols), values)
+            .map(Self::Sequence)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+}
+
+impl<T: Element + Clone> MatrixArg<'_, T> {
+    pub fn view(&self) -> ArrayView2<'_, T> {
+        match self {
+            Self::Array(array) => array.as_array(),
+            Self::Sequence(values) => values.view(),
+        }
+    }
+
+    pub fn into_array(self) -> Array2<T> {
+        match self {
+            Self::Array(array) => array.as_array().to_owned(),
+            Self::Sequence(values) => values,
+        }
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<T>> {
+        self.view().outer_iter().map(|row| row.to_vec()).collect()
+    }
+}
+
+/// Index argument such as strata or cluster ids. Any NumPy integer array is
+/// accepted without going through Python objects; negative values are
+/// rejected.
+pub struct IndexArg(Vec<usize>);
+
+fn non_negative<T: Copy + TryInto<usize>>(values: ArrayView1<'_, T>) -> PyResult<Vec<usize>> {
+    values
+        .iter()
+        .map(|&v| {
+            v.try_into()
+                .map_err(|_| PyValueError::new_err("indices must be non-negative integers"))
+        })
+        .collect()
+}
+
+impl<'a, 'py> FromPyObject<'a, 'py> for IndexArg {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = obj.cast::<PyArray1<i64>>() {
+            return non_negative(array.readonly().as_array()).map(Self);
+        }
+        if let Ok(array) = obj.cast::<PyArray1<i32>>() {
+            return non_negative(array.readonly().as_array()).map(Self);
+        }
+        if let Ok(array) = obj.cast::<PyArray1<u64>>() {
+            return non_negative(array.readonly().as_array()).map(Self);
+        }
+        Ok(Self(obj.extract()?))
+    }
+}
+
+impl IndexArg {
+    pub fn into_vec(self) -> Vec<usize> {
+        self.0
+    }
+}
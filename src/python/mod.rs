@@ -1,3 +1,8 @@
+#[cfg(feature = "python")]
+pub mod arrays;
+#[cfg(feature = "python")]
 pub mod cox_py_callback;
+#[cfg(feature = "python")]
+mod module;
 pub mod pyears3b;
 pub mod pystep;
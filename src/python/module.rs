@@ -0,0 +1,231 @@
+use pyo3::prelude::*;
+
+use crate::concordance::concordance::concordance as concordance_fn;
+use crate::concordance::concordance_index::{
+    ConcordanceComparison, ConcordanceIndexResult, concordance_index_py,
+};
+use crate::concordance::concordance1::perform_concordance1_calculation;
+use crate::concordance::concordance3::perform_concordance3_calculation;
+use crate::concordance::concordance5::perform_concordance_calculation;
+use crate::core::coxcount1::{CoxCountOutput, coxcount1, coxcount2};
+use crate::core::coxscho::schoenfeld_residuals;
+use crate::core::pspline::PSpline;
+use crate::python::cox_py_callback::cox_callback;
+use crate::python::pyears3b::perform_pyears_calculation;
+use crate::python::pystep::{perform_pystep_calculation, perform_pystep_simple_calculation};
+use crate::regression::aareg::{
+    AaregOptions, AaregResult, ConfidenceInterval, Diagnostics, FitDetails, aareg_py,
+};
+use crate::regression::agfit5::perform_cox_regression_frailty;
+use crate::regression::blogit::LinkFunctionParams;
+use crate::regression::clogit::{ClogitDataSet, ConditionalLogisticRegression};
+use crate::regression::coxnet::{CoxNetCVResult, CoxNetPath, coxnet_py, cv_coxnet_py};
+use crate::regression::coxph::{
+    CoefficientRow, CoxPHModel, StratumBaseline, Subject, SurvivalPrediction,
+};
+use crate::regression::frailty::{FrailtyFit, coxph_frailty_py};
+use crate::regression::multistate_cox::{MultiStateCoxModel, multistate_coxph_py};
+use crate::regression::survreg6::{
+    DistributionType, SurvivalFit, survreg_formula_py, survreg_interval_py, survreg_py,
+};
+use crate::residuals::agmart::agmart;
+use crate::residuals::coxmart::coxmart;
+use crate::scoring::agscore2::perform_score_calculation;
+use crate::scoring::agscore3::perform_agscore3_calculation;
+use crate::scoring::coxscore2::cox_score_residuals;
+use crate::specialized::brier::{SurvivalBrierResult, brier, integrated_brier, survival_brier_py};
+use crate::specialized::cch::{CchMethod, CohortData};
+use crate::specialized::cipoisson::{cipoisson, cipoisson_anscombe, cipoisson_exact};
+use crate::specialized::finegray::{FineGrayOutput, finegray};
+use crate::specialized::norisk::norisk;
+use crate::surv_analysis::agsurv4::agsurv4;
+use crate::surv_analysis::agsurv5::agsurv5;
+use crate::surv_analysis::multistate::{MultiStateCurve, MultiStateFit, multistate_fit_py};
+use crate::surv_analysis::nelson_aalen::{
+    NelsonAalenResult, StratifiedKMResult, nelson_aalen_estimator, stratified_kaplan_meier,
+};
+use crate::surv_analysis::survdiff2::{SurvDiffResult, survdiff2};
+use crate::surv_analysis::survfitaj::{SurvFitAJ, survfitaj};
+use crate::surv_analysis::survfitkm::{SurvFitKMOutput, survfitkm};
+use crate::surv_analysis::turnbull::{TurnbullResult, turnbull_py};
+use crate::utilities::agexact::agexact;
+use crate::utilities::collapse::collapse;
+use crate::utilities::formula::{ModelFrame, PenalizedTerm, SurvType, model_frame_py};
+use crate::utilities::parallel::{num_threads_py, set_num_threads_py};
+use crate::utilities::survsplit::{SplitResult, survsplit};
+use crate::utilities::tmerge::{tmerge, tmerge2, tmerge3};
+use crate::validation::bootstrap::{BootstrapResult, bootstrap_cox_ci, bootstrap_survreg_ci};
+use crate::validation::calibration::{
+    CalibrationResult, PredictionResult, RiskStratificationResult, TdAUCResult, calibration,
+    predict_cox, risk_stratification, td_auc,
+};
+use crate::validation::cox_zph::{CoxZphResult, cox_zph_py};
+use crate::validation::crossval::{CVResult, cv_cox_concordance, cv_survreg_loglik};
+use crate::validation::landmark::{
+    ConditionalSurvivalResult, HazardRatioResult, LandmarkResult, LifeTableResult,
+    SurvivalAtTimeResult, conditional_survival, hazard_ratio, landmark_analysis, life_table,
+    survival_at_times,
+};
+use crate::validation::logrank::{
+    LogRankResult, TrendTestResult, fleming_harrington_test, logrank_test, logrank_trend,
+};
+use crate::validation::power::{
+    AccrualResult, SampleSizeResult, expected_events, power_survival, sample_size_survival,
+    sample_size_survival_freedman,
+};
+use crate::validation::rmst::{
+    CumulativeIncidenceResult, MedianSurvivalResult, NNTResult, RMSTComparisonResult, RMSTResult,
+    cumulative_incidence, number_needed_to_treat, rmst, rmst_comparison, survival_quantile,
+};
+use crate::validation::tests::{
+    ProportionalityTest, TestResult, lrt_test, ph_test, score_test_py, wald_test_py,
+};
+
+#[pymodule]
+fn survival(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(perform_cox_regression_frailty, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_pyears_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_concordance1_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_concordance3_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_concordance_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(concordance_index_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_score_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_agscore3_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_pystep_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(perform_pystep_simple_calculation, &m)?)?;
+    m.add_function(wrap_pyfunction!(aareg_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(collapse, &m)?)?;
+    m.add_function(wrap_pyfunction!(cox_callback, &m)?)?;
+    m.add_function(wrap_pyfunction!(coxcount1, &m)?)?;
+    m.add_function(wrap_pyfunction!(coxcount2, &m)?)?;
+    m.add_function(wrap_pyfunction!(norisk, &m)?)?;
+    m.add_function(wrap_pyfunction!(cipoisson, &m)?)?;
+    m.add_function(wrap_pyfunction!(cipoisson_exact, &m)?)?;
+    m.add_function(wrap_pyfunction!(cipoisson_anscombe, &m)?)?;
+    m.add_function(wrap_pyfunction!(concordance_fn, &m)?)?;
+    m.add_function(wrap_pyfunction!(agexact, &m)?)?;
+    m.add_function(wrap_pyfunction!(agsurv4, &m)?)?;
+    m.add_function(wrap_pyfunction!(agsurv5, &m)?)?;
+    m.add_function(wrap_pyfunction!(agmart, &m)?)?;
+    m.add_function(wrap_pyfunction!(coxmart, &m)?)?;
+    m.add_function(wrap_pyfunction!(survfitkm, &m)?)?;
+    m.add_function(wrap_pyfunction!(turnbull_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(survfitaj, &m)?)?;
+    m.add_function(wrap_pyfunction!(multistate_fit_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(multistate_coxph_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(coxph_frailty_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(survdiff2, &m)?)?;
+    m.add_function(wrap_pyfunction!(finegray, &m)?)?;
+    m.add_function(wrap_pyfunction!(survreg_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(coxnet_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(cv_coxnet_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(survreg_formula_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(survreg_interval_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(model_frame_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(brier, &m)?)?;
+    m.add_function(wrap_pyfunction!(integrated_brier, &m)?)?;
+    m.add_function(wrap_pyfunction!(survival_brier_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(tmerge, &m)?)?;
+    m.add_function(wrap_pyfunction!(tmerge2, &m)?)?;
+    m.add_function(wrap_pyfunction!(tmerge3, &m)?)?;
+    m.add_function(wrap_pyfunction!(survsplit, &m)?)?;
+    m.add_function(wrap_pyfunction!(schoenfeld_residuals, &m)?)?;
+    m.add_function(wrap_pyfunction!(cox_score_residuals, &m)?)?;
+    m.add_function(wrap_pyfunction!(bootstrap_cox_ci, &m)?)?;
+    m.add_function(wrap_pyfunction!(bootstrap_survreg_ci, &m)?)?;
+    m.add_function(wrap_pyfunction!(cv_cox_concordance, &m)?)?;
+    m.add_function(wrap_pyfunction!(cv_survreg_loglik, &m)?)?;
+    m.add_function(wrap_pyfunction!(lrt_test, &m)?)?;
+    m.add_function(wrap_pyfunction!(wald_test_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(score_test_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(ph_test, &m)?)?;
+    m.add_function(wrap_pyfunction!(nelson_aalen_estimator, &m)?)?;
+    m.add_function(wrap_pyfunction!(stratified_kaplan_meier, &m)?)?;
+    m.add_function(wrap_pyfunction!(logrank_test, &m)?)?;
+    m.add_function(wrap_pyfunction!(fleming_harrington_test, &m)?)?;
+    m.add_function(wrap_pyfunction!(logrank_trend, &m)?)?;
+    m.add_function(wrap_pyfunction!(sample_size_survival, &m)?)?;
+    m.add_function(wrap_pyfunction!(sample_size_survival_freedman, &m)?)?;
+    m.add_function(wrap_pyfunction!(power_survival, &m)?)?;
+    m.add_function(wrap_pyfunction!(expected_events, &m)?)?;
+    m.add_function(wrap_pyfunction!(calibration, &m)?)?;
+    m.add_function(wrap_pyfunction!(predict_cox, &m)?)?;
+    m.add_function(wrap_pyfunction!(risk_stratification, &m)?)?;
+    m.add_function(wrap_pyfunction!(td_auc, &m)?)?;
+    m.add_function(wrap_pyfunction!(cox_zph_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(rmst, &m)?)?;
+    m.add_function(wrap_pyfunction!(rmst_comparison, &m)?)?;
+    m.add_function(wrap_pyfunction!(survival_quantile, &m)?)?;
+    m.add_function(wrap_pyfunction!(cumulative_incidence, &m)?)?;
+    m.add_function(wrap_pyfunction!(number_needed_to_treat, &m)?)?;
+    m.add_function(wrap_pyfunction!(landmark_analysis, &m)?)?;
+    m.add_function(wrap_pyfunction!(conditional_survival, &m)?)?;
+    m.add_function(wrap_pyfunction!(hazard_ratio, &m)?)?;
+    m.add_function(wrap_pyfunction!(survival_at_times, &m)?)?;
+    m.add_function(wrap_pyfunction!(life_table, &m)?)?;
+    m.add_function(wrap_pyfunction!(set_num_threads_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(num_threads_py, &m)?)?;
+    m.add_class::<AaregOptions>()?;
+    m.add_class::<AaregResult>()?;
+    m.add_class::<ConfidenceInterval>()?;
+    m.add_class::<FitDetails>()?;
+    m.add_class::<Diagnostics>()?;
+    m.add_class::<PSpline>()?;
+    m.add_class::<ModelFrame>()?;
+    m.add_class::<PenalizedTerm>()?;
+    m.add_class::<SurvType>()?;
+    m.add_class::<CoxCountOutput>()?;
+    m.add_class::<LinkFunctionParams>()?;
+    m.add_class::<CoxPHModel>()?;
+    m.add_class::<Subject>()?;
+    m.add_class::<StratumBaseline>()?;
+    m.add_class::<CoefficientRow>()?;
+    m.add_class::<SurvivalPrediction>()?;
+    m.add_class::<SurvFitKMOutput>()?;
+    m.add_class::<TurnbullResult>()?;
+    m.add_class::<CoxNetPath>()?;
+    m.add_class::<CoxNetCVResult>()?;
+    m.add_class::<SurvFitAJ>()?;
+    m.add_class::<MultiStateFit>()?;
+    m.add_class::<MultiStateCurve>()?;
+    m.add_class::<MultiStateCoxModel>()?;
+    m.add_class::<FrailtyFit>()?;
+    m.add_class::<FineGrayOutput>()?;
+    m.add_class::<SurvivalFit>()?;
+    m.add_class::<DistributionType>()?;
+    m.add_class::<SurvDiffResult>()?;
+    m.add_class::<CchMethod>()?;
+    m.add_class::<CohortData>()?;
+    m.add_class::<SplitResult>()?;
+    m.add_class::<ClogitDataSet>()?;
+    m.add_class::<ConditionalLogisticRegression>()?;
+    m.add_class::<BootstrapResult>()?;
+    m.add_class::<CVResult>()?;
+    m.add_class::<TestResult>()?;
+    m.add_class::<ProportionalityTest>()?;
+    m.add_class::<NelsonAalenResult>()?;
+    m.add_class::<StratifiedKMResult>()?;
+    m.add_class::<LogRankResult>()?;
+    m.add_class::<TrendTestResult>()?;
+    m.add_class::<SampleSizeResult>()?;
+    m.add_class::<AccrualResult>()?;
+    m.add_class::<CalibrationResult>()?;
+    m.add_class::<PredictionResult>()?;
+    m.add_class::<RiskStratificationResult>()?;
+    m.add_class::<TdAUCResult>()?;
+    m.add_class::<CoxZphResult>()?;
+    m.add_class::<SurvivalBrierResult>()?;
+    m.add_class::<ConcordanceIndexResult>()?;
+    m.add_class::<ConcordanceComparison>()?;
+    m.add_class::<RMSTResult>()?;
+    m.add_class::<RMSTComparisonResult>()?;
+    m.add_class::<MedianSurvivalResult>()?;
+    m.add_class::<CumulativeIncidenceResult>()?;
+    m.add_class::<NNTResult>()?;
+    m.add_class::<LandmarkResult>()?;
+    m.add_class::<ConditionalSurvivalResult>()?;
+    m.add_class::<HazardRatioResult>()?;
+    m.add_class::<SurvivalAtTimeResult>()?;
+    m.add_class::<LifeTableResult>()?;
+    Ok(())
+}
@@ -1,6 +1,13 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use itertools::izip;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 #[allow(clippy::too_many_arguments)]
@@ -267,26 +274,39 @@ fn pystep(
     (et2, indx, indx2, wt)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[allow(clippy::too_many_arguments)]
 pub fn perform_pyears_calculation(
-    time_data: Vec<f64>,
-    weights: Vec<f64>,
+    py: Python<'_>,
+    time_data: VectorArg<'_, f64>,
+    weights: VectorArg<'_, f64>,
     expected_dim: usize,
-    expected_factors: Vec<i32>,
-    expected_dims: Vec<usize>,
-    expected_cuts: Vec<f64>,
-    expected_rates: Vec<f64>,
-    expected_data: Vec<f64>,
+    expected_factors: VectorArg<'_, i32>,
+    expected_dims: IndexArg,
+    expected_cuts: VectorArg<'_, f64>,
+    expected_rates: VectorArg<'_, f64>,
+    expected_data: VectorArg<'_, f64>,
     observed_dim: usize,
-    observed_factors: Vec<i32>,
-    observed_dims: Vec<usize>,
-    observed_cuts: Vec<f64>,
+    observed_factors: VectorArg<'_, i32>,
+    observed_dims: IndexArg,
+    observed_cuts: VectorArg<'_, f64>,
     method: i32,
-    observed_data: Vec<f64>,
+    observed_data: VectorArg<'_, f64>,
     do_event: Option<i32>,
     ny: Option<usize>,
 ) -> PyResult<Py<PyAny>> {
+    let time_data = time_data.as_slice();
+    let weights = weights.as_slice();
+    let expected_factors = expected_factors.as_slice();
+    let expected_dims = expected_dims.into_vec();
+    let expected_cuts = expected_cuts.as_slice();
+    let expected_rates = expected_rates.as_slice();
+    let expected_data = expected_data.as_slice();
+    let observed_factors = observed_factors.as_slice();
+    let observed_dims = observed_dims.into_vec();
+    let observed_cuts = observed_cuts.as_slice();
+    let observed_data = observed_data.as_slice();
     let n = weights.len();
     if n == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
@@ -314,30 +334,32 @@ pub fn perform_pyears_calculation(
     let mut pexpect = vec![0.0; total_observed];
     let mut offtable = 0.0;
 
-    pyears3b(
-        n,
-        ny,
-        doevent,
-        &time_data,
-        &weights,
-        expected_dim,
-        &expected_factors,
-        &expected_dims,
-        &expected_cuts,
-        &expected_rates,
-        &expected_data,
-        observed_dim,
-        &observed_factors,
-        &observed_dims,
-        &observed_cuts,
-        method,
-        &observed_data,
-        &mut pyears,
-        &mut pn,
-        &mut pcount,
-        &mut pexpect,
-        &mut offtable,
-    );
+    parallel::detach(py, || {
+        pyears3b(
+            n,
+            ny,
+            doevent,
+            &time_data,
+            &weights,
+            expected_dim,
+            &expected_factors,
+            &expected_dims,
+            &expected_cuts,
+            &expected_rates,
+            &expected_data,
+            observed_dim,
+            &observed_factors,
+            &observed_dims,
+            &observed_cuts,
+            method,
+            &observed_data,
+            &mut pyears,
+            &mut pn,
+            &mut pcount,
+            &mut pexpect,
+            &mut offtable,
+        )
+    });
 
     Python::attach(|py| {
         let dict = PyDict::new(py);
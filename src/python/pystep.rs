@@ -1,5 +1,10 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 fn find_interval(cuts: &[f64], x: f64) -> Option<usize> {
@@ -171,15 +176,20 @@ pub fn pystep_simple(
     (maxtime, index as i32)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn perform_pystep_calculation(
     edim: usize,
-    data: Vec<f64>,
-    efac: Vec<i32>,
-    edims: Vec<usize>,
-    ecut: Vec<Vec<f64>>,
+    data: VectorArg<'_, f64>,
+    efac: VectorArg<'_, i32>,
+    edims: IndexArg,
+    ecut: MatrixArg<'_, f64>,
     tmax: f64,
 ) -> PyResult<Py<PyAny>> {
+    let data = data.as_slice();
+    let efac = efac.as_slice();
+    let edims = edims.into_vec();
+    let ecut = ecut.into_rows();
     if data.len() != edim {
         return Err(PyRuntimeError::new_err("Data length does not match edim"));
     }
@@ -200,7 +210,7 @@ pub fn perform_pystep_calculation(
         ));
     }
 
-    let mut data_mut = data.clone();
+    let mut data_mut = data.into_owned();
     let ecut_refs: Vec<&[f64]> = ecut.iter().map(|v| v.as_slice()).collect();
 
     let (time_step, current_index, next_index, weight) =
@@ -217,15 +227,20 @@ pub fn perform_pystep_calculation(
     })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn perform_pystep_simple_calculation(
     odim: usize,
-    data: Vec<f64>,
-    ofac: Vec<i32>,
-    odims: Vec<usize>,
-    ocut: Vec<Vec<f64>>,
+    data: VectorArg<'_, f64>,
+    ofac: VectorArg<'_, i32>,
+    odims: IndexArg,
+    ocut: MatrixArg<'_, f64>,
     timeleft: f64,
 ) -> PyResult<Py<PyAny>> {
+    let data = data.as_slice();
+    let ofac = ofac.as_slice();
+    let odims = odims.into_vec();
+    let ocut = ocut.into_rows();
     if data.len() != odim {
         return Err(PyRuntimeError::new_err("Data length does not match odim"));
     }
@@ -1,49 +1,41 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::MatrixArg;
+use crate::utilities::formula::{ModelFrame, model_frame};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::utilities::persistence::impl_persist;
 use ndarray::{Array1, Array2, Axis};
 use ndarray_linalg::Solve;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 #[derive(Clone)]
 pub struct AaregOptions {
-    #[pyo3(get, set)]
-    formula: String,
-    #[pyo3(get, set)]
-    data: Vec<Vec<f64>>,
-    #[pyo3(get, set)]
-    variable_names: Vec<String>,
-    #[pyo3(get, set)]
-    weights: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    subset: Option<Vec<usize>>,
-    #[pyo3(get, set)]
-    na_action: Option<String>,
-    #[pyo3(get, set)]
-    qrtol: f64,
-    #[pyo3(get, set)]
-    nmin: Option<usize>,
-    #[pyo3(get, set)]
-    dfbeta: bool,
-    #[pyo3(get, set)]
-    taper: f64,
-    #[pyo3(get, set)]
-    test: Vec<String>,
-    #[pyo3(get, set)]
-    cluster: Option<HashMap<String, i32>>,
-    #[pyo3(get, set)]
-    model: bool,
-    #[pyo3(get, set)]
-    x: bool,
-    #[pyo3(get, set)]
-    y: bool,
+    pub formula: String,
+    pub data: Vec<Vec<f64>>,
+    pub variable_names: Vec<String>,
+    pub weights: Option<Vec<f64>>,
+    pub subset: Option<Vec<usize>>,
+    pub na_action: Option<String>,
+    pub qrtol: f64,
+    pub nmin: Option<usize>,
+    pub dfbeta: bool,
+    pub taper: f64,
+    pub test: Vec<String>,
+    pub cluster: Option<HashMap<String, i32>>,
+    pub model: bool,
+    pub x: bool,
+    pub y: bool,
 }
 
-#[pymethods]
 impl AaregOptions {
-    #[new]
-    fn new(formula: String, data: Vec<Vec<f64>>, variable_names: Vec<String>) -> Self {
+    pub fn new(formula: String, data: Vec<Vec<f64>>, variable_names: Vec<String>) -> Self {
         AaregOptions {
             formula,
             data,
@@ -64,81 +56,67 @@ impl AaregOptions {
     }
 }
 
-#[pyclass]
-#[derive(Clone)]
+#[cfg(feature = "python")]
+#[pymethods]
+impl AaregOptions {
+    #[new]
+    fn py_new(formula: String, data: MatrixArg<'_, f64>, variable_names: Vec<String>) -> Self {
+        let data = data.into_rows();
+        Self::new(formula, data, variable_names)
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "survival", get_all, set_all))]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AaregResult {
-    #[pyo3(get, set)]
-    coefficients: Vec<f64>,
-    #[pyo3(get, set)]
-    standard_errors: Vec<f64>,
-    #[pyo3(get, set)]
-    confidence_intervals: Vec<ConfidenceInterval>,
-    #[pyo3(get, set)]
-    p_values: Vec<f64>,
-    #[pyo3(get, set)]
-    goodness_of_fit: f64,
-    #[pyo3(get, set)]
-    fit_details: Option<FitDetails>,
-    #[pyo3(get, set)]
-    residuals: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    diagnostics: Option<Diagnostics>,
+    pub coefficients: Vec<f64>,
+    pub standard_errors: Vec<f64>,
+    pub confidence_intervals: Vec<ConfidenceInterval>,
+    pub p_values: Vec<f64>,
+    pub goodness_of_fit: f64,
+    pub fit_details: Option<FitDetails>,
+    pub residuals: Option<Vec<f64>>,
+    pub diagnostics: Option<Diagnostics>,
 }
 
-#[pyclass]
-#[derive(Clone)]
-struct ConfidenceInterval {
-    #[pyo3(get, set)]
-    lower_bound: f64,
-    #[pyo3(get, set)]
-    upper_bound: f64,
+impl_persist!(AaregResult);
+
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    pub lower_bound: f64,
+    pub upper_bound: f64,
 }
 
-#[pyclass]
-#[derive(Clone)]
-struct FitDetails {
-    #[pyo3(get, set)]
-    iterations: u32,
-    #[pyo3(get, set)]
-    converged: bool,
-    #[pyo3(get, set)]
-    final_objective_value: f64,
-    #[pyo3(get, set)]
-    convergence_threshold: f64,
-    #[pyo3(get, set)]
-    change_in_objective: Option<f64>,
-    #[pyo3(get, set)]
-    max_iterations: Option<u32>,
-    #[pyo3(get, set)]
-    optimization_method: Option<String>,
-    #[pyo3(get, set)]
-    warnings: Vec<String>,
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FitDetails {
+    pub iterations: u32,
+    pub converged: bool,
+    pub final_objective_value: f64,
+    pub convergence_threshold: f64,
+    pub change_in_objective: Option<f64>,
+    pub max_iterations: Option<u32>,
+    pub optimization_method: Option<String>,
+    pub warnings: Vec<String>,
 }
 
-#[pyclass]
-#[derive(Clone)]
-struct Diagnostics {
-    #[pyo3(get, set)]
-    dfbetas: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    cooks_distance: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    leverage: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    deviance_residuals: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    martingale_residuals: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    schoenfeld_residuals: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    score_residuals: Option<Vec<f64>>,
-    #[pyo3(get, set)]
-    additional_measures: Option<Vec<f64>>,
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub dfbetas: Option<Vec<f64>>,
+    pub cooks_distance: Option<Vec<f64>>,
+    pub leverage: Option<Vec<f64>>,
+    pub deviance_residuals: Option<Vec<f64>>,
+    pub martingale_residuals: Option<Vec<f64>>,
+    pub schoenfeld_residuals: Option<Vec<f64>>,
+    pub score_residuals: Option<Vec<f64>>,
+    pub additional_measures: Option<Vec<f64>>,
 }
 
 #[derive(Debug)]
 #[allow(clippy::enum_variant_names)]
-enum AaregError {
+pub enum AaregError {
     DataError(String),
     FormulaError(String),
     WeightsError(String),
@@ -164,67 +142,50 @@ impl fmt::Display for AaregError {
     }
 }
 
+impl std::error::Error for AaregError {}
+
+#[cfg(feature = "python")]
 impl From<pyo3::PyErr> for AaregError {
     fn from(err: pyo3::PyErr) -> AaregError {
         AaregError::GenericError(err.to_string())
     }
 }
 
+#[cfg(feature = "python")]
 impl From<AaregError> for PyErr {
     fn from(err: AaregError) -> PyErr {
         PyRuntimeError::new_err(format!("Aareg error: {}", err))
     }
 }
 
-#[pyfunction]
-#[pyo3(name = "aareg")]
-pub fn aareg(options: AaregOptions) -> PyResult<AaregResult> {
+pub fn aareg(options: &AaregOptions) -> Result<AaregResult, AaregError> {
     let data_array = Array2::from_shape_vec(
         (options.data.len(), options.data[0].len()),
         options.data.clone().into_iter().flatten().collect(),
     )
     .map_err(|e| AaregError::DataError(e.to_string()))?;
 
-    let (response_name, covariate_names) = parse_formula(&options.formula)?;
-
     let subset_data = apply_subset(&data_array, &options.subset)?;
 
     let weighted_data = apply_weights(&subset_data, options.weights.clone())?;
 
     let filtered_data = handle_missing_data(&weighted_data, options.na_action.clone())?;
 
-    let (y, x) = prepare_data_for_regression(
-        &filtered_data,
-        &response_name,
-        &covariate_names,
-        &options.variable_names,
-    )?;
+    let (y, x) =
+        prepare_data_for_regression(&filtered_data, &options.formula, &options.variable_names)?;
 
-    let regression_result = perform_aalen_regression(&y, &x, &options)?;
+    let regression_result = perform_aalen_regression(&y, &x, options)?;
 
-    let processed_result = post_process_results(regression_result, &options)?;
+    let processed_result = post_process_results(regression_result, options)?;
 
     Ok(processed_result)
 }
 
-fn parse_formula(formula: &str) -> Result<(String, Vec<String>), AaregError> {
-    let mut formula_parts = formula.splitn(2, '~');
-    let response = formula_parts
-        .next()
-        .ok_or_else(|| {
-            AaregError::FormulaError("Formula is missing a response variable.".to_string())
-        })?
-        .trim()
-        .to_string();
-    let covariates_str = formula_parts
-        .next()
-        .ok_or_else(|| AaregError::FormulaError("Formula is missing covariates.".to_string()))?
-        .trim();
-    let covariates = covariates_str
-        .split('+')
-        .map(|s| s.trim().to_string())
-        .collect();
-    Ok((response, covariates))
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "aareg")]
+pub fn aareg_py(py: Python<'_>, options: AaregOptions) -> PyResult<AaregResult> {
+    parallel::detach(py, || Ok(aareg(&options)?))
 }
 
 fn apply_subset(
@@ -301,31 +262,21 @@ fn handle_missing_data(
 
 fn prepare_data_for_regression(
     data: &Array2<f64>,
-    response_name: &String,
-    covariate_names: &[String],
+    formula: &str,
     variable_names: &[String],
 ) -> Result<(Array1<f64>, Array2<f64>), AaregError> {
-    let mut name_to_index = HashMap::new();
-    for (i, name) in variable_names.iter().enumerate() {
-        name_to_index.insert(name.clone(), i);
-    }
-
-    let response_index = name_to_index.get(response_name).ok_or_else(|| {
-        AaregError::FormulaError(format!("Response variable '{}' not found.", response_name))
-    })?;
-
-    let mut covariate_indices = Vec::new();
-    for cov_name in covariate_names {
-        let idx = name_to_index.get(cov_name).ok_or_else(|| {
-            AaregError::FormulaError(format!("Covariate '{}' not found.", cov_name))
-        })?;
-        covariate_indices.push(*idx);
-    }
-
-    let y = data.column(*response_index).to_owned();
-    let x = data.select(Axis(1), &covariate_indices);
+    let frame = model_frame(formula, data.view(), variable_names)
+        .and_then(ModelFrame::unpenalized)
+        .map_err(|e| AaregError::FormulaError(e.to_string()))?;
+
+    let p = frame.column_names.len();
+    let x = Array2::from_shape_vec(
+        (frame.covariates.len(), p),
+        frame.covariates.into_iter().flatten().collect(),
+    )
+    .map_err(|e| AaregError::DataError(e.to_string()))?;
 
-    Ok((y, x))
+    Ok((Array1::from_vec(frame.time), x))
 }
 
 fn perform_aalen_regression(
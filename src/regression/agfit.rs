@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use thiserror::Error;
 
@@ -25,7 +26,7 @@ struct Covariate {
     values: Vec<f64>,
 }
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass)]
 struct SurvivalModel {
     maxiter: usize,
     nused: usize,
@@ -194,6 +195,7 @@ impl SurvivalModel {
     }
 }
 
+#[cfg(feature = "python")]
 #[pymodule]
 #[pyo3(name = "pySurvivalModel")]
 fn py_survival_model(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
@@ -1,8 +1,20 @@
-use ndarray::{Array1, Array2};
-use ndarray_linalg::{Inverse, Solve};
+use crate::core::survpenal::{self, MatrixBuffers, PenaltyParams, PenaltyResult};
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+use crate::regression::coxfit6::CoxError;
+use crate::regression::coxph::CoxMethod;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, ArrayViewMut1, Axis, concatenate, s};
+use ndarray_linalg::{Inverse, SolveC};
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
+#[cfg(feature = "python")]
+use std::borrow::Cow;
 
 #[derive(Debug)]
 pub struct CoxResult {
@@ -18,214 +30,506 @@ pub struct CoxResult {
     pub variance_matrix: Vec<Vec<f64>>,
 }
 
-struct CoxState {
-    covar: Vec<Vec<f64>>,
-    a: Vec<f64>,
-    a2: Vec<f64>,
+const MAX_HALVINGS: usize = 20;
+const STEP_TOL: f64 = 1e-8;
+
+/// Inputs of [`AgData::new`]. `group` gives each row a frailty in
+/// `0..ngroup`, and rows without `start` enter at minus infinity.
+pub(crate) struct AgInput<'a> {
+    pub covariates: ArrayView2<'a, f64>,
+    pub start: Option<&'a [f64]>,
+    pub stop: &'a [f64],
+    pub status: &'a [bool],
+    pub offset: &'a [f64],
+    pub weights: &'a [f64],
+    pub strata: &'a [usize],
+    pub group: Option<&'a [usize]>,
+    pub method: CoxMethod,
+}
+
+/// Counting process data for the Andersen-Gill fit, with the covariates
+/// centred and the parameters laid out as the coefficients followed by one
+/// frailty per group.
+pub(crate) struct AgData {
+    x: Array2<f64>,
+    start: Option<Vec<f64>>,
+    stop: Vec<f64>,
+    status: Vec<bool>,
     offset: Vec<f64>,
     weights: Vec<f64>,
-    event: Vec<i32>,
-    frail: Vec<i32>,
-    score: Vec<f64>,
-    strata: Vec<i32>,
+    strata: Vec<usize>,
+    group: Vec<usize>,
+    ngroup: usize,
+    method: CoxMethod,
+    /// Rows by stratum and decreasing stop time.
+    sort2: Vec<usize>,
+    /// Rows by stratum and decreasing start time.
+    sort1: Vec<usize>,
 }
 
-impl CoxState {
-    #[allow(clippy::too_many_arguments)]
-    fn new(
-        nused: usize,
-        nvar: usize,
-        nfrail: usize,
-        yy: &[f64],
-        covar2: &[f64],
-        offset2: &[f64],
-        weights2: &[f64],
-        strata: &[i32],
-        _sort: &[i32],
-        _ptype: i32,
-        _pdiag: i32,
-        frail2: &[i32],
-    ) -> Self {
-        let mut covar = vec![vec![0.0; nused]; nvar];
-        let mut k = 0;
-        for covar_row in covar.iter_mut().take(nvar) {
-            for covar_elem in covar_row.iter_mut().take(nused) {
-                *covar_elem = covar2[k];
-                k += 1;
-            }
-        }
+/// Penalty subtracted from the partial likelihood, on the frailties and on
+/// the coefficients of penalized terms.
+pub(crate) trait Penalty {
+    /// Value, gradient and the diagonal of the Hessian at the frailties `b`.
+    fn frailty(&self, b: &[f64]) -> (f64, Vec<f64>, Vec<f64>) {
+        (0.0, vec![0.0; b.len()], vec![0.0; b.len()])
+    }
 
-        let mut state = CoxState {
-            covar,
-            a: vec![0.0; 4 * (nvar + nfrail) + 5 * nused],
-            a2: vec![0.0; nvar + nfrail],
-            offset: offset2.to_vec(),
-            weights: weights2.to_vec(),
-            event: yy[2 * nused..3 * nused].iter().map(|&x| x as i32).collect(),
-            frail: frail2.to_vec(),
-            score: vec![0.0; nused],
-            strata: strata.to_vec(),
-        };
+    /// Dense penalty on the coefficients `beta` as survpenal applies it: the
+    /// change of the log-likelihood, minus the gradient and the Hessian by
+    /// rows.
+    fn coefficients(&self, _beta: &[f64]) -> Option<PenaltyResult> {
+        None
+    }
 
-        for i in 0..nvar {
-            let mean = state.covar[i].iter().sum::<f64>() / nused as f64;
-            for val in &mut state.covar[i] {
-                *val -= mean;
-            }
+    /// Moves `b` along a direction in which the partial likelihood is flat.
+    fn recentre(&self, _b: ArrayViewMut1<f64>) {}
+}
+
+/// Information matrix with the frailty block reduced to its diagonal, as R
+/// does for sparse frailty terms. `cross` holds the frailty by coefficient
+/// block.
+#[derive(Clone)]
+pub(crate) struct SparseInformation {
+    pub imat: Array2<f64>,
+    pub cross: Array2<f64>,
+    pub fdiag: Array1<f64>,
+}
+
+/// Result of [`AgData::fit`]. `loglik` holds the partial likelihood at the
+/// start and at the end, `variance` is the penalized variance of the
+/// coefficients and `frailty_variance` the diagonal of that of the frailties.
+pub(crate) struct AgFit {
+    pub coef: Array1<f64>,
+    pub loglik: [f64; 2],
+    pub penalized: f64,
+    pub score_test: f64,
+    pub variance: Array2<f64>,
+    pub frailty_variance: Array1<f64>,
+    pub df: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+struct Evaluation {
+    loglik: f64,
+    penalized: f64,
+    score: Array1<f64>,
+    info: SparseInformation,
+    /// Second derivatives of the frailty penalty and of the coefficient
+    /// penalty.
+    hess: Vec<f64>,
+    dense: Option<Array2<f64>>,
+}
+
+/// Weighted sums of the risk scores, covariates and their cross products,
+/// overall and per group.
+struct RiskSums {
+    s0: f64,
+    s1: Array1<f64>,
+    s2: Array2<f64>,
+    g0: Array1<f64>,
+    g1: Array2<f64>,
+}
+
+impl RiskSums {
+    fn new(p: usize, q: usize) -> Self {
+        Self {
+            s0: 0.0,
+            s1: Array1::zeros(p),
+            s2: Array2::zeros((p, p)),
+            g0: Array1::zeros(q),
+            g1: Array2::zeros((q, p)),
         }
+    }
 
-        state
+    fn add(&mut self, x: ArrayView1<f64>, group: Option<usize>, r: f64) {
+        self.s0 += r;
+        self.s1.scaled_add(r, &x);
+        for a in 0..x.len() {
+            self.s2.row_mut(a).scaled_add(r * x[a], &x);
+        }
+        if let Some(g) = group {
+            self.g0[g] += r;
+            self.g1.row_mut(g).scaled_add(r, &x);
+        }
     }
+}
 
-    #[allow(clippy::too_many_arguments)]
-    fn update(&mut self, beta: &mut [f64], u: &mut [f64], imat: &mut [f64], loglik: &mut f64) {
-        let nvar = beta.len();
-        let nfrail = self.frail.len();
-        let nvar2 = nvar + nfrail;
-
-        self.a.iter_mut().for_each(|x| *x = 0.0);
-        self.a2.iter_mut().for_each(|x| *x = 0.0);
-        u.iter_mut().for_each(|x| *x = 0.0);
-        imat.iter_mut().for_each(|x| *x = 0.0);
-
-        for person in 0..self.weights.len() {
-            let mut zbeta = self.offset[person];
-            for (i, beta_val) in beta.iter().enumerate().take(nvar) {
-                zbeta += beta_val * self.covar[i][person];
-            }
-            if nfrail > 0 {
-                zbeta += beta[nvar] * self.frail[person] as f64;
-            }
-            self.score[person] = zbeta;
+impl SparseInformation {
+    /// Schur complement of the frailty block and the frailty block scaled
+    /// cross terms.
+    fn reduce(&self) -> Option<(Array2<f64>, Array2<f64>)> {
+        if !self.fdiag.iter().all(|&d| d > 0.0) {
+            return None;
+        }
+        let mut scaled = self.cross.clone();
+        for (mut row, &d) in scaled.outer_iter_mut().zip(&self.fdiag) {
+            row /= d;
         }
+        Some((&self.imat - &self.cross.t().dot(&scaled), scaled))
+    }
 
-        *loglik = 0.0;
-        let mut istrat = 0;
-        let _indx2 = 0;
+    /// Solves for the coefficients first and then the frailties.
+    pub fn solve(&self, score: &Array1<f64>) -> Option<Array1<f64>> {
+        let p = self.imat.nrows();
+        let (schur, scaled) = self.reduce()?;
+        let frail = score.slice(s![p..]);
+        let rhs = &score.slice(s![..p]) - &scaled.t().dot(&frail);
+        let beta = if p == 0 {
+            rhs
+        } else {
+            schur.solvec(&rhs).ok()?
+        };
+        let frail = (&frail - &self.cross.dot(&beta)) / &self.fdiag;
+        Some(concatenate![Axis(0), beta, frail])
+    }
 
-        while istrat < self.strata.len() {
-            let _denom = 0.0;
-            let mut risk_sum = 0.0;
+    /// Variance of the coefficients and the diagonal of the variance of the
+    /// frailties.
+    pub fn variance(&self) -> Option<(Array2<f64>, Array1<f64>)> {
+        let p = self.imat.nrows();
+        let (schur, scaled) = self.reduce()?;
+        let var = if p == 0 { schur } else { schur.inv().ok()? };
+        let fvar = Array1::from_shape_fn(self.fdiag.len(), |j| {
+            let w = scaled.row(j);
+            1.0 / self.fdiag[j] + w.dot(&var.dot(&w))
+        });
+        Some((var, fvar))
+    }
+}
 
-            for person in istrat..self.weights.len() {
-                if self.strata[person] != self.strata[istrat] {
-                    break;
-                }
-                let risk_score = self.score[person].exp();
-                risk_sum += self.weights[person] * risk_score;
-                let _ = self.weights[person] * risk_score * risk_score;
-            }
+impl AgData {
+    pub fn new(input: AgInput) -> Self {
+        let n = input.stop.len();
+        let p = input.covariates.ncols();
+        let total: f64 = input.weights.iter().sum();
+        let means: Vec<f64> = (0..p)
+            .map(|c| {
+                (0..n)
+                    .map(|i| input.weights[i] * input.covariates[[i, c]])
+                    .sum::<f64>()
+                    / total
+            })
+            .collect();
+        let by_time = |time: &[f64]| {
+            let mut order: Vec<usize> = (0..n).collect();
+            order.sort_by(|&i, &j| {
+                input.strata[i]
+                    .cmp(&input.strata[j])
+                    .then_with(|| time[j].total_cmp(&time[i]))
+            });
+            order
+        };
+        let group = input.group.map_or_else(|| vec![0; n], <[usize]>::to_vec);
+        Self {
+            x: Array2::from_shape_fn((n, p), |(i, c)| input.covariates[[i, c]] - means[c]),
+            sort2: by_time(input.stop),
+            sort1: input.start.map(by_time).unwrap_or_default(),
+            start: input.start.map(<[f64]>::to_vec),
+            stop: input.stop.to_vec(),
+            status: input.status.to_vec(),
+            offset: input.offset.to_vec(),
+            weights: input.weights.to_vec(),
+            strata: input.strata.to_vec(),
+            ngroup: input
+                .group
+                .map_or(0, |_| group.iter().max().map_or(0, |g| g + 1)),
+            group,
+            method: input.method,
+        }
+    }
 
-            for person in istrat..self.weights.len() {
-                if self.strata[person] != self.strata[istrat] {
-                    break;
-                }
+    pub fn nvar(&self) -> usize {
+        self.x.ncols()
+    }
 
-                if self.event[person] == 1 {
-                    *loglik += self.weights[person] * self.score[person];
-                    *loglik -= self.weights[person] * risk_sum.ln();
-
-                    for (i, u_elem) in u.iter_mut().enumerate().take(nvar) {
-                        let mut temp = 0.0;
-                        for j in person..self.weights.len() {
-                            if self.strata[j] == self.strata[person] {
-                                temp += self.weights[j] * self.score[j].exp() * self.covar[i][j];
-                            }
-                        }
-                        *u_elem += self.weights[person] * (self.covar[i][person] - temp / risk_sum);
-                    }
+    /// Partial log-likelihood, score and sparse information at `coef`. Risk
+    /// sets are updated as in agfit4, adding rows at their stop time and
+    /// removing them once the death time reaches their start, so each death
+    /// costs O(p^2 + p * ngroup) for p covariates.
+    pub fn terms(&self, coef: &Array1<f64>) -> (f64, Array1<f64>, SparseInformation) {
+        let p = self.nvar();
+        let q = self.ngroup;
+        let beta = coef.slice(s![..p]);
+        let eta: Vec<f64> = (0..self.stop.len())
+            .map(|i| {
+                let frailty = if q > 0 { coef[p + self.group[i]] } else { 0.0 };
+                self.x.row(i).dot(&beta) + self.offset[i] + frailty
+            })
+            .collect();
+        let mut loglik = 0.0;
+        let mut u = Array1::zeros(p + q);
+        let mut info = SparseInformation {
+            imat: Array2::zeros((p, p)),
+            cross: Array2::zeros((q, p)),
+            fdiag: Array1::zeros(q),
+        };
 
-                    if nfrail > 0 {
-                        let mut temp = 0.0;
-                        for j in person..self.weights.len() {
-                            if self.strata[j] == self.strata[person] {
-                                temp +=
-                                    self.weights[j] * self.score[j].exp() * self.frail[j] as f64;
-                            }
-                        }
-                        u[nvar] +=
-                            self.weights[person] * (self.frail[person] as f64 - temp / risk_sum);
-                    }
+        // Sums over the risk set and over the deaths at the current time.
+        let mut risk = RiskSums::new(p, q);
+        let mut dead = RiskSums::new(p, q);
+        let mut touched = Vec::new();
 
-                    for i in 0..nvar {
-                        for j in i..nvar {
-                            let mut temp = 0.0;
-                            for k in person..self.weights.len() {
-                                if self.strata[k] == self.strata[person] {
-                                    temp += self.weights[k]
-                                        * self.score[k].exp()
-                                        * self.covar[i][k]
-                                        * self.covar[j][k];
-                                }
-                            }
-                            let idx = i * nvar2 + j;
-                            imat[idx] += self.weights[person]
-                                * (temp / risk_sum
-                                    - (self.a[i] * self.a[j]) / (risk_sum * risk_sum));
-                        }
+        let n = self.sort2.len();
+        let mut k = 0;
+        let mut k1 = 0;
+        while k < n {
+            let first = self.sort2[k];
+            let stratum = self.strata[first];
+            if k == 0 || self.strata[self.sort2[k - 1]] != stratum {
+                risk = RiskSums::new(p, q);
+                while k1 < self.sort1.len() && self.strata[self.sort1[k1]] < stratum {
+                    k1 += 1;
+                }
+            }
+            let t = self.stop[first];
+            let mut ndead = 0usize;
+            let mut deadwt = 0.0;
+            while k < n && self.strata[self.sort2[k]] == stratum && self.stop[self.sort2[k]] == t {
+                let i = self.sort2[k];
+                let g = (q > 0).then(|| self.group[i]);
+                let r = self.weights[i] * eta[i].exp();
+                risk.add(self.x.row(i), g, r);
+                if self.status[i] {
+                    ndead += 1;
+                    deadwt += self.weights[i];
+                    if let Some(g) = g
+                        && dead.g0[g] == 0.0
+                    {
+                        touched.push(g);
                     }
-
-                    if nfrail > 0 {
-                        for i in 0..nvar {
-                            let mut temp = 0.0;
-                            for k in person..self.weights.len() {
-                                if self.strata[k] == self.strata[person] {
-                                    temp += self.weights[k]
-                                        * self.score[k].exp()
-                                        * self.covar[i][k]
-                                        * self.frail[k] as f64;
-                                }
-                            }
-                            let idx = i * nvar2 + nvar;
-                            imat[idx] += self.weights[person]
-                                * (temp / risk_sum
-                                    - (self.a[i] * self.a[nvar]) / (risk_sum * risk_sum));
-                        }
-
-                        let mut temp = 0.0;
-                        for k in person..self.weights.len() {
-                            if self.strata[k] == self.strata[person] {
-                                temp += self.weights[k]
-                                    * self.score[k].exp()
-                                    * (self.frail[k] as f64).powi(2);
-                            }
-                        }
-                        let idx = nvar * nvar2 + nvar;
-                        imat[idx] += self.weights[person]
-                            * (temp / risk_sum
-                                - (self.a[nvar] * self.a[nvar]) / (risk_sum * risk_sum));
+                    dead.add(self.x.row(i), g, r);
+                    loglik += self.weights[i] * eta[i];
+                    u.slice_mut(s![..p])
+                        .scaled_add(self.weights[i], &self.x.row(i));
+                    if let Some(g) = g {
+                        u[p + g] += self.weights[i];
                     }
                 }
-
-                if person < self.weights.len() - 1 && self.strata[person + 1] == self.strata[person]
-                {
-                    let risk_score = self.score[person].exp();
-                    risk_sum -= self.weights[person] * risk_score;
-                    let _ = self.weights[person] * risk_score * risk_score;
+                k += 1;
+            }
+            if let Some(start) = &self.start {
+                while k1 < self.sort1.len() {
+                    let i = self.sort1[k1];
+                    if self.strata[i] != stratum || start[i] < t {
+                        break;
+                    }
+                    let g = (q > 0).then(|| self.group[i]);
+                    risk.add(self.x.row(i), g, -self.weights[i] * eta[i].exp());
+                    k1 += 1;
                 }
             }
+            if ndead == 0 {
+                continue;
+            }
+            let (steps, wt) = match self.method {
+                CoxMethod::Breslow => (1, deadwt),
+                CoxMethod::Efron => (ndead, deadwt / ndead as f64),
+            };
+            for step in 0..steps {
+                let f = step as f64 / ndead as f64;
+                let a0 = risk.s0 - f * dead.s0;
+                let a1 = &risk.s1 - &(&dead.s1 * f);
+                loglik -= wt * a0.ln();
+                u.slice_mut(s![..p]).scaled_add(-wt / a0, &a1);
+                info.imat.scaled_add(wt / a0, &(&risk.s2 - &(&dead.s2 * f)));
+                for a in 0..p {
+                    info.imat
+                        .row_mut(a)
+                        .scaled_add(-wt * a1[a] / (a0 * a0), &a1);
+                }
+                for h in 0..q {
+                    let c0 = risk.g0[h] - f * dead.g0[h];
+                    if c0 == 0.0 {
+                        continue;
+                    }
+                    let c1 = &risk.g1.row(h) - &(&dead.g1.row(h) * f);
+                    u[p + h] -= wt * c0 / a0;
+                    info.fdiag[h] += wt * (c0 / a0 - c0 * c0 / (a0 * a0));
+                    let mut row = info.cross.row_mut(h);
+                    row.scaled_add(wt / a0, &c1);
+                    row.scaled_add(-wt * c0 / (a0 * a0), &a1);
+                }
+            }
+            dead.s0 = 0.0;
+            dead.s1.fill(0.0);
+            dead.s2.fill(0.0);
+            for g in touched.drain(..) {
+                dead.g0[g] = 0.0;
+                dead.g1.row_mut(g).fill(0.0);
+            }
+        }
+        (loglik, u, info)
+    }
+
+    fn evaluate(&self, coef: &Array1<f64>, penalty: Option<&dyn Penalty>) -> Evaluation {
+        let p = self.nvar();
+        let (loglik, mut score, mut info) = self.terms(coef);
+        let Some(penalty) = penalty else {
+            return Evaluation {
+                loglik,
+                penalized: loglik,
+                score,
+                info,
+                hess: vec![0.0; self.ngroup],
+                dense: None,
+            };
+        };
+        let b = coef.slice(s![p..]).to_vec();
+        let (value, grad, hess) = penalty.frailty(&b);
+        for (j, (g, d)) in grad.iter().zip(&hess).enumerate() {
+            score[p + j] -= g;
+            info.fdiag[j] += d;
+        }
+        let mut penalized = loglik - value;
+        let mut beta = coef.slice(s![..p]).to_vec();
+        let dense = penalty.coefficients(&beta).map(|result| {
+            let second = Array2::from_shape_vec((p, p), result.second_deriv.clone())
+                .expect("dense penalty Hessian has p * p entries");
+            let mut jj = vec![0.0; p * p];
+            let u = score.as_slice_mut().expect("score is contiguous");
+            survpenal::survpenal(
+                PenaltyParams {
+                    whichcase: 0,
+                    nfrail: 0,
+                    nvar: p,
+                    ptype: 2,
+                    pdiag: 1,
+                },
+                MatrixBuffers {
+                    hmat: info.imat.as_slice_mut().expect("imat is contiguous"),
+                    JJ: &mut jj,
+                    hdiag: &mut [],
+                    jdiag: &mut [],
+                    u: &mut u[..p],
+                    beta: &mut beta,
+                },
+                &mut penalized,
+                |_| unreachable!("no sparse penalty"),
+                |_| result.clone(),
+            );
+            second
+        });
+        Evaluation {
+            loglik,
+            penalized,
+            score,
+            info,
+            hess,
+            dense,
+        }
+    }
 
-            while istrat < self.strata.len() {
-                istrat += 1;
+    /// Newton-Raphson with step halving on the penalized partial likelihood.
+    /// A step that still fails to improve it after `MAX_HALVINGS` halvings is
+    /// dropped and the fit is reported as not converged.
+    pub fn fit(
+        &self,
+        penalty: Option<&dyn Penalty>,
+        start: Array1<f64>,
+        max_iter: usize,
+        eps: f64,
+    ) -> Result<AgFit, CoxError> {
+        let p = self.nvar();
+        let recentre = |coef: &mut Array1<f64>| {
+            if let Some(penalty) = penalty {
+                penalty.recentre(coef.slice_mut(s![p..]));
+            }
+        };
+        let mut coef = start;
+        let mut current = self.evaluate(&coef, penalty);
+        let initial = current.loglik;
+        let score_test = current
+            .info
+            .solve(&current.score)
+            .map_or(f64::NAN, |a| a.dot(&current.score));
+        let mut iterations = 0;
+        let mut converged = false;
+        while iterations < max_iter {
+            let step = current
+                .info
+                .solve(&current.score)
+                .ok_or(CoxError::CholeskyDecomposition)?;
+            let penalized = current.penalized;
+            let improves =
+                |value: f64| value.is_finite() && value >= penalized - eps * penalized.abs();
+            let mut candidate = &coef + &step;
+            recentre(&mut candidate);
+            let mut next = self.evaluate(&candidate, penalty);
+            let mut halvings = 0;
+            while !improves(next.penalized) && halvings < MAX_HALVINGS {
+                candidate = (&coef + &candidate) / 2.0;
+                recentre(&mut candidate);
+                next = self.evaluate(&candidate, penalty);
+                halvings += 1;
+            }
+            if !improves(next.penalized) {
+                break;
+            }
+            iterations += 1;
+            let change = (next.penalized - penalized).abs();
+            let moved = (&candidate - &coef)
+                .iter()
+                .fold(0.0_f64, |m, d| m.max(d.abs()));
+            coef = candidate;
+            current = next;
+            if change <= eps * (current.penalized.abs() + 1.0) && moved <= STEP_TOL {
+                converged = true;
+                break;
             }
         }
+
+        let (variance, frailty_variance) =
+            current.info.variance().ok_or(CoxError::MatrixInversion)?;
+        // The trace of the variance times the unpenalized information is
+        // the number of parameters less the share taken by the penalty.
+        let dense = current
+            .dense
+            .as_ref()
+            .map_or(0.0, |second| (&variance * second).sum());
+        let df =
+            (p + self.ngroup) as f64 - frailty_variance.dot(&Array1::from(current.hess)) - dense;
+        Ok(AgFit {
+            coef,
+            loglik: [initial, current.loglik],
+            penalized: current.penalized,
+            score_test,
+            variance,
+            frailty_variance,
+            df,
+            iterations,
+            converged,
+        })
     }
 }
 
 #[allow(clippy::too_many_arguments)]
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn perform_cox_regression_frailty(
-    time: Vec<f64>,
-    event: Vec<i32>,
-    covariates: Vec<Vec<f64>>,
-    offset: Option<Vec<f64>>,
-    weights: Option<Vec<f64>>,
-    strata: Option<Vec<i32>>,
-    frail: Option<Vec<i32>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    event: VectorArg<'_, i32>,
+    covariates: MatrixArg<'_, f64>,
+    offset: Option<VectorArg<'_, f64>>,
+    weights: Option<VectorArg<'_, f64>>,
+    strata: Option<VectorArg<'_, i32>>,
+    frail: Option<VectorArg<'_, i32>>,
     max_iter: Option<i32>,
     eps: Option<f64>,
+    start: Option<VectorArg<'_, f64>>,
 ) -> PyResult<Py<PyAny>> {
+    let time = time.as_slice();
+    let event = event.as_slice();
+    let covariates = covariates.view();
+    let offset = offset.as_ref().map(VectorArg::as_slice);
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let strata = strata.as_ref().map(VectorArg::as_slice);
+    let frail = frail.as_ref().map(VectorArg::as_slice);
+    let start = start.as_ref().map(VectorArg::as_slice);
     let config = CoxRegressionConfig {
+        start,
         offset,
         weights,
         strata,
@@ -233,9 +537,12 @@ pub fn perform_cox_regression_frailty(
         max_iter,
         eps,
     };
-    perform_cox_regression_internal(time, event, covariates, config)
+    perform_cox_regression_internal(py, &time, &event, covariates, config)
 }
 
+/// Andersen-Gill Cox fit of `yy` = (start, stop, status), with `covar` and
+/// `frail` stored by column. A non-zero `frail` enters the model as one more
+/// covariate after the `nvar` columns of `covar`.
 #[allow(clippy::too_many_arguments)]
 pub fn agfit5(
     nused: usize,
@@ -246,75 +553,44 @@ pub fn agfit5(
     offset: &[f64],
     weights: &[f64],
     strata: &[i32],
-    sort: &[i32],
-    ptype: i32,
-    pdiag: i32,
+    _sort: &[i32],
+    _ptype: i32,
+    _pdiag: i32,
     frail: &[i32],
     max_iter: i32,
     eps: f64,
 ) -> Result<CoxResult, Box<dyn std::error::Error>> {
-    let mut state = CoxState::new(
-        nused, nvar, nfrail, yy, covar, offset, weights, strata, sort, ptype, pdiag, frail,
-    );
-
     let nvar2 = nvar + nfrail;
-    let mut beta = vec![0.0; nvar2];
-    let mut u = vec![0.0; nvar2];
-    let mut imat = vec![0.0; nvar2 * nvar2];
-    let mut loglik = 0.0;
-
-    let mut iter = 0;
-    let mut converged = false;
-
-    while iter < max_iter {
-        let old_loglik = loglik;
-
-        state.update(&mut beta, &mut u, &mut imat, &mut loglik);
-
-        if (loglik - old_loglik).abs() < eps {
-            converged = true;
-            break;
-        }
-
-        let mut imat_array = Array2::from_shape_vec((nvar2, nvar2), imat.clone())?;
-        let u_array = Array1::from_vec(u.clone());
-
-        for i in 0..nvar2 {
-            imat_array[[i, i]] += 1e-8;
-        }
-
-        match imat_array.solve_into(u_array) {
-            Ok(delta) => {
-                for i in 0..nvar2 {
-                    beta[i] += delta[i];
-                }
-            }
-            Err(_) => {
-                return Err("Failed to solve linear system".into());
-            }
-        }
-
-        iter += 1;
-    }
-
-    state.update(&mut beta, &mut u, &mut imat, &mut loglik);
-
-    let mut variance_matrix = vec![vec![0.0; nvar2]; nvar2];
-    let imat_array = Array2::from_shape_vec((nvar2, nvar2), imat)?;
-
-    match imat_array.inv() {
-        Ok(inv_imat) => {
-            for i in 0..nvar2 {
-                for j in 0..nvar2 {
-                    variance_matrix[i][j] = inv_imat[[i, j]];
-                }
-            }
+    let covariates = Array2::from_shape_fn((nused, nvar2), |(i, j)| {
+        if j < nvar {
+            covar[j * nused + i]
+        } else {
+            frail[i] as f64
         }
-        Err(_) => {
-            return Err("Failed to invert information matrix".into());
-        }
-    }
-
+    });
+    let status: Vec<bool> = yy[2 * nused..3 * nused].iter().map(|&s| s != 0.0).collect();
+    let mut levels = strata.to_vec();
+    levels.sort_unstable();
+    levels.dedup();
+    let strata: Vec<usize> = strata
+        .iter()
+        .map(|s| levels.binary_search(s).unwrap_or(0))
+        .collect();
+    let data = AgData::new(AgInput {
+        covariates: covariates.view(),
+        start: Some(&yy[..nused]),
+        stop: &yy[nused..2 * nused],
+        status: &status,
+        offset,
+        weights,
+        strata: &strata,
+        group: None,
+        method: CoxMethod::Breslow,
+    });
+    let fit = data.fit(None, Array1::zeros(nvar2), max_iter.max(0) as usize, eps)?;
+
+    let beta = fit.coef.to_vec();
+    let variance_matrix: Vec<Vec<f64>> = fit.variance.outer_iter().map(|r| r.to_vec()).collect();
     let standard_errors: Vec<f64> = (0..nvar2).map(|i| variance_matrix[i][i].sqrt()).collect();
 
     let p_values: Vec<f64> = (0..nvar2)
@@ -336,8 +612,6 @@ pub fn agfit5(
         })
         .collect();
 
-    let score: f64 = u.iter().map(|&x| x * x).sum();
-
     let wald_test: f64 = beta
         .iter()
         .zip(standard_errors.iter())
@@ -349,11 +623,11 @@ pub fn agfit5(
         standard_errors,
         p_values,
         confidence_intervals,
-        log_likelihood: loglik,
-        score,
+        log_likelihood: fit.loglik[1],
+        score: fit.score_test,
         wald_test,
-        iterations: iter,
-        converged,
+        iterations: fit.iterations as i32,
+        converged: fit.converged,
         variance_matrix,
     })
 }
@@ -379,27 +653,31 @@ fn erf(x: f64) -> f64 {
     sign * y
 }
 
+#[cfg(feature = "python")]
 #[derive(Clone, Default)]
-struct CoxRegressionConfig {
-    offset: Option<Vec<f64>>,
-    weights: Option<Vec<f64>>,
-    strata: Option<Vec<i32>>,
-    frail: Option<Vec<i32>>,
+struct CoxRegressionConfig<'a> {
+    start: Option<Cow<'a, [f64]>>,
+    offset: Option<Cow<'a, [f64]>>,
+    weights: Option<Cow<'a, [f64]>>,
+    strata: Option<Cow<'a, [i32]>>,
+    frail: Option<Cow<'a, [i32]>>,
     max_iter: Option<i32>,
     eps: Option<f64>,
 }
 
+#[cfg(feature = "python")]
 fn perform_cox_regression_internal(
-    time: Vec<f64>,
-    event: Vec<i32>,
-    covariates: Vec<Vec<f64>>,
+    py: Python<'_>,
+    time: &[f64],
+    event: &[i32],
+    covariates: ArrayView2<f64>,
     config: CoxRegressionConfig,
 ) -> PyResult<Py<PyAny>> {
     let nused = time.len();
     if nused == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
     }
-    let nvar = covariates.len();
+    let nvar = covariates.nrows();
     if nvar == 0 {
         return Err(PyRuntimeError::new_err("No covariates provided"));
     }
@@ -408,36 +686,43 @@ fn perform_cox_regression_internal(
             "Event vector length does not match time vector",
         ));
     }
-    for cov in &covariates {
-        if cov.len() != nused {
-            return Err(PyRuntimeError::new_err(
-                "Covariate vector length does not match time vector",
-            ));
-        }
+    if covariates.ncols() != nused {
+        return Err(PyRuntimeError::new_err(
+            "Covariate vector length does not match time vector",
+        ));
     }
-    let offset = config.offset.unwrap_or_else(|| vec![0.0; nused]);
-    let weights = config.weights.unwrap_or_else(|| vec![1.0; nused]);
-    let strata = config.strata.unwrap_or_else(|| vec![1; nused]);
-    let frail = config.frail.unwrap_or_else(|| vec![0; nused]);
+    if let Some(start) = &config.start
+        && start.len() != nused
+    {
+        return Err(PyRuntimeError::new_err(
+            "Start vector length does not match time vector",
+        ));
+    }
+    let offset = config.offset.unwrap_or(Cow::Owned(vec![0.0; nused]));
+    let weights = config.weights.unwrap_or(Cow::Owned(vec![1.0; nused]));
+    let strata = config.strata.unwrap_or(Cow::Owned(vec![1; nused]));
+    let frail = config.frail.unwrap_or(Cow::Owned(vec![0; nused]));
     let max_iter = config.max_iter.unwrap_or(20);
     let eps = config.eps.unwrap_or(1e-6);
     let mut yy = Vec::with_capacity(3 * nused);
-    yy.extend_from_slice(&time);
-    yy.extend_from_slice(&time);
-    yy.extend(event.iter().map(|&x| x as f64));
-    let mut covar = Vec::with_capacity(nvar * nused);
-    for i in 0..nused {
-        for covariate_row in covariates.iter().take(nvar) {
-            covar.push(covariate_row[i]);
-        }
+    match &config.start {
+        Some(start) => yy.extend_from_slice(start),
+        None => yy.extend(std::iter::repeat_n(f64::NEG_INFINITY, nused)),
     }
+    yy.extend_from_slice(time);
+    yy.extend(event.iter().map(|&x| x as f64));
+    let covar: Vec<f64> = covariates.t().iter().copied().collect();
     let sort: Vec<i32> = (1..=nused as i32).collect();
     let nfrail = if frail.iter().any(|&x| x != 0) { 1 } else { 0 };
-    match agfit5(
-        nused, nvar, nfrail, &yy, &covar, &offset, &weights, &strata, &sort, 0, 0, &frail,
-        max_iter, eps,
-    ) {
-        Ok(result) => Python::attach(|py| {
+    let fit = parallel::detach(py, || {
+        agfit5(
+            nused, nvar, nfrail, &yy, &covar, &offset, &weights, &strata, &sort, 0, 0, &frail,
+            max_iter, eps,
+        )
+        .map_err(|e| e.to_string())
+    });
+    match fit {
+        Ok(result) => {
             let dict = PyDict::new(py);
             dict.set_item("coefficients", result.coefficients)?;
             dict.set_item("standard_errors", result.standard_errors)?;
@@ -450,7 +735,7 @@ fn perform_cox_regression_internal(
             dict.set_item("converged", result.converged)?;
             dict.set_item("variance_matrix", result.variance_matrix)?;
             Ok(dict.into())
-        }),
+        }
         Err(e) => Err(PyRuntimeError::new_err(format!(
             "Cox regression failed: {}",
             e
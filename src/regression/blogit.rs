@@ -1,3 +1,4 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use statrs::distribution::{ContinuousCDF, Normal};
 
@@ -13,12 +14,13 @@ fn cloglog(p: f64) -> f64 {
     (-(1.0 - p).ln()).ln()
 }
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass)]
 pub struct LinkFunctionParams {
     #[allow(dead_code)]
     edge: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl LinkFunctionParams {
     #[new]
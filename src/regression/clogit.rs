@@ -1,16 +1,97 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+use crate::utilities::agexact::exact_set_moments;
+use crate::utilities::formula::{FormulaError, model_frame};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::utilities::persistence::impl_persist;
+use crate::validation::tests::{TestResult, likelihood_ratio_test, score_test, wald_test_matrix};
+use ndarray::{Array1, Array2, ArrayView2};
+use ndarray_linalg::{Inverse, SolveC};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
 
-#[pyclass]
-#[derive(Clone)]
+/// Matched sets whose exact likelihood would sum over more case subsets than
+/// this are rejected in favour of the Breslow or Efron approximation.
+const MAX_EXACT_SUBSETS: f64 = 1e6;
+
+#[derive(Error, Debug)]
+pub enum ClogitError {
+    #[error("cannot fit model: no observations provided")]
+    NoData,
+    #[error("cannot fit model: no covariates provided")]
+    NoCovariates,
+    #[error("covariate dimension mismatch: expected {expected}, got {got}")]
+    DimensionMismatch { expected: usize, got: usize },
+    #[error("unknown clogit method '{0}', expected 'exact', 'breslow' or 'efron'")]
+    InvalidMethod(String),
+    #[error(
+        "stratum {stratum} has {subsets:.0} case subsets; use the 'breslow' or 'efron' approximation"
+    )]
+    ExactTooLarge { stratum: usize, subsets: f64 },
+    #[error("information matrix is singular")]
+    Singular,
+}
+
+#[cfg(feature = "python")]
+impl From<ClogitError> for PyErr {
+    fn from(err: ClogitError) -> PyErr {
+        match err {
+            ClogitError::Singular => PyRuntimeError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// How tied cases within a matched set enter the conditional likelihood.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClogitMethod {
+    #[default]
+    Exact,
+    Breslow,
+    Efron,
+}
+
+impl ClogitMethod {
+    pub fn parse(method: &str) -> Result<Self, ClogitError> {
+        match method.to_lowercase().as_str() {
+            "exact" => Ok(Self::Exact),
+            "breslow" | "approximate" => Ok(Self::Breslow),
+            "efron" => Ok(Self::Efron),
+            _ => Err(ClogitError::InvalidMethod(method.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Breslow => "breslow",
+            Self::Efron => "efron",
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ClogitDataSet {
     case_control_status: Vec<u8>,
-    strata: Vec<u8>,
+    strata: Vec<usize>,
     covariates: Vec<Vec<f64>>,
 }
 
-#[pymethods]
+impl Default for ClogitDataSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ClogitDataSet {
-    #[new]
     pub fn new() -> ClogitDataSet {
         ClogitDataSet {
             case_control_status: Vec::new(),
@@ -19,7 +100,27 @@ impl ClogitDataSet {
         }
     }
 
-    pub fn add_observation(&mut self, case_control_status: u8, stratum: u8, covariates: Vec<f64>) {
+    pub fn from_formula(
+        formula: &str,
+        data: ArrayView2<f64>,
+        variable_names: &[String],
+    ) -> Result<ClogitDataSet, FormulaError> {
+        let frame = model_frame(formula, data, variable_names)?.unpenalized()?;
+        let status = frame.status.unwrap_or(frame.time);
+        let strata = frame.strata.unwrap_or_else(|| vec![0; status.len()]);
+        let mut dataset = ClogitDataSet::new();
+        for ((case, stratum), covariates) in status.into_iter().zip(strata).zip(frame.covariates) {
+            dataset.add_observation(u8::from(case != 0.0), stratum, covariates);
+        }
+        Ok(dataset)
+    }
+
+    pub fn add_observation(
+        &mut self,
+        case_control_status: u8,
+        stratum: usize,
+        covariates: Vec<f64>,
+    ) {
         self.case_control_status.push(case_control_status);
         self.strata.push(stratum);
         self.covariates.push(covariates);
@@ -36,14 +137,11 @@ impl ClogitDataSet {
             self.covariates[0].len()
         }
     }
-}
 
-impl ClogitDataSet {
     pub(crate) fn get_case_control_status(&self, id: usize) -> u8 {
         self.case_control_status[id]
     }
-    #[allow(dead_code)]
-    pub(crate) fn get_stratum(&self, id: usize) -> u8 {
+    pub(crate) fn get_stratum(&self, id: usize) -> usize {
         self.strata[id]
     }
     pub(crate) fn get_covariates(&self, id: usize) -> &Vec<f64> {
@@ -51,83 +149,333 @@ impl ClogitDataSet {
     }
 }
 
-#[pyclass]
+#[cfg(feature = "python")]
+#[pymethods]
+impl ClogitDataSet {
+    #[new]
+    fn py_new() -> Self {
+        Self::new()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_formula")]
+    fn py_from_formula(
+        formula: &str,
+        data: MatrixArg<'_, f64>,
+        variable_names: Vec<String>,
+    ) -> PyResult<Self> {
+        Ok(Self::from_formula(formula, data.view(), &variable_names)?)
+    }
+
+    #[pyo3(name = "add_observation")]
+    fn py_add_observation(
+        &mut self,
+        case_control_status: u8,
+        stratum: usize,
+        covariates: VectorArg<'_, f64>,
+    ) {
+        let covariates = covariates.into_vec();
+        self.add_observation(case_control_status, stratum, covariates);
+    }
+
+    #[pyo3(name = "get_num_observations")]
+    fn py_get_num_observations(&self) -> usize {
+        self.get_num_observations()
+    }
+
+    #[pyo3(name = "get_num_covariates")]
+    fn py_get_num_covariates(&self) -> usize {
+        self.get_num_covariates()
+    }
+}
+
+/// Log-likelihood, score and information of one matched set.
+struct SetContribution {
+    loglik: f64,
+    score: Array1<f64>,
+    information: Array2<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(module = "survival"))]
 pub struct ConditionalLogisticRegression {
     data: ClogitDataSet,
-    #[pyo3(get)]
-    coefficients: Vec<f64>,
-    #[pyo3(get, set)]
-    max_iter: u32,
-    #[pyo3(get, set)]
-    tol: f64,
-    #[pyo3(get)]
-    iterations: u32,
-    #[pyo3(get)]
-    converged: bool,
+    pub coefficients: Vec<f64>,
+    pub max_iter: u32,
+    pub tol: f64,
+    pub method: ClogitMethod,
+    pub iterations: u32,
+    pub converged: bool,
+    pub variance: Vec<Vec<f64>>,
+    pub log_likelihood: f64,
+    pub null_log_likelihood: f64,
+    null_score: Vec<f64>,
+    null_information: Vec<Vec<f64>>,
 }
 
-#[pymethods]
+impl_persist!(ConditionalLogisticRegression);
+
 impl ConditionalLogisticRegression {
-    #[new]
-    #[pyo3(signature = (data, max_iter=100, tol=1e-6))]
     pub fn new(data: ClogitDataSet, max_iter: u32, tol: f64) -> ConditionalLogisticRegression {
+        Self::with_method(data, max_iter, tol, ClogitMethod::default())
+    }
+
+    pub fn with_method(
+        data: ClogitDataSet,
+        max_iter: u32,
+        tol: f64,
+        method: ClogitMethod,
+    ) -> ConditionalLogisticRegression {
         ConditionalLogisticRegression {
             data,
             coefficients: Vec::new(),
             max_iter,
             tol,
+            method,
             iterations: 0,
             converged: false,
+            variance: Vec::new(),
+            log_likelihood: 0.0,
+            null_log_likelihood: 0.0,
+            null_score: Vec::new(),
+            null_information: Vec::new(),
         }
     }
 
-    pub fn fit(&mut self) {
-        let num_covariates = self.data.get_num_covariates();
-        if num_covariates == 0 {
-            return;
+    /// Rows of each matched set that contains both cases and controls.
+    fn matched_sets(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut sets: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for id in 0..self.data.get_num_observations() {
+            sets.entry(self.data.get_stratum(id)).or_default().push(id);
         }
-        self.coefficients = vec![0.0; num_covariates];
-        let mut old_coefficients = vec![0.0; num_covariates];
-        self.iterations = 0;
-        self.converged = false;
+        sets.into_iter()
+            .filter(|(_, rows)| {
+                let cases = rows
+                    .iter()
+                    .filter(|&&id| self.data.get_case_control_status(id) != 0)
+                    .count();
+                cases > 0 && cases < rows.len()
+            })
+            .collect()
+    }
 
-        while self.iterations < self.max_iter {
-            for covariate_idx in 0..num_covariates {
-                let mut numerator = 0.0;
-                let mut denominator = 0.0;
-                for observation in 0..self.data.get_num_observations() {
-                    let case_control_status = self.data.get_case_control_status(observation);
-                    let covariates = self.data.get_covariates(observation);
-                    let exp_sum: f64 = self
-                        .coefficients
+    fn set_contribution(&self, rows: &[usize], beta: &[f64]) -> SetContribution {
+        let p = beta.len();
+        let m = rows.len();
+        let mut covar = vec![0.0; p * m];
+        let mut eta = vec![0.0; m];
+        let mut cases = Vec::new();
+        for (local, &id) in rows.iter().enumerate() {
+            let x = self.data.get_covariates(id);
+            for (i, &value) in x.iter().enumerate() {
+                covar[i * m + local] = value;
+            }
+            eta[local] = x.iter().zip(beta).map(|(x, b)| x * b).sum();
+            if self.data.get_case_control_status(id) != 0 {
+                cases.push(local);
+            }
+        }
+        let risk: Vec<f64> = eta.iter().map(|e| e.exp()).collect();
+
+        let mut loglik: f64 = cases.iter().map(|&k| eta[k]).sum();
+        let mut score = Array1::<f64>::zeros(p);
+        for &k in &cases {
+            for i in 0..p {
+                score[i] += covar[i * m + k];
+            }
+        }
+        let mut information = Array2::<f64>::zeros((p, p));
+
+        // Adds the set's normalising term `-log(denom)` with moments `a` and
+        // lower-triangular `cmat`, scaled by `weight` copies of itself.
+        let mut subtract = |denom: f64, a: &[f64], cmat: &[f64], weight: f64| {
+            loglik -= weight * denom.ln();
+            for i in 0..p {
+                score[i] -= weight * a[i] / denom;
+                for j in 0..=i {
+                    let v = weight * (cmat[i * p + j] - a[i] * a[j] / denom) / denom;
+                    information[[i, j]] += v;
+                    if i != j {
+                        information[[j, i]] += v;
+                    }
+                }
+            }
+        };
+
+        let mut a = vec![0.0; p];
+        let mut cmat = vec![0.0; p * p];
+        let all: Vec<usize> = (0..m).collect();
+        match self.method {
+            ClogitMethod::Exact => {
+                let denom = exact_set_moments(&risk, &covar, &all, cases.len(), &mut a, &mut cmat);
+                subtract(denom, &a, &cmat, 1.0);
+            }
+            ClogitMethod::Breslow => {
+                let denom = exact_set_moments(&risk, &covar, &all, 1, &mut a, &mut cmat);
+                subtract(denom, &a, &cmat, cases.len() as f64);
+            }
+            ClogitMethod::Efron => {
+                let denom = exact_set_moments(&risk, &covar, &all, 1, &mut a, &mut cmat);
+                let mut case_a = vec![0.0; p];
+                let mut case_cmat = vec![0.0; p * p];
+                let case_denom =
+                    exact_set_moments(&risk, &covar, &cases, 1, &mut case_a, &mut case_cmat);
+                let d = cases.len() as f64;
+                for r in 0..cases.len() {
+                    let f = r as f64 / d;
+                    let shifted_a: Vec<f64> =
+                        a.iter().zip(&case_a).map(|(x, c)| x - f * c).collect();
+                    let shifted_cmat: Vec<f64> = cmat
                         .iter()
-                        .zip(covariates.iter())
-                        .map(|(coef, cov)| coef * cov)
-                        .sum();
-                    let exp = exp_sum.exp();
-                    numerator += case_control_status as f64 * covariates[covariate_idx] * exp;
-                    denominator += covariates[covariate_idx] * exp;
+                        .zip(&case_cmat)
+                        .map(|(x, c)| x - f * c)
+                        .collect();
+                    subtract(denom - f * case_denom, &shifted_a, &shifted_cmat, 1.0);
                 }
-                old_coefficients[covariate_idx] = self.coefficients[covariate_idx];
-                if denominator.abs() > 1e-10 {
-                    self.coefficients[covariate_idx] += numerator / denominator;
+            }
+        }
+
+        SetContribution {
+            loglik,
+            score,
+            information,
+        }
+    }
+
+    fn evaluate(&self, sets: &[(usize, Vec<usize>)], beta: &[f64]) -> SetContribution {
+        let p = beta.len();
+        let parts: Vec<SetContribution> = sets
+            .par_iter()
+            .map(|(_, rows)| self.set_contribution(rows, beta))
+            .collect();
+        parts.into_iter().fold(
+            SetContribution {
+                loglik: 0.0,
+                score: Array1::zeros(p),
+                information: Array2::zeros((p, p)),
+            },
+            |mut acc, part| {
+                acc.loglik += part.loglik;
+                acc.score += &part.score;
+                acc.information += &part.information;
+                acc
+            },
+        )
+    }
+
+    fn check_data(&self) -> Result<Vec<(usize, Vec<usize>)>, ClogitError> {
+        let n = self.data.get_num_observations();
+        if n == 0 {
+            return Err(ClogitError::NoData);
+        }
+        let p = self.data.get_num_covariates();
+        if p == 0 {
+            return Err(ClogitError::NoCovariates);
+        }
+        for id in 0..n {
+            let got = self.data.get_covariates(id).len();
+            if got != p {
+                return Err(ClogitError::DimensionMismatch { expected: p, got });
+            }
+        }
+        let sets = self.matched_sets();
+        if self.method == ClogitMethod::Exact {
+            for (stratum, rows) in &sets {
+                let cases = rows
+                    .iter()
+                    .filter(|&&id| self.data.get_case_control_status(id) != 0)
+                    .count();
+                let subsets =
+                    (0..cases).fold(1.0, |acc, r| acc * (rows.len() - r) as f64 / (r + 1) as f64);
+                if subsets > MAX_EXACT_SUBSETS {
+                    return Err(ClogitError::ExactTooLarge {
+                        stratum: *stratum,
+                        subsets,
+                    });
                 }
             }
-            let diff: f64 = self
-                .coefficients
-                .iter()
-                .zip(old_coefficients.iter())
-                .map(|(coef, old_coef)| (coef - old_coef).abs())
-                .sum();
+        }
+        Ok(sets)
+    }
+
+    /// Newton-Raphson maximisation of the conditional likelihood, with each
+    /// stratum a matched set, halving the step whenever the likelihood drops.
+    pub fn fit(&mut self) -> Result<(), ClogitError> {
+        let sets = self.check_data()?;
+        let p = self.data.get_num_covariates();
+
+        let mut beta = vec![0.0; p];
+        let mut current = self.evaluate(&sets, &beta);
+        self.null_log_likelihood = current.loglik;
+        self.null_score = current.score.to_vec();
+        self.null_information = current
+            .information
+            .outer_iter()
+            .map(|row| row.to_vec())
+            .collect();
+        self.iterations = 0;
+        self.converged = false;
+
+        let mut step = current
+            .information
+            .solvec(&current.score)
+            .map_err(|_| ClogitError::Singular)?;
+        while self.iterations < self.max_iter {
             self.iterations += 1;
-            if diff < self.tol {
+            let candidate: Vec<f64> = beta.iter().zip(&step).map(|(b, s)| b + s).collect();
+            let next = self.evaluate(&sets, &candidate);
+            if !next.loglik.is_finite() || next.loglik < current.loglik {
+                step.mapv_inplace(|s| s / 2.0);
+                continue;
+            }
+            let done = (1.0 - current.loglik / next.loglik).abs() <= self.tol
+                || (next.loglik - current.loglik).abs() <= self.tol;
+            beta = candidate;
+            current = next;
+            if done {
                 self.converged = true;
                 break;
             }
+            step = current
+                .information
+                .solvec(&current.score)
+                .map_err(|_| ClogitError::Singular)?;
         }
+
+        let variance = current
+            .information
+            .inv()
+            .map_err(|_| ClogitError::Singular)?;
+        self.coefficients = beta;
+        self.log_likelihood = current.loglik;
+        self.variance = variance.outer_iter().map(|row| row.to_vec()).collect();
+        Ok(())
+    }
+
+    pub fn std_errors(&self) -> Vec<f64> {
+        (0..self.variance.len())
+            .map(|i| self.variance[i][i].sqrt())
+            .collect()
     }
 
-    pub fn predict(&self, covariates: Vec<f64>) -> f64 {
+    pub fn wald_test(&self) -> TestResult {
+        wald_test_matrix(&self.coefficients, &self.variance)
+    }
+
+    pub fn likelihood_ratio_test(&self) -> TestResult {
+        likelihood_ratio_test(
+            self.log_likelihood,
+            self.null_log_likelihood,
+            self.coefficients.len(),
+        )
+    }
+
+    /// Score test of all coefficients being zero.
+    pub fn score_test(&self) -> TestResult {
+        score_test(&self.null_score, &self.null_information)
+    }
+
+    pub fn predict(&self, covariates: &[f64]) -> f64 {
         let exp_sum: f64 = self
             .coefficients
             .iter()
@@ -141,3 +489,108 @@ impl ConditionalLogisticRegression {
         self.coefficients.iter().map(|c| c.exp()).collect()
     }
 }
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ConditionalLogisticRegression {
+    #[new]
+    #[pyo3(signature = (data, max_iter=100, tol=1e-6, method="exact"))]
+    fn py_new(data: ClogitDataSet, max_iter: u32, tol: f64, method: &str) -> PyResult<Self> {
+        Ok(Self::with_method(
+            data,
+            max_iter,
+            tol,
+            ClogitMethod::parse(method)?,
+        ))
+    }
+
+    #[pyo3(name = "fit")]
+    fn py_fit(&mut self, py: Python<'_>) -> PyResult<()> {
+        Ok(parallel::detach(py, || self.fit())?)
+    }
+
+    #[pyo3(name = "predict")]
+    fn py_predict(&self, covariates: VectorArg<'_, f64>) -> f64 {
+        self.predict(&covariates.as_slice())
+    }
+
+    #[pyo3(name = "odds_ratios")]
+    fn py_odds_ratios(&self) -> Vec<f64> {
+        self.odds_ratios()
+    }
+
+    #[pyo3(name = "std_errors")]
+    fn py_std_errors(&self) -> Vec<f64> {
+        self.std_errors()
+    }
+
+    #[pyo3(name = "wald_test")]
+    fn py_wald_test(&self) -> TestResult {
+        self.wald_test()
+    }
+
+    #[pyo3(name = "likelihood_ratio_test")]
+    fn py_likelihood_ratio_test(&self) -> TestResult {
+        self.likelihood_ratio_test()
+    }
+
+    #[pyo3(name = "score_test")]
+    fn py_score_test(&self) -> TestResult {
+        self.score_test()
+    }
+
+    #[getter]
+    fn get_coefficients(&self) -> Vec<f64> {
+        self.coefficients.clone()
+    }
+
+    #[getter]
+    fn get_max_iter(&self) -> u32 {
+        self.max_iter
+    }
+
+    #[setter]
+    fn set_max_iter(&mut self, max_iter: u32) {
+        self.max_iter = max_iter;
+    }
+
+    #[getter]
+    fn get_tol(&self) -> f64 {
+        self.tol
+    }
+
+    #[setter]
+    fn set_tol(&mut self, tol: f64) {
+        self.tol = tol;
+    }
+
+    #[getter]
+    fn get_iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    #[getter]
+    fn get_converged(&self) -> bool {
+        self.converged
+    }
+
+    #[getter]
+    fn get_method(&self) -> &'static str {
+        self.method.name()
+    }
+
+    #[getter]
+    fn get_variance(&self) -> Vec<Vec<f64>> {
+        self.variance.clone()
+    }
+
+    #[getter]
+    fn get_log_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    #[getter]
+    fn get_null_log_likelihood(&self) -> f64 {
+        self.null_log_likelihood
+    }
+}
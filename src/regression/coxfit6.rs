@@ -2,6 +2,8 @@
 use ndarray::{Array1, Array2};
 use ndarray_linalg::cholesky::CholeskyInto;
 use ndarray_linalg::{Inverse, Solve};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,9 +16,10 @@ pub enum CoxError {
     NonFinite,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Method {
     Breslow,
+    #[default]
     Efron,
 }
 
@@ -31,6 +34,12 @@ pub type CoxFitResults = (
     usize,
 );
 
+struct StratumTerms {
+    loglik: f64,
+    u: Vec<f64>,
+    imat: Array2<f64>,
+}
+
 pub struct CoxFit {
     time: Array1<f64>,
     status: Array1<i32>,
@@ -98,6 +107,14 @@ impl CoxFit {
         Ok(cox)
     }
 
+    fn linear_predictor(&self, beta: &[f64], person: usize) -> f64 {
+        self.offset[person]
+            + beta
+                .iter()
+                .enumerate()
+                .fold(0.0, |acc, (i, &b)| acc + b * self.covar[(person, i)])
+    }
+
     fn scale_center(&mut self, doscale: Vec<bool>) -> Result<(), CoxError> {
         let nvar = self.covar.ncols();
         let total_weight: f64 = self.weights.sum();
@@ -145,43 +162,65 @@ impl CoxFit {
         Ok(())
     }
 
+    fn stratum_ranges(&self) -> Vec<(usize, usize)> {
+        let n = self.time.len();
+        let mut ranges = Vec::new();
+        let mut lo = 0;
+        for i in 0..n {
+            if self.strata[i] == 1 || i + 1 == n {
+                ranges.push((lo, i + 1));
+                lo = i + 1;
+            }
+        }
+        ranges
+    }
+
     fn iterate(&mut self, beta: &[f64]) -> Result<f64, CoxError> {
         let nvar = self.covar.ncols();
-        let nused = self.covar.nrows();
-        let method = self.method;
+        let ranges = self.stratum_ranges();
+        let terms: Vec<StratumTerms> = ranges
+            .par_iter()
+            .map(|&(lo, hi)| self.stratum_terms(beta, lo, hi))
+            .collect();
 
         self.u.fill(0.0);
         self.imat.fill(0.0);
+        let mut loglik = 0.0;
+        for term in &terms {
+            loglik += term.loglik;
+            for i in 0..nvar {
+                self.u[i] += term.u[i];
+            }
+            self.imat += &term.imat;
+        }
+
+        Ok(loglik)
+    }
+
+    fn stratum_terms(&self, beta: &[f64], lo: usize, hi: usize) -> StratumTerms {
+        let nvar = self.covar.ncols();
+        let method = self.method;
+
+        let mut u = vec![0.0; nvar];
+        let mut imat = Array2::zeros((nvar, nvar));
         let mut a = vec![0.0; nvar];
         let mut a2 = vec![0.0; nvar];
-        let mut cmat = Array2::zeros((nvar, nvar));
-        let mut cmat2 = Array2::zeros((nvar, nvar));
+        let mut cmat = Array2::<f64>::zeros((nvar, nvar));
+        let mut cmat2 = Array2::<f64>::zeros((nvar, nvar));
 
         let mut loglik = 0.0;
-        let mut person = nused as isize - 1;
-
-        while person >= 0 {
-            let person_idx = person as usize;
-            if self.strata[person_idx] == 1 {
-                a.fill(0.0);
-                cmat.fill(0.0);
-            }
+        let mut denom = 0.0;
+        let mut person = hi;
 
-            let dtime = self.time[person_idx];
+        while person > lo {
+            let dtime = self.time[person - 1];
             let mut ndead = 0;
             let mut deadwt = 0.0;
             let mut denom2 = 0.0;
-            let mut _nrisk = 0;
-            let mut denom = 0.0;
-
-            while person >= 0 && self.time[person as usize] == dtime {
-                let person_i = person as usize;
-                _nrisk += 1;
-                let zbeta = self.offset[person_i]
-                    + beta
-                        .iter()
-                        .enumerate()
-                        .fold(0.0, |acc, (i, &b)| acc + b * self.covar[(person_i, i)]);
+
+            while person > lo && self.time[person - 1] == dtime {
+                let person_i = person - 1;
+                let zbeta = self.linear_predictor(beta, person_i);
                 let risk = zbeta.exp() * self.weights[person_i];
 
                 if self.status[person_i] == 0 {
@@ -202,7 +241,7 @@ impl CoxFit {
 
                     #[allow(clippy::needless_range_loop)]
                     for i in 0..nvar {
-                        self.u[i] += self.weights[person_i] * self.covar[(person_i, i)];
+                        u[i] += self.weights[person_i] * self.covar[(person_i, i)];
                         a2[i] += risk * self.covar[(person_i, i)];
                         for j in 0..=i {
                             cmat2[(i, j)] +=
@@ -212,9 +251,6 @@ impl CoxFit {
                 }
 
                 person -= 1;
-                if person >= 0 && self.strata[person as usize] == 1 {
-                    break;
-                }
             }
 
             if ndead > 0 {
@@ -227,36 +263,35 @@ impl CoxFit {
                         for i in 0..nvar {
                             a[i] += a2[i];
                             let temp = a[i] / denom;
-                            self.u[i] -= deadwt * temp;
+                            u[i] -= deadwt * temp;
 
                             for j in 0..=i {
                                 cmat[(i, j)] += cmat2[(i, j)];
                                 let val = deadwt * (cmat[(i, j)] - temp * a[j]) / denom;
-                                self.imat[(j, i)] += val;
+                                imat[(j, i)] += val;
                                 if i != j {
-                                    self.imat[(i, j)] += val;
+                                    imat[(i, j)] += val;
                                 }
                             }
                         }
                     }
                     Method::Efron => {
                         let wtave = deadwt / ndead as f64;
-                        for k in 0..ndead {
-                            let _kf = k as f64;
+                        for _ in 0..ndead {
                             denom += denom2 / ndead as f64;
                             loglik -= wtave * denom.ln();
 
                             for i in 0..nvar {
                                 a[i] += a2[i] / ndead as f64;
                                 let temp = a[i] / denom;
-                                self.u[i] -= wtave * temp;
+                                u[i] -= wtave * temp;
 
                                 for j in 0..=i {
                                     cmat[(i, j)] += cmat2[(i, j)] / ndead as f64;
                                     let val = wtave * (cmat[(i, j)] - temp * a[j]) / denom;
-                                    self.imat[(j, i)] += val;
+                                    imat[(j, i)] += val;
                                     if i != j {
-                                        self.imat[(i, j)] += val;
+                                        imat[(i, j)] += val;
                                     }
                                 }
                             }
@@ -269,7 +304,7 @@ impl CoxFit {
             }
         }
 
-        Ok(loglik)
+        StratumTerms { loglik, u, imat }
     }
 
     pub fn fit(&mut self) -> Result<(), CoxError> {
@@ -329,6 +364,7 @@ impl CoxFit {
 
             if !_notfinite && ((self.loglik[1] - newlk).abs() / newlk.abs() <= self.eps) {
                 self.loglik[1] = newlk;
+                self.beta.copy_from_slice(&newbeta);
                 Self::chinv(&mut self.imat)?;
                 self.rescale_params();
                 if halving > 0 {
@@ -424,7 +460,7 @@ impl CoxFit {
             Ok(inv) => inv,
             Err(_) => return Err(CoxError::MatrixInversion),
         };
-        *mat = inv;
+        *mat = inv.t().dot(&inv);
         Ok(())
     }
 
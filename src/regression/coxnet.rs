@@ -0,0 +1,618 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::utilities::persistence::impl_persist;
+use crate::validation::crossval::{CVConfig, create_folds};
+use ndarray::ArrayView2;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CoxNetError {
+    #[error("covariate, time, status or weight dimensions do not match")]
+    DimensionMismatch,
+    #[error("no observations or covariates provided")]
+    NoData,
+    #[error("alpha must be in [0, 1], got {0}")]
+    InvalidAlpha(f64),
+    #[error("lambda values must be positive and decreasing")]
+    InvalidLambda,
+    #[error("at least 2 folds are required for cross-validation, got {0}")]
+    InvalidFolds(usize),
+}
+
+#[cfg(feature = "python")]
+impl From<CoxNetError> for PyErr {
+    fn from(err: CoxNetError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+pub struct CoxNetConfig {
+    pub alpha: f64,
+    pub n_lambda: usize,
+    pub lambda_min_ratio: Option<f64>,
+    pub lambdas: Option<Vec<f64>>,
+    pub standardize: bool,
+    pub max_iter: usize,
+    pub tol: f64,
+}
+
+impl Default for CoxNetConfig {
+    fn default() -> Self {
+        Self {
+            alpha: 1.0,
+            n_lambda: 100,
+            lambda_min_ratio: None,
+            lambdas: None,
+            standardize: true,
+            max_iter: 10000,
+            tol: 1e-7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(module = "survival", get_all))]
+pub struct CoxNetPath {
+    pub lambdas: Vec<f64>,
+    pub coefficients: Vec<Vec<f64>>,
+    pub df: Vec<usize>,
+    pub log_likelihood: Vec<f64>,
+    pub null_log_likelihood: f64,
+    pub alpha: f64,
+}
+
+impl_persist!(CoxNetPath);
+
+impl CoxNetPath {
+    pub fn linear_predictors(
+        &self,
+        covariates: ArrayView2<f64>,
+        index: usize,
+    ) -> Result<Vec<f64>, CoxNetError> {
+        let beta = &self.coefficients[index];
+        if covariates.ncols() != beta.len() {
+            return Err(CoxNetError::DimensionMismatch);
+        }
+        Ok(covariates
+            .outer_iter()
+            .map(|row| row.iter().zip(beta).map(|(x, b)| x * b).sum())
+            .collect())
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CoxNetPath {
+    #[pyo3(name = "linear_predictors")]
+    fn py_linear_predictors(
+        &self,
+        covariates: MatrixArg<'_, f64>,
+        index: usize,
+    ) -> PyResult<Vec<f64>> {
+        if index >= self.lambdas.len() {
+            return Err(PyValueError::new_err("lambda index out of range"));
+        }
+        Ok(self.linear_predictors(covariates.view(), index)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct CoxNetCVResult {
+    pub path: CoxNetPath,
+    pub cv_mean: Vec<f64>,
+    pub cv_se: Vec<f64>,
+    pub lambda_min: f64,
+    pub lambda_1se: f64,
+    pub index_min: usize,
+    pub index_1se: usize,
+    pub fold_id: Vec<usize>,
+}
+
+/// Data sorted by ascending time, with covariates stored by column.
+struct CoxNetData {
+    time: Vec<f64>,
+    status: Vec<bool>,
+    weights: Vec<f64>,
+    columns: Vec<Vec<f64>>,
+}
+
+impl CoxNetData {
+    fn new(
+        time: &[f64],
+        status: &[i32],
+        covariates: ArrayView2<f64>,
+        weights: &[f64],
+        rows: &[usize],
+    ) -> Self {
+        let mut order = rows.to_vec();
+        order.sort_by(|&a, &b| time[a].total_cmp(&time[b]));
+        let nvar = covariates.ncols();
+        Self {
+            time: order.iter().map(|&i| time[i]).collect(),
+            status: order.iter().map(|&i| status[i] != 0).collect(),
+            weights: order.iter().map(|&i| weights[i]).collect(),
+            columns: (0..nvar)
+                .map(|j| order.iter().map(|&i| covariates[[i, j]]).collect())
+                .collect(),
+        }
+    }
+
+    fn n(&self) -> usize {
+        self.time.len()
+    }
+
+    fn eta(&self, beta: &[f64]) -> Vec<f64> {
+        let mut eta = vec![0.0; self.n()];
+        for (column, &b) in self.columns.iter().zip(beta) {
+            if b != 0.0 {
+                for (e, x) in eta.iter_mut().zip(column) {
+                    *e += b * x;
+                }
+            }
+        }
+        eta
+    }
+
+    /// Breslow partial likelihood plus, when requested, the gradient and
+    /// diagonal Hessian with respect to the linear predictor.
+    fn partial_likelihood(&self, eta: &[f64], derivatives: bool) -> (f64, Vec<f64>, Vec<f64>) {
+        let n = self.n();
+        let risk: Vec<f64> = eta
+            .iter()
+            .zip(&self.weights)
+            .map(|(e, w)| w * e.exp())
+            .collect();
+
+        let mut loglik = 0.0;
+        let mut hazard = vec![0.0; n];
+        let mut hazard2 = vec![0.0; n];
+        let mut at_risk = vec![0.0; n + 1];
+        for k in (0..n).rev() {
+            at_risk[k] = at_risk[k + 1] + risk[k];
+        }
+        let mut cum_hazard = 0.0;
+        let mut cum_hazard2 = 0.0;
+        let mut i = 0;
+        while i < n {
+            let mut j = i;
+            let mut deaths = 0.0;
+            while j < n && self.time[j] == self.time[i] {
+                if self.status[j] {
+                    deaths += self.weights[j];
+                    loglik += self.weights[j] * eta[j];
+                }
+                j += 1;
+            }
+            let denominator = at_risk[i];
+            if deaths > 0.0 {
+                loglik -= deaths * denominator.ln();
+                cum_hazard += deaths / denominator;
+                cum_hazard2 += deaths / (denominator * denominator);
+            }
+            for k in i..j {
+                hazard[k] = cum_hazard;
+                hazard2[k] = cum_hazard2;
+            }
+            i = j;
+        }
+
+        if !derivatives {
+            return (loglik, Vec::new(), Vec::new());
+        }
+        let gradient = (0..n)
+            .map(|k| f64::from(u8::from(self.status[k])) * self.weights[k] - risk[k] * hazard[k])
+            .collect();
+        let hessian = (0..n)
+            .map(|k| risk[k] * hazard[k] - risk[k] * risk[k] * hazard2[k])
+            .collect();
+        (loglik, gradient, hessian)
+    }
+}
+
+fn soft_threshold(z: f64, gamma: f64) -> f64 {
+    if z > gamma {
+        z - gamma
+    } else if z < -gamma {
+        z + gamma
+    } else {
+        0.0
+    }
+}
+
+/// Centers each column and, when enabled, scales it to unit weighted
+/// variance. Returns the scale factors.
+fn standardize(data: &mut CoxNetData, enabled: bool) -> Vec<f64> {
+    let total: f64 = data.weights.iter().sum();
+    let mut scale = Vec::with_capacity(data.columns.len());
+    for column in &mut data.columns {
+        let mean = column
+            .iter()
+            .zip(&data.weights)
+            .map(|(x, w)| w * x)
+            .sum::<f64>()
+            / total;
+        let sd = (column
+            .iter()
+            .zip(&data.weights)
+            .map(|(x, w)| w * (x - mean).powi(2))
+            .sum::<f64>()
+            / total)
+            .sqrt();
+        let sd = if enabled && sd > 0.0 { sd } else { 1.0 };
+        for x in column.iter_mut() {
+            *x = (*x - mean) / sd;
+        }
+        scale.push(sd);
+    }
+    scale
+}
+
+fn lambda_sequence(data: &CoxNetData, config: &CoxNetConfig) -> Result<Vec<f64>, CoxNetError> {
+    if let Some(lambda) = &config.lambdas {
+        if lambda.is_empty()
+            || lambda.iter().any(|&l| !l.is_finite() || l <= 0.0)
+            || lambda.windows(2).any(|w| w[1] > w[0])
+        {
+            return Err(CoxNetError::InvalidLambda);
+        }
+        return Ok(lambda.clone());
+    }
+    let total: f64 = data.weights.iter().sum();
+    let eta = vec![0.0; data.n()];
+    let (_, gradient, _) = data.partial_likelihood(&eta, true);
+    let max_gradient = data
+        .columns
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .zip(&gradient)
+                .map(|(x, g)| x * g)
+                .sum::<f64>()
+                .abs()
+        })
+        .fold(0.0, f64::max);
+    let lambda_max = max_gradient / (total * config.alpha.max(1e-3));
+    let ratio = config
+        .lambda_min_ratio
+        .unwrap_or(if data.n() < data.columns.len() {
+            0.01
+        } else {
+            1e-4
+        });
+    let n_lambda = config.n_lambda.max(1);
+    Ok((0..n_lambda)
+        .map(|k| {
+            let fraction = if n_lambda == 1 {
+                0.0
+            } else {
+                k as f64 / (n_lambda - 1) as f64
+            };
+            lambda_max * ratio.powf(fraction)
+        })
+        .collect())
+}
+
+/// Proximal Newton fit at one lambda, warm-started from `beta`. Each outer
+/// step cycles coordinate descent over a weighted least-squares
+/// approximation of the partial likelihood; the fit has converged once the
+/// first cycle over a fresh approximation no longer moves the coefficients.
+fn fit_lambda(data: &CoxNetData, lambda: f64, config: &CoxNetConfig, beta: &mut [f64]) {
+    let total: f64 = data.weights.iter().sum();
+    let l1 = lambda * config.alpha;
+    let l2 = lambda * (1.0 - config.alpha);
+    let mut cycles = 0;
+
+    while cycles < config.max_iter {
+        let eta = data.eta(beta);
+        let (_, gradient, hessian) = data.partial_likelihood(&eta, true);
+        let mut residual: Vec<f64> = gradient
+            .iter()
+            .zip(&hessian)
+            .map(|(g, h)| if *h > 0.0 { g / h } else { 0.0 })
+            .collect();
+
+        let mut first_change = None;
+        while cycles < config.max_iter {
+            cycles += 1;
+            let mut max_change: f64 = 0.0;
+            for (j, column) in data.columns.iter().enumerate() {
+                let mut xwx = 0.0;
+                let mut xwr = 0.0;
+                for ((x, h), r) in column.iter().zip(&hessian).zip(&residual) {
+                    xwx += h * x * x;
+                    xwr += h * x * r;
+                }
+                xwx /= total;
+                xwr /= total;
+                if xwx <= 0.0 {
+                    continue;
+                }
+                let updated = soft_threshold(xwr + xwx * beta[j], l1) / (xwx + l2);
+                let delta = updated - beta[j];
+                if delta != 0.0 {
+                    for (r, x) in residual.iter_mut().zip(column) {
+                        *r -= delta * x;
+                    }
+                    beta[j] = updated;
+                    max_change = max_change.max(xwx * delta * delta);
+                }
+            }
+            first_change.get_or_insert(max_change);
+            if max_change < config.tol {
+                break;
+            }
+        }
+        if first_change.is_none_or(|change| change < config.tol) {
+            break;
+        }
+    }
+}
+
+fn fit_path(
+    data: &CoxNetData,
+    lambda: &[f64],
+    config: &CoxNetConfig,
+    scale: &[f64],
+) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let nvar = data.columns.len();
+    let mut beta = vec![0.0; nvar];
+    let mut coefficients = Vec::with_capacity(lambda.len());
+    let mut loglik = Vec::with_capacity(lambda.len());
+    for &l in lambda {
+        fit_lambda(data, l, config, &mut beta);
+        loglik.push(data.partial_likelihood(&data.eta(&beta), false).0);
+        coefficients.push(beta.iter().zip(scale).map(|(b, s)| b / s).collect());
+    }
+    (coefficients, loglik)
+}
+
+fn validate(
+    time: &[f64],
+    status: &[i32],
+    covariates: ArrayView2<f64>,
+    weights: Option<&[f64]>,
+    config: &CoxNetConfig,
+) -> Result<Vec<f64>, CoxNetError> {
+    let n = time.len();
+    if n == 0 || covariates.ncols() == 0 {
+        return Err(CoxNetError::NoData);
+    }
+    if status.len() != n || covariates.nrows() != n || weights.is_some_and(|w| w.len() != n) {
+        return Err(CoxNetError::DimensionMismatch);
+    }
+    if !(0.0..=1.0).contains(&config.alpha) {
+        return Err(CoxNetError::InvalidAlpha(config.alpha));
+    }
+    Ok(weights.map_or_else(|| vec![1.0; n], |w| w.to_vec()))
+}
+
+/// Elastic-net penalized Cox regression (Breslow ties) fitted by cyclic
+/// coordinate descent over a decreasing lambda path with warm starts. The
+/// penalty is lambda * (alpha * |b|_1 + (1 - alpha) / 2 * |b|_2^2) on the
+/// standardized coefficients; returned coefficients are on the original scale.
+pub fn coxnet(
+    time: &[f64],
+    status: &[i32],
+    covariates: ArrayView2<f64>,
+    weights: Option<&[f64]>,
+    config: &CoxNetConfig,
+) -> Result<CoxNetPath, CoxNetError> {
+    let weights = validate(time, status, covariates, weights, config)?;
+    let rows: Vec<usize> = (0..time.len()).collect();
+    let mut data = CoxNetData::new(time, status, covariates, &weights, &rows);
+    let scale = standardize(&mut data, config.standardize);
+    let lambda = lambda_sequence(&data, config)?;
+    let (coefficients, log_likelihood) = fit_path(&data, &lambda, config, &scale);
+
+    let null_log_likelihood = data.partial_likelihood(&vec![0.0; data.n()], false).0;
+    Ok(CoxNetPath {
+        df: coefficients
+            .iter()
+            .map(|b| b.iter().filter(|&&v| v != 0.0).count())
+            .collect(),
+        lambdas: lambda,
+        coefficients,
+        log_likelihood,
+        null_log_likelihood,
+        alpha: config.alpha,
+    })
+}
+
+/// K-fold cross-validation of the elastic-net path using the grouped partial
+/// likelihood deviance of Verweij and van Houwelingen.
+pub fn cv_coxnet(
+    time: &[f64],
+    status: &[i32],
+    covariates: ArrayView2<f64>,
+    weights: Option<&[f64]>,
+    config: &CoxNetConfig,
+    cv_config: &CVConfig,
+) -> Result<CoxNetCVResult, CoxNetError> {
+    if cv_config.n_folds < 2 {
+        return Err(CoxNetError::InvalidFolds(cv_config.n_folds));
+    }
+    let path = coxnet(time, status, covariates, weights, config)?;
+    let weights = validate(time, status, covariates, weights, config)?;
+    let n = time.len();
+    let folds = create_folds(n, cv_config.n_folds, cv_config.shuffle, cv_config.seed);
+    let mut fold_id = vec![0; n];
+    for (k, fold) in folds.iter().enumerate() {
+        for &i in fold {
+            fold_id[i] = k;
+        }
+    }
+
+    let fold_config = CoxNetConfig {
+        alpha: config.alpha,
+        n_lambda: path.lambdas.len(),
+        lambda_min_ratio: None,
+        lambdas: Some(path.lambdas.clone()),
+        standardize: config.standardize,
+        max_iter: config.max_iter,
+        tol: config.tol,
+    };
+    let all_rows: Vec<usize> = (0..n).collect();
+    let full = CoxNetData::new(time, status, covariates, &weights, &all_rows);
+
+    let deviance: Vec<(f64, Vec<f64>)> = folds
+        .par_iter()
+        .enumerate()
+        .map(|(k, fold)| {
+            let train: Vec<usize> = (0..n).filter(|&i| fold_id[i] != k).collect();
+            let mut data = CoxNetData::new(time, status, covariates, &weights, &train);
+            let raw = CoxNetData::new(time, status, covariates, &weights, &train);
+            let scale = standardize(&mut data, fold_config.standardize);
+            let (coefficients, _) = fit_path(&data, &path.lambdas, &fold_config, &scale);
+            let fold_weight: f64 = fold.iter().map(|&i| weights[i]).sum();
+            let scores = coefficients
+                .iter()
+                .map(|beta| {
+                    let full_ll = full.partial_likelihood(&full.eta(beta), false).0;
+                    let train_ll = raw.partial_likelihood(&raw.eta(beta), false).0;
+                    -2.0 * (full_ll - train_ll) / fold_weight
+                })
+                .collect();
+            (fold_weight, scores)
+        })
+        .collect();
+
+    let total_weight: f64 = deviance.iter().map(|(w, _)| w).sum();
+    let nfolds = deviance.len() as f64;
+    let nlambda = path.lambdas.len();
+    let mut cv_mean = vec![0.0; nlambda];
+    let mut cv_se = vec![0.0; nlambda];
+    for l in 0..nlambda {
+        let mean = deviance.iter().map(|(w, s)| w * s[l]).sum::<f64>() / total_weight;
+        let var = deviance
+            .iter()
+            .map(|(w, s)| w * (s[l] - mean).powi(2))
+            .sum::<f64>()
+            / total_weight;
+        cv_mean[l] = mean;
+        cv_se[l] = (var / (nfolds - 1.0)).sqrt();
+    }
+
+    let index_min = (0..nlambda)
+        .min_by(|&a, &b| cv_mean[a].total_cmp(&cv_mean[b]))
+        .unwrap_or(0);
+    let threshold = cv_mean[index_min] + cv_se[index_min];
+    let index_1se = (0..=index_min)
+        .find(|&l| cv_mean[l] <= threshold)
+        .unwrap_or(index_min);
+
+    Ok(CoxNetCVResult {
+        lambda_min: path.lambdas[index_min],
+        lambda_1se: path.lambdas[index_1se],
+        path,
+        cv_mean,
+        cv_se,
+        index_min,
+        index_1se,
+        fold_id,
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "coxnet",
+    signature = (time, status, covariates, weights=None, alpha=1.0, n_lambda=100, lambda_min_ratio=None, lambdas=None, standardize=true, max_iter=10000, tol=1e-7)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn coxnet_py(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    covariates: MatrixArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    alpha: f64,
+    n_lambda: usize,
+    lambda_min_ratio: Option<f64>,
+    lambdas: Option<VectorArg<'_, f64>>,
+    standardize: bool,
+    max_iter: usize,
+    tol: f64,
+) -> PyResult<CoxNetPath> {
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let covariates = covariates.view();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let lambdas = lambdas.map(VectorArg::into_vec);
+    let config = CoxNetConfig {
+        alpha,
+        n_lambda,
+        lambda_min_ratio,
+        lambdas,
+        standardize,
+        max_iter,
+        tol,
+    };
+    Ok(parallel::detach(py, || {
+        coxnet(&time, &status, covariates, weights.as_deref(), &config)
+    })?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "cv_coxnet",
+    signature = (time, status, covariates, weights=None, alpha=1.0, n_lambda=100, lambda_min_ratio=None, lambdas=None, standardize=true, n_folds=10, shuffle=true, seed=None, max_iter=10000, tol=1e-7)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn cv_coxnet_py(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    covariates: MatrixArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    alpha: f64,
+    n_lambda: usize,
+    lambda_min_ratio: Option<f64>,
+    lambdas: Option<VectorArg<'_, f64>>,
+    standardize: bool,
+    n_folds: usize,
+    shuffle: bool,
+    seed: Option<u64>,
+    max_iter: usize,
+    tol: f64,
+) -> PyResult<CoxNetCVResult> {
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let covariates = covariates.view();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let lambdas = lambdas.map(VectorArg::into_vec);
+    let config = CoxNetConfig {
+        alpha,
+        n_lambda,
+        lambda_min_ratio,
+        lambdas,
+        standardize,
+        max_iter,
+        tol,
+    };
+    let cv_config = CVConfig {
+        n_folds,
+        shuffle,
+        seed,
+    };
+    Ok(parallel::detach(py, || {
+        cv_coxnet(
+            &time,
+            &status,
+            covariates,
+            weights.as_deref(),
+            &config,
+            &cv_config,
+        )
+    })?)
+}
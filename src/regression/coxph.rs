@@ -1,31 +1,121 @@
-use crate::regression::coxfit6::{CoxFit, Method as CoxMethod};
-use ndarray::{Array1, Array2};
+use crate::core::pspline::difference_penalty;
+use crate::core::survpenal::PenaltyResult;
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+use crate::regression::agfit5::{AgData, AgFit, AgInput, Penalty};
+pub use crate::regression::coxfit6::Method as CoxMethod;
+use crate::regression::coxfit6::{CoxError, CoxFit};
+#[cfg(feature = "python")]
+use crate::regression::coxph_tt::extract_time_transforms;
+use crate::scoring::agscore3::agscore3;
+use crate::scoring::coxscore2::{CoxScoreData, CoxScoreParams, cox_score_residuals_internal};
+use crate::specialized::brier::{BrierError, SurvivalBrierResult, survival_brier};
+use crate::surv_analysis::agsurv5::agsurv5_sums;
+use crate::surv_analysis::coxsurv2::coxsurv2;
+use crate::utilities::cluster::{cluster, robust_variance};
+use crate::utilities::formula::{FormulaError, PenalizedTerm, SurvType, model_frame};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::utilities::persistence::impl_persist;
+use crate::validation::tests::{TestResult, likelihood_ratio_test, wald_test_matrix};
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, s};
+#[cfg(feature = "python")]
+use numpy::{IntoPyArray, PyArray1, PyArray2, ToPyArray};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ChiSquared, ContinuousCDF, Normal};
+use thiserror::Error;
+
+#[cfg(feature = "python")]
+type PyCurves<'py> = (Bound<'py, PyArray1<f64>>, Bound<'py, PyArray2<f64>>);
+
+/// Hazard ratios with the lower and upper confidence limits.
+pub type HazardRatioIntervals = (Vec<f64>, Vec<f64>, Vec<f64>);
+
+#[derive(Error, Debug)]
+pub enum CoxPHError {
+    #[error("covariate dimension mismatch: expected {expected}, got {got}")]
+    DimensionMismatch { expected: usize, got: usize },
+    #[error("cannot fit model: no data provided")]
+    NoData,
+    #[error("cannot fit model: no covariates provided")]
+    NoCovariates,
+    #[error("weights must be finite and non-negative")]
+    InvalidWeights,
+    #[error("unknown stratum: {0}")]
+    UnknownStratum(usize),
+    #[error("unknown tie method '{0}', expected 'efron' or 'breslow'")]
+    InvalidTies(String),
+    #[error("entry time must be before exit time for observation {0}")]
+    InvalidInterval(usize),
+    #[error("score residual computation failed: {0}")]
+    Residuals(String),
+    #[error("Cox fit initialization failed: {0}")]
+    Initialization(CoxError),
+    #[error("Cox fit failed: {0}")]
+    Fit(CoxError),
+    #[error("unknown confidence interval type '{0}', expected 'log', 'log-log' or 'plain'")]
+    InvalidConfType(String),
+    #[error("confidence_level must be between 0 and 1, got {0}")]
+    InvalidConfidenceLevel(f64),
+    #[error("model has not been fitted")]
+    NotFitted,
+    #[error("time transform failed: {0}")]
+    TimeTransform(String),
+    #[error("{0} is not available for (start, stop] data")]
+    CountingProcess(&'static str),
+    #[error(transparent)]
+    Brier(#[from] BrierError),
+    #[error(transparent)]
+    Formula(#[from] FormulaError),
+}
+
+#[cfg(feature = "python")]
+impl From<CoxPHError> for PyErr {
+    fn from(err: CoxPHError) -> PyErr {
+        match err {
+            CoxPHError::Initialization(_) | CoxPHError::Fit(_) => {
+                PyRuntimeError::new_err(err.to_string())
+            }
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
 
 #[derive(Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Subject {
-    #[pyo3(get, set)]
     pub id: usize,
-    #[pyo3(get, set)]
     pub covariates: Vec<f64>,
-    #[pyo3(get, set)]
     pub is_case: bool,
-    #[pyo3(get, set)]
     pub is_subcohort: bool,
-    #[pyo3(get, set)]
     pub stratum: usize,
+    pub time: f64,
+    pub entry_time: Option<f64>,
 }
 
-#[pymethods]
 impl Subject {
-    #[new]
     pub fn new(
         id: usize,
         covariates: Vec<f64>,
         is_case: bool,
         is_subcohort: bool,
         stratum: usize,
+    ) -> Self {
+        Self::new_with_time(id, covariates, is_case, is_subcohort, stratum, 0.0, None)
+    }
+
+    pub fn new_with_time(
+        id: usize,
+        covariates: Vec<f64>,
+        is_case: bool,
+        is_subcohort: bool,
+        stratum: usize,
+        time: f64,
+        entry_time: Option<f64>,
     ) -> Self {
         Self {
             id,
@@ -33,79 +123,436 @@ impl Subject {
             is_case,
             is_subcohort,
             stratum,
+            time,
+            entry_time,
         }
     }
 }
 
-#[pyclass]
+#[cfg(feature = "python")]
+#[pymethods]
+impl Subject {
+    #[new]
+    #[pyo3(signature = (id, covariates, is_case, is_subcohort, stratum, time=0.0, entry_time=None))]
+    fn py_new(
+        id: usize,
+        covariates: VectorArg<'_, f64>,
+        is_case: bool,
+        is_subcohort: bool,
+        stratum: usize,
+        time: f64,
+        entry_time: Option<f64>,
+    ) -> Self {
+        let covariates = covariates.into_vec();
+        Self::new_with_time(
+            id,
+            covariates,
+            is_case,
+            is_subcohort,
+            stratum,
+            time,
+            entry_time,
+        )
+    }
+}
+
+pub fn parse_tie_method(ties: &str) -> Result<CoxMethod, CoxPHError> {
+    match ties.to_lowercase().as_str() {
+        "efron" => Ok(CoxMethod::Efron),
+        "breslow" => Ok(CoxMethod::Breslow),
+        _ => Err(CoxPHError::InvalidTies(ties.to_string())),
+    }
+}
+
+fn rows_to_matrix(rows: &[Vec<f64>]) -> Array2<f64> {
+    let ncols = rows.first().map_or(0, Vec::len);
+    let mut matrix = Array2::<f64>::zeros((rows.len(), ncols));
+    for (i, row) in rows.iter().enumerate() {
+        for (j, &val) in row.iter().take(ncols).enumerate() {
+            matrix[[i, j]] = val;
+        }
+    }
+    matrix
+}
+
+fn matrix_to_rows(matrix: &Array2<f64>) -> Vec<Vec<f64>> {
+    matrix.outer_iter().map(|row| row.to_vec()).collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct StratumBaseline {
+    pub stratum: usize,
+    pub time: Vec<f64>,
+    pub cumulative_hazard: Vec<f64>,
+}
+
+impl StratumBaseline {
+    pub fn cumulative_hazard_at(&self, t: f64) -> f64 {
+        let idx = self.time.partition_point(|&ut| ut <= t);
+        if idx == 0 {
+            0.0
+        } else {
+            self.cumulative_hazard[idx - 1]
+        }
+    }
+}
+
+/// One row of [`CoxPHModel::coefficient_table`]; the interval is for the
+/// hazard ratio `exp_coef`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct CoefficientRow {
+    pub name: String,
+    pub coef: f64,
+    pub exp_coef: f64,
+    pub se: f64,
+    pub z: f64,
+    pub p_value: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Scale on which [`CoxPHModel::predict_survival`] builds confidence limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfType {
+    #[default]
+    Log,
+    LogLog,
+    Plain,
+}
+
+impl ConfType {
+    pub fn parse(conf_type: &str) -> Result<Self, CoxPHError> {
+        match conf_type.to_ascii_lowercase().as_str() {
+            "log" => Ok(Self::Log),
+            "log-log" | "loglog" => Ok(Self::LogLog),
+            "plain" => Ok(Self::Plain),
+            _ => Err(CoxPHError::InvalidConfType(conf_type.to_string())),
+        }
+    }
+
+    /// Limits for `survival` given the standard error of its cumulative
+    /// hazard and the normal critical value `z`.
+    fn limits(self, survival: f64, std_err: f64, z: f64) -> (f64, f64) {
+        match self {
+            Self::Log => (
+                survival * (-z * std_err).exp(),
+                (survival * (z * std_err).exp()).min(1.0),
+            ),
+            Self::LogLog => {
+                if survival <= 0.0 || survival >= 1.0 {
+                    return (survival, survival);
+                }
+                let log_s = survival.ln();
+                let centre = (-log_s).ln();
+                let width = z * std_err / log_s;
+                (
+                    (-(centre - width).exp()).exp(),
+                    (-(centre + width).exp()).exp(),
+                )
+            }
+            Self::Plain => (
+                (survival - z * std_err * survival).max(0.0),
+                (survival + z * std_err * survival).min(1.0),
+            ),
+        }
+    }
+}
+
+/// Predicted curve of one subject from [`CoxPHModel::predict_survival`].
+/// `std_err` is the standard error of the cumulative hazard, and so also of
+/// `-log(survival)`; it and the limits are `None` when not requested.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct SurvivalPrediction {
+    pub stratum: usize,
+    pub time: Vec<f64>,
+    pub survival: Vec<f64>,
+    pub cumulative_hazard: Vec<f64>,
+    pub std_err: Option<Vec<f64>>,
+    pub lower: Option<Vec<f64>>,
+    pub upper: Option<Vec<f64>>,
+}
+
+/// Cumulative baseline hazard of one stratum at its event times, with the
+/// cumulated variance increments and hazard-weighted covariate means.
+struct HazardVariance {
+    stratum: usize,
+    time: Vec<f64>,
+    hazard: Vec<f64>,
+    varhaz: Vec<f64>,
+    xbar: Array2<f64>,
+}
+
+/// Partial log-likelihood at the initial and final coefficients together with
+/// the score test at the initial coefficients and the iteration summary.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct FitSummary {
+    loglik: [f64; 2],
+    score_test: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+const MAX_PENALTY_SWEEPS: usize = 20;
+const MAX_PENALTY_ITER: usize = 50;
+const PENALTY_DF_TOL: f64 = 1e-6;
+/// Widest search for the log penalty of a `pspline()` term.
+const LOG_LAMBDA_SPAN: f64 = 30.0;
+
+/// Second-order difference penalties of the `pspline()` terms, each scaled
+/// by `lambda = theta / (1 - theta)`.
+struct SplinePenalty {
+    nvar: usize,
+    blocks: Vec<(usize, Array2<f64>)>,
+    lambda: Vec<f64>,
+}
+
+impl SplinePenalty {
+    fn new(terms: &[PenalizedTerm], nvar: usize) -> Self {
+        Self {
+            nvar,
+            blocks: terms
+                .iter()
+                .map(|term| (term.first, difference_penalty(term.columns, 2)))
+                .collect(),
+            lambda: vec![1.0; terms.len()],
+        }
+    }
+
+    fn matrix(&self) -> Array2<f64> {
+        let mut pmat = Array2::zeros((self.nvar, self.nvar));
+        for ((first, block), &lambda) in self.blocks.iter().zip(&self.lambda) {
+            let cols = *first..first + block.nrows();
+            pmat.slice_mut(s![cols.clone(), cols])
+                .scaled_add(lambda, block);
+        }
+        pmat
+    }
+
+    /// Degrees of freedom of each term: its share of the trace of the
+    /// penalized variance times the unpenalized information.
+    fn df(&self, variance: &Array2<f64>) -> Vec<f64> {
+        let vp = variance.dot(&self.matrix());
+        self.blocks
+            .iter()
+            .map(|(first, block)| {
+                (*first..first + block.nrows())
+                    .map(|j| 1.0 - vp[[j, j]])
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl Penalty for SplinePenalty {
+    fn coefficients(&self, beta: &[f64]) -> Option<PenaltyResult> {
+        let pmat = self.matrix();
+        let beta = ArrayView1::from(beta);
+        let grad = pmat.dot(&beta);
+        Some(PenaltyResult {
+            new_coef: beta.to_vec(),
+            first_deriv: grad.iter().map(|g| -g).collect(),
+            loglik_penalty: -grad.dot(&beta) / 2.0,
+            second_deriv: pmat.iter().copied().collect(),
+            flags: vec![0; self.nvar],
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(module = "survival"))]
 pub struct CoxPHModel {
     coefficients: Array2<f64>,
-    #[pyo3(get)]
     pub baseline_hazard: Vec<f64>,
-    #[pyo3(get)]
     pub risk_scores: Vec<f64>,
-    #[pyo3(get, set)]
     pub event_times: Vec<f64>,
-    #[pyo3(get, set)]
+    pub entry_times: Option<Vec<f64>>,
     pub censoring: Vec<u8>,
+    pub strata: Vec<usize>,
+    pub weights: Vec<f64>,
+    pub offsets: Vec<f64>,
+    pub cluster: Option<Vec<usize>>,
+    /// Row of the original data each row came from, for models expanded by
+    /// [`with_time_transforms`](Self::with_time_transforms).
+    #[serde(default)]
+    pub source_rows: Option<Vec<usize>>,
+    pub method: CoxMethod,
+    pub covariate_names: Vec<String>,
     covariates: Array2<f64>,
+    baselines: Vec<StratumBaseline>,
+    naive_var: Array2<f64>,
+    robust_var: Array2<f64>,
+    score_residuals: Array2<f64>,
+    #[serde(default)]
+    fit_summary: Option<FitSummary>,
+    /// Basis columns of `pspline()` terms and their target df.
+    #[serde(default)]
+    pub penalized_terms: Vec<PenalizedTerm>,
+    /// Fitted penalty of each penalized term on R's `theta` scale.
+    #[serde(default)]
+    penalty_theta: Vec<f64>,
 }
 
+impl_persist!(CoxPHModel);
+
 impl Default for CoxPHModel {
     fn default() -> Self {
         Self::new()
     }
 }
 
-#[pymethods]
 impl CoxPHModel {
-    #[new]
     pub fn new() -> Self {
         Self {
             coefficients: Array2::<f64>::zeros((1, 1)),
             baseline_hazard: Vec::new(),
             risk_scores: Vec::new(),
             event_times: Vec::new(),
+            entry_times: None,
             censoring: Vec::new(),
-            covariates: Array2::<f64>::zeros((1, 1)),
+            strata: Vec::new(),
+            weights: Vec::new(),
+            offsets: Vec::new(),
+            cluster: None,
+            source_rows: None,
+            method: CoxMethod::default(),
+            covariate_names: Vec::new(),
+            covariates: Array2::<f64>::zeros((0, 0)),
+            baselines: Vec::new(),
+            naive_var: Array2::<f64>::zeros((0, 0)),
+            robust_var: Array2::<f64>::zeros((0, 0)),
+            score_residuals: Array2::<f64>::zeros((0, 0)),
+            fit_summary: None,
+            penalized_terms: Vec::new(),
+            penalty_theta: Vec::new(),
         }
     }
 
-    #[pyo3(signature = (covariates, event_times, censoring))]
-    #[staticmethod]
     pub fn new_with_data(
         covariates: Vec<Vec<f64>>,
         event_times: Vec<f64>,
         censoring: Vec<u8>,
     ) -> Self {
-        let nrows = covariates.len();
-        let ncols = if nrows > 0 { covariates[0].len() } else { 0 };
-        let mut cov_array = Array2::<f64>::zeros((nrows, ncols));
-        for (i, row) in covariates.iter().enumerate() {
-            for (j, &val) in row.iter().enumerate() {
-                cov_array[[i, j]] = val;
-            }
-        }
+        Self::new_with_options(
+            covariates,
+            event_times,
+            censoring,
+            None,
+            None,
+            None,
+            CoxMethod::default(),
+        )
+    }
+
+    pub fn new_with_options(
+        covariates: Vec<Vec<f64>>,
+        event_times: Vec<f64>,
+        censoring: Vec<u8>,
+        strata: Option<Vec<usize>>,
+        weights: Option<Vec<f64>>,
+        offsets: Option<Vec<f64>>,
+        method: CoxMethod,
+    ) -> Self {
+        Self::new_with_covariate_matrix(
+            rows_to_matrix(&covariates),
+            event_times,
+            censoring,
+            strata,
+            weights,
+            offsets,
+            method,
+        )
+    }
+
+    /// Same as [`new_with_options`](Self::new_with_options) with the
+    /// covariates already packed one observation per row.
+    pub fn new_with_covariate_matrix(
+        covariates: Array2<f64>,
+        event_times: Vec<f64>,
+        censoring: Vec<u8>,
+        strata: Option<Vec<usize>>,
+        weights: Option<Vec<f64>>,
+        offsets: Option<Vec<f64>>,
+        method: CoxMethod,
+    ) -> Self {
+        let ncols = covariates.ncols();
+        let n = event_times.len();
         Self {
             coefficients: Array2::<f64>::zeros((ncols, 1)),
             baseline_hazard: Vec::new(),
             risk_scores: Vec::new(),
             event_times,
+            entry_times: None,
             censoring,
-            covariates: cov_array,
+            strata: strata.unwrap_or_else(|| vec![0; n]),
+            weights: weights.unwrap_or_else(|| vec![1.0; n]),
+            offsets: offsets.unwrap_or_else(|| vec![0.0; n]),
+            cluster: None,
+            source_rows: None,
+            method,
+            covariate_names: Vec::new(),
+            covariates,
+            baselines: Vec::new(),
+            naive_var: Array2::<f64>::zeros((0, 0)),
+            robust_var: Array2::<f64>::zeros((0, 0)),
+            score_residuals: Array2::<f64>::zeros((0, 0)),
+            fit_summary: None,
+            penalized_terms: Vec::new(),
+            penalty_theta: Vec::new(),
+        }
+    }
+
+    pub fn from_formula(
+        formula: &str,
+        data: ArrayView2<f64>,
+        variable_names: &[String],
+        weights: Option<Vec<f64>>,
+        method: CoxMethod,
+    ) -> Result<Self, CoxPHError> {
+        let frame = model_frame(formula, data, variable_names)?;
+        if matches!(
+            frame.surv_type,
+            Some(SurvType::Interval | SurvType::Interval2)
+        ) {
+            return Err(FormulaError::InvalidResponse(formula.to_string()).into());
         }
+        if frame.time.is_empty() {
+            return Err(CoxPHError::NoData);
+        }
+        let status = frame
+            .status
+            .ok_or_else(|| FormulaError::InvalidResponse(formula.to_string()))?;
+        let mut model = Self::new_with_options(
+            frame.covariates,
+            frame.time,
+            status.iter().map(|&s| u8::from(s != 0.0)).collect(),
+            frame.strata,
+            weights,
+            frame.offset,
+            method,
+        );
+        model.entry_times = frame.start;
+        model.cluster = frame.cluster;
+        model.covariate_names = frame.column_names;
+        model.penalized_terms = frame.penalized;
+        Ok(model)
     }
 
-    pub fn add_subject(&mut self, subject: &Subject) -> PyResult<()> {
+    pub fn add_subject(&mut self, subject: &Subject) -> Result<(), CoxPHError> {
         let n = self.event_times.len();
-        let ncols = self.covariates.ncols();
+        let ncols = if n == 0 {
+            subject.covariates.len()
+        } else {
+            self.covariates.ncols()
+        };
 
         if ncols != subject.covariates.len() {
-            return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                "covariate dimension mismatch: expected {}, got {}",
-                ncols,
-                subject.covariates.len()
-            )));
+            return Err(CoxPHError::DimensionMismatch {
+                expected: ncols,
+                got: subject.covariates.len(),
+            });
         }
 
         let mut new_covariates = Array2::<f64>::zeros((n + 1, ncols));
@@ -119,68 +566,171 @@ impl CoxPHModel {
         }
 
         self.covariates = new_covariates;
-        self.event_times.push(0.0);
+        self.event_times.push(subject.time);
+        if subject.entry_time.is_some() && self.entry_times.is_none() {
+            self.entry_times = Some(vec![f64::NEG_INFINITY; n]);
+        }
+        if let Some(entry) = self.entry_times.as_mut() {
+            entry.push(subject.entry_time.unwrap_or(f64::NEG_INFINITY));
+        }
+        if let Some(cluster) = self.cluster.as_mut() {
+            cluster.push(subject.id);
+        }
         self.censoring.push(if subject.is_case { 1 } else { 0 });
+        self.strata.push(subject.stratum);
+        self.weights.push(1.0);
+        self.offsets.push(0.0);
+        Ok(())
+    }
+
+    pub(crate) fn check_inputs(&self) -> Result<(), CoxPHError> {
+        let n = self.event_times.len();
+        for len in [
+            self.censoring.len(),
+            self.covariates.nrows(),
+            self.strata.len(),
+            self.weights.len(),
+            self.offsets.len(),
+        ] {
+            if len != n {
+                return Err(CoxPHError::DimensionMismatch {
+                    expected: n,
+                    got: len,
+                });
+            }
+        }
+        if self.weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(CoxPHError::InvalidWeights);
+        }
+        if let Some(cluster) = &self.cluster
+            && cluster.len() != n
+        {
+            return Err(CoxPHError::DimensionMismatch {
+                expected: n,
+                got: cluster.len(),
+            });
+        }
+        if let Some(entry) = &self.entry_times {
+            if entry.len() != n {
+                return Err(CoxPHError::DimensionMismatch {
+                    expected: n,
+                    got: entry.len(),
+                });
+            }
+            if let Some(i) = (0..n).find(|&i| entry[i].is_nan() || entry[i] >= self.event_times[i])
+            {
+                return Err(CoxPHError::InvalidInterval(i));
+            }
+        }
+        let nvar = self.covariates.ncols();
+        if let Some(term) = self
+            .penalized_terms
+            .iter()
+            .find(|term| term.first + term.columns > nvar)
+        {
+            return Err(CoxPHError::DimensionMismatch {
+                expected: nvar,
+                got: term.first + term.columns,
+            });
+        }
         Ok(())
     }
 
-    #[pyo3(signature = (n_iters = 20))]
-    pub fn fit(&mut self, n_iters: u16) -> PyResult<()> {
+    fn entry_time(&self, i: usize) -> f64 {
+        self.entry_times
+            .as_ref()
+            .and_then(|entry| entry.get(i).copied())
+            .unwrap_or(f64::NEG_INFINITY)
+    }
+
+    fn at_risk(&self, j: usize, i: usize) -> bool {
+        let t = self.event_times[i];
+        self.strata.get(j) == self.strata.get(i)
+            && self.entry_time(j) < t
+            && self.event_times[j] >= t
+    }
+
+    fn sorted_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.event_times.len()).collect();
+        order.sort_by(|&i, &j| {
+            self.strata[i].cmp(&self.strata[j]).then_with(|| {
+                self.event_times[i]
+                    .partial_cmp(&self.event_times[j])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+        order
+    }
+
+    pub fn fit(&mut self, n_iters: u16) -> Result<(), CoxPHError> {
         if self.event_times.is_empty() || self.covariates.nrows() == 0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "cannot fit model: no data provided",
-            ));
+            return Err(CoxPHError::NoData);
         }
 
         let n = self.event_times.len();
         let nvar = self.covariates.ncols();
 
         if nvar == 0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "cannot fit model: no covariates provided",
-            ));
+            return Err(CoxPHError::NoCovariates);
         }
-
-        let time_array = Array1::from_vec(self.event_times.clone());
-        let status_array: Array1<i32> =
-            Array1::from_vec(self.censoring.iter().map(|&x| x as i32).collect());
-        let strata = Array1::zeros(n);
-        let offset = Array1::zeros(n);
-        let weights = Array1::from_elem(n, 1.0);
-
-        let initial_beta: Vec<f64> =
-            if self.coefficients.nrows() == nvar && self.coefficients.ncols() > 0 {
-                self.coefficients.column(0).to_vec()
-            } else {
-                vec![0.0; nvar]
+        self.check_inputs()?;
+
+        let order = self.sorted_order();
+        let time_array: Array1<f64> = order.iter().map(|&i| self.event_times[i]).collect();
+        let status_array: Array1<i32> = order.iter().map(|&i| self.censoring[i] as i32).collect();
+        let offset: Array1<f64> = order.iter().map(|&i| self.offsets[i]).collect();
+        let weights: Array1<f64> = order.iter().map(|&i| self.weights[i]).collect();
+        let mut covar = Array2::<f64>::zeros((n, nvar));
+        for (row, &i) in order.iter().enumerate() {
+            covar.row_mut(row).assign(&self.covariates.row(i));
+        }
+        let mut strata = Array1::<i32>::zeros(n);
+        for (pos, pair) in order.windows(2).enumerate() {
+            if self.strata[pair[0]] != self.strata[pair[1]] {
+                strata[pos] = 1;
+            }
+        }
+        strata[n - 1] = 1;
+
+        let (beta, summary) = if self.entry_times.is_some() || !self.penalized_terms.is_empty() {
+            let fit = self.fit_agreg(n_iters as usize)?;
+            self.naive_var = fit.variance;
+            let summary = FitSummary {
+                loglik: fit.loglik,
+                score_test: fit.score_test,
+                iterations: fit.iterations,
+                converged: fit.converged,
             };
-
-        let mut cox_fit = CoxFit::new(
-            time_array,
-            status_array,
-            self.covariates.clone(),
-            strata,
-            offset,
-            weights,
-            CoxMethod::Breslow,
-            n_iters as usize,
-            1e-5,
-            1e-9,
-            vec![true; nvar],
-            initial_beta,
-        )
-        .map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "Cox fit initialization failed: {}",
-                e
-            ))
-        })?;
-
-        cox_fit.fit().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Cox fit failed: {}", e))
-        })?;
-
-        let (beta, _means, _u, _imat, _loglik, _sctest, _flag, _iter) = cox_fit.results();
+            (fit.coef.to_vec(), summary)
+        } else {
+            let mut cox_fit = CoxFit::new(
+                time_array,
+                status_array,
+                covar,
+                strata,
+                offset,
+                weights,
+                self.method,
+                n_iters as usize,
+                1e-5,
+                1e-9,
+                vec![true; nvar],
+                vec![0.0; nvar],
+            )
+            .map_err(CoxPHError::Initialization)?;
+            cox_fit.fit().map_err(CoxPHError::Fit)?;
+
+            let (beta, _means, _u, imat, loglik, sctest, flag, iter) = cox_fit.results();
+            self.naive_var = imat;
+            let summary = FitSummary {
+                loglik,
+                score_test: sctest,
+                iterations: iter,
+                converged: flag != 1000 && iter > 0,
+            };
+            (beta, summary)
+        };
+        self.fit_summary = Some(summary);
 
         let mut coefficients_array = Array2::<f64>::zeros((nvar, 1));
         for (idx, &beta_val) in beta.iter().enumerate() {
@@ -189,109 +739,317 @@ impl CoxPHModel {
         self.coefficients = coefficients_array;
 
         self.risk_scores.clear();
-        for row in self.covariates.outer_iter() {
-            let risk_score = self.coefficients.column(0).dot(&row);
+        for (row, &offset) in self.covariates.outer_iter().zip(&self.offsets) {
+            let risk_score = self.coefficients.column(0).dot(&row) + offset;
             self.risk_scores.push(risk_score.exp());
         }
 
-        self.calculate_baseline_hazard();
+        self.calculate_baseline_hazard(&order);
+        self.calculate_robust_variance(&order)?;
         Ok(())
     }
 
-    fn calculate_baseline_hazard(&mut self) {
-        let n = self.event_times.len();
-        if n == 0 {
-            self.baseline_hazard = Vec::new();
-            return;
-        }
-
-        let mut indices: Vec<usize> = (0..n).collect();
-        indices.sort_by(|&i, &j| {
-            self.event_times[i]
-                .partial_cmp(&self.event_times[j])
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| self.censoring[j].cmp(&self.censoring[i]))
+    /// Fits on the agfit5 core, for (start, stop] data and for models with
+    /// `pspline()` terms. The penalty of each term is tuned until the term
+    /// has its target degrees of freedom, as for R's `pspline(x, df)`.
+    fn fit_agreg(&mut self, n_iters: usize) -> Result<AgFit, CoxPHError> {
+        let nvar = self.covariates.ncols();
+        let status: Vec<bool> = self.censoring.iter().map(|&s| s != 0).collect();
+        let data = AgData::new(AgInput {
+            covariates: self.covariates.view(),
+            start: self.entry_times.as_deref(),
+            stop: &self.event_times,
+            status: &status,
+            offset: &self.offsets,
+            weights: &self.weights,
+            strata: &self.strata,
+            group: None,
+            method: self.method,
         });
+        let mut start = Array1::zeros(nvar);
+        if self.penalized_terms.is_empty() {
+            return data
+                .fit(None, start, n_iters, 1e-9)
+                .map_err(CoxPHError::Fit);
+        }
 
-        let mut unique_times = Vec::new();
-        let mut baseline_hazard = Vec::new();
-        let mut cum_hazard = 0.0;
-
-        for &idx in &indices {
-            if self.censoring[idx] == 0 {
-                continue;
+        let mut penalty = SplinePenalty::new(&self.penalized_terms, nvar);
+        let targets: Vec<f64> = self.penalized_terms.iter().map(|t| t.df).collect();
+        let mut fit_df = |penalty: &SplinePenalty| -> Result<(AgFit, Vec<f64>), CoxPHError> {
+            let fit = data
+                .fit(Some(penalty), start.clone(), n_iters, 1e-9)
+                .map_err(CoxPHError::Fit)?;
+            start = fit.coef.clone();
+            let df = penalty.df(&fit.variance);
+            Ok((fit, df))
+        };
+        let (_, mut df) = fit_df(&penalty)?;
+        let mut sweeps = 0;
+        while sweeps < MAX_PENALTY_SWEEPS
+            && df
+                .iter()
+                .zip(&targets)
+                .any(|(df, target)| (df - target).abs() > PENALTY_DF_TOL)
+        {
+            sweeps += 1;
+            for (k, &target) in targets.iter().enumerate() {
+                // The df fall as the penalty grows: bracket the target on the
+                // log scale, then close in by Illinois regula falsi.
+                let mut excess = |penalty: &mut SplinePenalty, log_lambda: f64| {
+                    penalty.lambda[k] = log_lambda.exp();
+                    fit_df(penalty).map(|(_, df)| df[k] - target)
+                };
+                let mut a = penalty.lambda[k].ln();
+                let mut fa = excess(&mut penalty, a)?;
+                let step = if fa > 0.0 { 2.0 } else { -2.0 };
+                let origin = a;
+                let mut b = a;
+                let mut fb = fa;
+                while fa * fb > 0.0 && (b - origin).abs() < LOG_LAMBDA_SPAN {
+                    a = b;
+                    fa = fb;
+                    b += step;
+                    fb = excess(&mut penalty, b)?;
+                }
+                let mut x = if fa.abs() < fb.abs() { a } else { b };
+                if fa * fb < 0.0 {
+                    for _ in 0..MAX_PENALTY_ITER {
+                        let c = b - fb * (b - a) / (fb - fa);
+                        let fc = excess(&mut penalty, c)?;
+                        x = c;
+                        if fc.abs() <= PENALTY_DF_TOL {
+                            break;
+                        }
+                        if fc * fb < 0.0 {
+                            a = b;
+                            fa = fb;
+                        } else {
+                            fa /= 2.0;
+                        }
+                        b = c;
+                        fb = fc;
+                    }
+                }
+                penalty.lambda[k] = x.exp();
             }
+            (_, df) = fit_df(&penalty)?;
+        }
+        // Refit from zero for the null likelihood and the score test.
+        let mut fit = data
+            .fit(Some(&penalty), Array1::zeros(nvar), n_iters, 1e-9)
+            .map_err(CoxPHError::Fit)?;
+        fit.converged &= df
+            .iter()
+            .zip(&targets)
+            .all(|(df, target)| (df - target).abs() <= PENALTY_DF_TOL);
+        self.penalty_theta = penalty.lambda.iter().map(|l| l / (1.0 + l)).collect();
+        Ok(fit)
+    }
 
-            let current_time = self.event_times[idx];
+    fn calculate_robust_variance(&mut self, order: &[usize]) -> Result<(), CoxPHError> {
+        let n = order.len();
+        let nvar = self.covariates.ncols();
+        let method = match self.method {
+            CoxMethod::Breslow => 0,
+            CoxMethod::Efron => 1,
+        };
+        let strata: Vec<i32> = order.iter().map(|&i| self.strata[i] as i32).collect();
+        let score: Vec<f64> = order.iter().map(|&i| self.risk_scores[i]).collect();
+        let weights: Vec<f64> = order.iter().map(|&i| self.weights[i]).collect();
+        let status = order.iter().map(|&i| self.censoring[i] as f64);
+        let stop = order.iter().map(|&i| self.event_times[i]);
+
+        let mut resid = Array2::<f64>::zeros((self.event_times.len(), nvar));
+        match &self.entry_times {
+            None => {
+                let y: Vec<f64> = stop.chain(status).collect();
+                let covar: Vec<f64> = order
+                    .iter()
+                    .flat_map(|&i| self.covariates.row(i).to_vec())
+                    .collect();
+                let flat = cox_score_residuals_internal(
+                    CoxScoreData {
+                        y: &y,
+                        strata: &strata,
+                        covar: &covar,
+                        score: &score,
+                        weights: &weights,
+                    },
+                    CoxScoreParams { method, n, nvar },
+                );
+                for (pos, &i) in order.iter().enumerate() {
+                    for j in 0..nvar {
+                        resid[[i, j]] = flat[pos * nvar + j];
+                    }
+                }
+            }
+            Some(entry) => {
+                let start: Vec<f64> = order.iter().map(|&i| entry[i]).collect();
+                let mut sort1: Vec<usize> = (0..n).collect();
+                sort1.sort_by(|&a, &b| {
+                    strata[a].cmp(&strata[b]).then_with(|| {
+                        start[a]
+                            .partial_cmp(&start[b])
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                });
+                let sort1: Vec<i32> = sort1.iter().map(|&k| k as i32 + 1).collect();
+                let y: Vec<f64> = start.iter().copied().chain(stop).chain(status).collect();
+                let mut covar = Vec::with_capacity(n * nvar);
+                for j in 0..nvar {
+                    covar.extend(order.iter().map(|&i| self.covariates[[i, j]]));
+                }
+                let flat = agscore3(&y, &covar, &strata, &score, &weights, method, &sort1)
+                    .map_err(CoxPHError::Residuals)?;
+                for (pos, &i) in order.iter().enumerate() {
+                    for j in 0..nvar {
+                        resid[[i, j]] = flat[j * n + pos];
+                    }
+                }
+            }
+        }
 
-            let should_add = if unique_times.is_empty() {
-                true
-            } else {
-                let last_time: f64 = unique_times[unique_times.len() - 1];
-                (current_time - last_time).abs() > 1e-9
-            };
+        self.score_residuals = resid;
+        let dfbeta = self.score_residuals.dot(&self.naive_var);
+        let groups = self.cluster.as_deref().map(cluster);
+        self.robust_var = robust_variance(&dfbeta, &self.weights, groups.as_deref());
+        Ok(())
+    }
 
-            if should_add {
-                let mut events = 0.0;
-                let mut risk_sum = 0.0;
+    fn calculate_baseline_hazard(&mut self, order: &[usize]) {
+        let n = order.len();
+        self.baselines.clear();
 
-                for &j in &indices {
-                    if self.event_times[j] >= current_time {
-                        risk_sum += self.risk_scores[j];
-                        if self.event_times[j] == current_time && self.censoring[j] == 1 {
-                            events += 1.0;
-                        }
+        let mut start = 0;
+        while start < n {
+            let stratum = self.strata[order[start]];
+            let mut end = start;
+            while end < n && self.strata[order[end]] == stratum {
+                end += 1;
+            }
+            let group = &order[start..end];
+
+            let mut by_entry = group.to_vec();
+            by_entry.sort_by(|&i, &j| {
+                self.entry_time(i)
+                    .partial_cmp(&self.entry_time(j))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            let mut next_entry = 0;
+            let mut risk_sum = 0.0;
+            let mut times = Vec::new();
+            let mut cumulative_hazard = Vec::new();
+            let mut cum_hazard = 0.0;
+
+            let mut k = 0;
+            while k < group.len() {
+                let current_time = self.event_times[group[k]];
+                while next_entry < by_entry.len()
+                    && self.entry_time(by_entry[next_entry]) < current_time
+                {
+                    let i = by_entry[next_entry];
+                    risk_sum += self.weights[i] * self.risk_scores[i];
+                    next_entry += 1;
+                }
+                let mut ndead = 0usize;
+                let mut deadwt = 0.0;
+                let mut deadrisk = 0.0;
+                let mut tied_risk = 0.0;
+                while k < group.len() && self.event_times[group[k]] == current_time {
+                    let i = group[k];
+                    let risk = self.weights[i] * self.risk_scores[i];
+                    tied_risk += risk;
+                    if self.censoring[i] != 0 {
+                        ndead += 1;
+                        deadwt += self.weights[i];
+                        deadrisk += risk;
                     }
+                    k += 1;
                 }
 
-                if risk_sum > 0.0 {
-                    let hazard = events / risk_sum;
-                    cum_hazard += hazard;
+                if ndead > 0 && risk_sum > 0.0 {
+                    cum_hazard += match self.method {
+                        CoxMethod::Breslow => deadwt / risk_sum,
+                        CoxMethod::Efron => {
+                            let wtave = deadwt / ndead as f64;
+                            (0..ndead)
+                                .map(|m| wtave / (risk_sum - m as f64 / ndead as f64 * deadrisk))
+                                .sum()
+                        }
+                    };
+                    times.push(current_time);
+                    cumulative_hazard.push(cum_hazard);
                 }
-
-                unique_times.push(current_time);
-                baseline_hazard.push(cum_hazard);
+                risk_sum -= tied_risk;
             }
+
+            self.baselines.push(StratumBaseline {
+                stratum,
+                time: times,
+                cumulative_hazard,
+            });
+            start = end;
         }
 
-        if baseline_hazard.is_empty() {
-            self.baseline_hazard = vec![0.0; n];
-        } else {
-            let mut full_baseline = vec![0.0; n];
-            for (i, &t) in self.event_times.iter().enumerate() {
-                let mut closest_hazard = 0.0;
-                for (j, &ut) in unique_times.iter().enumerate() {
-                    if ut <= t {
-                        closest_hazard = baseline_hazard[j];
+        self.baseline_hazard = (0..n)
+            .map(|i| {
+                self.stratum_baseline(self.strata[i]).map_or(0.0, |b| {
+                    let exit = b.cumulative_hazard_at(self.event_times[i]);
+                    let entry = self.entry_time(i);
+                    if entry.is_finite() {
+                        exit - b.cumulative_hazard_at(entry)
                     } else {
-                        break;
+                        exit
                     }
-                }
-                full_baseline[i] = closest_hazard;
-            }
-            self.baseline_hazard = full_baseline;
-        }
+                })
+            })
+            .collect();
     }
 
-    pub fn predict(&self, covariates: Vec<Vec<f64>>) -> Vec<f64> {
-        let nrows = covariates.len();
-        let ncols = if nrows > 0 { covariates[0].len() } else { 0 };
-        let mut cov_array = Array2::<f64>::zeros((nrows, ncols));
-        for (row_idx, row) in covariates.iter().enumerate() {
-            for (col_idx, &val) in row.iter().enumerate() {
-                cov_array[[row_idx, col_idx]] = val;
+    pub fn baseline_hazards(&self) -> &[StratumBaseline] {
+        &self.baselines
+    }
+
+    fn stratum_baseline(&self, stratum: usize) -> Option<&StratumBaseline> {
+        self.baselines.iter().find(|b| b.stratum == stratum)
+    }
+
+    fn resolve_strata(
+        &self,
+        nrows: usize,
+        strata: Option<Vec<usize>>,
+    ) -> Result<Vec<usize>, CoxPHError> {
+        let strata = match strata {
+            Some(s) => s,
+            None => {
+                let default = self.baselines.first().map_or(0, |b| b.stratum);
+                return Ok(vec![default; nrows]);
             }
+        };
+        if strata.len() != nrows {
+            return Err(CoxPHError::DimensionMismatch {
+                expected: nrows,
+                got: strata.len(),
+            });
         }
-        let mut risk_scores = Vec::new();
-        for row in cov_array.outer_iter() {
-            let risk_score = self.coefficients.column(0).dot(&row);
-            risk_scores.push(risk_score);
+        if let Some(&s) = strata.iter().find(|&&s| self.stratum_baseline(s).is_none()) {
+            return Err(CoxPHError::UnknownStratum(s));
         }
-        risk_scores
+        Ok(strata)
     }
 
-    #[getter]
-    pub fn get_coefficients(&self) -> Vec<Vec<f64>> {
+    pub fn predict(&self, covariates: Vec<Vec<f64>>) -> Vec<f64> {
+        self.predict_matrix(rows_to_matrix(&covariates).view())
+    }
+
+    pub fn predict_matrix(&self, covariates: ArrayView2<f64>) -> Vec<f64> {
+        covariates
+            .outer_iter()
+            .map(|row| self.coefficients.column(0).dot(&row))
+            .collect()
+    }
+
+    pub fn coefficients(&self) -> Vec<Vec<f64>> {
         let mut result = Vec::new();
         for col in self.coefficients.columns() {
             result.push(col.iter().cloned().collect());
@@ -299,93 +1057,275 @@ impl CoxPHModel {
         result
     }
 
-    pub fn brier_score(&self) -> f64 {
-        let mut score = 0.0;
-        let mut count = 0.0;
-        for (time, &status) in self.event_times.iter().zip(self.censoring.iter()) {
-            let pred = self.predict_survival(*time);
-            score += (pred - status as f64).powi(2);
-            count += 1.0;
+    /// Brier score of the fitted model on its own data, with predicted
+    /// survival `exp(-H0_s(t) exp(eta))` from each observation's stratum
+    /// baseline. Censoring is handled by [`survival_brier`], with the reverse
+    /// Kaplan-Meier weights; case weights are not used. `times` defaults to
+    /// the distinct event times.
+    pub fn brier_score(&self, times: Option<Vec<f64>>) -> Result<SurvivalBrierResult, CoxPHError> {
+        if self.baselines.is_empty() || self.risk_scores.len() != self.event_times.len() {
+            return Err(CoxPHError::NotFitted);
         }
-        if count > 0.0 { score / count } else { 0.0 }
-    }
-
-    fn predict_survival(&self, time: f64) -> f64 {
-        if self.baseline_hazard.is_empty() || self.risk_scores.is_empty() {
-            return 0.5;
+        if self.entry_times.is_some() {
+            return Err(CoxPHError::CountingProcess("brier_score"));
         }
-
-        let baseline_haz = self
-            .baseline_hazard
-            .iter()
-            .zip(&self.event_times)
-            .filter(|&(_, &et)| et <= time)
-            .map(|(h, _)| *h)
-            .next_back()
-            .unwrap_or(0.0);
-
-        let avg_risk = if !self.risk_scores.is_empty() {
-            self.risk_scores.iter().sum::<f64>() / self.risk_scores.len() as f64
-        } else {
-            1.0
-        };
-
-        (-baseline_haz * avg_risk).exp()
+        let times = times.unwrap_or_else(|| {
+            let mut times: Vec<f64> = self
+                .event_times
+                .iter()
+                .zip(&self.censoring)
+                .filter(|&(_, &s)| s != 0)
+                .map(|(&t, _)| t)
+                .collect();
+            times.sort_by(f64::total_cmp);
+            times.dedup();
+            times
+        });
+        let mut survival = Array2::zeros((self.event_times.len(), times.len()));
+        for ((mut row, &risk), &stratum) in survival
+            .outer_iter_mut()
+            .zip(&self.risk_scores)
+            .zip(&self.strata)
+        {
+            let baseline = self.stratum_baseline(stratum);
+            for (value, &t) in row.iter_mut().zip(&times) {
+                *value = (-baseline.map_or(0.0, |b| b.cumulative_hazard_at(t)) * risk).exp();
+            }
+        }
+        let status: Vec<i32> = self.censoring.iter().map(|&s| i32::from(s != 0)).collect();
+        Ok(survival_brier(
+            &self.event_times,
+            &status,
+            survival.view(),
+            &times,
+        )?)
     }
 
     pub fn survival_curve(
         &self,
         covariates: Vec<Vec<f64>>,
         time_points: Option<Vec<f64>>,
-    ) -> PyResult<(Vec<f64>, Vec<Vec<f64>>)> {
-        let nrows = covariates.len();
-        let ncols = if nrows > 0 { covariates[0].len() } else { 0 };
-        let mut cov_array = Array2::<f64>::zeros((nrows, ncols));
-        for (row_idx, row) in covariates.iter().enumerate() {
-            for (col_idx, &val) in row.iter().enumerate() {
-                cov_array[[row_idx, col_idx]] = val;
-            }
-        }
+        strata: Option<Vec<usize>>,
+    ) -> Result<(Vec<f64>, Vec<Vec<f64>>), CoxPHError> {
+        let times = time_points.unwrap_or_else(|| self.unique_event_times());
+
+        self.check_rows(&covariates)?;
+        let (times, survival) =
+            self.survival_curve_matrix(rows_to_matrix(&covariates).view(), Some(times), strata)?;
+        Ok((times, matrix_to_rows(&survival)))
+    }
 
-        let times = time_points.unwrap_or_else(|| {
-            let mut t = self.event_times.clone();
-            t.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-            t.dedup();
-            t
-        });
+    /// Survival curves with one row per observation in `covariates` and one
+    /// column per time point.
+    pub fn survival_curve_matrix(
+        &self,
+        covariates: ArrayView2<f64>,
+        time_points: Option<Vec<f64>>,
+        strata: Option<Vec<usize>>,
+    ) -> Result<(Vec<f64>, Array2<f64>), CoxPHError> {
+        let times = time_points.unwrap_or_else(|| self.unique_event_times());
+        let cumulative_hazards = self.cumulative_hazard_matrix_at(covariates, &times, strata)?;
+        Ok((times, cumulative_hazards.mapv(|h| (-h).exp())))
+    }
 
-        let mut risk_scores = Vec::new();
-        for row in cov_array.outer_iter() {
-            let risk = self.coefficients.column(0).dot(&row);
-            risk_scores.push(risk.exp());
+    /// Survival and cumulative hazard for each row of `covariates` at `times`
+    /// (default: the unique observed times). With `se`, the standard error
+    /// of the cumulative hazard combines the baseline hazard variance with
+    /// the variance of the coefficients, as in `survfit.coxph`, and the
+    /// limits use `conf_type`.
+    pub fn predict_survival(
+        &self,
+        covariates: ArrayView2<f64>,
+        times: Option<Vec<f64>>,
+        strata: Option<Vec<usize>>,
+        se: bool,
+        conf_type: ConfType,
+        conf_level: f64,
+    ) -> Result<Vec<SurvivalPrediction>, CoxPHError> {
+        if self.baselines.is_empty() || self.risk_scores.len() != self.event_times.len() {
+            return Err(CoxPHError::NotFitted);
         }
-
-        let mut cum_baseline_haz = Vec::new();
-        let mut cum = 0.0;
-        for &haz in &self.baseline_hazard {
-            cum += haz;
-            cum_baseline_haz.push(cum);
+        let strata = self.resolve_strata(covariates.nrows(), strata)?;
+        let nvar = self.coefficients.nrows();
+        if covariates.nrows() > 0 && covariates.ncols() != nvar {
+            return Err(CoxPHError::DimensionMismatch {
+                expected: nvar,
+                got: covariates.ncols(),
+            });
         }
+        let times = times.unwrap_or_else(|| self.unique_event_times());
+        let terms = self.hazard_variance();
+        let var = self.variance(self.cluster.is_some());
+        let z_crit = Normal::new(0.0, 1.0)
+            .map(|n| n.inverse_cdf(0.5 + conf_level / 2.0))
+            .unwrap_or(f64::NAN);
+        let beta = self.coefficients.column(0);
+
+        Ok(covariates
+            .outer_iter()
+            .zip(&strata)
+            .map(|(row, &stratum)| {
+                let risk = beta.dot(&row).exp();
+                let term = terms.iter().find(|t| t.stratum == stratum);
+                let mut cumulative_hazard = Vec::with_capacity(times.len());
+                let mut std_err = Vec::with_capacity(times.len());
+                for &t in &times {
+                    let idx = term.map_or(0, |term| term.time.partition_point(|&u| u <= t));
+                    match term {
+                        Some(term) if idx > 0 => {
+                            let h0 = term.hazard[idx - 1];
+                            cumulative_hazard.push(h0 * risk);
+                            if se {
+                                let d = row.mapv(|x| x * h0) - term.xbar.row(idx - 1);
+                                let v = term.varhaz[idx - 1] + d.dot(&var.dot(&d));
+                                std_err.push(risk * v.max(0.0).sqrt());
+                            }
+                        }
+                        _ => {
+                            cumulative_hazard.push(0.0);
+                            if se {
+                                std_err.push(0.0);
+                            }
+                        }
+                    }
+                }
+                let survival: Vec<f64> = cumulative_hazard.iter().map(|h| (-h).exp()).collect();
+                let (lower, upper) = if se {
+                    let (lower, upper) = survival
+                        .iter()
+                        .zip(&std_err)
+                        .map(|(&s, &e)| conf_type.limits(s, e, z_crit))
+                        .unzip();
+                    (Some(lower), Some(upper))
+                } else {
+                    (None, None)
+                };
+                SurvivalPrediction {
+                    stratum,
+                    time: times.clone(),
+                    survival,
+                    cumulative_hazard,
+                    std_err: se.then_some(std_err),
+                    lower,
+                    upper,
+                }
+            })
+            .collect())
+    }
 
-        let mut survival_curves = Vec::new();
-        for risk_exp in &risk_scores {
-            let mut surv = Vec::new();
-            for &t in &times {
-                let baseline_haz = self
-                    .baseline_hazard
-                    .iter()
-                    .zip(&self.event_times)
-                    .filter(|&(_, et)| *et <= t)
-                    .map(|(h, _)| *h)
-                    .sum::<f64>();
-
-                let s = (-baseline_haz * risk_exp).exp();
-                surv.push(s);
-            }
-            survival_curves.push(surv);
+    /// Per-stratum hazard and variance terms from the `coxsurv2` counts,
+    /// with Efron ties handled by `agsurv5`.
+    fn hazard_variance(&self) -> Vec<HazardVariance> {
+        let n = self.event_times.len();
+        let nvar = self.covariates.ncols();
+        let mut levels = self.strata.clone();
+        levels.sort_unstable();
+        levels.dedup();
+        let mut otime: Vec<f64> = (0..n)
+            .filter(|&i| self.censoring[i] != 0)
+            .map(|i| self.event_times[i])
+            .collect();
+        otime.sort_by(|a, b| a.total_cmp(b));
+        otime.dedup();
+        if otime.is_empty() {
+            return Vec::new();
         }
 
-        Ok((times, survival_curves))
+        let trans: Vec<i32> = self
+            .strata
+            .iter()
+            .map(|s| levels.binary_search(s).unwrap_or(0) as i32)
+            .collect();
+        let y = Array2::from_shape_fn((n, 3), |(i, j)| match j {
+            0 => self.entry_time(i),
+            1 => self.event_times[i],
+            _ => f64::from(self.censoring[i] != 0),
+        });
+        let mut sort1: Vec<usize> = (0..n).collect();
+        sort1.sort_by(|&i, &j| {
+            trans[i]
+                .cmp(&trans[j])
+                .then_with(|| self.entry_time(i).total_cmp(&self.entry_time(j)))
+        });
+        let mut sort2: Vec<usize> = (0..n).collect();
+        sort2.sort_by(|&i, &j| {
+            trans[i]
+                .cmp(&trans[j])
+                .then_with(|| self.event_times[i].total_cmp(&self.event_times[j]))
+        });
+        let (_, count, xmean, xsum2) = coxsurv2(
+            &otime,
+            y.view(),
+            &self.weights,
+            &sort1,
+            &sort2,
+            &vec![1; n],
+            &trans,
+            self.covariates.view(),
+            &self.risk_scores,
+        );
+
+        let ntime = otime.len();
+        levels
+            .iter()
+            .enumerate()
+            .map(|(b, &stratum)| {
+                let rows: Vec<usize> = (b * ntime..(b + 1) * ntime)
+                    .filter(|&r| count[[r, 3]] > 0.0 && count[[r, 2]] > 0.0)
+                    .collect();
+                let m = rows.len();
+                let nrisk: Vec<f64> = rows.iter().map(|&r| count[[r, 2]]).collect();
+                let nevent: Vec<f64> = rows.iter().map(|&r| count[[r, 4]]).collect();
+                let mut hazard = vec![0.0; m];
+                let mut varhaz = vec![0.0; m];
+                let mut xbar = Array2::zeros((m, nvar));
+                match self.method {
+                    CoxMethod::Breslow => {
+                        for (i, &r) in rows.iter().enumerate() {
+                            hazard[i] = nevent[i] / nrisk[i];
+                            varhaz[i] = nevent[i] / nrisk[i].powi(2);
+                            for k in 0..nvar {
+                                xbar[[i, k]] = xmean[[r, k]] * hazard[i];
+                            }
+                        }
+                    }
+                    CoxMethod::Efron => {
+                        let ndeath: Vec<i32> = rows.iter().map(|&r| count[[r, 3]] as i32).collect();
+                        let erisk: Vec<f64> = rows.iter().map(|&r| count[[r, 5]]).collect();
+                        let column_major = |f: &dyn Fn(usize, usize) -> f64| -> Vec<f64> {
+                            (0..nvar)
+                                .flat_map(|k| rows.iter().map(move |&r| f(r, k)))
+                                .collect()
+                        };
+                        let xsum = column_major(&|r, k| xmean[[r, k]] * count[[r, 2]]);
+                        let xsum_dead = column_major(&|r, k| xsum2[[r, k]]);
+                        let (sum1, sum2, xb) =
+                            agsurv5_sums(nvar, &ndeath, &nrisk, &erisk, &xsum, &xsum_dead);
+                        for i in 0..m {
+                            hazard[i] = nevent[i] * sum1[i];
+                            varhaz[i] = nevent[i] * sum2[i];
+                            for k in 0..nvar {
+                                xbar[[i, k]] = nevent[i] * xb[i + m * k];
+                            }
+                        }
+                    }
+                }
+                for i in 1..m {
+                    hazard[i] += hazard[i - 1];
+                    varhaz[i] += varhaz[i - 1];
+                    for k in 0..nvar {
+                        xbar[[i, k]] += xbar[[i - 1, k]];
+                    }
+                }
+                HazardVariance {
+                    stratum,
+                    time: rows.iter().map(|&r| otime[r - b * ntime]).collect(),
+                    hazard,
+                    varhaz,
+                    xbar,
+                }
+            })
+            .collect()
     }
 
     pub fn hazard_ratios(&self) -> Vec<f64> {
@@ -396,85 +1336,104 @@ impl CoxPHModel {
             .collect()
     }
 
-    #[pyo3(signature = (confidence_level = 0.95))]
-    pub fn hazard_ratios_with_ci(&self, confidence_level: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    pub fn hazard_ratios_with_ci(
+        &self,
+        confidence_level: f64,
+        robust: bool,
+    ) -> Result<HazardRatioIntervals, CoxPHError> {
+        if !(confidence_level > 0.0 && confidence_level < 1.0) {
+            return Err(CoxPHError::InvalidConfidenceLevel(confidence_level));
+        }
         let coefs: Vec<f64> = self.coefficients.column(0).to_vec();
         let n = coefs.len();
+        let z = Normal::standard().inverse_cdf(0.5 + confidence_level / 2.0);
 
-        let z = if confidence_level >= 0.99 {
-            2.576
-        } else if confidence_level >= 0.95 {
-            1.96
-        } else {
-            1.645
-        };
-
-        let se = self.compute_standard_errors();
+        let se = self.standard_errors(robust);
 
         let mut hr = Vec::with_capacity(n);
         let mut ci_lower = Vec::with_capacity(n);
         let mut ci_upper = Vec::with_capacity(n);
 
         for (i, &beta) in coefs.iter().enumerate() {
-            let se_i = se.get(i).copied().unwrap_or(0.1);
+            let se_i = se.get(i).copied().unwrap_or(f64::NAN);
             hr.push(beta.exp());
             ci_lower.push((beta - z * se_i).exp());
             ci_upper.push((beta + z * se_i).exp());
         }
 
-        (hr, ci_lower, ci_upper)
+        Ok((hr, ci_lower, ci_upper))
     }
 
-    fn compute_standard_errors(&self) -> Vec<f64> {
-        let n = self.event_times.len();
-        let nvar = self.coefficients.nrows();
-
-        if n == 0 || nvar == 0 {
-            return vec![0.1; nvar];
+    fn variance(&self, robust: bool) -> &Array2<f64> {
+        if robust {
+            &self.robust_var
+        } else {
+            &self.naive_var
         }
+    }
 
-        let mut fisher_diag = vec![0.0; nvar];
-
-        for i in 0..n {
-            if self.censoring[i] == 0 {
-                continue;
-            }
-
-            let risk_set_sum: f64 = (0..n)
-                .filter(|&j| self.event_times[j] >= self.event_times[i])
-                .map(|j| self.risk_scores.get(j).copied().unwrap_or(1.0))
-                .sum();
+    pub fn naive_var(&self) -> Vec<Vec<f64>> {
+        self.naive_var
+            .outer_iter()
+            .map(|row| row.to_vec())
+            .collect()
+    }
 
-            if risk_set_sum <= 0.0 {
-                continue;
-            }
+    pub fn robust_var(&self) -> Vec<Vec<f64>> {
+        self.robust_var
+            .outer_iter()
+            .map(|row| row.to_vec())
+            .collect()
+    }
 
-            for (k, fisher_k) in fisher_diag.iter_mut().enumerate() {
-                let mut weighted_cov = 0.0;
-                let mut weighted_cov_sq = 0.0;
+    pub(crate) fn covariate_matrix(&self) -> &Array2<f64> {
+        &self.covariates
+    }
 
-                for j in 0..n {
-                    if self.event_times[j] >= self.event_times[i] {
-                        let risk_j = self.risk_scores.get(j).copied().unwrap_or(1.0);
-                        let cov_jk = self.covariates.get([j, k]).copied().unwrap_or(0.0);
-                        weighted_cov += risk_j * cov_jk;
-                        weighted_cov_sq += risk_j * cov_jk * cov_jk;
-                    }
-                }
+    pub(crate) fn set_robust_var(&mut self, var: Array2<f64>) {
+        self.robust_var = var;
+    }
 
-                let mean_cov = weighted_cov / risk_set_sum;
-                let var_cov = weighted_cov_sq / risk_set_sum - mean_cov * mean_cov;
-                *fisher_k += var_cov;
-            }
+    pub fn standard_errors(&self, robust: bool) -> Vec<f64> {
+        let nvar = self.coefficients.nrows();
+        let var = self.variance(robust);
+        if var.nrows() != nvar {
+            return vec![f64::NAN; nvar];
         }
+        var.diag().iter().map(|&v| v.sqrt()).collect()
+    }
 
-        fisher_diag
-            .iter()
-            .map(|&f| if f > 0.0 { (1.0 / f).sqrt() } else { 0.1 })
+    pub fn wald_test(&self, robust: bool) -> TestResult {
+        if self.variance(robust).nrows() != self.coefficients.nrows() {
+            return TestResult {
+                statistic: f64::NAN,
+                df: self.coefficients.nrows(),
+                p_value: f64::NAN,
+                test_name: "WaldTest".to_string(),
+            };
+        }
+        wald_test_matrix(
+            &self.coefficients.column(0).to_vec(),
+            &self
+                .variance(robust)
+                .outer_iter()
+                .map(|row| row.to_vec())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn score_residuals(&self) -> Vec<Vec<f64>> {
+        self.score_residuals
+            .outer_iter()
+            .map(|row| row.to_vec())
             .collect()
     }
 
+    /// Partial log-likelihood at the fitted coefficients.
     pub fn log_likelihood(&self) -> f64 {
+        if let Some(summary) = &self.fit_summary {
+            return summary.loglik[1];
+        }
         if self.event_times.is_empty() || self.risk_scores.is_empty() {
             return 0.0;
         }
@@ -490,7 +1449,7 @@ impl CoxPHModel {
             let risk_score_i = self.risk_scores.get(i).copied().unwrap_or(1.0).ln();
 
             let risk_set_sum: f64 = (0..n)
-                .filter(|&j| self.event_times[j] >= self.event_times[i])
+                .filter(|&j| self.at_risk(j, i))
                 .map(|j| self.risk_scores.get(j).copied().unwrap_or(1.0))
                 .sum();
 
@@ -502,6 +1461,88 @@ impl CoxPHModel {
         loglik
     }
 
+    /// Partial log-likelihood of the null model with all coefficients zero.
+    pub fn null_log_likelihood(&self) -> Option<f64> {
+        self.fit_summary.map(|s| s.loglik[0])
+    }
+
+    pub fn iterations(&self) -> Option<usize> {
+        self.fit_summary.map(|s| s.iterations)
+    }
+
+    pub fn converged(&self) -> bool {
+        self.fit_summary.is_some_and(|s| s.converged)
+    }
+
+    /// Penalty of each of `penalized_terms` after the fit, as R's `theta`:
+    /// the difference penalty is scaled by `theta / (1 - theta)`.
+    pub fn penalty_theta(&self) -> &[f64] {
+        &self.penalty_theta
+    }
+
+    /// Global likelihood-ratio test of all coefficients being zero.
+    pub fn likelihood_ratio_test(&self) -> TestResult {
+        let df = self.coefficients.nrows();
+        match self.fit_summary {
+            Some(s) => likelihood_ratio_test(s.loglik[1], s.loglik[0], df),
+            None => TestResult {
+                statistic: f64::NAN,
+                df,
+                p_value: f64::NAN,
+                test_name: "LikelihoodRatioTest".to_string(),
+            },
+        }
+    }
+
+    /// Global score (log-rank) test of all coefficients being zero.
+    pub fn score_test(&self) -> TestResult {
+        let df = self.coefficients.nrows();
+        let statistic = self.fit_summary.map_or(f64::NAN, |s| s.score_test);
+        let p_value = match ChiSquared::new(df as f64) {
+            Ok(chi2) if statistic.is_finite() => chi2.sf(statistic),
+            _ => f64::NAN,
+        };
+        TestResult {
+            statistic,
+            df,
+            p_value,
+            test_name: "ScoreTest".to_string(),
+        }
+    }
+
+    /// Coefficients with hazard ratios, standard errors, Wald z statistics,
+    /// two-sided p-values and hazard ratio confidence intervals.
+    pub fn coefficient_table(&self, confidence_level: f64, robust: bool) -> Vec<CoefficientRow> {
+        let z_crit = Normal::new(0.0, 1.0)
+            .map(|n| n.inverse_cdf(0.5 + confidence_level / 2.0))
+            .unwrap_or(f64::NAN);
+        let normal = Normal::new(0.0, 1.0).ok();
+        let se = self.standard_errors(robust);
+        self.coefficients
+            .column(0)
+            .iter()
+            .zip(se)
+            .enumerate()
+            .map(|(i, (&coef, se))| {
+                let z = coef / se;
+                CoefficientRow {
+                    name: self
+                        .covariate_names
+                        .get(i)
+                        .cloned()
+                        .unwrap_or_else(|| format!("var{}", i)),
+                    coef,
+                    exp_coef: coef.exp(),
+                    se,
+                    z,
+                    p_value: normal.map_or(f64::NAN, |n| 2.0 * n.sf(z.abs())),
+                    lower: (coef - z_crit * se).exp(),
+                    upper: (coef + z_crit * se).exp(),
+                }
+            })
+            .collect()
+    }
+
     pub fn aic(&self) -> f64 {
         let k = self.coefficients.nrows() as f64;
         -2.0 * self.log_likelihood() + 2.0 * k
@@ -513,60 +1554,90 @@ impl CoxPHModel {
         -2.0 * self.log_likelihood() + k * n.ln()
     }
 
-    pub fn cumulative_hazard(&self, covariates: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
-        let nrows = covariates.len();
-        let ncols = if nrows > 0 { covariates[0].len() } else { 0 };
-        let mut cov_array = Array2::<f64>::zeros((nrows, ncols));
-        for (row_idx, row) in covariates.iter().enumerate() {
-            for (col_idx, &val) in row.iter().enumerate() {
-                cov_array[[row_idx, col_idx]] = val;
-            }
-        }
+    pub fn cumulative_hazard(
+        &self,
+        covariates: Vec<Vec<f64>>,
+        strata: Option<Vec<usize>>,
+    ) -> Result<(Vec<f64>, Vec<Vec<f64>>), CoxPHError> {
+        self.check_rows(&covariates)?;
+        let (times, cumulative_hazards) =
+            self.cumulative_hazard_matrix(rows_to_matrix(&covariates).view(), strata)?;
+        Ok((times, matrix_to_rows(&cumulative_hazards)))
+    }
+
+    /// Cumulative hazards at the unique event times, one row per observation
+    /// in `covariates`.
+    pub fn cumulative_hazard_matrix(
+        &self,
+        covariates: ArrayView2<f64>,
+        strata: Option<Vec<usize>>,
+    ) -> Result<(Vec<f64>, Array2<f64>), CoxPHError> {
+        let times = self.unique_event_times();
+        let cumulative_hazards = self.cumulative_hazard_matrix_at(covariates, &times, strata)?;
+        Ok((times, cumulative_hazards))
+    }
 
-        let mut unique_times: Vec<f64> = self.event_times.clone();
-        unique_times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        unique_times.dedup();
+    fn unique_event_times(&self) -> Vec<f64> {
+        let mut times = self.event_times.clone();
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        times.dedup();
+        times
+    }
 
-        let mut risk_scores = Vec::new();
-        for row in cov_array.outer_iter() {
-            let risk = self.coefficients.column(0).dot(&row);
-            risk_scores.push(risk.exp());
+    fn check_rows(&self, covariates: &[Vec<f64>]) -> Result<(), CoxPHError> {
+        let nvar = self.coefficients.nrows();
+        match covariates.iter().find(|row| row.len() != nvar) {
+            Some(row) => Err(CoxPHError::DimensionMismatch {
+                expected: nvar,
+                got: row.len(),
+            }),
+            None => Ok(()),
         }
+    }
 
-        let mut cumulative_hazards = Vec::new();
-        for risk_exp in &risk_scores {
-            let mut cum_haz = Vec::new();
-            for &t in &unique_times {
-                let baseline_haz = self
-                    .baseline_hazard
-                    .iter()
-                    .zip(&self.event_times)
-                    .filter(|&(_, et)| *et <= t)
-                    .map(|(h, _)| *h)
-                    .sum::<f64>();
-                cum_haz.push(baseline_haz * risk_exp);
+    fn cumulative_hazard_matrix_at(
+        &self,
+        covariates: ArrayView2<f64>,
+        times: &[f64],
+        strata: Option<Vec<usize>>,
+    ) -> Result<Array2<f64>, CoxPHError> {
+        let strata = self.resolve_strata(covariates.nrows(), strata)?;
+        let nvar = self.coefficients.nrows();
+        if covariates.nrows() > 0 && covariates.ncols() != nvar {
+            return Err(CoxPHError::DimensionMismatch {
+                expected: nvar,
+                got: covariates.ncols(),
+            });
+        }
+
+        let mut cumulative_hazards = Array2::zeros((covariates.nrows(), times.len()));
+        for ((row, &stratum), mut out) in covariates
+            .outer_iter()
+            .zip(&strata)
+            .zip(cumulative_hazards.outer_iter_mut())
+        {
+            let risk_exp = self.coefficients.column(0).dot(&row).exp();
+            let baseline = self.stratum_baseline(stratum);
+            for (value, &t) in out.iter_mut().zip(times) {
+                *value = baseline.map_or(0.0, |b| b.cumulative_hazard_at(t)) * risk_exp;
             }
-            cumulative_hazards.push(cum_haz);
         }
 
-        (unique_times, cumulative_hazards)
+        Ok(cumulative_hazards)
     }
 
-    #[pyo3(signature = (covariates, percentile = 0.5))]
     pub fn predicted_survival_time(
         &self,
-        covariates: Vec<Vec<f64>>,
+        covariates: ArrayView2<f64>,
         percentile: f64,
-    ) -> Vec<Option<f64>> {
-        let (times, survival_curves) = match self.survival_curve(covariates, None) {
-            Ok(result) => result,
-            Err(_) => return vec![],
-        };
+        strata: Option<Vec<usize>>,
+    ) -> Result<Vec<Option<f64>>, CoxPHError> {
+        let (times, survival_curves) = self.survival_curve_matrix(covariates, None, strata)?;
 
         let target_survival = 1.0 - percentile;
 
-        survival_curves
-            .iter()
+        Ok(survival_curves
+            .outer_iter()
             .map(|surv| {
                 for (i, &s) in surv.iter().enumerate() {
                     if s <= target_survival {
@@ -583,17 +1654,19 @@ impl CoxPHModel {
                 }
                 None
             })
-            .collect()
+            .collect())
     }
 
-    pub fn restricted_mean_survival_time(&self, covariates: Vec<Vec<f64>>, tau: f64) -> Vec<f64> {
-        let (times, survival_curves) = match self.survival_curve(covariates, None) {
-            Ok(result) => result,
-            Err(_) => return vec![],
-        };
-
-        survival_curves
-            .iter()
+    pub fn restricted_mean_survival_time(
+        &self,
+        covariates: ArrayView2<f64>,
+        tau: f64,
+        strata: Option<Vec<usize>>,
+    ) -> Result<Vec<f64>, CoxPHError> {
+        let (times, survival_curves) = self.survival_curve_matrix(covariates, None, strata)?;
+
+        Ok(survival_curves
+            .outer_iter()
             .map(|surv| {
                 let mut rmst = 0.0;
                 let mut prev_time = 0.0;
@@ -614,7 +1687,7 @@ impl CoxPHModel {
                 }
                 rmst
             })
-            .collect()
+            .collect())
     }
 
     pub fn martingale_residuals(&self) -> Vec<f64> {
@@ -646,42 +1719,14 @@ impl CoxPHModel {
     }
 
     pub fn dfbeta(&self) -> Vec<Vec<f64>> {
-        let n = self.event_times.len();
-        let nvar = self.coefficients.nrows();
-
-        if n == 0 || nvar == 0 {
-            return vec![];
-        }
-
-        let martingale = self.martingale_residuals();
-        let mut dfbeta = vec![vec![0.0; nvar]; n];
-
-        for (i, (&mart_i, dfbeta_row)) in martingale.iter().zip(dfbeta.iter_mut()).enumerate() {
-            for (k, dfbeta_cell) in dfbeta_row.iter_mut().enumerate() {
-                let cov_ik = self.covariates.get([i, k]).copied().unwrap_or(0.0);
-                let risk_i = self.risk_scores.get(i).copied().unwrap_or(1.0);
-
-                let mut weighted_mean = 0.0;
-                let mut risk_sum = 0.0;
-
-                for j in 0..n {
-                    if self.event_times[j] >= self.event_times[i] {
-                        let risk_j = self.risk_scores.get(j).copied().unwrap_or(1.0);
-                        let cov_jk = self.covariates.get([j, k]).copied().unwrap_or(0.0);
-                        weighted_mean += risk_j * cov_jk;
-                        risk_sum += risk_j;
-                    }
-                }
-
-                if risk_sum > 0.0 {
-                    weighted_mean /= risk_sum;
-                }
+        matrix_to_rows(&self.dfbeta_matrix())
+    }
 
-                *dfbeta_cell = mart_i * (cov_ik - weighted_mean) / risk_i.max(1e-10);
-            }
+    fn dfbeta_matrix(&self) -> Array2<f64> {
+        if self.score_residuals.ncols() != self.naive_var.nrows() {
+            return Array2::zeros((0, 0));
         }
-
-        dfbeta
+        self.score_residuals.dot(&self.naive_var)
     }
 
     pub fn n_events(&self) -> usize {
@@ -693,38 +1738,513 @@ impl CoxPHModel {
     }
 
     pub fn summary(&self) -> String {
-        let nvar = self.coefficients.nrows();
         let n_obs = self.n_observations();
         let n_events = self.n_events();
-        let loglik = self.log_likelihood();
-        let aic = self.aic();
+        let robust = self.cluster.is_some();
 
         let mut result = String::new();
         result.push_str("Cox Proportional Hazards Model\n");
         result.push_str("================================\n");
         result.push_str(&format!("n={}, events={}\n\n", n_obs, n_events));
-        result.push_str(&format!("Log-likelihood: {:.4}\n", loglik));
-        result.push_str(&format!("AIC: {:.4}\n\n", aic));
-
-        let hrs = self.hazard_ratios();
-        let (_, ci_lower, ci_upper) = self.hazard_ratios_with_ci(0.95);
 
         result.push_str(&format!(
-            "{:<10} {:>10} {:>10} {:>10}\n",
-            "Variable", "HR", "CI_Lower", "CI_Upper"
+            "{:<10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+            "Variable",
+            "coef",
+            "exp(coef)",
+            if robust { "robust se" } else { "se(coef)" },
+            "z",
+            "Pr(>|z|)",
+            "lower .95",
+            "upper .95"
         ));
-        result.push_str(&format!("{:-<43}\n", ""));
+        result.push_str(&format!("{:-<87}\n", ""));
+        for row in self.coefficient_table(0.95, robust) {
+            result.push_str(&format!(
+                "{:<10} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4}\n",
+                row.name, row.coef, row.exp_coef, row.se, row.z, row.p_value, row.lower, row.upper
+            ));
+        }
 
-        for i in 0..nvar {
+        result.push_str(&format!(
+            "\nLog-likelihood: {:.4} (null {:.4})\n",
+            self.log_likelihood(),
+            self.null_log_likelihood().unwrap_or(f64::NAN)
+        ));
+        result.push_str(&format!("AIC: {:.4}\n", self.aic()));
+        if let Some(iterations) = self.iterations() {
+            result.push_str(&format!(
+                "Iterations: {}{}\n",
+                iterations,
+                if self.converged() {
+                    ""
+                } else {
+                    " (not converged)"
+                }
+            ));
+        }
+        result.push('\n');
+        for test in [
+            self.likelihood_ratio_test(),
+            self.wald_test(robust),
+            self.score_test(),
+        ] {
             result.push_str(&format!(
-                "var{:<7} {:>10.4} {:>10.4} {:>10.4}\n",
-                i,
-                hrs.get(i).copied().unwrap_or(f64::NAN),
-                ci_lower.get(i).copied().unwrap_or(f64::NAN),
-                ci_upper.get(i).copied().unwrap_or(f64::NAN)
+                "{}: {:.4} on {} df, p={:.4}\n",
+                test.test_name, test.statistic, test.df, test.p_value
             ));
         }
 
         result
     }
 }
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CoxPHModel {
+    #[new]
+    fn py_new() -> Self {
+        Self::new()
+    }
+
+    #[staticmethod]
+    #[pyo3(
+        name = "new_with_data",
+        signature = (
+            covariates,
+            event_times,
+            censoring,
+            strata = None,
+            weights = None,
+            offsets = None,
+            ties = "efron",
+            entry_times = None,
+            cluster = None,
+            tt = None
+        )
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new_with_data(
+        covariates: MatrixArg<'_, f64>,
+        event_times: VectorArg<'_, f64>,
+        censoring: VectorArg<'_, u8>,
+        strata: Option<IndexArg>,
+        weights: Option<VectorArg<'_, f64>>,
+        offsets: Option<VectorArg<'_, f64>>,
+        ties: &str,
+        entry_times: Option<VectorArg<'_, f64>>,
+        cluster: Option<IndexArg>,
+        tt: Option<Vec<(usize, Bound<'_, PyAny>)>>,
+    ) -> PyResult<Self> {
+        let event_times = event_times.into_vec();
+        let censoring = censoring.into_vec();
+        let strata = strata.map(IndexArg::into_vec);
+        let weights = weights.map(VectorArg::into_vec);
+        let offsets = offsets.map(VectorArg::into_vec);
+        let entry_times = entry_times.map(VectorArg::into_vec);
+        let cluster = cluster.map(IndexArg::into_vec);
+        let mut model = Self::new_with_covariate_matrix(
+            covariates.into_array(),
+            event_times,
+            censoring,
+            strata,
+            weights,
+            offsets,
+            parse_tie_method(ties)?,
+        );
+        model.entry_times = entry_times;
+        model.cluster = cluster;
+        match tt {
+            Some(tt) => Ok(model.with_time_transforms(&extract_time_transforms(tt)?)?),
+            None => Ok(model),
+        }
+    }
+
+    #[pyo3(name = "with_time_transforms")]
+    fn py_with_time_transforms(&self, tt: Vec<(usize, Bound<'_, PyAny>)>) -> PyResult<Self> {
+        Ok(self.with_time_transforms(&extract_time_transforms(tt)?)?)
+    }
+
+    #[staticmethod]
+    #[pyo3(
+        name = "from_formula",
+        signature = (formula, data, variable_names, weights = None, ties = "efron")
+    )]
+    fn py_from_formula(
+        formula: &str,
+        data: MatrixArg<'_, f64>,
+        variable_names: Vec<String>,
+        weights: Option<VectorArg<'_, f64>>,
+        ties: &str,
+    ) -> PyResult<Self> {
+        let weights = weights.map(VectorArg::into_vec);
+        Ok(Self::from_formula(
+            formula,
+            data.view(),
+            &variable_names,
+            weights,
+            parse_tie_method(ties)?,
+        )?)
+    }
+
+    #[pyo3(name = "add_subject")]
+    fn py_add_subject(&mut self, subject: &Subject) -> PyResult<()> {
+        Ok(self.add_subject(subject)?)
+    }
+
+    #[pyo3(name = "fit", signature = (n_iters = 20))]
+    fn py_fit(&mut self, py: Python<'_>, n_iters: u16) -> PyResult<()> {
+        Ok(parallel::detach(py, || self.fit(n_iters))?)
+    }
+
+    #[pyo3(name = "predict")]
+    fn py_predict(&self, py: Python<'_>, covariates: MatrixArg<'_, f64>) -> Vec<f64> {
+        let covariates = covariates.view();
+        parallel::detach(py, || self.predict_matrix(covariates))
+    }
+
+    #[getter]
+    fn get_coefficients(&self) -> Vec<Vec<f64>> {
+        self.coefficients()
+    }
+
+    #[getter]
+    fn get_covariate_names(&self) -> Vec<String> {
+        self.covariate_names.clone()
+    }
+
+    #[getter]
+    fn get_baseline_hazard(&self) -> Vec<f64> {
+        self.baseline_hazard.clone()
+    }
+
+    #[getter]
+    fn get_risk_scores(&self) -> Vec<f64> {
+        self.risk_scores.clone()
+    }
+
+    #[getter]
+    fn get_event_times(&self) -> Vec<f64> {
+        self.event_times.clone()
+    }
+
+    #[setter]
+    fn set_event_times(&mut self, event_times: Vec<f64>) {
+        self.event_times = event_times;
+    }
+
+    #[getter]
+    fn get_entry_times(&self) -> Option<Vec<f64>> {
+        self.entry_times.clone()
+    }
+
+    #[setter]
+    fn set_entry_times(&mut self, entry_times: Option<Vec<f64>>) {
+        self.entry_times = entry_times;
+    }
+
+    #[getter]
+    fn get_censoring(&self) -> Vec<u8> {
+        self.censoring.clone()
+    }
+
+    #[setter]
+    fn set_censoring(&mut self, censoring: Vec<u8>) {
+        self.censoring = censoring;
+    }
+
+    #[getter]
+    fn get_strata(&self) -> Vec<usize> {
+        self.strata.clone()
+    }
+
+    #[setter]
+    fn set_strata(&mut self, strata: Vec<usize>) {
+        self.strata = strata;
+    }
+
+    #[getter]
+    fn get_weights(&self) -> Vec<f64> {
+        self.weights.clone()
+    }
+
+    #[setter]
+    fn set_weights(&mut self, weights: Vec<f64>) {
+        self.weights = weights;
+    }
+
+    #[getter]
+    fn get_offsets(&self) -> Vec<f64> {
+        self.offsets.clone()
+    }
+
+    #[setter]
+    fn set_offsets(&mut self, offsets: Vec<f64>) {
+        self.offsets = offsets;
+    }
+
+    #[getter]
+    fn get_ties(&self) -> &'static str {
+        match self.method {
+            CoxMethod::Breslow => "breslow",
+            CoxMethod::Efron => "efron",
+        }
+    }
+
+    #[setter]
+    fn set_ties(&mut self, ties: &str) -> PyResult<()> {
+        self.method = parse_tie_method(ties)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_baseline_hazards(&self) -> Vec<StratumBaseline> {
+        self.baselines.clone()
+    }
+
+    #[getter]
+    fn get_cluster(&self) -> Option<Vec<usize>> {
+        self.cluster.clone()
+    }
+
+    #[setter]
+    fn set_cluster(&mut self, cluster: Option<Vec<usize>>) {
+        self.cluster = cluster;
+    }
+
+    #[getter]
+    fn get_source_rows(&self) -> Option<Vec<usize>> {
+        self.source_rows.clone()
+    }
+
+    #[getter]
+    fn get_penalized_terms(&self) -> Vec<PenalizedTerm> {
+        self.penalized_terms.clone()
+    }
+
+    #[getter]
+    fn get_penalty_theta(&self) -> Vec<f64> {
+        self.penalty_theta.clone()
+    }
+
+    #[getter]
+    fn get_naive_var<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        self.naive_var.to_pyarray(py)
+    }
+
+    #[getter]
+    fn get_robust_var<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        self.robust_var.to_pyarray(py)
+    }
+
+    #[pyo3(name = "standard_errors", signature = (robust = false))]
+    fn py_standard_errors(&self, robust: bool) -> Vec<f64> {
+        self.standard_errors(robust)
+    }
+
+    #[pyo3(name = "wald_test", signature = (robust = false))]
+    fn py_wald_test(&self, robust: bool) -> TestResult {
+        self.wald_test(robust)
+    }
+
+    #[pyo3(name = "score_residuals")]
+    fn py_score_residuals<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        self.score_residuals.to_pyarray(py)
+    }
+
+    #[pyo3(name = "brier_score", signature = (times = None))]
+    fn py_brier_score(
+        &self,
+        py: Python<'_>,
+        times: Option<VectorArg<'_, f64>>,
+    ) -> PyResult<SurvivalBrierResult> {
+        let times = times.map(|t| t.as_slice().to_vec());
+        parallel::detach(py, || Ok(self.brier_score(times)?))
+    }
+
+    #[pyo3(name = "survival_curve", signature = (covariates, time_points = None, strata = None))]
+    fn py_survival_curve<'py>(
+        &self,
+        py: Python<'py>,
+        covariates: MatrixArg<'_, f64>,
+        time_points: Option<VectorArg<'_, f64>>,
+        strata: Option<IndexArg>,
+    ) -> PyResult<PyCurves<'py>> {
+        let time_points = time_points.map(VectorArg::into_vec);
+        let strata = strata.map(IndexArg::into_vec);
+        let covariates = covariates.view();
+        let (times, survival) = parallel::detach(py, || {
+            self.survival_curve_matrix(covariates, time_points, strata)
+        })?;
+        Ok((times.into_pyarray(py), survival.into_pyarray(py)))
+    }
+
+    #[pyo3(
+        name = "predict_survival",
+        signature = (covariates, times = None, strata = None, se = true, conf_type = "log", conf_level = 0.95)
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_predict_survival(
+        &self,
+        py: Python<'_>,
+        covariates: MatrixArg<'_, f64>,
+        times: Option<VectorArg<'_, f64>>,
+        strata: Option<IndexArg>,
+        se: bool,
+        conf_type: &str,
+        conf_level: f64,
+    ) -> PyResult<Vec<SurvivalPrediction>> {
+        let conf_type = ConfType::parse(conf_type)?;
+        let times = times.map(VectorArg::into_vec);
+        let strata = strata.map(IndexArg::into_vec);
+        let covariates = covariates.view();
+        Ok(parallel::detach(py, || {
+            self.predict_survival(covariates, times, strata, se, conf_type, conf_level)
+        })?)
+    }
+
+    #[pyo3(name = "hazard_ratios")]
+    fn py_hazard_ratios(&self) -> Vec<f64> {
+        self.hazard_ratios()
+    }
+
+    #[pyo3(
+        name = "hazard_ratios_with_ci",
+        signature = (confidence_level = 0.95, robust = false)
+    )]
+    fn py_hazard_ratios_with_ci(
+        &self,
+        confidence_level: f64,
+        robust: bool,
+    ) -> PyResult<HazardRatioIntervals> {
+        Ok(self.hazard_ratios_with_ci(confidence_level, robust)?)
+    }
+
+    #[pyo3(name = "log_likelihood")]
+    fn py_log_likelihood(&self) -> f64 {
+        self.log_likelihood()
+    }
+
+    #[pyo3(name = "null_log_likelihood")]
+    fn py_null_log_likelihood(&self) -> Option<f64> {
+        self.null_log_likelihood()
+    }
+
+    #[getter]
+    fn get_iterations(&self) -> Option<usize> {
+        self.iterations()
+    }
+
+    #[getter]
+    fn get_converged(&self) -> bool {
+        self.converged()
+    }
+
+    #[pyo3(name = "likelihood_ratio_test")]
+    fn py_likelihood_ratio_test(&self) -> TestResult {
+        self.likelihood_ratio_test()
+    }
+
+    #[pyo3(name = "score_test")]
+    fn py_score_test(&self) -> TestResult {
+        self.score_test()
+    }
+
+    #[pyo3(
+        name = "coefficient_table",
+        signature = (confidence_level = 0.95, robust = false)
+    )]
+    fn py_coefficient_table(&self, confidence_level: f64, robust: bool) -> Vec<CoefficientRow> {
+        self.coefficient_table(confidence_level, robust)
+    }
+
+    #[pyo3(name = "aic")]
+    fn py_aic(&self) -> f64 {
+        self.aic()
+    }
+
+    #[pyo3(name = "bic")]
+    fn py_bic(&self) -> f64 {
+        self.bic()
+    }
+
+    #[pyo3(name = "cumulative_hazard", signature = (covariates, strata = None))]
+    fn py_cumulative_hazard<'py>(
+        &self,
+        py: Python<'py>,
+        covariates: MatrixArg<'_, f64>,
+        strata: Option<IndexArg>,
+    ) -> PyResult<PyCurves<'py>> {
+        let strata = strata.map(IndexArg::into_vec);
+        let covariates = covariates.view();
+        let (times, cumulative_hazards) =
+            parallel::detach(py, || self.cumulative_hazard_matrix(covariates, strata))?;
+        Ok((times.into_pyarray(py), cumulative_hazards.into_pyarray(py)))
+    }
+
+    #[pyo3(
+        name = "predicted_survival_time",
+        signature = (covariates, percentile = 0.5, strata = None)
+    )]
+    fn py_predicted_survival_time(
+        &self,
+        py: Python<'_>,
+        covariates: MatrixArg<'_, f64>,
+        percentile: f64,
+        strata: Option<IndexArg>,
+    ) -> PyResult<Vec<Option<f64>>> {
+        let covariates = covariates.view();
+        let strata = strata.map(IndexArg::into_vec);
+        Ok(parallel::detach(py, || {
+            self.predicted_survival_time(covariates, percentile, strata)
+        })?)
+    }
+
+    #[pyo3(
+        name = "restricted_mean_survival_time",
+        signature = (covariates, tau, strata = None)
+    )]
+    fn py_restricted_mean_survival_time(
+        &self,
+        py: Python<'_>,
+        covariates: MatrixArg<'_, f64>,
+        tau: f64,
+        strata: Option<IndexArg>,
+    ) -> PyResult<Vec<f64>> {
+        let covariates = covariates.view();
+        let strata = strata.map(IndexArg::into_vec);
+        Ok(parallel::detach(py, || {
+            self.restricted_mean_survival_time(covariates, tau, strata)
+        })?)
+    }
+
+    #[pyo3(name = "martingale_residuals")]
+    fn py_martingale_residuals(&self, py: Python<'_>) -> Vec<f64> {
+        parallel::detach(py, || self.martingale_residuals())
+    }
+
+    #[pyo3(name = "deviance_residuals")]
+    fn py_deviance_residuals(&self, py: Python<'_>) -> Vec<f64> {
+        parallel::detach(py, || self.deviance_residuals())
+    }
+
+    #[pyo3(name = "dfbeta")]
+    fn py_dfbeta<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        parallel::detach(py, || self.dfbeta_matrix()).into_pyarray(py)
+    }
+
+    #[pyo3(name = "n_events")]
+    fn py_n_events(&self) -> usize {
+        self.n_events()
+    }
+
+    #[pyo3(name = "n_observations")]
+    fn py_n_observations(&self) -> usize {
+        self.n_observations()
+    }
+
+    #[pyo3(name = "summary")]
+    fn py_summary(&self) -> String {
+        self.summary()
+    }
+}
@@ -0,0 +1,219 @@
+use crate::regression::coxph::{CoxPHError, CoxPHModel};
+use crate::utilities::survsplit::survsplit;
+use ndarray::Array2;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+/// Vectorised user transform called with the covariate values and the risk
+/// set times of the expanded rows, returning one value per row.
+pub type TimeTransformFn = dyn Fn(&[f64], &[f64]) -> Result<Vec<f64>, String> + Send + Sync;
+
+/// Function `tt(x, t)` giving the value of a covariate in the risk set at
+/// event time `t`.
+#[derive(Clone)]
+pub enum TimeTransform {
+    /// `x * t`
+    Identity,
+    /// `x * log(t)`
+    Log,
+    /// `x * I(t > c)`, one column per cut point `c`.
+    Step(Vec<f64>),
+    Custom(Arc<TimeTransformFn>),
+}
+
+impl fmt::Debug for TimeTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identity => f.write_str("Identity"),
+            Self::Log => f.write_str("Log"),
+            Self::Step(cuts) => f.debug_tuple("Step").field(cuts).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+impl TimeTransform {
+    pub fn parse(name: &str) -> Result<Self, CoxPHError> {
+        match name.to_lowercase().as_str() {
+            "identity" | "t" => Ok(Self::Identity),
+            "log" => Ok(Self::Log),
+            _ => Err(CoxPHError::TimeTransform(format!(
+                "unknown transform '{name}', expected 'identity' or 'log'"
+            ))),
+        }
+    }
+
+    fn columns(
+        &self,
+        x: &[f64],
+        t: &[f64],
+        name: &str,
+    ) -> Result<Vec<(String, Vec<f64>)>, CoxPHError> {
+        let product = |g: &dyn Fn(f64) -> f64| x.iter().zip(t).map(|(&x, &t)| x * g(t)).collect();
+        Ok(match self {
+            Self::Identity => vec![(format!("{name}:t"), product(&|t| t))],
+            Self::Log => vec![(format!("{name}:log(t)"), product(&f64::ln))],
+            Self::Step(cuts) => cuts
+                .iter()
+                .map(|&c| {
+                    (
+                        format!("{name}:t>{c}"),
+                        product(&|t| f64::from(u8::from(t > c))),
+                    )
+                })
+                .collect(),
+            Self::Custom(f) => {
+                let values = f(x, t).map_err(CoxPHError::TimeTransform)?;
+                if values.len() != x.len() {
+                    return Err(CoxPHError::TimeTransform(format!(
+                        "transform returned {} values for {} rows",
+                        values.len(),
+                        x.len()
+                    )));
+                }
+                vec![(format!("tt({name})"), values)]
+            }
+        })
+    }
+}
+
+impl CoxPHModel {
+    /// Unfitted model with time-varying covariates. Each observation is
+    /// split with [`survsplit`] at the event times of its stratum, so every
+    /// row ends at the one risk set time where it contributes, and for each
+    /// `(column, transform)` the columns `tt(x, stop)` are appended after the
+    /// original covariates. The original row of each split is kept in
+    /// `source_rows`, and a cluster that is already set is carried over.
+    pub fn with_time_transforms(
+        &self,
+        terms: &[(usize, TimeTransform)],
+    ) -> Result<CoxPHModel, CoxPHError> {
+        self.check_inputs()?;
+        let n = self.event_times.len();
+        let covariates = self.covariate_matrix();
+        let nvar = covariates.ncols();
+        if let Some(&(column, _)) = terms.iter().find(|(column, _)| *column >= nvar) {
+            return Err(CoxPHError::TimeTransform(format!(
+                "column {column} is out of range for {nvar} covariates"
+            )));
+        }
+        let entry: Vec<f64> = self
+            .entry_times
+            .clone()
+            .unwrap_or_else(|| vec![f64::NEG_INFINITY; n]);
+
+        let mut levels = self.strata.clone();
+        levels.sort_unstable();
+        levels.dedup();
+        let mut source = Vec::new();
+        let mut start = Vec::new();
+        let mut stop = Vec::new();
+        let mut status = Vec::new();
+        for &stratum in &levels {
+            let rows: Vec<usize> = (0..n).filter(|&i| self.strata[i] == stratum).collect();
+            let mut cut: Vec<f64> = rows
+                .iter()
+                .filter(|&&i| self.censoring[i] != 0)
+                .map(|&i| self.event_times[i])
+                .collect();
+            cut.sort_by(|a, b| a.total_cmp(b));
+            cut.dedup();
+            let split = survsplit(
+                rows.iter().map(|&i| entry[i]).collect(),
+                rows.iter().map(|&i| self.event_times[i]).collect(),
+                cut,
+            );
+            for k in 0..split.row.len() {
+                let i = rows[split.row[k] - 1];
+                source.push(i);
+                start.push(split.start[k]);
+                stop.push(split.end[k]);
+                status.push(if split.censor[k] {
+                    0
+                } else {
+                    self.censoring[i]
+                });
+            }
+        }
+
+        let names: Vec<String> = (0..nvar)
+            .map(|c| {
+                self.covariate_names
+                    .get(c)
+                    .cloned()
+                    .unwrap_or_else(|| format!("var{c}"))
+            })
+            .collect();
+        let mut extra = Vec::new();
+        for (column, transform) in terms {
+            let x: Vec<f64> = source.iter().map(|&i| covariates[[i, *column]]).collect();
+            for (name, values) in transform.columns(&x, &stop, &names[*column])? {
+                if let Some(k) = values.iter().position(|v| !v.is_finite()) {
+                    return Err(CoxPHError::TimeTransform(format!(
+                        "{name} is not finite at t = {}",
+                        stop[k]
+                    )));
+                }
+                extra.push((name, values));
+            }
+        }
+
+        let m = source.len();
+        let expanded = Array2::from_shape_fn((m, nvar + extra.len()), |(r, c)| {
+            if c < nvar {
+                covariates[[source[r], c]]
+            } else {
+                extra[c - nvar].1[r]
+            }
+        });
+        let mut model = CoxPHModel::new_with_covariate_matrix(
+            expanded,
+            stop,
+            status,
+            Some(source.iter().map(|&i| self.strata[i]).collect()),
+            Some(source.iter().map(|&i| self.weights[i]).collect()),
+            Some(source.iter().map(|&i| self.offsets[i]).collect()),
+            self.method,
+        );
+        model.entry_times = Some(start);
+        model.cluster = self
+            .cluster
+            .as_ref()
+            .map(|cluster| source.iter().map(|&i| cluster[i]).collect());
+        model.source_rows = Some(source);
+        model.covariate_names = names
+            .into_iter()
+            .chain(extra.into_iter().map(|(name, _)| name))
+            .collect();
+        Ok(model)
+    }
+}
+
+/// Reads `(column, transform)` pairs where the transform is a name, a list
+/// of step cut points, or a callable `f(x, t)` taking and returning lists.
+#[cfg(feature = "python")]
+pub(crate) fn extract_time_transforms(
+    tt: Vec<(usize, Bound<'_, PyAny>)>,
+) -> PyResult<Vec<(usize, TimeTransform)>> {
+    tt.into_iter()
+        .map(|(column, transform)| {
+            let transform = if transform.is_callable() {
+                let f: Py<PyAny> = transform.unbind();
+                TimeTransform::Custom(Arc::new(move |x: &[f64], t: &[f64]| {
+                    Python::attach(|py| {
+                        f.call1(py, (x.to_vec(), t.to_vec()))
+                            .and_then(|values| values.extract::<Vec<f64>>(py))
+                            .map_err(|e| e.to_string())
+                    })
+                }))
+            } else if let Ok(name) = transform.extract::<String>() {
+                TimeTransform::parse(&name)?
+            } else {
+                TimeTransform::Step(transform.extract::<Vec<f64>>()?)
+            };
+            Ok((column, transform))
+        })
+        .collect()
+}
@@ -0,0 +1,405 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+use crate::regression::agfit5::{AgData, AgFit, AgInput, Penalty};
+use crate::regression::coxph::CoxMethod;
+#[cfg(feature = "python")]
+use crate::regression::coxph::parse_tie_method;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::{Array1, ArrayView2, ArrayViewMut1, s};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use statrs::function::gamma::ln_gamma;
+use thiserror::Error;
+
+const MAX_ITER: usize = 50;
+const EPS: f64 = 1e-9;
+const MAX_THETA_ITER: usize = 200;
+const THETA_TOL: f64 = 1e-6;
+/// Search interval for the gamma frailty variance on the log scale.
+const LOG_THETA_RANGE: (f64, f64) = (-9.0, 3.0);
+
+#[derive(Error, Debug)]
+pub enum FrailtyError {
+    #[error("cannot fit model: no observations provided")]
+    NoData,
+    #[error("dimension mismatch: expected {expected}, got {got}")]
+    DimensionMismatch { expected: usize, got: usize },
+    #[error("weights must be finite and non-negative")]
+    InvalidWeights,
+    #[error("entry time must be before exit time for observation {0}")]
+    InvalidInterval(usize),
+    #[error("frailty variance must be positive, got {0}")]
+    InvalidTheta(f64),
+    #[error("unknown frailty distribution '{0}', expected 'gamma' or 'gaussian'")]
+    UnknownDistribution(String),
+    #[error("cannot fit model: no events")]
+    NoEvents,
+    #[error("penalized information matrix is singular")]
+    Singular,
+}
+
+#[cfg(feature = "python")]
+impl From<FrailtyError> for PyErr {
+    fn from(err: FrailtyError) -> PyErr {
+        match err {
+            FrailtyError::Singular => PyRuntimeError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// Distribution of the shared frailty. Gamma frailties have mean one and
+/// variance theta; Gaussian ones are log-normal, with variance theta for the
+/// random effect on the log-hazard scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrailtyDistribution {
+    #[default]
+    Gamma,
+    Gaussian,
+}
+
+impl FrailtyDistribution {
+    pub fn parse(name: &str) -> Result<Self, FrailtyError> {
+        match name.to_lowercase().as_str() {
+            "gamma" => Ok(Self::Gamma),
+            "gaussian" | "lognormal" | "log-normal" => Ok(Self::Gaussian),
+            _ => Err(FrailtyError::UnknownDistribution(name.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gamma => "gamma",
+            Self::Gaussian => "gaussian",
+        }
+    }
+
+    /// Shifts the random effects by the constant that minimises the penalty.
+    /// Every row has a random effect, so the partial likelihood is unchanged,
+    /// and the shift is the direction the diagonal information resolves
+    /// slowest.
+    fn recentre(&self, mut b: ArrayViewMut1<f64>) {
+        let shift = match self {
+            Self::Gaussian => -b.mean().unwrap_or(0.0),
+            Self::Gamma => (b.len() as f64 / b.iter().map(|b| b.exp()).sum::<f64>()).ln(),
+        };
+        b += shift;
+    }
+
+    /// Penalty subtracted from the partial likelihood for random effects `b`,
+    /// with its gradient and the diagonal of its Hessian.
+    fn penalty(&self, theta: f64, b: &[f64]) -> (f64, Vec<f64>, Vec<f64>) {
+        match self {
+            Self::Gaussian => (
+                b.iter().map(|b| b * b).sum::<f64>() / (2.0 * theta),
+                b.iter().map(|b| b / theta).collect(),
+                vec![1.0 / theta; b.len()],
+            ),
+            Self::Gamma => {
+                let nu = 1.0 / theta;
+                (
+                    nu * b.iter().map(|b| b.exp() - b).sum::<f64>(),
+                    b.iter().map(|b| nu * (b.exp() - 1.0)).collect(),
+                    b.iter().map(|b| nu * b.exp()).collect(),
+                )
+            }
+        }
+    }
+}
+
+/// Shared frailty Cox model fitted by penalized partial likelihood. Gamma
+/// frailty variances maximise the integrated likelihood and Gaussian ones
+/// solve the REML equation, unless `theta` was fixed. `frailties` are the
+/// predicted random effects on the log-hazard scale, one per entry of
+/// `groups`. As for sparse frailty terms in R, the information of the
+/// frailties is reduced to its diagonal, which the standard errors and the
+/// REML estimate inherit. `df` is the trace of the penalized variance times
+/// the unpenalized information, and `aic` uses it with `log_likelihood`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct FrailtyFit {
+    pub distribution: String,
+    pub coefficients: Vec<f64>,
+    pub std_errors: Vec<f64>,
+    pub theta: f64,
+    pub groups: Vec<usize>,
+    pub frailties: Vec<f64>,
+    pub frailty_std_errors: Vec<f64>,
+    pub log_likelihood: f64,
+    pub penalized_log_likelihood: f64,
+    pub integrated_log_likelihood: Option<f64>,
+    pub df: f64,
+    pub aic: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Penalty of a frailty distribution with variance `theta`.
+struct ThetaPenalty {
+    distribution: FrailtyDistribution,
+    theta: f64,
+}
+
+impl Penalty for ThetaPenalty {
+    fn frailty(&self, b: &[f64]) -> (f64, Vec<f64>, Vec<f64>) {
+        self.distribution.penalty(self.theta, b)
+    }
+
+    fn recentre(&self, b: ArrayViewMut1<f64>) {
+        self.distribution.recentre(b);
+    }
+}
+
+struct FrailtyModel {
+    data: AgData,
+    /// Weighted number of events in each group.
+    events: Vec<f64>,
+}
+
+impl FrailtyModel {
+    fn fit_fixed(
+        &self,
+        distribution: FrailtyDistribution,
+        theta: f64,
+        start: Array1<f64>,
+    ) -> Result<AgFit, FrailtyError> {
+        let penalty = ThetaPenalty {
+            distribution,
+            theta,
+        };
+        self.data
+            .fit(Some(&penalty), start, MAX_ITER, EPS)
+            .map_err(|_| FrailtyError::Singular)
+    }
+
+    /// Gamma frailty log-likelihood integrated over the frailties, shifted by
+    /// the number of events so that it tends to the Cox partial likelihood as
+    /// theta goes to zero.
+    fn integrated_loglik(&self, theta: f64, fit: &AgFit) -> f64 {
+        let p = self.data.nvar();
+        let nu = 1.0 / theta;
+        let frailty: f64 = self
+            .events
+            .iter()
+            .enumerate()
+            .map(|(j, &dj)| {
+                nu * fit.coef[p + j] + ln_gamma(nu + dj) - ln_gamma(nu) + nu * nu.ln()
+                    - (nu + dj) * (nu + dj).ln()
+            })
+            .sum();
+        fit.loglik[1] + frailty + self.events.iter().sum::<f64>()
+    }
+}
+
+/// Shared frailty Cox model with one random effect per level of `group`.
+/// With `entry_times` the rows are (start, stop] intervals of counting
+/// process data, and with `theta` the frailty variance is held fixed.
+#[allow(clippy::too_many_arguments)]
+pub fn coxph_frailty(
+    covariates: ArrayView2<f64>,
+    times: &[f64],
+    status: &[u8],
+    group: &[usize],
+    strata: Option<&[usize]>,
+    weights: Option<&[f64]>,
+    entry_times: Option<&[f64]>,
+    distribution: FrailtyDistribution,
+    theta: Option<f64>,
+    method: CoxMethod,
+) -> Result<FrailtyFit, FrailtyError> {
+    let n = times.len();
+    if n == 0 {
+        return Err(FrailtyError::NoData);
+    }
+    for len in [covariates.nrows(), status.len(), group.len()]
+        .into_iter()
+        .chain(strata.map(<[usize]>::len))
+        .chain(weights.map(<[f64]>::len))
+        .chain(entry_times.map(<[f64]>::len))
+    {
+        if len != n {
+            return Err(FrailtyError::DimensionMismatch {
+                expected: n,
+                got: len,
+            });
+        }
+    }
+    let p = covariates.ncols();
+    let weights = weights.map_or_else(|| vec![1.0; n], <[f64]>::to_vec);
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return Err(FrailtyError::InvalidWeights);
+    }
+    if let Some(entry) = entry_times
+        && let Some(i) = (0..n).find(|&i| entry[i].is_nan() || entry[i] >= times[i])
+    {
+        return Err(FrailtyError::InvalidInterval(i));
+    }
+    if let Some(t) = theta
+        && !(t > 0.0 && t.is_finite())
+    {
+        return Err(FrailtyError::InvalidTheta(t));
+    }
+    if !status.iter().any(|&s| s != 0) {
+        return Err(FrailtyError::NoEvents);
+    }
+
+    let mut groups = group.to_vec();
+    groups.sort_unstable();
+    groups.dedup();
+    let group: Vec<usize> = group
+        .iter()
+        .map(|g| groups.binary_search(g).unwrap_or(0))
+        .collect();
+    let mut events = vec![0.0; groups.len()];
+    for i in 0..n {
+        if status[i] != 0 {
+            events[group[i]] += weights[i];
+        }
+    }
+    let strata = strata.map_or_else(|| vec![0; n], <[usize]>::to_vec);
+    let data = AgData::new(AgInput {
+        covariates,
+        start: entry_times,
+        stop: times,
+        status: &status.iter().map(|&s| s != 0).collect::<Vec<_>>(),
+        offset: &vec![0.0; n],
+        weights: &weights,
+        strata: &strata,
+        group: Some(&group),
+        method,
+    });
+    let data = FrailtyModel { data, events };
+
+    let start = Array1::zeros(p + groups.len());
+    let (theta, fit, iterations, converged) = match (theta, distribution) {
+        (Some(theta), _) => {
+            let fit = data.fit_fixed(distribution, theta, start)?;
+            let converged = fit.converged;
+            (theta, fit, 1, converged)
+        }
+        (None, FrailtyDistribution::Gaussian) => {
+            let q = groups.len() as f64;
+            let mut theta = 1.0;
+            let mut fit = data.fit_fixed(distribution, theta, start)?;
+            let mut iterations = 0;
+            let mut converged = false;
+            while iterations < MAX_THETA_ITER {
+                iterations += 1;
+                let b = fit.coef.slice(s![p..]);
+                let updated = (b.dot(&b) + fit.frailty_variance.sum()) / q;
+                let change = (updated - theta).abs();
+                theta = updated;
+                fit = data.fit_fixed(distribution, theta, fit.coef)?;
+                if change <= THETA_TOL * theta.max(THETA_TOL) {
+                    converged = fit.converged;
+                    break;
+                }
+            }
+            (theta, fit, iterations, converged)
+        }
+        (None, FrailtyDistribution::Gamma) => {
+            let golden = (5.0_f64.sqrt() - 1.0) / 2.0;
+            let mut warm = start;
+            let mut profile = |log_theta: f64| -> Result<f64, FrailtyError> {
+                let theta = log_theta.exp();
+                let fit = data.fit_fixed(distribution, theta, warm.clone())?;
+                warm = fit.coef.clone();
+                Ok(data.integrated_loglik(theta, &fit))
+            };
+            let (mut lo, mut hi) = LOG_THETA_RANGE;
+            let mut x1 = hi - golden * (hi - lo);
+            let mut x2 = lo + golden * (hi - lo);
+            let mut f1 = profile(x1)?;
+            let mut f2 = profile(x2)?;
+            let mut iterations = 2;
+            while hi - lo > THETA_TOL && iterations < MAX_THETA_ITER {
+                if f1 >= f2 {
+                    hi = x2;
+                    x2 = x1;
+                    f2 = f1;
+                    x1 = hi - golden * (hi - lo);
+                    f1 = profile(x1)?;
+                } else {
+                    lo = x1;
+                    x1 = x2;
+                    f1 = f2;
+                    x2 = lo + golden * (hi - lo);
+                    f2 = profile(x2)?;
+                }
+                iterations += 1;
+            }
+            let theta = ((lo + hi) / 2.0).exp();
+            let fit = data.fit_fixed(distribution, theta, warm)?;
+            let converged = fit.converged && hi - lo <= THETA_TOL;
+            (theta, fit, iterations, converged)
+        }
+    };
+
+    let integrated_log_likelihood =
+        (distribution == FrailtyDistribution::Gamma).then(|| data.integrated_loglik(theta, &fit));
+    let se = |v: f64| v.max(0.0).sqrt();
+    Ok(FrailtyFit {
+        distribution: distribution.name().to_string(),
+        coefficients: fit.coef.slice(s![..p]).to_vec(),
+        std_errors: fit.variance.diag().iter().map(|&v| se(v)).collect(),
+        theta,
+        frailties: fit.coef.slice(s![p..]).to_vec(),
+        frailty_std_errors: fit.frailty_variance.iter().map(|&v| se(v)).collect(),
+        groups,
+        log_likelihood: fit.loglik[1],
+        penalized_log_likelihood: fit.penalized,
+        integrated_log_likelihood,
+        df: fit.df,
+        aic: -2.0 * fit.loglik[1] + 2.0 * fit.df,
+        iterations,
+        converged,
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "coxph_frailty",
+    signature = (covariates, times, status, group, strata=None, weights=None, distribution="gamma", theta=None, ties="efron", entry_times=None)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn coxph_frailty_py(
+    py: Python<'_>,
+    covariates: MatrixArg<'_, f64>,
+    times: VectorArg<'_, f64>,
+    status: VectorArg<'_, u8>,
+    group: IndexArg,
+    strata: Option<IndexArg>,
+    weights: Option<VectorArg<'_, f64>>,
+    distribution: &str,
+    theta: Option<f64>,
+    ties: &str,
+    entry_times: Option<VectorArg<'_, f64>>,
+) -> PyResult<FrailtyFit> {
+    let distribution = FrailtyDistribution::parse(distribution)?;
+    let method = parse_tie_method(ties)?;
+    let covariates = covariates.view();
+    let times = times.as_slice();
+    let status = status.as_slice();
+    let group = group.into_vec();
+    let strata = strata.map(IndexArg::into_vec);
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let entry_times = entry_times.as_ref().map(VectorArg::as_slice);
+    Ok(parallel::detach(py, || {
+        coxph_frailty(
+            covariates,
+            &times,
+            &status,
+            &group,
+            strata.as_deref(),
+            weights.as_deref(),
+            entry_times.as_deref(),
+            distribution,
+            theta,
+            method,
+        )
+    })?)
+}
@@ -6,7 +6,12 @@ pub mod blogit;
 pub mod clogit;
 pub mod coxfit5;
 pub mod coxfit6;
+pub mod coxnet;
 pub mod coxph;
+pub mod coxph_tt;
+pub mod frailty;
+pub mod multistate_cox;
+pub mod survdist;
 pub mod survreg6;
 pub mod survreg7;
 pub mod survregc1;
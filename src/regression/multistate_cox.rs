@@ -0,0 +1,335 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::regression::coxph::parse_tie_method;
+use crate::regression::coxph::{CoxMethod, CoxPHError, CoxPHModel, StratumBaseline};
+use crate::surv_analysis::multistate::{MultiStateError, prepare_multistate};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::ArrayView2;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MultiStateCoxError {
+    #[error(transparent)]
+    Data(#[from] MultiStateError),
+    #[error("covariates must have one row per observation")]
+    CovariateRows,
+    #[error("expected {expected} covariate values, got {got}")]
+    DimensionMismatch { expected: usize, got: usize },
+    #[error("no observation is at risk for a transition")]
+    NoRiskSet,
+    #[error("unknown state '{0}'")]
+    UnknownState(String),
+    #[error(transparent)]
+    Cox(#[from] CoxPHError),
+}
+
+#[cfg(feature = "python")]
+impl From<MultiStateCoxError> for PyErr {
+    fn from(err: MultiStateCoxError) -> PyErr {
+        match err {
+            MultiStateCoxError::Data(e) => e.into(),
+            MultiStateCoxError::Cox(e) => e.into(),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// Cox model fitted to data expanded with one row per transition at risk.
+/// Baselines are stratified by transition, so `baselines[jk]` belongs to
+/// `transitions[jk]`, and `transition_coefficients[jk]` holds the effect of
+/// each original covariate on that transition. `converged` is false when the
+/// Newton-Raphson iterations hit `max_iter` first.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct MultiStateCoxModel {
+    pub states: Vec<String>,
+    pub transitions: Vec<(String, String)>,
+    pub coefficient_names: Vec<String>,
+    pub coefficients: Vec<f64>,
+    pub std_errors: Vec<f64>,
+    pub robust_std_errors: Vec<f64>,
+    pub log_likelihood: f64,
+    pub transition_coefficients: Vec<Vec<f64>>,
+    pub baselines: Vec<StratumBaseline>,
+    pub n_events: Vec<usize>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+impl MultiStateCoxModel {
+    /// Linear predictor of each transition for one covariate vector.
+    pub fn linear_predictors(&self, covariates: &[f64]) -> Result<Vec<f64>, MultiStateCoxError> {
+        let p = self.transition_coefficients.first().map_or(0, Vec::len);
+        if covariates.len() != p {
+            return Err(MultiStateCoxError::DimensionMismatch {
+                expected: p,
+                got: covariates.len(),
+            });
+        }
+        Ok(self
+            .transition_coefficients
+            .iter()
+            .map(|beta| beta.iter().zip(covariates).map(|(b, x)| b * x).sum())
+            .collect())
+    }
+
+    /// State occupancy probabilities for a subject with `covariates` who
+    /// starts in `initial_state`, from the Aalen-Johansen product integral of
+    /// the covariate-specific transition hazards. Returns the jump times and
+    /// one row of probabilities per time, with columns following `states`.
+    pub fn predict(
+        &self,
+        covariates: &[f64],
+        initial_state: &str,
+    ) -> Result<(Vec<f64>, Vec<Vec<f64>>), MultiStateCoxError> {
+        let start = self
+            .states
+            .iter()
+            .position(|s| s == initial_state)
+            .ok_or_else(|| MultiStateCoxError::UnknownState(initial_state.to_string()))?;
+        let risk: Vec<f64> = self
+            .linear_predictors(covariates)?
+            .into_iter()
+            .map(f64::exp)
+            .collect();
+        let state = |label: &String| {
+            self.states
+                .iter()
+                .position(|s| s == label)
+                .ok_or_else(|| MultiStateCoxError::UnknownState(label.clone()))
+        };
+        let index = self
+            .transitions
+            .iter()
+            .map(|(from, to)| Ok((state(from)?, state(to)?)))
+            .collect::<Result<Vec<_>, MultiStateCoxError>>()?;
+
+        let mut times: Vec<f64> = self
+            .baselines
+            .iter()
+            .flat_map(|b| b.time.iter().copied())
+            .collect();
+        times.sort_by(|a, b| a.total_cmp(b));
+        times.dedup();
+
+        let mut p = vec![0.0; self.states.len()];
+        p[start] = 1.0;
+        let mut previous = vec![0.0; self.baselines.len()];
+        let mut pstate = Vec::with_capacity(times.len());
+        for &t in &times {
+            let mut next = p.clone();
+            for (jk, baseline) in self.baselines.iter().enumerate() {
+                let h = baseline.cumulative_hazard_at(t);
+                let flow = p[index[jk].0] * (h - previous[jk]) * risk[jk];
+                next[index[jk].0] -= flow;
+                next[index[jk].1] += flow;
+                previous[jk] = h;
+            }
+            p = next;
+            pstate.push(p.clone());
+        }
+        Ok((times, pstate))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MultiStateCoxModel {
+    #[pyo3(name = "predict")]
+    fn predict_py(
+        &self,
+        covariates: VectorArg<'_, f64>,
+        initial_state: &str,
+    ) -> PyResult<(Vec<f64>, Vec<Vec<f64>>)> {
+        Ok(self.predict(&covariates.as_slice(), initial_state)?)
+    }
+}
+
+/// Multi-state Cox model from counting-process rows laid out as for
+/// [`multistate_fit`](crate::surv_analysis::multistate::multistate_fit).
+/// Each row is expanded into one row per transition out of its `from_state`,
+/// and the expanded data are fitted with the transition as stratum and the
+/// subject as cluster. Covariates flagged in `shared` get one coefficient
+/// across all transitions; the others get one per transition, named
+/// `x{c}:{from}->{to}`.
+#[allow(clippy::too_many_arguments)]
+pub fn multistate_coxph<S: AsRef<str>>(
+    id: &[usize],
+    tstart: &[f64],
+    tstop: &[f64],
+    from_state: &[S],
+    to_state: &[S],
+    covariates: ArrayView2<f64>,
+    shared: Option<&[bool]>,
+    weights: Option<&[f64]>,
+    method: CoxMethod,
+    max_iter: u16,
+) -> Result<MultiStateCoxModel, MultiStateCoxError> {
+    let data = prepare_multistate(id, tstart, tstop, from_state, to_state, weights, None)?;
+    if covariates.nrows() != id.len() {
+        return Err(MultiStateCoxError::CovariateRows);
+    }
+    let p = covariates.ncols();
+    let default_shared = vec![false; p];
+    let shared = shared.unwrap_or(&default_shared);
+    if shared.len() != p {
+        return Err(MultiStateCoxError::DimensionMismatch {
+            expected: p,
+            got: shared.len(),
+        });
+    }
+
+    let labels = data.transition_labels();
+    let nhaz = data.transitions.len();
+    let mut terms: Vec<(usize, Option<usize>)> = Vec::new();
+    let mut coefficient_names = Vec::new();
+    for (c, &is_shared) in shared.iter().enumerate() {
+        if is_shared {
+            terms.push((c, None));
+            coefficient_names.push(format!("x{}", c + 1));
+        } else {
+            for (jk, (from, to)) in labels.iter().enumerate() {
+                terms.push((c, Some(jk)));
+                coefficient_names.push(format!("x{}:{from}->{to}", c + 1));
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut entry = Vec::new();
+    let mut exit = Vec::new();
+    let mut status = Vec::new();
+    let mut strata = Vec::new();
+    let mut cluster = Vec::new();
+    let mut expanded_weights = Vec::new();
+    let mut n_events = vec![0usize; nhaz];
+    for r in 0..id.len() {
+        for (jk, &(j, k)) in data.transitions.iter().enumerate() {
+            if data.from[r] != j {
+                continue;
+            }
+            let event = data.to[r] == k;
+            n_events[jk] += usize::from(event);
+            rows.push(
+                terms
+                    .iter()
+                    .map(|&(c, t)| match t {
+                        Some(t) if t != jk => 0.0,
+                        _ => covariates[[r, c]],
+                    })
+                    .collect::<Vec<f64>>(),
+            );
+            entry.push(tstart[r]);
+            exit.push(tstop[r]);
+            status.push(u8::from(event));
+            strata.push(jk);
+            cluster.push(id[r]);
+            expanded_weights.push(data.weights[r]);
+        }
+    }
+    if rows.is_empty() {
+        return Err(MultiStateCoxError::NoRiskSet);
+    }
+
+    let mut model = CoxPHModel::new_with_options(
+        rows,
+        exit,
+        status,
+        Some(strata),
+        Some(expanded_weights),
+        None,
+        method,
+    );
+    model.entry_times = Some(entry);
+    model.cluster = Some(cluster);
+    model.covariate_names = coefficient_names.clone();
+    model.fit(max_iter)?;
+
+    let coefficients = model.coefficients().swap_remove(0);
+    let mut transition_coefficients = vec![vec![0.0; p]; nhaz];
+    for (&(c, t), &beta) in terms.iter().zip(&coefficients) {
+        match t {
+            Some(jk) => transition_coefficients[jk][c] = beta,
+            None => transition_coefficients
+                .iter_mut()
+                .for_each(|row| row[c] = beta),
+        }
+    }
+    let baselines = (0..nhaz)
+        .map(|jk| {
+            model
+                .baseline_hazards()
+                .iter()
+                .find(|b| b.stratum == jk)
+                .cloned()
+                .unwrap_or(StratumBaseline {
+                    stratum: jk,
+                    time: Vec::new(),
+                    cumulative_hazard: Vec::new(),
+                })
+        })
+        .collect();
+
+    Ok(MultiStateCoxModel {
+        states: data.states,
+        transitions: labels,
+        coefficient_names,
+        std_errors: model.standard_errors(false),
+        robust_std_errors: model.standard_errors(true),
+        log_likelihood: model.log_likelihood(),
+        coefficients,
+        transition_coefficients,
+        baselines,
+        n_events,
+        iterations: model.iterations().unwrap_or(0),
+        converged: model.converged(),
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "multistate_coxph",
+    signature = (id, tstart, tstop, from_state, to_state, covariates, shared=None, weights=None, ties="efron", max_iter=20)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn multistate_coxph_py(
+    py: Python<'_>,
+    id: IndexArg,
+    tstart: VectorArg<'_, f64>,
+    tstop: VectorArg<'_, f64>,
+    from_state: Vec<String>,
+    to_state: Vec<String>,
+    covariates: MatrixArg<'_, f64>,
+    shared: Option<Vec<bool>>,
+    weights: Option<VectorArg<'_, f64>>,
+    ties: &str,
+    max_iter: u16,
+) -> PyResult<MultiStateCoxModel> {
+    let method = parse_tie_method(ties)?;
+    let id = id.into_vec();
+    let tstart = tstart.as_slice();
+    let tstop = tstop.as_slice();
+    let covariates = covariates.view();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    Ok(parallel::detach(py, || {
+        multistate_coxph(
+            &id,
+            &tstart,
+            &tstop,
+            &from_state,
+            &to_state,
+            covariates,
+            shared.as_deref(),
+            weights.as_deref(),
+            method,
+            max_iter,
+        )
+    })?)
+}
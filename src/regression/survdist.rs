@@ -0,0 +1,735 @@
+use crate::regression::survreg6::DistributionType;
+use ndarray::{Array1, Array2};
+use ndarray_linalg::{Inverse, Solve};
+use statrs::distribution::{ContinuousCDF, Gamma, Normal};
+use statrs::function::erf::erfc;
+use statrs::function::gamma::{checked_gamma_lr, digamma, ln_gamma};
+
+const SMALL_Q: f64 = 1e-6;
+const SMALL_SHAPE: f64 = 1e-10;
+const LOG_FLOOR: f64 = -200.0;
+/// Below this |shape| the generalized gamma log density is expanded in
+/// powers of the shape, avoiding the cancellation in the closed form.
+const SERIES_Q: f64 = 0.05;
+/// Step and half-width of the exp-sinh rule for the tail integrals.
+const TAIL_STEP: f64 = 1.0 / 32.0;
+const TAIL_SPAN: f64 = 4.5;
+
+pub(crate) fn uses_log_time(distribution: DistributionType) -> bool {
+    matches!(
+        distribution,
+        DistributionType::Weibull
+            | DistributionType::Exponential
+            | DistributionType::Lognormal
+            | DistributionType::LogLogistic
+            | DistributionType::GeneralizedGamma
+    )
+}
+
+pub(crate) fn is_location_scale(distribution: DistributionType) -> bool {
+    !matches!(
+        distribution,
+        DistributionType::GeneralizedGamma | DistributionType::Gompertz
+    )
+}
+
+fn gamma_p(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        0.0
+    } else if x.is_infinite() {
+        1.0
+    } else {
+        checked_gamma_lr(a, x).unwrap_or(f64::NAN)
+    }
+}
+
+fn standard_normal() -> Normal {
+    Normal::new(0.0, 1.0).expect("standard normal parameters are valid")
+}
+
+pub(crate) fn survival_probability(
+    distribution: DistributionType,
+    t: f64,
+    eta: f64,
+    scale: f64,
+    shape: f64,
+) -> f64 {
+    if uses_log_time(distribution) && t <= 0.0 {
+        return 1.0;
+    }
+    let y = if uses_log_time(distribution) {
+        t.ln()
+    } else {
+        t
+    };
+    let z = (y - eta) / scale;
+    match distribution {
+        DistributionType::ExtremeValue
+        | DistributionType::Weibull
+        | DistributionType::Exponential => (-z.min(700.0).exp()).exp(),
+        DistributionType::Logistic | DistributionType::LogLogistic => {
+            1.0 / (1.0 + z.min(700.0).exp())
+        }
+        DistributionType::Gaussian | DistributionType::Lognormal => {
+            0.5 * erfc(z / std::f64::consts::SQRT_2)
+        }
+        DistributionType::GeneralizedGamma => {
+            if shape.abs() < SERIES_Q {
+                return GenGamma::new(shape).small_shape_survival(z);
+            }
+            let gamma = 1.0 / (shape * shape);
+            let u = gamma * (shape * z).min(700.0).exp();
+            if shape > 0.0 {
+                1.0 - gamma_p(gamma, u)
+            } else {
+                gamma_p(gamma, u)
+            }
+        }
+        DistributionType::Gompertz => {
+            if t <= 0.0 {
+                return 1.0;
+            }
+            (-gompertz_cumhaz(t, eta, shape)).exp()
+        }
+    }
+}
+
+fn gompertz_cumhaz(t: f64, eta: f64, shape: f64) -> f64 {
+    let rate = eta.exp();
+    if shape.abs() < SMALL_SHAPE {
+        rate * t
+    } else {
+        rate * (shape * t).exp_m1() / shape
+    }
+}
+
+fn log_density(distribution: DistributionType, t: f64, eta: f64, scale: f64, shape: f64) -> f64 {
+    match distribution {
+        DistributionType::GeneralizedGamma => {
+            if t <= 0.0 {
+                return LOG_FLOOR;
+            }
+            let w = (t.ln() - eta) / scale;
+            if shape.abs() < SERIES_Q {
+                return GenGamma::new(shape).log_density(w).value - scale.ln() - t.ln();
+            }
+            let gamma = 1.0 / (shape * shape);
+            shape.abs().ln() + gamma * gamma.ln() - scale.ln() - t.ln() - ln_gamma(gamma)
+                + gamma * (shape * w - (shape * w).min(700.0).exp())
+        }
+        DistributionType::Gompertz => {
+            if t < 0.0 {
+                return LOG_FLOOR;
+            }
+            eta + shape * t - gompertz_cumhaz(t, eta, shape)
+        }
+        _ => LOG_FLOOR,
+    }
+}
+
+pub(crate) fn quantile(
+    distribution: DistributionType,
+    p: f64,
+    eta: f64,
+    scale: f64,
+    shape: f64,
+) -> f64 {
+    let z = match distribution {
+        DistributionType::ExtremeValue
+        | DistributionType::Weibull
+        | DistributionType::Exponential => (-(-p).ln_1p()).ln(),
+        DistributionType::Logistic | DistributionType::LogLogistic => (p / (1.0 - p)).ln(),
+        DistributionType::Gaussian | DistributionType::Lognormal => {
+            standard_normal().inverse_cdf(p)
+        }
+        DistributionType::GeneralizedGamma => {
+            if shape.abs() < SMALL_Q {
+                standard_normal().inverse_cdf(p)
+            } else {
+                let gamma = 1.0 / (shape * shape);
+                let target = if shape > 0.0 { p } else { 1.0 - p };
+                let u = Gamma::new(gamma, 1.0)
+                    .map(|g| g.inverse_cdf(target))
+                    .unwrap_or(f64::NAN);
+                (u / gamma).ln() / shape
+            }
+        }
+        DistributionType::Gompertz => {
+            let h = -(-p).ln_1p();
+            let rate = eta.exp();
+            if shape.abs() < SMALL_SHAPE {
+                return h / rate;
+            }
+            let arg = 1.0 + shape * h / rate;
+            return if arg <= 0.0 {
+                f64::INFINITY
+            } else {
+                arg.ln() / shape
+            };
+        }
+    };
+    let y = eta + scale * z;
+    if uses_log_time(distribution) {
+        y.exp()
+    } else {
+        y
+    }
+}
+
+pub(crate) struct GeneralFit {
+    pub coefficients: Vec<f64>,
+    pub iterations: usize,
+    pub variance_matrix: Array2<f64>,
+    pub log_likelihood: f64,
+    pub converged: bool,
+    pub score_vector: Vec<f64>,
+}
+
+pub(crate) struct GeneralData<'a> {
+    pub time1: &'a [f64],
+    pub time2: Option<&'a [f64]>,
+    pub status: &'a [f64],
+    pub covariates: &'a Array2<f64>,
+    pub weights: &'a [f64],
+    pub offsets: &'a [f64],
+    pub strata: &'a [usize],
+    pub nstrat: usize,
+}
+
+impl GeneralData<'_> {
+    fn nvar(&self) -> usize {
+        self.covariates.nrows()
+    }
+
+    fn parameters(
+        &self,
+        distribution: DistributionType,
+        theta: &[f64],
+        i: usize,
+    ) -> (f64, f64, f64) {
+        let nvar = self.nvar();
+        let eta = self.offsets[i]
+            + theta[..nvar]
+                .iter()
+                .zip(self.covariates.column(i))
+                .map(|(b, x)| b * x)
+                .sum::<f64>();
+        match distribution {
+            DistributionType::Gompertz => (eta, 1.0, theta[nvar]),
+            _ => {
+                let scale = theta[nvar + self.strata[i]].exp();
+                (eta, scale, theta[nvar + self.nstrat])
+            }
+        }
+    }
+
+    fn loglik(&self, distribution: DistributionType, theta: &[f64]) -> f64 {
+        let mut total = 0.0;
+        for i in 0..self.time1.len() {
+            let (eta, scale, shape) = self.parameters(distribution, theta, i);
+            let surv = |t: f64| survival_probability(distribution, t, eta, scale, shape);
+            let t1 = self.time1[i];
+            let term = match self.status[i] as i32 {
+                1 => log_density(distribution, t1, eta, scale, shape),
+                0 => surv(t1).ln(),
+                2 => (1.0 - surv(t1)).ln(),
+                _ => {
+                    let t2 = self.time2.map_or(f64::INFINITY, |t| t[i]);
+                    (surv(t1) - surv(t2)).ln()
+                }
+            };
+            total += self.weights[i] * term.max(LOG_FLOOR);
+        }
+        total
+    }
+}
+
+/// Value, gradient and Hessian of one observation's log-likelihood term, or
+/// of its survival probability, in the local parameters (eta, log scale,
+/// shape). The Gompertz model has no scale, so its middle entries stay zero.
+#[derive(Clone, Copy, Default)]
+struct Local {
+    value: f64,
+    grad: [f64; 3],
+    hess: [[f64; 3]; 3],
+}
+
+impl Local {
+    /// Maps derivatives in (w, shape), with `w = (log t - eta) / scale`, to
+    /// (eta, log scale, shape).
+    fn from_standardized(terms: &ShapeTerms, w: f64, scale: f64) -> Self {
+        let mut local = Local {
+            value: terms.value,
+            grad: [-terms.w / scale, -w * terms.w, terms.q],
+            ..Local::default()
+        };
+        let h = &mut local.hess;
+        h[0][0] = terms.ww / (scale * scale);
+        h[0][1] = (w * terms.ww + terms.w) / scale;
+        h[1][1] = w * w * terms.ww + w * terms.w;
+        h[0][2] = -terms.wq / scale;
+        h[1][2] = -w * terms.wq;
+        h[2][2] = terms.qq;
+        local.symmetrize();
+        local
+    }
+
+    fn symmetrize(&mut self) {
+        for j in 0..3 {
+            for k in 0..j {
+                self.hess[j][k] = self.hess[k][j];
+            }
+        }
+    }
+
+    /// `log(self.value - other.value)` for two survival probabilities, or
+    /// `log(self.value)` when `other` is `None`.
+    fn log_difference(&self, other: Option<&Local>) -> Local {
+        let zero = Local::default();
+        let other = other.unwrap_or(&zero);
+        let a = self.value - other.value;
+        let mut out = Local {
+            value: a.ln(),
+            ..Local::default()
+        };
+        for j in 0..3 {
+            let gj = (self.grad[j] - other.grad[j]) / a;
+            out.grad[j] = gj;
+            for k in 0..3 {
+                let gk = (self.grad[k] - other.grad[k]) / a;
+                out.hess[j][k] = (self.hess[j][k] - other.hess[j][k]) / a - gj * gk;
+            }
+        }
+        out
+    }
+
+    fn complement(&self) -> Local {
+        Local {
+            value: 1.0 - self.value,
+            grad: self.grad.map(|g| -g),
+            hess: self.hess.map(|row| row.map(|h| -h)),
+        }
+    }
+}
+
+/// A log density or survival probability of the standardized variable `w`
+/// with its derivatives in `w` and the shape.
+#[derive(Clone, Copy, Default)]
+struct ShapeTerms {
+    value: f64,
+    w: f64,
+    q: f64,
+    ww: f64,
+    wq: f64,
+    qq: f64,
+}
+
+fn trigamma(mut x: f64) -> f64 {
+    let mut value = 0.0;
+    while x < 10.0 {
+        value += 1.0 / (x * x);
+        x += 1.0;
+    }
+    let inv = 1.0 / x;
+    let inv2 = inv * inv;
+    value
+        + inv
+        + inv2 / 2.0
+        + inv * inv2 * (1.0 / 6.0 - inv2 * (1.0 / 30.0 - inv2 * (1.0 / 42.0 - inv2 / 30.0)))
+}
+
+/// Prentice's generalized gamma for `w = (log t - eta) / scale` at shape `q`,
+/// with log density `log|q| + g log g - lgamma(g) + g (q w - exp(q w))` for
+/// `g = 1 / q^2`, tending to the standard normal as `q -> 0`.
+struct GenGamma {
+    q: f64,
+    g: f64,
+    lgamma: f64,
+    digamma: f64,
+    trigamma: f64,
+}
+
+impl GenGamma {
+    fn new(q: f64) -> Self {
+        let g = 1.0 / (q * q);
+        if q == 0.0 {
+            return GenGamma {
+                q,
+                g,
+                lgamma: 0.0,
+                digamma: 0.0,
+                trigamma: 0.0,
+            };
+        }
+        GenGamma {
+            q,
+            g,
+            lgamma: ln_gamma(g),
+            digamma: digamma(g),
+            trigamma: trigamma(g),
+        }
+    }
+
+    fn log_density(&self, w: f64) -> ShapeTerms {
+        let q = self.q;
+        if q.abs() < SERIES_Q && (q * w).abs() < 1.0 {
+            // -(exp(q w) - 1 - q w) / q^2 and Stirling's series for
+            // lgamma(1 / q^2), as power series in q.
+            let q2 = q * q;
+            let q4 = q2 * q2;
+            let mut terms = ShapeTerms {
+                value: -0.5 * (2.0 * std::f64::consts::PI).ln() - q2 / 12.0 + q4 * q2 / 360.0
+                    - q4 * q4 * q2 / 1260.0,
+                q: -q / 6.0 + q4 * q / 60.0 - q4 * q4 * q / 126.0,
+                qq: -1.0 / 6.0 + q4 / 12.0 - q4 * q4 / 14.0,
+                ..ShapeTerms::default()
+            };
+            // c[j] = w^(m+j) / (m+j)! and the powers q^m, q^(m-1), q^(m-2).
+            let mut c = [1.0, w, w * w / 2.0];
+            let (mut p0, mut p1, mut p2) = (1.0, 0.0, 0.0);
+            for m in 0..200 {
+                let mf = f64::from(m);
+                let step = [
+                    p0 * c[2],
+                    p0 * c[1],
+                    p0 * c[0],
+                    mf * p1 * c[2],
+                    mf * p1 * c[1],
+                    mf * (mf - 1.0) * p2 * c[2],
+                ];
+                terms.value -= step[0];
+                terms.w -= step[1];
+                terms.ww -= step[2];
+                terms.q -= step[3];
+                terms.wq -= step[4];
+                terms.qq -= step[5];
+                if m >= 3 && step.iter().all(|d| d.abs() < 1e-17) {
+                    break;
+                }
+                c = [c[1], c[2], c[2] * w / (mf + 3.0)];
+                (p2, p1, p0) = (p1, p0, p0 * q);
+            }
+            return terms;
+        }
+        let e = (q * w).min(700.0).exp();
+        let l = q.abs().ln();
+        let q2 = q * q;
+        let q3 = q2 * q;
+        let a = 4.0 * l - 2.0 + 2.0 * self.digamma + 2.0 * e;
+        ShapeTerms {
+            value: l - 2.0 * self.g * l - self.lgamma + w / q - e / q2,
+            w: (1.0 - e) / q,
+            ww: -e,
+            q: 1.0 / q + a / q3 - w * (1.0 + e) / q2,
+            wq: -w * e / q - (1.0 - e) / q2,
+            qq: -1.0 / q2 - 3.0 * a / (q2 * q2)
+                + (4.0 / q - 4.0 * self.trigamma / q3 + 2.0 * w * e) / q3
+                + 2.0 * w * (1.0 + e) / q3
+                - w * w * e / q2,
+        }
+    }
+
+    /// Integrals of the density, of the density times the shape score and
+    /// of the density times the second shape derivative of the density over
+    /// its log, over `v > w` (`upper`) or `v < w`, by the exp-sinh rule.
+    fn tail_integrals(&self, w: f64, upper: bool) -> [f64; 3] {
+        let mut sums = [0.0; 3];
+        let steps = (TAIL_SPAN / TAIL_STEP) as i32;
+        for k in -steps..=steps {
+            let t = f64::from(k) * TAIL_STEP;
+            let x = (std::f64::consts::FRAC_PI_2 * t.sinh()).exp();
+            let weight = TAIL_STEP * x * std::f64::consts::FRAC_PI_2 * t.cosh();
+            let terms = self.log_density(if upper { w + x } else { w - x });
+            let g = weight * terms.value.exp();
+            if g == 0.0 || !terms.q.is_finite() || !terms.qq.is_finite() {
+                continue;
+            }
+            sums[0] += g;
+            sums[1] += g * terms.q;
+            sums[2] += g * (terms.qq + terms.q * terms.q);
+        }
+        sums
+    }
+
+    /// Survival probability at `w` for a shape below [`SERIES_Q`], where the
+    /// incomplete gamma ratio with parameter `1 / q^2` loses precision.
+    fn small_shape_survival(&self, w: f64) -> f64 {
+        if w >= 0.0 {
+            self.tail_integrals(w, true)[0]
+        } else {
+            1.0 - self.tail_integrals(w, false)[0]
+        }
+    }
+
+    /// Survival probability `surv` of `w` with its derivatives. The shape
+    /// derivatives of the incomplete gamma ratio have no closed form: they
+    /// are the integrals of the density times the shape score over the
+    /// smaller tail beyond `w`.
+    fn survival(&self, w: f64, surv: f64) -> ShapeTerms {
+        let density = self.log_density(w);
+        let f = density.value.exp();
+        let upper = surv <= 0.5;
+        let [_, mut first, mut second] = self.tail_integrals(w, upper);
+        if !upper {
+            first = -first;
+            second = -second;
+        }
+        ShapeTerms {
+            value: surv,
+            w: -f,
+            q: first,
+            ww: -f * density.w,
+            wq: -f * density.q,
+            qq: second,
+        }
+    }
+}
+
+/// `e = (exp(b t) - 1) / b` and its first two derivatives in `b`, so that
+/// the Gompertz cumulative hazard is `exp(eta) e`.
+fn gompertz_time(t: f64, b: f64) -> [f64; 3] {
+    if b.abs() < SMALL_SHAPE {
+        return [t, t * t / 2.0, t * t * t / 3.0];
+    }
+    let bt = b * t;
+    if bt.abs() < 1.0 {
+        // The k-th term of e is t (b t)^k / (k + 1)!.
+        let mut out = [0.0; 3];
+        let mut term = t;
+        for k in 0..40 {
+            let kf = f64::from(k);
+            out[0] += term;
+            out[1] += kf * term / b;
+            out[2] += kf * (kf - 1.0) * term / (b * b);
+            term *= bt / (kf + 2.0);
+        }
+        return out;
+    }
+    let e_bt = bt.min(700.0).exp();
+    let e = (e_bt - 1.0) / b;
+    let e_b = (t * e_bt - e) / b;
+    let e_bb = (t * t * e_bt - 2.0 * e_b) / b;
+    [e, e_b, e_bb]
+}
+
+impl GeneralData<'_> {
+    /// Survival probability at `t` with its derivatives.
+    fn survival_terms(
+        distribution: DistributionType,
+        model: Option<&GenGamma>,
+        t: f64,
+        eta: f64,
+        scale: f64,
+        shape: f64,
+    ) -> Local {
+        let surv = survival_probability(distribution, t, eta, scale, shape);
+        match (distribution, model) {
+            (DistributionType::GeneralizedGamma, Some(model)) => {
+                if t <= 0.0 || t.is_infinite() {
+                    return Local {
+                        value: surv,
+                        ..Local::default()
+                    };
+                }
+                let w = (t.ln() - eta) / scale;
+                Local::from_standardized(&model.survival(w, surv), w, scale)
+            }
+            _ => {
+                if t <= 0.0 || t.is_infinite() {
+                    return Local {
+                        value: surv,
+                        ..Local::default()
+                    };
+                }
+                // S = exp(-H): dS = -S dH and d2S = S (dH dH' - d2H).
+                let [e, e_b, e_bb] = gompertz_time(t, shape);
+                let rate = eta.exp();
+                let dh = [rate * e, 0.0, rate * e_b];
+                let d2h = [
+                    [rate * e, 0.0, rate * e_b],
+                    [0.0; 3],
+                    [rate * e_b, 0.0, rate * e_bb],
+                ];
+                let mut local = Local {
+                    value: surv,
+                    ..Local::default()
+                };
+                for j in 0..3 {
+                    local.grad[j] = -surv * dh[j];
+                    for k in 0..3 {
+                        local.hess[j][k] = surv * (dh[j] * dh[k] - d2h[j][k]);
+                    }
+                }
+                local
+            }
+        }
+    }
+
+    /// Log density at `t` with its derivatives.
+    fn density_terms(
+        distribution: DistributionType,
+        model: Option<&GenGamma>,
+        t: f64,
+        eta: f64,
+        scale: f64,
+        shape: f64,
+    ) -> Local {
+        let value = log_density(distribution, t, eta, scale, shape);
+        match (distribution, model) {
+            (DistributionType::GeneralizedGamma, Some(model)) => {
+                if t <= 0.0 {
+                    return Local {
+                        value,
+                        ..Local::default()
+                    };
+                }
+                let w = (t.ln() - eta) / scale;
+                let mut local = Local::from_standardized(&model.log_density(w), w, scale);
+                local.value = value;
+                local.grad[1] -= 1.0;
+                local
+            }
+            _ => {
+                if t < 0.0 {
+                    return Local {
+                        value,
+                        ..Local::default()
+                    };
+                }
+                // log f = eta + b t - exp(eta) e(b).
+                let [e, e_b, e_bb] = gompertz_time(t, shape);
+                let rate = eta.exp();
+                let mut local = Local {
+                    value,
+                    grad: [1.0 - rate * e, 0.0, t - rate * e_b],
+                    ..Local::default()
+                };
+                local.hess[0][0] = -rate * e;
+                local.hess[0][2] = -rate * e_b;
+                local.hess[2][0] = -rate * e_b;
+                local.hess[2][2] = -rate * e_bb;
+                local
+            }
+        }
+    }
+
+    /// Log-likelihood with its analytic gradient and Hessian in `theta`.
+    fn derivatives(
+        &self,
+        distribution: DistributionType,
+        theta: &[f64],
+    ) -> (f64, Array1<f64>, Array2<f64>) {
+        let nvar = self.nvar();
+        let p = theta.len();
+        let model = (distribution == DistributionType::GeneralizedGamma)
+            .then(|| GenGamma::new(theta[nvar + self.nstrat]));
+        let mut loglik = 0.0;
+        let mut grad = Array1::zeros(p);
+        let mut hess = Array2::zeros((p, p));
+        for i in 0..self.time1.len() {
+            let (eta, scale, shape) = self.parameters(distribution, theta, i);
+            let at =
+                |t: f64| Self::survival_terms(distribution, model.as_ref(), t, eta, scale, shape);
+            let t1 = self.time1[i];
+            let term = match self.status[i] as i32 {
+                1 => Self::density_terms(distribution, model.as_ref(), t1, eta, scale, shape),
+                0 => at(t1).log_difference(None),
+                2 => at(t1).complement().log_difference(None),
+                _ => {
+                    let t2 = self.time2.map_or(f64::INFINITY, |t| t[i]);
+                    at(t1).log_difference(Some(&at(t2)))
+                }
+            };
+            let weight = self.weights[i];
+            if term.value.is_nan() || term.value <= LOG_FLOOR {
+                loglik += weight * LOG_FLOOR;
+                continue;
+            }
+            loglik += weight * term.value;
+
+            // (local parameter, theta index, derivative of the first in
+            // the second): eta is the linear predictor.
+            let x = self.covariates.column(i);
+            let mut chain: Vec<(usize, usize, f64)> = (0..nvar).map(|j| (0, j, x[j])).collect();
+            match distribution {
+                DistributionType::Gompertz => chain.push((2, nvar, 1.0)),
+                _ => {
+                    chain.push((1, nvar + self.strata[i], 1.0));
+                    chain.push((2, nvar + self.nstrat, 1.0));
+                }
+            }
+            for &(k, a, da) in &chain {
+                grad[a] += weight * term.grad[k] * da;
+                for &(l, b, db) in &chain {
+                    hess[[a, b]] += weight * term.hess[k][l] * da * db;
+                }
+            }
+        }
+        (loglik, grad, hess)
+    }
+}
+
+pub(crate) fn fit_general(
+    distribution: DistributionType,
+    data: &GeneralData,
+    mut theta: Vec<f64>,
+    max_iter: usize,
+    eps: f64,
+) -> GeneralFit {
+    let objective = |point: &[f64]| data.loglik(distribution, point);
+    let (mut loglik, mut grad, mut hess) = data.derivatives(distribution, &theta);
+
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iter {
+        iterations += 1;
+        let information = -&hess;
+        let (mut step, mut full_step) = match information.solve(&grad) {
+            Ok(step) if step.dot(&grad) > 0.0 => (step, true),
+            _ => (
+                &grad / grad.iter().map(|g| g.abs()).fold(1.0, f64::max),
+                false,
+            ),
+        };
+
+        let mut accepted = None;
+        for halving in 0..30 {
+            full_step &= halving == 0;
+            let candidate: Vec<f64> = theta.iter().zip(step.iter()).map(|(t, s)| t + s).collect();
+            let value = objective(&candidate);
+            if value.is_finite() && value >= loglik {
+                accepted = Some((candidate, value));
+                break;
+            }
+            step /= 2.0;
+        }
+        let Some((candidate, value)) = accepted else {
+            break;
+        };
+
+        let change = value - loglik;
+        theta = candidate;
+        let (new_loglik, new_grad, new_hess) = data.derivatives(distribution, &theta);
+        loglik = new_loglik;
+        grad = new_grad;
+        hess = new_hess;
+        if full_step && change.abs() <= eps * (loglik.abs() + eps) {
+            converged = true;
+            break;
+        }
+    }
+
+    let p = theta.len();
+    let variance_matrix = (-&hess)
+        .inv()
+        .unwrap_or_else(|_| Array2::from_elem((p, p), f64::NAN));
+
+    GeneralFit {
+        coefficients: theta,
+        iterations,
+        variance_matrix,
+        log_likelihood: loglik,
+        converged,
+        score_vector: grad.to_vec(),
+    }
+}
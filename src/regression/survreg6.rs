@@ -1,24 +1,238 @@
 #![allow(clippy::redundant_closure)]
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+use crate::regression::survdist::{
+    GeneralData, fit_general, is_location_scale, quantile, survival_probability, uses_log_time,
+};
 use crate::regression::survregc1::{SurvivalDist, survregc1};
-use ndarray::{Array1, Array2, ArrayView1};
-use ndarray_linalg::{Cholesky, Solve, UPLO};
+use crate::utilities::formula::{FormulaError, SurvType, model_frame};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::utilities::persistence::impl_persist;
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+use ndarray_linalg::SolveC;
+#[cfg(feature = "python")]
+use numpy::{IntoPyArray, PyArray2};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SurvregError {
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    Fit(String),
+    #[error(transparent)]
+    Formula(#[from] FormulaError),
+}
+
+#[cfg(feature = "python")]
+impl From<SurvregError> for PyErr {
+    fn from(err: SurvregError) -> PyErr {
+        match err {
+            SurvregError::InvalidInput(msg) => PyValueError::new_err(msg),
+            SurvregError::Fit(msg) => PyRuntimeError::new_err(msg),
+            SurvregError::Formula(err) => err.into(),
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
-#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(module = "survival", get_all))]
 pub struct SurvivalFit {
-    #[pyo3(get)]
     pub coefficients: Vec<f64>,
-    #[pyo3(get)]
     pub iterations: usize,
-    #[pyo3(get)]
     pub variance_matrix: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub log_likelihood: f64,
-    #[pyo3(get)]
     pub convergence_flag: i32,
-    #[pyo3(get)]
     pub score_vector: Vec<f64>,
+    pub distribution: DistributionType,
+    pub scale: Vec<f64>,
+    pub shape: Option<f64>,
+}
+
+impl_persist!(SurvivalFit);
+
+impl SurvivalFit {
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.log_likelihood + 2.0 * self.coefficients.len() as f64
+    }
+
+    fn n_linear(&self) -> usize {
+        let n_aux = match self.distribution {
+            DistributionType::Exponential => 0,
+            DistributionType::Gompertz => 1,
+            DistributionType::GeneralizedGamma => self.scale.len() + 1,
+            _ => self.scale.len(),
+        };
+        self.coefficients.len().saturating_sub(n_aux)
+    }
+
+    pub fn linear_predictors(&self, covariates: &[Vec<f64>]) -> Result<Vec<f64>, SurvregError> {
+        let nvar = self.n_linear();
+        covariates
+            .iter()
+            .map(|row| {
+                if row.len() != nvar {
+                    return Err(SurvregError::InvalidInput(format!(
+                        "expected {} covariates, got {}",
+                        nvar,
+                        row.len()
+                    )));
+                }
+                Ok(row.iter().zip(&self.coefficients).map(|(x, b)| x * b).sum())
+            })
+            .collect()
+    }
+
+    /// Same as [`linear_predictors`](Self::linear_predictors) for covariates
+    /// packed one observation per row.
+    pub fn linear_predictors_matrix(
+        &self,
+        covariates: ArrayView2<f64>,
+    ) -> Result<Vec<f64>, SurvregError> {
+        let nvar = self.n_linear();
+        if covariates.nrows() > 0 && covariates.ncols() != nvar {
+            return Err(SurvregError::InvalidInput(format!(
+                "expected {} covariates, got {}",
+                nvar,
+                covariates.ncols()
+            )));
+        }
+        let beta = ArrayView1::from(&self.coefficients[..nvar]);
+        Ok(covariates.outer_iter().map(|row| row.dot(&beta)).collect())
+    }
+
+    fn parameters(
+        &self,
+        eta: Vec<f64>,
+        strata: Option<&[usize]>,
+    ) -> Result<Vec<(f64, f64)>, SurvregError> {
+        if let Some(strata) = strata
+            && strata.len() != eta.len()
+        {
+            return Err(SurvregError::InvalidInput(
+                "strata must have one entry per covariate row".to_string(),
+            ));
+        }
+        eta.into_iter()
+            .enumerate()
+            .map(|(i, eta)| {
+                let stratum = strata.map_or(0, |s| s[i]);
+                let scale = match self.distribution {
+                    DistributionType::Gompertz => 1.0,
+                    _ => *self.scale.get(stratum).ok_or_else(|| {
+                        SurvregError::InvalidInput(format!("unknown stratum {}", stratum))
+                    })?,
+                };
+                Ok((eta, scale))
+            })
+            .collect()
+    }
+
+    pub fn predict_quantile(
+        &self,
+        covariates: &[Vec<f64>],
+        p: f64,
+        strata: Option<&[usize]>,
+    ) -> Result<Vec<f64>, SurvregError> {
+        self.quantiles(self.linear_predictors(covariates)?, p, strata)
+    }
+
+    fn quantiles(
+        &self,
+        eta: Vec<f64>,
+        p: f64,
+        strata: Option<&[usize]>,
+    ) -> Result<Vec<f64>, SurvregError> {
+        if !(p > 0.0 && p < 1.0) {
+            return Err(SurvregError::InvalidInput(
+                "p must be between 0 and 1".to_string(),
+            ));
+        }
+        let shape = self.shape.unwrap_or(0.0);
+        Ok(self
+            .parameters(eta, strata)?
+            .into_iter()
+            .map(|(eta, scale)| quantile(self.distribution, p, eta, scale, shape))
+            .collect())
+    }
+
+    pub fn predict_survival(
+        &self,
+        covariates: &[Vec<f64>],
+        times: &[f64],
+        strata: Option<&[usize]>,
+    ) -> Result<Vec<Vec<f64>>, SurvregError> {
+        let survival = self.survival_matrix(self.linear_predictors(covariates)?, times, strata)?;
+        Ok(survival.outer_iter().map(|row| row.to_vec()).collect())
+    }
+
+    /// Survival probabilities with one row per linear predictor in `eta` and
+    /// one column per time.
+    fn survival_matrix(
+        &self,
+        eta: Vec<f64>,
+        times: &[f64],
+        strata: Option<&[usize]>,
+    ) -> Result<Array2<f64>, SurvregError> {
+        let shape = self.shape.unwrap_or(0.0);
+        let parameters = self.parameters(eta, strata)?;
+        Ok(Array2::from_shape_fn(
+            (parameters.len(), times.len()),
+            |(i, j)| {
+                let (eta, scale) = parameters[i];
+                survival_probability(self.distribution, times[j], eta, scale, shape)
+            },
+        ))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SurvivalFit {
+    #[pyo3(name = "aic")]
+    fn py_aic(&self) -> f64 {
+        self.aic()
+    }
+
+    #[pyo3(name = "predict_quantile", signature = (covariates, p = 0.5, strata = None))]
+    fn py_predict_quantile(
+        &self,
+        py: Python<'_>,
+        covariates: MatrixArg<'_, f64>,
+        p: f64,
+        strata: Option<IndexArg>,
+    ) -> PyResult<Vec<f64>> {
+        let strata = strata.map(IndexArg::into_vec);
+        let covariates = covariates.view();
+        Ok(parallel::detach(py, || {
+            let eta = self.linear_predictors_matrix(covariates)?;
+            self.quantiles(eta, p, strata.as_deref())
+        })?)
+    }
+
+    #[pyo3(name = "predict_survival", signature = (covariates, times, strata = None))]
+    fn py_predict_survival<'py>(
+        &self,
+        py: Python<'py>,
+        covariates: MatrixArg<'_, f64>,
+        times: VectorArg<'_, f64>,
+        strata: Option<IndexArg>,
+    ) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        let strata = strata.map(IndexArg::into_vec);
+        let covariates = covariates.view();
+        let times = times.as_slice();
+        let survival = parallel::detach(py, || {
+            let eta = self.linear_predictors_matrix(covariates)?;
+            self.survival_matrix(eta, &times, strata.as_deref())
+        })?;
+        Ok(survival.into_pyarray(py))
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -178,11 +392,16 @@ fn calculate_likelihood(
     u: &mut Array1<f64>,
 ) -> Result<f64, Box<dyn std::error::Error>> {
     let dist = match distribution {
-        DistributionType::ExtremeValue => SurvivalDist::ExtremeValue,
-        DistributionType::Logistic => SurvivalDist::Logistic,
+        DistributionType::ExtremeValue | DistributionType::Exponential => {
+            SurvivalDist::ExtremeValue
+        }
+        DistributionType::Logistic | DistributionType::LogLogistic => SurvivalDist::Logistic,
         DistributionType::Gaussian => SurvivalDist::Gaussian,
         DistributionType::Weibull => SurvivalDist::Weibull,
-        DistributionType::LogNormal => SurvivalDist::LogNormal,
+        DistributionType::Lognormal => SurvivalDist::LogNormal,
+        DistributionType::GeneralizedGamma | DistributionType::Gompertz => {
+            return Err("not a location-scale distribution".into());
+        }
     };
 
     let strat_vec: Vec<i32> = strata.iter().map(|&s| (s + 1) as i32).collect();
@@ -222,17 +441,12 @@ fn calculate_likelihood(
     }
 
     for i in 0..nvar2.min(imat.nrows()) {
-        for j in 0..nvar2.min(imat.ncols()) {
+        for j in 0..=i {
             if i < result.imat.nrows() && j < result.imat.ncols() {
-                imat[[i, j]] = -result.imat[[i, j]];
-            }
-        }
-    }
-
-    for i in 0..nvar2.min(jj.nrows()) {
-        for j in 0..nvar2.min(jj.ncols()) {
-            if i < result.jj.nrows() && j < result.jj.ncols() {
+                imat[[i, j]] = result.imat[[i, j]];
+                imat[[j, i]] = result.imat[[i, j]];
                 jj[[i, j]] = result.jj[[i, j]];
+                jj[[j, i]] = result.jj[[i, j]];
             }
         }
     }
@@ -254,10 +468,8 @@ fn cholesky_solve(
         return Ok(Array1::zeros(vector.len()));
     }
 
-    match matrix.cholesky(UPLO::Lower) {
-        Ok(chol) => chol
-            .solve(vector)
-            .map_err(|e| format!("Cholesky solve failed: {}", e).into()),
+    match matrix.solvec(vector) {
+        Ok(solution) => Ok(solution),
         Err(_) => {
             let n = matrix.nrows();
             let mut reg_matrix = matrix.clone();
@@ -265,10 +477,8 @@ fn cholesky_solve(
             for i in 0..n {
                 reg_matrix[[i, i]] += ridge;
             }
-            match reg_matrix.cholesky(UPLO::Lower) {
-                Ok(chol) => chol
-                    .solve(vector)
-                    .map_err(|e| format!("Cholesky solve failed: {}", e).into()),
+            match reg_matrix.solvec(vector) {
+                Ok(solution) => Ok(solution),
                 Err(_) => Ok(Array1::zeros(vector.len())),
             }
         }
@@ -310,22 +520,41 @@ fn calculate_variance_matrix(
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(rename_all = "snake_case"))]
 pub enum DistributionType {
-    #[pyo3(name = "extreme_value")]
     ExtremeValue,
-    #[pyo3(name = "logistic")]
     Logistic,
-    #[pyo3(name = "gaussian")]
     Gaussian,
-    #[pyo3(name = "weibull")]
     Weibull,
-    #[pyo3(name = "lognormal")]
-    LogNormal,
+    Lognormal,
+    Exponential,
+    LogLogistic,
+    GeneralizedGamma,
+    Gompertz,
+}
+
+pub fn parse_distribution(distribution: Option<&str>) -> DistributionType {
+    match distribution.map(|d| d.to_ascii_lowercase()).as_deref() {
+        Some("logistic") => DistributionType::Logistic,
+        Some("gaussian") | Some("normal") => DistributionType::Gaussian,
+        Some("weibull") => DistributionType::Weibull,
+        Some("lognormal") | Some("lognorm") => DistributionType::Lognormal,
+        Some("exponential") => DistributionType::Exponential,
+        Some("loglogistic") | Some("llogis") => DistributionType::LogLogistic,
+        Some("gengamma") | Some("generalized_gamma") => DistributionType::GeneralizedGamma,
+        Some("gompertz") => DistributionType::Gompertz,
+        _ => DistributionType::ExtremeValue,
+    }
+}
+
+fn rows_to_matrix(rows: &[Vec<f64>]) -> Result<Array2<f64>, SurvregError> {
+    let ncols = rows.first().map_or(0, Vec::len);
+    let flat: Vec<f64> = rows.iter().flatten().copied().collect();
+    Array2::from_shape_vec((rows.len(), ncols), flat)
+        .map_err(|e| SurvregError::InvalidInput(format!("{}", e)))
 }
 
-#[pyfunction]
 #[allow(clippy::too_many_arguments)]
 pub fn survreg(
     time: Vec<f64>,
@@ -339,22 +568,142 @@ pub fn survreg(
     max_iter: Option<usize>,
     eps: Option<f64>,
     tol_chol: Option<f64>,
-) -> PyResult<SurvivalFit> {
+) -> Result<SurvivalFit, SurvregError> {
+    survreg_response(
+        time,
+        None,
+        status,
+        rows_to_matrix(&covariates)?.view(),
+        weights,
+        offsets,
+        initial_beta,
+        strata,
+        distribution,
+        max_iter,
+        eps,
+        tol_chol,
+    )
+}
+
+/// Fits a survreg model to a (time, time2, status) response where status is
+/// 0 = right censored at time, 1 = exact, 2 = left censored at time and
+/// 3 = interval censored in (time, time2].
+#[allow(clippy::too_many_arguments)]
+pub fn survreg_interval(
+    time: Vec<f64>,
+    time2: Vec<f64>,
+    status: Vec<f64>,
+    covariates: Vec<Vec<f64>>,
+    weights: Option<Vec<f64>>,
+    offsets: Option<Vec<f64>>,
+    initial_beta: Option<Vec<f64>>,
+    strata: Option<Vec<usize>>,
+    distribution: Option<&str>,
+    max_iter: Option<usize>,
+    eps: Option<f64>,
+    tol_chol: Option<f64>,
+) -> Result<SurvivalFit, SurvregError> {
+    survreg_response(
+        time,
+        Some(time2),
+        status,
+        rows_to_matrix(&covariates)?.view(),
+        weights,
+        offsets,
+        initial_beta,
+        strata,
+        distribution,
+        max_iter,
+        eps,
+        tol_chol,
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn interval_response(
+    mut time: Vec<f64>,
+    time2: Option<Vec<f64>>,
+    mut status: Vec<f64>,
+    positive: bool,
+) -> Result<(Vec<f64>, Option<Vec<f64>>, Vec<f64>), SurvregError> {
+    if let Some(&bad) = status
+        .iter()
+        .find(|&&s| !matches!(s, 0.0 | 1.0 | 2.0 | 3.0))
+    {
+        return Err(SurvregError::InvalidInput(format!(
+            "invalid status {}: expected 0 (right), 1 (exact), 2 (left) or 3 (interval)",
+            bad
+        )));
+    }
+    let Some(mut time2) = time2 else {
+        if status.contains(&3.0) {
+            return Err(SurvregError::InvalidInput(
+                "interval-censored observations require time2".to_string(),
+            ));
+        }
+        return Ok((time, None, status));
+    };
+    if time2.len() != time.len() {
+        return Err(SurvregError::InvalidInput(
+            "time and time2 must have the same length".to_string(),
+        ));
+    }
+
+    for i in 0..time.len() {
+        if status[i] != 3.0 {
+            time2[i] = time[i];
+            continue;
+        }
+        let (lower, upper) = (time[i], time2[i]);
+        if upper.is_nan() || lower.is_nan() || upper < lower {
+            return Err(SurvregError::InvalidInput(format!(
+                "observation {} has an invalid interval ({}, {}]",
+                i, lower, upper
+            )));
+        }
+        if upper == f64::INFINITY {
+            status[i] = 0.0;
+        } else if lower == f64::NEG_INFINITY || (positive && lower <= 0.0) {
+            status[i] = 2.0;
+            time[i] = upper;
+        } else if lower == upper {
+            status[i] = 1.0;
+        }
+    }
+    let time2 = status.contains(&3.0).then_some(time2);
+    Ok((time, time2, status))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn survreg_response(
+    time: Vec<f64>,
+    time2: Option<Vec<f64>>,
+    status: Vec<f64>,
+    covariates: ArrayView2<f64>,
+    weights: Option<Vec<f64>>,
+    offsets: Option<Vec<f64>>,
+    initial_beta: Option<Vec<f64>>,
+    strata: Option<Vec<usize>>,
+    distribution: Option<&str>,
+    max_iter: Option<usize>,
+    eps: Option<f64>,
+    tol_chol: Option<f64>,
+) -> Result<SurvivalFit, SurvregError> {
     let n = time.len();
     if status.len() != n {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "time and status must have the same length",
+        return Err(SurvregError::InvalidInput(
+            "time and status must have the same length".to_string(),
         ));
     }
 
-    let nvar = if !covariates.is_empty() {
-        covariates[0].len()
+    let nvar = if covariates.nrows() > 0 {
+        covariates.ncols()
     } else {
         0
     };
-    if !covariates.is_empty() && covariates.len() != n {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "covariates must have the same number of rows as time",
+    if covariates.nrows() > 0 && covariates.nrows() != n {
+        return Err(SurvregError::InvalidInput(
+            "covariates must have the same number of rows as time".to_string(),
         ));
     }
 
@@ -365,17 +714,7 @@ pub fn survreg(
     let eps = eps.unwrap_or(1e-5);
     let tol_chol = tol_chol.unwrap_or(1e-9);
 
-    let dist_type = match distribution {
-        Some("logistic") | Some("Logistic") => DistributionType::Logistic,
-        Some("gaussian") | Some("Gaussian") | Some("normal") | Some("Normal") => {
-            DistributionType::Gaussian
-        }
-        Some("weibull") | Some("Weibull") => DistributionType::Weibull,
-        Some("lognormal") | Some("LogNormal") | Some("lognorm") | Some("LogNorm") => {
-            DistributionType::LogNormal
-        }
-        _ => DistributionType::ExtremeValue,
-    };
+    let dist_type = parse_distribution(distribution);
 
     let nstrat = if strata.is_empty() {
         1
@@ -383,24 +722,98 @@ pub fn survreg(
         strata.iter().max().copied().unwrap_or(0) + 1
     };
 
-    let initial_beta = initial_beta.unwrap_or_else(|| vec![0.0; nvar + nstrat]);
+    let log_time = uses_log_time(dist_type);
+    let positive = log_time || dist_type == DistributionType::Gompertz;
+    let (time, time2, status) = interval_response(time, time2, status, positive)?;
+    if (log_time || dist_type == DistributionType::Gompertz) && time.iter().any(|&t| t <= 0.0) {
+        return Err(SurvregError::InvalidInput(
+            "survival times must be positive for this distribution".to_string(),
+        ));
+    }
+
+    let cov_array = if nvar > 0 {
+        covariates.t().to_owned()
+    } else {
+        Array2::zeros((0, n))
+    };
+
+    if !is_location_scale(dist_type) {
+        let data = GeneralData {
+            time1: &time,
+            time2: time2.as_deref(),
+            status: &status,
+            covariates: &cov_array,
+            weights: &weights,
+            offsets: &offsets,
+            strata: &strata,
+            nstrat,
+        };
+        return survreg_general(
+            dist_type,
+            &data,
+            covariates,
+            initial_beta,
+            max_iter,
+            eps,
+            tol_chol,
+        );
+    }
+
+    let nscale = if dist_type == DistributionType::Exponential {
+        0
+    } else {
+        nstrat
+    };
+    let initial_beta = match initial_beta {
+        Some(beta) => beta,
+        None => {
+            let transform = |t: f64| if log_time { t.ln() } else { t };
+            let y: Vec<f64> = (0..n)
+                .map(|i| match &time2 {
+                    Some(upper) if status[i] == 3.0 => {
+                        (transform(time[i]) + transform(upper[i])) / 2.0
+                    }
+                    _ => transform(time[i]),
+                })
+                .collect();
+            let (location, log_scale) = initial_location_scale(dist_type, &y, &weights);
+            let mut beta = vec![0.0; nvar + nscale];
+            if let Some(j) = (0..nvar).find(|&j| covariates.column(j).iter().all(|&x| x == 1.0)) {
+                beta[j] = location;
+            }
+            beta[nvar..].fill(log_scale);
+            beta
+        }
+    };
+    if initial_beta.len() != nvar + nscale {
+        return Err(SurvregError::InvalidInput(format!(
+            "initial_beta must have length {}",
+            nvar + nscale
+        )));
+    }
 
     let y = {
-        let mut y_data = Vec::new();
+        let transform = |t: f64| if log_time { t.ln() } else { t };
+        let ny = if time2.is_some() { 3 } else { 2 };
+        let mut y_data = Vec::with_capacity(n * ny);
         for i in 0..n {
-            y_data.push(vec![time[i], status[i]]);
+            y_data.push(transform(time[i]));
+            if let Some(upper) = &time2 {
+                y_data.push(transform(upper[i]));
+            }
+            y_data.push(status[i]);
         }
-        Array2::from_shape_vec((n, 2), y_data.into_iter().flatten().collect())
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?
+        Array2::from_shape_vec((n, ny), y_data)
+            .map_err(|e| SurvregError::InvalidInput(format!("{}", e)))?
     };
 
-    let cov_array = if nvar > 0 {
-        let flat: Vec<f64> = covariates.into_iter().flatten().collect();
-        let temp = Array2::from_shape_vec((n, nvar), flat)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
-        temp.t().to_owned()
+    let jacobian: f64 = if log_time {
+        (0..n)
+            .filter(|&i| status[i] == 1.0)
+            .map(|i| weights[i] * time[i].ln())
+            .sum()
     } else {
-        Array2::zeros((0, n))
+        0.0
     };
 
     let weights_arr = Array1::from_vec(weights);
@@ -414,13 +827,13 @@ pub fn survreg(
         &weights_arr,
         &offsets_arr,
         initial_beta,
-        nstrat,
+        nscale,
         &strata,
         eps,
         tol_chol,
         dist_type,
     )
-    .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))?;
+    .map_err(|e| SurvregError::Fit(format!("{}", e)))?;
 
     let variance_matrix = result
         .variance_matrix
@@ -428,16 +841,322 @@ pub fn survreg(
         .map(|row| row.iter().cloned().collect())
         .collect();
 
+    let scale = if nscale == 0 {
+        vec![1.0]
+    } else {
+        result.coefficients[nvar..]
+            .iter()
+            .map(|s| s.exp())
+            .collect()
+    };
+
     Ok(SurvivalFit {
         coefficients: result.coefficients,
         iterations: result.iterations,
         variance_matrix,
-        log_likelihood: result.log_likelihood,
+        log_likelihood: result.log_likelihood - jacobian,
         convergence_flag: result.convergence_flag,
         score_vector: result.score_vector,
+        distribution: dist_type,
+        scale,
+        shape: None,
+    })
+}
+
+fn initial_location_scale(
+    distribution: DistributionType,
+    y: &[f64],
+    weights: &[f64],
+) -> (f64, f64) {
+    let total: f64 = weights.iter().sum();
+    let mean = y.iter().zip(weights).map(|(y, w)| w * y).sum::<f64>() / total;
+    let var = y
+        .iter()
+        .zip(weights)
+        .map(|(y, w)| w * (y - mean).powi(2))
+        .sum::<f64>()
+        / (total - 1.0).max(1.0);
+    if var <= 0.0 {
+        return (mean, 0.0);
+    }
+    let pi2 = std::f64::consts::PI.powi(2);
+    match distribution {
+        DistributionType::ExtremeValue
+        | DistributionType::Weibull
+        | DistributionType::Exponential => {
+            (mean + 0.572 * var.sqrt(), (6.0 * var / pi2).ln() / 2.0)
+        }
+        DistributionType::Logistic | DistributionType::LogLogistic => {
+            (mean, (3.0 * var / pi2).ln() / 2.0)
+        }
+        _ => (mean, var.ln() / 2.0),
+    }
+}
+
+fn survreg_general(
+    distribution: DistributionType,
+    data: &GeneralData,
+    covariates: ArrayView2<f64>,
+    initial_beta: Option<Vec<f64>>,
+    max_iter: usize,
+    eps: f64,
+    tol_chol: f64,
+) -> Result<SurvivalFit, SurvregError> {
+    let nvar = data.covariates.nrows();
+    let n_aux = match distribution {
+        DistributionType::Gompertz => {
+            if data.nstrat > 1 {
+                return Err(SurvregError::InvalidInput(
+                    "strata are not supported for the Gompertz distribution".to_string(),
+                ));
+            }
+            1
+        }
+        _ => data.nstrat + 1,
+    };
+
+    let initial = match initial_beta {
+        Some(beta) if beta.len() == nvar + n_aux => beta,
+        Some(_) => {
+            return Err(SurvregError::InvalidInput(format!(
+                "initial_beta must have length {}",
+                nvar + n_aux
+            )));
+        }
+        None => {
+            let (start_dist, sign) = match distribution {
+                DistributionType::Gompertz => ("exponential", -1.0),
+                _ => ("weibull", 1.0),
+            };
+            let start = survreg_response(
+                data.time1.to_vec(),
+                data.time2.map(|t| t.to_vec()),
+                data.status.to_vec(),
+                covariates,
+                Some(data.weights.to_vec()),
+                Some(data.offsets.to_vec()),
+                None,
+                Some(data.strata.to_vec()),
+                Some(start_dist),
+                Some(max_iter),
+                Some(eps),
+                Some(tol_chol),
+            )?;
+            let mut beta: Vec<f64> = start.coefficients.iter().map(|b| sign * b).collect();
+            beta.push(if distribution == DistributionType::Gompertz {
+                0.0
+            } else {
+                1.0
+            });
+            beta
+        }
+    };
+
+    let fit = fit_general(distribution, data, initial, max_iter, eps);
+    let (scale, shape) = match distribution {
+        DistributionType::Gompertz => (Vec::new(), fit.coefficients[nvar]),
+        _ => (
+            fit.coefficients[nvar..nvar + data.nstrat]
+                .iter()
+                .map(|s| s.exp())
+                .collect(),
+            fit.coefficients[nvar + data.nstrat],
+        ),
+    };
+
+    Ok(SurvivalFit {
+        variance_matrix: fit
+            .variance_matrix
+            .outer_iter()
+            .map(|row| row.to_vec())
+            .collect(),
+        coefficients: fit.coefficients,
+        iterations: fit.iterations,
+        log_likelihood: fit.log_likelihood,
+        convergence_flag: if fit.converged { 0 } else { -1 },
+        score_vector: fit.score_vector,
+        distribution,
+        scale,
+        shape: Some(shape),
     })
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn survreg_formula(
+    formula: &str,
+    data: ArrayView2<f64>,
+    variable_names: &[String],
+    weights: Option<Vec<f64>>,
+    distribution: Option<&str>,
+    max_iter: Option<usize>,
+    eps: Option<f64>,
+    tol_chol: Option<f64>,
+) -> Result<SurvivalFit, SurvregError> {
+    let frame = model_frame(formula, data, variable_names)?.unpenalized()?;
+    if frame.surv_type == Some(SurvType::Counting) {
+        return Err(SurvregError::InvalidInput(
+            "survreg does not accept (start, stop] responses; interval-censored data need Surv(..., type = \"interval\") or \"interval2\""
+                .to_string(),
+        ));
+    }
+    let status = frame
+        .status
+        .ok_or_else(|| FormulaError::InvalidResponse(formula.to_string()))?;
+    if frame.cluster.is_some() {
+        return Err(SurvregError::InvalidInput(
+            "survreg does not support cluster() terms".to_string(),
+        ));
+    }
+    let covariates = if frame.intercept {
+        frame
+            .covariates
+            .into_iter()
+            .map(|row| std::iter::once(1.0).chain(row).collect())
+            .collect()
+    } else {
+        frame.covariates
+    };
+    survreg_response(
+        frame.start.clone().unwrap_or_else(|| frame.time.clone()),
+        frame.start.is_some().then_some(frame.time),
+        status,
+        rows_to_matrix(&covariates)?.view(),
+        weights,
+        frame.offset,
+        None,
+        frame.strata,
+        distribution,
+        max_iter,
+        eps,
+        tol_chol,
+    )
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "survreg")]
+#[allow(clippy::too_many_arguments)]
+pub fn survreg_py(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, f64>,
+    covariates: MatrixArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    offsets: Option<VectorArg<'_, f64>>,
+    initial_beta: Option<VectorArg<'_, f64>>,
+    strata: Option<IndexArg>,
+    distribution: Option<&str>,
+    max_iter: Option<usize>,
+    eps: Option<f64>,
+    tol_chol: Option<f64>,
+) -> PyResult<SurvivalFit> {
+    let time = time.into_vec();
+    let status = status.into_vec();
+    let weights = weights.map(VectorArg::into_vec);
+    let offsets = offsets.map(VectorArg::into_vec);
+    let initial_beta = initial_beta.map(VectorArg::into_vec);
+    let strata = strata.map(IndexArg::into_vec);
+    let covariates = covariates.view();
+    Ok(parallel::detach(py, || {
+        survreg_response(
+            time,
+            None,
+            status,
+            covariates,
+            weights,
+            offsets,
+            initial_beta,
+            strata,
+            distribution,
+            max_iter,
+            eps,
+            tol_chol,
+        )
+    })?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "survreg_interval",
+    signature = (time, time2, status, covariates, weights=None, offsets=None, initial_beta=None, strata=None, distribution=None, max_iter=None, eps=None, tol_chol=None)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn survreg_interval_py(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    time2: VectorArg<'_, f64>,
+    status: VectorArg<'_, f64>,
+    covariates: MatrixArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    offsets: Option<VectorArg<'_, f64>>,
+    initial_beta: Option<VectorArg<'_, f64>>,
+    strata: Option<IndexArg>,
+    distribution: Option<&str>,
+    max_iter: Option<usize>,
+    eps: Option<f64>,
+    tol_chol: Option<f64>,
+) -> PyResult<SurvivalFit> {
+    let time = time.into_vec();
+    let time2 = time2.into_vec();
+    let status = status.into_vec();
+    let weights = weights.map(VectorArg::into_vec);
+    let offsets = offsets.map(VectorArg::into_vec);
+    let initial_beta = initial_beta.map(VectorArg::into_vec);
+    let strata = strata.map(IndexArg::into_vec);
+    let covariates = covariates.view();
+    Ok(parallel::detach(py, || {
+        survreg_response(
+            time,
+            Some(time2),
+            status,
+            covariates,
+            weights,
+            offsets,
+            initial_beta,
+            strata,
+            distribution,
+            max_iter,
+            eps,
+            tol_chol,
+        )
+    })?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "survreg_formula",
+    signature = (formula, data, variable_names, weights=None, distribution=None, max_iter=None, eps=None, tol_chol=None)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn survreg_formula_py(
+    py: Python<'_>,
+    formula: &str,
+    data: MatrixArg<'_, f64>,
+    variable_names: Vec<String>,
+    weights: Option<VectorArg<'_, f64>>,
+    distribution: Option<&str>,
+    max_iter: Option<usize>,
+    eps: Option<f64>,
+    tol_chol: Option<f64>,
+) -> PyResult<SurvivalFit> {
+    let data = data.view();
+    let weights = weights.map(VectorArg::into_vec);
+    Ok(parallel::detach(py, || {
+        survreg_formula(
+            formula,
+            data,
+            &variable_names,
+            weights,
+            distribution,
+            max_iter,
+            eps,
+            tol_chol,
+        )
+    })?)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn survreg_internal(
     max_iter: usize,
@@ -504,17 +1223,18 @@ fn survreg_internal(
 
     let mut iter = 0;
     let mut halving = 0;
+    let mut converged = false;
     while iter < max_iter {
-        let chol_result = cholesky_solve(&imat, &u, tol_chol);
-        let delta = match chol_result {
-            Ok(d) => d,
-            Err(_) => cholesky_solve(&jj, &u, tol_chol)?,
-        };
-
-        newbeta
-            .iter_mut()
-            .zip(beta.iter().zip(delta.iter()))
-            .for_each(|(nb, (b, d))| *nb = b + d);
+        if halving == 0 {
+            let delta = match cholesky_solve(&imat, &u, tol_chol) {
+                Ok(d) => d,
+                Err(_) => cholesky_solve(&jj, &u, tol_chol)?,
+            };
+            newbeta
+                .iter_mut()
+                .zip(beta.iter().zip(delta.iter()))
+                .for_each(|(nb, (b, d))| *nb = b + d);
+        }
 
         let newlik = calculate_likelihood(
             n,
@@ -538,6 +1258,7 @@ fn survreg_internal(
             loglik = newlik;
             beta = newbeta.clone();
             iter += 1;
+            converged = true;
             break;
         }
 
@@ -560,9 +1281,25 @@ fn survreg_internal(
         iter += 1;
     }
 
-    let converged = iter < max_iter;
     let convergence_flag = if converged { 0 } else { -1 };
 
+    calculate_likelihood(
+        n,
+        nvar,
+        nstrat,
+        &beta,
+        &distribution,
+        strata,
+        offsets,
+        &time1,
+        time2_view.as_ref(),
+        &status,
+        weights,
+        covariates,
+        &mut imat,
+        &mut jj,
+        &mut u,
+    )?;
     let variance = calculate_variance_matrix(imat, nvar2, tol_chol)?;
 
     Ok(SurvivalFitInternal {
@@ -584,10 +1321,13 @@ pub(crate) struct SurvivalFitInternal {
     score_vector: Vec<f64>,
 }
 
+#[cfg(feature = "python")]
 #[pymodule]
 #[pyo3(name = "survreg")]
 fn survreg_module(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(survreg, &m)?)?;
+    m.add_function(wrap_pyfunction!(survreg_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(survreg_formula_py, &m)?)?;
+    m.add_function(wrap_pyfunction!(survreg_interval_py, &m)?)?;
     m.add_class::<SurvivalFit>()?;
     m.add_class::<DistributionType>()?;
     Ok(())
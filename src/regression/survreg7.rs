@@ -3,7 +3,7 @@
 use crate::core::survpenal::{self, MatrixBuffers, PenaltyParams, PenaltyResult};
 use crate::regression::survregc1::SurvivalDist;
 use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
-use ndarray_linalg::{Cholesky, Inverse, Solve, UPLO};
+use ndarray_linalg::{Inverse, SolveC};
 
 #[derive(Debug)]
 pub struct SurvivalResult {
@@ -99,18 +99,11 @@ pub fn survreg(
 
     let mut iter = 0;
     while iter < max_iter {
-        let delta = match hmat.cholesky(UPLO::Lower) {
-            Ok(chol) => chol
-                .solve(&u)
+        let delta = match hmat.solvec(&u) {
+            Ok(delta) => delta,
+            Err(_) => jj
+                .solvec(&u)
                 .map_err(|_| "Cholesky solve failed".to_string())?,
-            Err(_) => {
-                let jj_chol = jj
-                    .cholesky(UPLO::Lower)
-                    .map_err(|_| "Cholesky decomposition failed".to_string())?;
-                jj_chol
-                    .solve(&u)
-                    .map_err(|_| "Cholesky solve failed".to_string())?
-            }
         };
 
         newbeta
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+use statrs::function::erf::erfc;
 use thiserror::Error;
 
 const SMALL: f64 = -200.0;
@@ -70,8 +71,10 @@ pub fn survregc1(
         if nstrat > 1 {
             strata = (strat[person] - 1) as usize;
             sigma = beta[nvar + nf + strata].exp();
-        } else {
+        } else if nstrat == 1 {
             sigma = beta[nvar + nf].exp();
+        } else {
+            sigma = 1.0;
         }
         _sig2 = 1.0 / (sigma * sigma);
 
@@ -162,97 +165,103 @@ fn compute_exact(
     }
 }
 
+fn tail_terms(z: f64, dist: SurvivalDist) -> Result<(f64, f64, f64, f64), DistributionError> {
+    let ((cdf, surv, f), (_, dlogf, _)) = match dist {
+        SurvivalDist::ExtremeValue | SurvivalDist::Weibull => (exvalue_d(z, 2)?, exvalue_d(z, 1)?),
+        SurvivalDist::Logistic => (logistic_d(z, 2)?, logistic_d(z, 1)?),
+        SurvivalDist::Gaussian | SurvivalDist::LogNormal => (gauss_d(z, 2)?, gauss_d(z, 1)?),
+    };
+    Ok((cdf, surv, f, f * dlogf))
+}
+
+fn chain_terms(z: f64, sigma: f64, g: f64, dg: f64, ddg: f64) -> (f64, f64, f64, f64, f64, f64) {
+    (
+        g,
+        -dg / sigma,
+        ddg / (sigma * sigma),
+        -z * dg,
+        z * dg + z * z * ddg,
+        (z * ddg + dg) / sigma,
+    )
+}
+
 #[allow(clippy::type_complexity)]
 fn compute_right_censored(
     z: f64,
-    sz: f64,
+    _sz: f64,
     sigma: f64,
     dist: SurvivalDist,
 ) -> Result<(f64, f64, f64, f64, f64, f64), Box<dyn std::error::Error>> {
-    let (f, df, _ddf) = match dist {
-        SurvivalDist::ExtremeValue | SurvivalDist::Weibull => exvalue_d(z, 2)?,
-        SurvivalDist::Logistic => logistic_d(z, 2)?,
-        SurvivalDist::Gaussian | SurvivalDist::LogNormal => gauss_d(z, 2)?,
-    };
-
-    if f <= 0.0 || f >= 1.0 {
-        Ok((SMALL, 0.0, 0.0, 0.0, 0.0, 0.0))
+    let (_, surv, f, df) = tail_terms(z, dist)?;
+    if surv <= 0.0 {
+        Ok((SMALL, z / sigma, 0.0, 0.0, 0.0, 0.0))
     } else {
-        let g = f.ln();
-        let temp = df / (f * sigma);
-        let dg = temp;
-        let dsig = temp * sz;
-        let ddg = -dg.powi(2);
-        let dsg = -sz * dg.powi(2);
-        let ddsig = -sz.powi(2) * dg.powi(2);
-        Ok((g, dg, ddg, dsig, ddsig, dsg))
+        let ratio = f / surv;
+        Ok(chain_terms(
+            z,
+            sigma,
+            surv.ln(),
+            -ratio,
+            -df / surv - ratio * ratio,
+        ))
     }
 }
 
 #[allow(clippy::type_complexity)]
 fn compute_left_censored(
     z: f64,
-    sz: f64,
+    _sz: f64,
     sigma: f64,
     dist: SurvivalDist,
 ) -> Result<(f64, f64, f64, f64, f64, f64), Box<dyn std::error::Error>> {
-    let (f, df, _ddf) = match dist {
-        SurvivalDist::ExtremeValue | SurvivalDist::Weibull => exvalue_d(z, 2)?,
-        SurvivalDist::Logistic => logistic_d(z, 2)?,
-        SurvivalDist::Gaussian | SurvivalDist::LogNormal => gauss_d(z, 2)?,
-    };
-
-    if f <= 0.0 || f >= 1.0 {
-        Ok((SMALL, 0.0, 0.0, 0.0, 0.0, 0.0))
+    let (cdf, _, f, df) = tail_terms(z, dist)?;
+    if cdf <= 0.0 {
+        Ok((SMALL, -z / sigma, 0.0, 0.0, 0.0, 0.0))
     } else {
-        let g = (1.0 - f).ln();
-        let temp = -df / ((1.0 - f) * sigma);
-        let dg = temp;
-        let dsig = temp * sz;
-        let ddg = -dg.powi(2);
-        let dsg = -sz * dg.powi(2);
-        let ddsig = -sz.powi(2) * dg.powi(2);
-        Ok((g, dg, ddg, dsig, ddsig, dsg))
+        let ratio = f / cdf;
+        Ok(chain_terms(
+            z,
+            sigma,
+            cdf.ln(),
+            ratio,
+            df / cdf - ratio * ratio,
+        ))
     }
 }
 
 #[allow(clippy::type_complexity)]
 fn compute_interval_censored(
     z: f64,
-    sz: f64,
+    _sz: f64,
     time2: f64,
     eta: f64,
     sigma: f64,
     dist: SurvivalDist,
 ) -> Result<(f64, f64, f64, f64, f64, f64), Box<dyn std::error::Error>> {
-    let sz2 = time2 - eta;
-    let z2 = sz2 / sigma;
+    let z2 = (time2 - eta) / sigma;
+    let (cdf1, surv1, f1, df1) = tail_terms(z, dist)?;
+    let (cdf2, surv2, f2, df2) = tail_terms(z2, dist)?;
 
-    let (f1, df1, _ddf1) = match dist {
-        SurvivalDist::ExtremeValue | SurvivalDist::Weibull => exvalue_d(z, 2)?,
-        SurvivalDist::Logistic => logistic_d(z, 2)?,
-        SurvivalDist::Gaussian | SurvivalDist::LogNormal => gauss_d(z, 2)?,
-    };
-
-    let (f2, df2, _ddf2) = match dist {
-        SurvivalDist::ExtremeValue | SurvivalDist::Weibull => exvalue_d(z2, 2)?,
-        SurvivalDist::Logistic => logistic_d(z2, 2)?,
-        SurvivalDist::Gaussian | SurvivalDist::LogNormal => gauss_d(z2, 2)?,
-    };
-
-    let diff = f2 - f1;
+    let diff = if z > 0.0 { surv1 - surv2 } else { cdf2 - cdf1 };
     if diff <= 0.0 {
-        Ok((SMALL, 0.0, 0.0, 0.0, 0.0, 0.0))
+        Ok((SMALL, 1.0, 0.0, 0.0, 0.0, 0.0))
     } else {
-        let g = diff.ln();
-        let temp1 = df1 / (diff * sigma);
-        let temp2 = df2 / (diff * sigma);
-        let dg = temp2 - temp1;
-        let dsig = (temp2 * sz2 - temp1 * sz) / sigma;
-        let ddg = -(dg.powi(2));
-        let dsg = -(sz * temp1.powi(2) + sz2 * temp2.powi(2)) / sigma;
-        let ddsig = -(sz.powi(2) * temp1.powi(2) + sz2.powi(2) * temp2.powi(2)) / (sigma * sigma);
-        Ok((g, dg, ddg, dsig, ddsig, dsg))
+        let d_eta = -(f2 - f1) / sigma;
+        let d_sig = -(z2 * f2 - z * f1);
+        let dd_eta = (df2 - df1) / (sigma * sigma);
+        let dd_sig = z2 * f2 + z2 * z2 * df2 - z * f1 - z * z * df1;
+        let d_eta_sig = (z2 * df2 - z * df1 + f2 - f1) / sigma;
+
+        let dg = d_eta / diff;
+        let dsig = d_sig / diff;
+        Ok((
+            diff.ln(),
+            dg,
+            dd_eta / diff - dg * dg,
+            dsig,
+            dd_sig / diff - dsig * dsig,
+            d_eta_sig / diff - dg * dsig,
+        ))
     }
 }
 
@@ -272,10 +281,12 @@ fn logistic_d(z: f64, case: i32) -> Result<(f64, f64, f64), DistributionError> {
             Ok((f, df, ddf))
         }
         2 => {
-            let f = w / temp;
-            let df = w / temp.powi(2);
-            let ddf = sign * df * (1.0 - w) / temp;
-            Ok((f, df, ddf))
+            let (cdf, surv) = if z > 0.0 {
+                (1.0 / temp, w / temp)
+            } else {
+                (w / temp, 1.0 / temp)
+            };
+            Ok((cdf, surv, w / temp.powi(2)))
         }
         _ => Err(DistributionError::InvalidCase {
             case,
@@ -289,12 +300,9 @@ fn gauss_d(z: f64, case: i32) -> Result<(f64, f64, f64), DistributionError> {
     match case {
         1 => Ok((f, -z, z.powi(2) - 1.0)),
         2 => {
-            let (f0, f1) = if z > 0.0 {
-                ((1.0 + erf(z / ROOT_2)) / 2.0, erfc(z / ROOT_2) / 2.0)
-            } else {
-                (erfc(-z / ROOT_2) / 2.0, (1.0 + erf(-z / ROOT_2)) / 2.0)
-            };
-            Ok((f0, f1, -z * f))
+            let cdf = erfc(-z / ROOT_2) / 2.0;
+            let surv = erfc(z / ROOT_2) / 2.0;
+            Ok((cdf, surv, f))
         }
         _ => Err(DistributionError::InvalidCase {
             case,
@@ -309,7 +317,7 @@ fn exvalue_d(z: f64, case: i32) -> Result<(f64, f64, f64), DistributionError> {
 
     match case {
         1 => Ok((w * temp, 1.0 - w, w * (w - 3.0) + 1.0)),
-        2 => Ok((1.0 - temp, temp, w * temp * (1.0 - w))),
+        2 => Ok((-(-w).exp_m1(), temp, w * temp)),
         _ => Err(DistributionError::InvalidCase {
             case,
             distribution: "extreme value".to_string(),
@@ -317,25 +325,6 @@ fn exvalue_d(z: f64, case: i32) -> Result<(f64, f64, f64), DistributionError> {
     }
 }
 
-fn erf(x: f64) -> f64 {
-    let a1 = 0.254829592;
-    let a2 = -0.284496736;
-    let a3 = 1.421413741;
-    let a4 = -1.453152027;
-    let a5 = 1.061405429;
-    let p = 0.3275911;
-
-    let sign = if x < 0.0 { -1.0 } else { 1.0 };
-    let x = x.abs();
-    let t = 1.0 / (1.0 + p * x);
-    let y = 1.0 - ((((a5 * t + a4) * t + a3) * t + a2) * t + a1) * t * (-x * x).exp();
-    sign * y
-}
-
-fn erfc(x: f64) -> f64 {
-    1.0 - erf(x)
-}
-
 #[allow(clippy::type_complexity)]
 #[allow(clippy::too_many_arguments)]
 fn update_derivatives(
@@ -1,4 +1,10 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use crate::utilities::validation::{validate_length, validate_non_empty};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 // agmart computes martingale residuals using a counting process formulation.
@@ -14,22 +20,22 @@ use pyo3::prelude::*;
 //   sort orders for entries and exits. More flexible for complex
 //   sorting scenarios but requires pre-computed sort indices.
 
-struct AgmartInput {
-    start: Vec<f64>,
-    stop: Vec<f64>,
-    event: Vec<i32>,
-    score: Vec<f64>,
-    wt: Vec<f64>,
-    strata: Vec<i32>,
+pub struct AgmartInput<'a> {
+    pub start: &'a [f64],
+    pub stop: &'a [f64],
+    pub event: &'a [i32],
+    pub score: &'a [f64],
+    pub wt: &'a [f64],
+    pub strata: &'a [i32],
 }
 
-fn agmart_internal(n: usize, method: i32, input: AgmartInput) -> Vec<f64> {
-    let start_slice = &input.start;
-    let stop_slice = &input.stop;
-    let event_slice = &input.event;
-    let score_slice = &input.score;
-    let wt_slice = &input.wt;
-    let strata_slice = &input.strata;
+pub fn agmart_internal(n: usize, method: i32, input: AgmartInput) -> Vec<f64> {
+    let start_slice = input.start;
+    let stop_slice = input.stop;
+    let event_slice = input.event;
+    let score_slice = input.score;
+    let wt_slice = input.wt;
+    let strata_slice = input.strata;
     let mut resid = vec![0.0; n];
     let nused = n;
     let mut local_strata = strata_slice.to_vec();
@@ -113,32 +119,42 @@ fn agmart_internal(n: usize, method: i32, input: AgmartInput) -> Vec<f64> {
 }
 
 #[allow(clippy::too_many_arguments)]
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn agmart(
+    py: Python<'_>,
     n: usize,
     method: i32,
-    start: Vec<f64>,
-    stop: Vec<f64>,
-    event: Vec<i32>,
-    score: Vec<f64>,
-    wt: Vec<f64>,
-    strata: Vec<i32>,
+    start: VectorArg<'_, f64>,
+    stop: VectorArg<'_, f64>,
+    event: VectorArg<'_, i32>,
+    score: VectorArg<'_, f64>,
+    wt: VectorArg<'_, f64>,
+    strata: VectorArg<'_, i32>,
 ) -> PyResult<Vec<f64>> {
-    validate_non_empty(&start, "start")?;
-    validate_length(n, start.len(), "start")?;
-    validate_length(n, stop.len(), "stop")?;
-    validate_length(n, event.len(), "event")?;
-    validate_length(n, score.len(), "score")?;
-    validate_length(n, wt.len(), "wt")?;
-    validate_length(n, strata.len(), "strata")?;
-
-    let input = AgmartInput {
-        start,
-        stop,
-        event,
-        score,
-        wt,
-        strata,
-    };
-    Ok(agmart_internal(n, method, input))
+    let start = start.as_slice();
+    let stop = stop.as_slice();
+    let event = event.as_slice();
+    let score = score.as_slice();
+    let wt = wt.as_slice();
+    let strata = strata.as_slice();
+    parallel::detach(py, || {
+        validate_non_empty(&start, "start")?;
+        validate_length(n, start.len(), "start")?;
+        validate_length(n, stop.len(), "stop")?;
+        validate_length(n, event.len(), "event")?;
+        validate_length(n, score.len(), "score")?;
+        validate_length(n, wt.len(), "wt")?;
+        validate_length(n, strata.len(), "strata")?;
+
+        let input = AgmartInput {
+            start: &start,
+            stop: &stop,
+            event: &event,
+            score: &score,
+            wt: &wt,
+            strata: &strata,
+        };
+        Ok(agmart_internal(n, method, input))
+    })
 }
@@ -11,6 +11,7 @@ pub struct Weights<'a> {
     pub wt: &'a [f64],
 }
 
+#[cfg(feature = "python")]
 #[pymodule]
 #[pyo3(name = "coxmart")]
 fn coxmart_module(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
@@ -18,55 +19,69 @@ fn coxmart_module(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
     Ok(())
 }
 
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn coxmart(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    score: Vec<f64>,
-    weights: Option<Vec<f64>>,
-    strata: Option<Vec<i32>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    score: VectorArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    strata: Option<VectorArg<'_, i32>>,
     method: Option<i32>,
 ) -> PyResult<Vec<f64>> {
-    let n = time.len();
-    if status.len() != n || score.len() != n {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "time, status, and score must have the same length",
-        ));
-    }
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let score = score.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let strata = strata.map(VectorArg::into_vec);
+    parallel::detach(py, || {
+        let n = time.len();
+        if status.len() != n || score.len() != n {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "time, status, and score must have the same length",
+            ));
+        }
 
-    let weights_vec = weights.unwrap_or_else(|| vec![1.0; n]);
-    let mut strata_vec = strata.unwrap_or_else(|| vec![0; n]);
-    let method_val = method.unwrap_or(0);
+        let weights_vec = weights.unwrap_or_else(|| vec![1.0; n].into());
+        let mut strata_vec = strata.unwrap_or_else(|| vec![0; n]);
+        let method_val = method.unwrap_or(0);
 
-    if weights_vec.len() != n {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "weights must have the same length as time",
-        ));
-    }
-    if strata_vec.len() != n {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "strata must have the same length as time",
-        ));
-    }
+        if weights_vec.len() != n {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "weights must have the same length as time",
+            ));
+        }
+        if strata_vec.len() != n {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "strata must have the same length as time",
+            ));
+        }
 
-    let mut expect = vec![0.0; n];
+        let mut expect = vec![0.0; n];
 
-    let surv_data = SurvivalData {
-        time: &time,
-        status: &status,
-        strata: &mut strata_vec,
-    };
+        let surv_data = SurvivalData {
+            time: &time,
+            status: &status,
+            strata: &mut strata_vec,
+        };
 
-    let weights_data = Weights {
-        score: &score,
-        wt: &weights_vec,
-    };
+        let weights_data = Weights {
+            score: &score,
+            wt: &weights_vec,
+        };
 
-    coxmart_internal(n, method_val, surv_data, weights_data, &mut expect);
+        coxmart_internal(n, method_val, surv_data, weights_data, &mut expect);
 
-    Ok(expect)
+        Ok(expect)
+    })
 }
 
 #[allow(dead_code)]
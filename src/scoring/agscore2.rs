@@ -1,6 +1,13 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use ndarray::Array2;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 pub fn agscore2(
@@ -152,15 +159,22 @@ pub fn agscore2(
     Ok(resid_matrix.into_raw_vec_and_offset().0)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn perform_score_calculation(
-    time_data: Vec<f64>,
-    covariates: Vec<f64>,
-    strata: Vec<i32>,
-    score: Vec<f64>,
-    weights: Vec<f64>,
+    py: Python<'_>,
+    time_data: VectorArg<'_, f64>,
+    covariates: VectorArg<'_, f64>,
+    strata: VectorArg<'_, i32>,
+    score: VectorArg<'_, f64>,
+    weights: VectorArg<'_, f64>,
     method: i32,
 ) -> PyResult<Py<PyAny>> {
+    let time_data = time_data.as_slice();
+    let covariates = covariates.as_slice();
+    let strata = strata.as_slice();
+    let score = score.as_slice();
+    let weights = weights.as_slice();
     let n = weights.len();
     if n == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
@@ -196,8 +210,10 @@ pub fn perform_score_calculation(
         ));
     }
 
-    let residuals = agscore2(&time_data, &covariates, &strata, &score, &weights, method)
-        .map_err(PyRuntimeError::new_err)?;
+    let residuals = parallel::detach(py, || {
+        agscore2(&time_data, &covariates, &strata, &score, &weights, method)
+    })
+    .map_err(PyRuntimeError::new_err)?;
 
     let nvar = covariates.len() / n;
     let mut summary_stats = Vec::new();
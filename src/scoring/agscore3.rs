@@ -1,6 +1,13 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use ndarray::{Array2, ArrayView2};
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 pub fn agscore3(
@@ -13,6 +20,9 @@ pub fn agscore3(
     sort1: &[i32],
 ) -> Result<Vec<f64>, String> {
     let n = y.len() / 3;
+    if n == 0 {
+        return Ok(Vec::new());
+    }
     let nvar = covar.len() / n;
 
     let tstart = &y[0..n];
@@ -38,16 +48,17 @@ pub fn agscore3(
     let mut cumhaz = 0.0;
     let mut denom = 0.0;
     let mut current_stratum = *strata.last().unwrap_or(&0);
-    let mut i1 = n - 1;
     let sort1: Vec<usize> = sort1.iter().map(|&x| (x - 1) as usize).collect();
+    let mut i1 = n as isize - 1;
 
-    let mut person = n - 1;
-    while person > 0 {
-        let dtime = tstop[person];
+    let mut person = n as isize - 1;
+    while person >= 0 {
+        let p = person as usize;
+        let dtime = tstop[p];
 
-        if strata[person] != current_stratum {
-            while i1 > 0 && sort1[i1] > person {
-                let k = sort1[i1];
+        if strata[p] != current_stratum {
+            while i1 >= 0 && strata[sort1[i1 as usize]] == current_stratum {
+                let k = sort1[i1 as usize];
                 for j in 0..nvar {
                     resid_matrix[[j, k]] -= score[k] * (cumhaz * covar_matrix[[j, k]] - xhaz[j]);
                 }
@@ -58,23 +69,23 @@ pub fn agscore3(
             denom = 0.0;
             a.iter_mut().for_each(|x| *x = 0.0);
             xhaz.iter_mut().for_each(|x| *x = 0.0);
-            current_stratum = strata[person];
-        } else {
-            while i1 > 0 && tstart[sort1[i1]] >= dtime {
-                let k = sort1[i1];
-                if strata[k] != current_stratum {
-                    break;
-                }
+            current_stratum = strata[p];
+        }
 
-                let risk = score[k] * weights[k];
-                denom -= risk;
+        while i1 >= 0 {
+            let k = sort1[i1 as usize];
+            if strata[k] != current_stratum || tstart[k] < dtime {
+                break;
+            }
 
-                for j in 0..nvar {
-                    resid_matrix[[j, k]] -= score[k] * (cumhaz * covar_matrix[[j, k]] - xhaz[j]);
-                    a[j] -= risk * covar_matrix[[j, k]];
-                }
-                i1 -= 1;
+            let risk = score[k] * weights[k];
+            denom -= risk;
+
+            for j in 0..nvar {
+                resid_matrix[[j, k]] -= score[k] * (cumhaz * covar_matrix[[j, k]] - xhaz[j]);
+                a[j] -= risk * covar_matrix[[j, k]];
             }
+            i1 -= 1;
         }
 
         let mut e_denom = 0.0;
@@ -82,35 +93,34 @@ pub fn agscore3(
         let mut meanwt = 0.0;
         a2.iter_mut().for_each(|x| *x = 0.0);
 
-        let mut processed = 0;
-        while person > 0 && tstop[person] == dtime {
-            if strata[person] != current_stratum {
-                break;
-            }
-
+        let last = p;
+        while person >= 0
+            && tstop[person as usize] == dtime
+            && strata[person as usize] == current_stratum
+        {
+            let q = person as usize;
             for j in 0..nvar {
-                resid_matrix[[j, person]] =
-                    (covar_matrix[[j, person]] * cumhaz - xhaz[j]) * score[person];
+                resid_matrix[[j, q]] = (covar_matrix[[j, q]] * cumhaz - xhaz[j]) * score[q];
             }
 
-            let risk = score[person] * weights[person];
+            let risk = score[q] * weights[q];
             denom += risk;
             for j in 0..nvar {
-                a[j] += risk * covar_matrix[[j, person]];
+                a[j] += risk * covar_matrix[[j, q]];
             }
 
-            if event[person] > 0.5 {
+            if event[q] > 0.5 {
                 deaths += 1.0;
                 e_denom += risk;
-                meanwt += weights[person];
+                meanwt += weights[q];
                 for j in 0..nvar {
-                    a2[j] += risk * covar_matrix[[j, person]];
+                    a2[j] += risk * covar_matrix[[j, q]];
                 }
             }
 
             person -= 1;
-            processed += 1;
         }
+        let first = (person + 1) as usize;
 
         if deaths > 0.0 {
             if deaths < 2.0 || method == 0 {
@@ -121,8 +131,10 @@ pub fn agscore3(
                     mean[j] = a[j] / denom;
                     xhaz[j] += mean[j] * hazard;
 
-                    for k in person + 1..=person + processed {
-                        resid_matrix[[j, k]] += covar_matrix[[j, k]] - mean[j];
+                    for k in first..=last {
+                        if event[k] > 0.5 {
+                            resid_matrix[[j, k]] += covar_matrix[[j, k]] - mean[j];
+                        }
                     }
                 }
             } else {
@@ -146,7 +158,10 @@ pub fn agscore3(
                     }
                 }
 
-                for k in person + 1..=person + processed {
+                for k in first..=last {
+                    if event[k] <= 0.5 {
+                        continue;
+                    }
                     for j in 0..nvar {
                         resid_matrix[[j, k]] += (covar_matrix[[j, k]] - mh3[j])
                             + score[k] * (covar_matrix[[j, k]] * mh1[j] - mh2[j]);
@@ -156,8 +171,8 @@ pub fn agscore3(
         }
     }
 
-    while i1 > 0 {
-        let k = sort1[i1];
+    while i1 >= 0 {
+        let k = sort1[i1 as usize];
         for j in 0..nvar {
             resid_matrix[[j, k]] -= score[k] * (cumhaz * covar_matrix[[j, k]] - xhaz[j]);
         }
@@ -167,16 +182,25 @@ pub fn agscore3(
     Ok(resid_matrix.into_raw_vec_and_offset().0)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
+#[allow(clippy::too_many_arguments)]
 pub fn perform_agscore3_calculation(
-    time_data: Vec<f64>,
-    covariates: Vec<f64>,
-    strata: Vec<i32>,
-    score: Vec<f64>,
-    weights: Vec<f64>,
+    py: Python<'_>,
+    time_data: VectorArg<'_, f64>,
+    covariates: VectorArg<'_, f64>,
+    strata: VectorArg<'_, i32>,
+    score: VectorArg<'_, f64>,
+    weights: VectorArg<'_, f64>,
     method: i32,
-    sort1: Vec<i32>,
+    sort1: VectorArg<'_, i32>,
 ) -> PyResult<Py<PyAny>> {
+    let time_data = time_data.as_slice();
+    let covariates = covariates.as_slice();
+    let strata = strata.as_slice();
+    let score = score.as_slice();
+    let weights = weights.as_slice();
+    let sort1 = sort1.as_slice();
     let n = weights.len();
     if n == 0 {
         return Err(PyRuntimeError::new_err("No observations provided"));
@@ -218,15 +242,17 @@ pub fn perform_agscore3_calculation(
         ));
     }
 
-    let residuals = agscore3(
-        &time_data,
-        &covariates,
-        &strata,
-        &score,
-        &weights,
-        method,
-        &sort1,
-    )
+    let residuals = parallel::detach(py, || {
+        agscore3(
+            &time_data,
+            &covariates,
+            &strata,
+            &score,
+            &weights,
+            method,
+            &sort1,
+        )
+    })
     .map_err(PyRuntimeError::new_err)?;
 
     let nvar = covariates.len() / n;
@@ -1,6 +1,11 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-pub(crate) struct CoxScoreData<'a> {
+pub struct CoxScoreData<'a> {
     pub y: &'a [f64],
     pub strata: &'a [i32],
     pub covar: &'a [f64],
@@ -8,13 +13,13 @@ pub(crate) struct CoxScoreData<'a> {
     pub weights: &'a [f64],
 }
 
-pub(crate) struct CoxScoreParams {
+pub struct CoxScoreParams {
     pub method: i32,
     pub n: usize,
     pub nvar: usize,
 }
 
-pub(crate) fn cox_score_residuals_internal(data: CoxScoreData, params: CoxScoreParams) -> Vec<f64> {
+pub fn cox_score_residuals_internal(data: CoxScoreData, params: CoxScoreParams) -> Vec<f64> {
     let time = &data.y[0..params.n];
     let status = &data.y[params.n..2 * params.n];
     let mut resid = vec![0.0; params.n * params.nvar];
@@ -97,8 +102,10 @@ pub(crate) fn cox_score_residuals_internal(data: CoxScoreData, params: CoxScoreP
 
                     for k in processed_start..=processed_end {
                         let k_usize = k as usize;
-                        let idx = k_usize * params.nvar + var;
-                        resid[idx] += data.covar[idx] - xbar;
+                        if status[k_usize] == 1.0 {
+                            let idx = k_usize * params.nvar + var;
+                            resid[idx] += data.covar[idx] - xbar;
+                        }
                     }
                 }
             } else {
@@ -115,6 +122,9 @@ pub(crate) fn cox_score_residuals_internal(data: CoxScoreData, params: CoxScoreP
 
                         for k in processed_start..=processed_end {
                             let k_usize = k as usize;
+                            if status[k_usize] != 1.0 {
+                                continue;
+                            }
                             let idx = k_usize * params.nvar + var;
                             let temp2 = data.covar[idx] - xbar;
                             resid[idx] += temp2 / deaths;
@@ -155,49 +165,59 @@ pub(crate) fn cox_score_residuals_internal(data: CoxScoreData, params: CoxScoreP
     resid
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (y, strata, covar, score, weights, nvar, method=0))]
+#[allow(clippy::too_many_arguments)]
 pub fn cox_score_residuals(
-    y: Vec<f64>,
-    strata: Vec<i32>,
-    covar: Vec<f64>,
-    score: Vec<f64>,
-    weights: Vec<f64>,
+    py: Python<'_>,
+    y: VectorArg<'_, f64>,
+    strata: VectorArg<'_, i32>,
+    covar: VectorArg<'_, f64>,
+    score: VectorArg<'_, f64>,
+    weights: VectorArg<'_, f64>,
     nvar: usize,
     method: i32,
 ) -> PyResult<Vec<f64>> {
-    let n = score.len();
-
-    if y.len() < 2 * n {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "y array must have length >= 2 * n (time, status)",
-        ));
-    }
-    if strata.len() < n {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "strata array length must match n",
-        ));
-    }
-    if covar.len() < n * nvar {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "covar array must have length >= n * nvar",
-        ));
-    }
-    if weights.len() < n {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "weights array length must match n",
-        ));
-    }
+    let y = y.as_slice();
+    let strata = strata.as_slice();
+    let covar = covar.as_slice();
+    let score = score.as_slice();
+    let weights = weights.as_slice();
+    parallel::detach(py, || {
+        let n = score.len();
+
+        if y.len() < 2 * n {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "y array must have length >= 2 * n (time, status)",
+            ));
+        }
+        if strata.len() < n {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "strata array length must match n",
+            ));
+        }
+        if covar.len() < n * nvar {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "covar array must have length >= n * nvar",
+            ));
+        }
+        if weights.len() < n {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "weights array length must match n",
+            ));
+        }
 
-    let data = CoxScoreData {
-        y: &y,
-        strata: &strata,
-        covar: &covar,
-        score: &score,
-        weights: &weights,
-    };
+        let data = CoxScoreData {
+            y: &y,
+            strata: &strata,
+            covar: &covar,
+            score: &score,
+            weights: &weights,
+        };
 
-    let params = CoxScoreParams { method, n, nvar };
+        let params = CoxScoreParams { method, n, nvar };
 
-    Ok(cox_score_residuals_internal(data, params))
+        Ok(cox_score_residuals_internal(data, params))
+    })
 }
@@ -1,7 +1,53 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+use crate::surv_analysis::survfitkm::survfitkm_internal;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::validation::calibration::CensoringDistribution;
+use ndarray::ArrayView2;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use rayon::prelude::*;
+use thiserror::Error;
 
-fn brier_internal(predictions: &[f64], outcomes: &[i32], weights: Option<&[f64]>) -> Option<f64> {
+#[derive(Error, Debug)]
+pub enum BrierError {
+    #[error("time, status and predictions must have the same number of observations")]
+    LengthMismatch,
+    #[error("each prediction row must have one survival probability per evaluation time")]
+    PredictionWidth,
+    #[error("evaluation times must be non-empty and increasing")]
+    InvalidTimes,
+    #[error("predicted survival probabilities must be between 0 and 1")]
+    InvalidPrediction,
+}
+
+#[cfg(feature = "python")]
+impl From<BrierError> for PyErr {
+    fn from(err: BrierError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct SurvivalBrierResult {
+    pub times: Vec<f64>,
+    pub brier: Vec<f64>,
+    pub reference_brier: Vec<f64>,
+    pub ipa: Vec<f64>,
+    pub integrated_brier: f64,
+    pub reference_integrated_brier: f64,
+    pub integrated_ipa: f64,
+}
+
+pub fn brier_internal(
+    predictions: &[f64],
+    outcomes: &[i32],
+    weights: Option<&[f64]>,
+) -> Option<f64> {
     let n = predictions.len();
     if n != outcomes.len() {
         return None;
@@ -34,137 +80,291 @@ fn brier_internal(predictions: &[f64], outcomes: &[i32], weights: Option<&[f64]>
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (predictions, outcomes, weights=None))]
 pub fn brier(
-    predictions: Vec<f64>,
-    outcomes: Vec<i32>,
-    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+    predictions: VectorArg<'_, f64>,
+    outcomes: VectorArg<'_, i32>,
+    weights: Option<VectorArg<'_, f64>>,
 ) -> PyResult<f64> {
-    let n = predictions.len();
-    if n != outcomes.len() {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "predictions and outcomes must have the same length",
-        ));
-    }
-
-    if n == 0 {
-        return Ok(0.0);
-    }
-
-    let weights = if let Some(w) = weights {
-        if w.len() != n {
+    let predictions = predictions.as_slice();
+    let outcomes = outcomes.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    parallel::detach(py, || {
+        let n = predictions.len();
+        if n != outcomes.len() {
             return Err(pyo3::exceptions::PyValueError::new_err(
-                "weights must have the same length as predictions",
+                "predictions and outcomes must have the same length",
             ));
         }
-        w
-    } else {
-        vec![1.0; n]
-    };
 
-    let mut score = 0.0;
-    let mut total_weight = 0.0;
+        if n == 0 {
+            return Ok(0.0);
+        }
 
-    for i in 0..n {
-        let pred = predictions[i];
-        let obs = outcomes[i] as f64;
-        let w = weights[i];
+        let weights = if let Some(w) = weights {
+            if w.len() != n {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "weights must have the same length as predictions",
+                ));
+            }
+            w
+        } else {
+            vec![1.0; n].into()
+        };
 
-        if !(0.0..=1.0).contains(&pred) {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "predictions must be between 0 and 1",
-            ));
-        }
+        let mut score = 0.0;
+        let mut total_weight = 0.0;
 
-        score += w * (pred - obs).powi(2);
-        total_weight += w;
-    }
+        for i in 0..n {
+            let pred = predictions[i];
+            let obs = outcomes[i] as f64;
+            let w = weights[i];
 
-    if total_weight > 0.0 {
-        Ok(score / total_weight)
-    } else {
-        Ok(0.0)
-    }
+            if !(0.0..=1.0).contains(&pred) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "predictions must be between 0 and 1",
+                ));
+            }
+
+            score += w * (pred - obs).powi(2);
+            total_weight += w;
+        }
+
+        if total_weight > 0.0 {
+            Ok(score / total_weight)
+        } else {
+            Ok(0.0)
+        }
+    })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (predictions, outcomes, times, weights=None))]
 pub fn integrated_brier(
-    predictions: Vec<Vec<f64>>,
-    outcomes: Vec<i32>,
-    times: Vec<f64>,
-    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+    predictions: MatrixArg<'_, f64>,
+    outcomes: VectorArg<'_, i32>,
+    times: VectorArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
 ) -> PyResult<f64> {
-    if predictions.is_empty() {
-        return Ok(0.0);
-    }
-
-    let n_obs = predictions.len();
-    let n_times = predictions[0].len();
+    let predictions = predictions.view();
+    let outcomes = outcomes.as_slice();
+    let times = times.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    parallel::detach(py, || {
+        if predictions.nrows() == 0 {
+            return Ok(0.0);
+        }
 
-    if n_times != times.len() {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "number of time points must match number of prediction columns",
-        ));
-    }
+        let (n_obs, n_times) = predictions.dim();
 
-    if n_obs != outcomes.len() {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "predictions and outcomes must have the same number of observations",
-        ));
-    }
+        if n_times != times.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "number of time points must match number of prediction columns",
+            ));
+        }
 
-    for pred_row in &predictions {
-        if pred_row.len() != n_times {
+        if n_obs != outcomes.len() {
             return Err(pyo3::exceptions::PyValueError::new_err(
-                "all prediction rows must have the same length",
+                "predictions and outcomes must have the same number of observations",
             ));
         }
-    }
 
-    let mut time_intervals = Vec::with_capacity(n_times);
+        let mut time_intervals = Vec::with_capacity(n_times);
 
-    for i in 0..n_times {
-        let interval_width = if i == 0 {
-            if n_times > 1 {
-                times[1] - times[0]
+        for i in 0..n_times {
+            let interval_width = if i == 0 {
+                if n_times > 1 {
+                    times[1] - times[0]
+                } else {
+                    1.0
+                }
+            } else if i == n_times - 1 {
+                times[i] - times[i - 1]
             } else {
-                1.0
+                (times[i + 1] - times[i - 1]) / 2.0
+            };
+            time_intervals.push(interval_width);
+        }
+
+        let total_time: f64 = time_intervals.iter().sum();
+
+        let weights_ref = weights.as_deref();
+
+        let result: Result<Vec<f64>, _> = time_intervals
+            .par_iter()
+            .enumerate()
+            .map(|(t_idx, &interval)| {
+                let preds_at_t = predictions.column(t_idx).to_vec();
+                brier_internal(&preds_at_t, &outcomes, weights_ref)
+                    .map(|score| score * interval)
+                    .ok_or("invalid prediction value")
+            })
+            .collect();
+
+        match result {
+            Ok(scores) => {
+                let integrated_score: f64 = scores.iter().sum();
+                if total_time > 0.0 {
+                    Ok(integrated_score / total_time)
+                } else {
+                    Ok(0.0)
+                }
             }
-        } else if i == n_times - 1 {
-            times[i] - times[i - 1]
-        } else {
-            (times[i + 1] - times[i - 1]) / 2.0
-        };
-        time_intervals.push(interval_width);
+            Err(_) => Err(pyo3::exceptions::PyValueError::new_err(
+                "predictions must be between 0 and 1",
+            )),
+        }
+    })
+}
+
+fn ipcw_brier_at(
+    time: &[f64],
+    status: &[i32],
+    survival: impl Fn(usize) -> f64,
+    censoring: &CensoringDistribution,
+    t: f64,
+) -> f64 {
+    let control_weight = 1.0 / censoring.at(t, true);
+    let total: f64 = (0..time.len())
+        .map(|i| {
+            let s = survival(i);
+            if time[i] <= t && status[i] == 1 {
+                s * s / censoring.at(time[i], false)
+            } else if time[i] > t {
+                (1.0 - s) * (1.0 - s) * control_weight
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    total / time.len() as f64
+}
+
+fn integrate_over(times: &[f64], values: &[f64]) -> f64 {
+    if times.len() < 2 {
+        return values.first().copied().unwrap_or(0.0);
     }
+    let area: f64 = times
+        .windows(2)
+        .zip(values.windows(2))
+        .map(|(t, v)| (t[1] - t[0]) * (v[0] + v[1]) / 2.0)
+        .sum();
+    area / (times[times.len() - 1] - times[0])
+}
 
-    let total_time: f64 = time_intervals.iter().sum();
+/// Graf et al. (1999) Brier score for censored data. `survival_predictions`
+/// holds one row per observation with the predicted survival probability at
+/// each evaluation time; censoring is handled with inverse-probability weights
+/// from the reverse Kaplan-Meier estimate. The reference model is the pooled
+/// Kaplan-Meier curve, giving the index of prediction accuracy
+/// `ipa = 1 - brier / reference_brier`.
+pub fn survival_brier(
+    time: &[f64],
+    status: &[i32],
+    survival_predictions: ArrayView2<f64>,
+    eval_times: &[f64],
+) -> Result<SurvivalBrierResult, BrierError> {
+    let n = time.len();
+    if n == 0 || status.len() != n || survival_predictions.nrows() != n {
+        return Err(BrierError::LengthMismatch);
+    }
+    if eval_times.is_empty() || eval_times.windows(2).any(|w| w[1] <= w[0]) {
+        return Err(BrierError::InvalidTimes);
+    }
+    if survival_predictions.ncols() != eval_times.len() {
+        return Err(BrierError::PredictionWidth);
+    }
+    if survival_predictions
+        .iter()
+        .any(|p| !(0.0..=1.0).contains(p))
+    {
+        return Err(BrierError::InvalidPrediction);
+    }
 
-    let weights_ref = weights.as_deref();
+    let censoring = CensoringDistribution::new(time, status);
+    let status_f64: Vec<f64> = status.iter().map(|&s| f64::from(s)).collect();
+    let km = survfitkm_internal(
+        time,
+        &status_f64,
+        &vec![1.0; n],
+        None,
+        &vec![0; n],
+        false,
+        0,
+    );
 
-    let result = time_intervals
+    let (brier, reference_brier): (Vec<f64>, Vec<f64>) = eval_times
         .par_iter()
         .enumerate()
-        .map(|(t_idx, &interval)| {
-            let preds_at_t: Vec<f64> = predictions.iter().map(|row| row[t_idx]).collect();
-            brier_internal(&preds_at_t, &outcomes, weights_ref)
-                .map(|score| score * interval)
-                .ok_or("invalid prediction value")
+        .map(|(k, &t)| {
+            let steps = km.time.partition_point(|&u| u <= t);
+            let pooled = if steps == 0 {
+                1.0
+            } else {
+                km.estimate[steps - 1]
+            };
+            (
+                ipcw_brier_at(
+                    time,
+                    status,
+                    |i| survival_predictions[[i, k]],
+                    &censoring,
+                    t,
+                ),
+                ipcw_brier_at(time, status, |_| pooled, &censoring, t),
+            )
         })
-        .try_reduce(|| 0.0, |a, b| Ok(a + b));
+        .unzip();
 
-    match result {
-        Ok(integrated_score) => {
-            if total_time > 0.0 {
-                Ok(integrated_score / total_time)
-            } else {
-                Ok(0.0)
-            }
-        }
-        Err(_) => Err(pyo3::exceptions::PyValueError::new_err(
-            "predictions must be between 0 and 1",
-        )),
-    }
+    let ipa = brier
+        .iter()
+        .zip(&reference_brier)
+        .map(|(b, r)| if *r > 0.0 { 1.0 - b / r } else { f64::NAN })
+        .collect();
+    let integrated_brier = integrate_over(eval_times, &brier);
+    let reference_integrated_brier = integrate_over(eval_times, &reference_brier);
+    let integrated_ipa = if reference_integrated_brier > 0.0 {
+        1.0 - integrated_brier / reference_integrated_brier
+    } else {
+        f64::NAN
+    };
+
+    Ok(SurvivalBrierResult {
+        times: eval_times.to_vec(),
+        brier,
+        reference_brier,
+        ipa,
+        integrated_brier,
+        reference_integrated_brier,
+        integrated_ipa,
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "survival_brier", signature = (time, status, survival_predictions, eval_times))]
+pub fn survival_brier_py(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    survival_predictions: MatrixArg<'_, f64>,
+    eval_times: VectorArg<'_, f64>,
+) -> PyResult<SurvivalBrierResult> {
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let survival_predictions = survival_predictions.view();
+    let eval_times = eval_times.as_slice();
+    parallel::detach(py, || {
+        Ok(survival_brier(
+            &time,
+            &status,
+            survival_predictions,
+            &eval_times,
+        )?)
+    })
 }
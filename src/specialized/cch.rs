@@ -1,8 +1,51 @@
-use crate::regression::coxph::{CoxPHModel, Subject};
+#[cfg(feature = "python")]
+use crate::python::arrays::IndexArg;
+use crate::regression::coxph::{CoxMethod, CoxPHError, CoxPHModel, Subject};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::{Array1, Array2};
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use thiserror::Error;
 
-#[derive(Clone)]
-#[pyclass]
+const EXCLUDED_OFFSET: f64 = -100.0;
+
+#[derive(Error, Debug)]
+pub enum CchError {
+    #[error("cannot fit model: no cases or subcohort members provided")]
+    NoSubjects,
+    #[error("cohort_size is required for this method")]
+    MissingCohortSize,
+    #[error("no cohort size given for sampling stratum {0}")]
+    MissingStratumSize(usize),
+    #[error(
+        "cohort size {size} in stratum {stratum} is smaller than the {sampled} sampled subjects"
+    )]
+    CohortTooSmall {
+        stratum: usize,
+        size: usize,
+        sampled: usize,
+    },
+    #[error("stratum {0} has no subcohort members without an event")]
+    NoSubcohortControls(usize),
+    #[error(transparent)]
+    Model(#[from] CoxPHError),
+}
+
+#[cfg(feature = "python")]
+impl From<CchError> for PyErr {
+    fn from(err: CchError) -> PyErr {
+        match err {
+            CchError::Model(err) => err.into(),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(eq, eq_int))]
 pub enum CchMethod {
     Prentice,
     SelfPrentice,
@@ -11,17 +54,99 @@ pub enum CchMethod {
     IIBorgan,
 }
 
-#[pyclass]
+impl CchMethod {
+    fn is_stratified(self) -> bool {
+        matches!(self, CchMethod::IBorgan | CchMethod::IIBorgan)
+    }
+
+    fn samples_controls_only(self) -> bool {
+        matches!(self, CchMethod::LinYing | CchMethod::IIBorgan)
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass)]
 pub struct CohortData {
     subjects: Vec<Subject>,
+    pub cohort_size: Option<usize>,
+    pub stratum_sizes: Option<Vec<usize>>,
+}
+
+impl Default for CohortData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default, Clone)]
+struct SamplingStratum {
+    cohort: usize,
+    subcohort: usize,
+    subcohort_cases: usize,
+    cases: usize,
+}
+
+impl SamplingStratum {
+    fn sampling_fraction(&self, controls_only: bool) -> f64 {
+        if controls_only {
+            (self.subcohort - self.subcohort_cases) as f64 / (self.cohort - self.cases) as f64
+        } else {
+            self.subcohort as f64 / self.cohort as f64
+        }
+    }
+}
+
+struct CchRecords {
+    covariates: Vec<Vec<f64>>,
+    entry: Vec<f64>,
+    exit: Vec<f64>,
+    status: Vec<u8>,
+    weights: Vec<f64>,
+    offsets: Vec<f64>,
+    subject: Vec<usize>,
+}
+
+impl CchRecords {
+    fn new() -> Self {
+        Self {
+            covariates: Vec::new(),
+            entry: Vec::new(),
+            exit: Vec::new(),
+            status: Vec::new(),
+            weights: Vec::new(),
+            offsets: Vec::new(),
+            subject: Vec::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        subject: &Subject,
+        index: usize,
+        entry: f64,
+        status: bool,
+        weight: f64,
+        offset: f64,
+    ) {
+        self.covariates.push(subject.covariates.clone());
+        self.entry.push(entry);
+        self.exit.push(subject.time);
+        self.status.push(u8::from(status));
+        self.weights.push(weight);
+        self.offsets.push(offset);
+        self.subject.push(index);
+    }
+}
+
+fn entry_at_event(time: f64) -> f64 {
+    time - 1e-8 * time.abs().max(1.0)
 }
 
-#[pymethods]
 impl CohortData {
-    #[staticmethod]
     pub fn new() -> CohortData {
         CohortData {
             subjects: Vec::new(),
+            cohort_size: None,
+            stratum_sizes: None,
         }
     }
     pub fn add_subject(&mut self, subject: Subject) {
@@ -30,14 +155,211 @@ impl CohortData {
     pub fn get_subject(&self, id: usize) -> Subject {
         self.subjects[id].clone()
     }
-    pub fn fit(&self, _method: CchMethod) -> PyResult<CoxPHModel> {
-        let mut model = CoxPHModel::new();
-        for subject in &self.subjects {
+
+    fn sampling_strata(
+        &self,
+        sample: &[&Subject],
+        method: CchMethod,
+    ) -> Result<Vec<SamplingStratum>, CchError> {
+        let stratum_of = |s: &Subject| if method.is_stratified() { s.stratum } else { 0 };
+        let nstrata = sample.iter().map(|s| stratum_of(s) + 1).max().unwrap_or(0);
+        let mut strata = vec![SamplingStratum::default(); nstrata];
+
+        if method.is_stratified() {
+            let sizes = self
+                .stratum_sizes
+                .as_ref()
+                .ok_or(CchError::MissingStratumSize(0))?;
+            for (s, stratum) in strata.iter_mut().enumerate() {
+                stratum.cohort = *sizes.get(s).ok_or(CchError::MissingStratumSize(s))?;
+            }
+        } else {
+            strata[0].cohort = self.cohort_size.ok_or(CchError::MissingCohortSize)?;
+        }
+
+        for subject in sample {
+            let stratum = &mut strata[stratum_of(subject)];
             if subject.is_subcohort {
-                model.add_subject(subject)?;
+                stratum.subcohort += 1;
+                if subject.is_case {
+                    stratum.subcohort_cases += 1;
+                }
+            }
+            if subject.is_case {
+                stratum.cases += 1;
+            }
+        }
+
+        for (s, stratum) in strata.iter().enumerate() {
+            let sampled = stratum.subcohort + stratum.cases - stratum.subcohort_cases;
+            if stratum.cohort < sampled {
+                return Err(CchError::CohortTooSmall {
+                    stratum: s,
+                    size: stratum.cohort,
+                    sampled,
+                });
+            }
+            if method.samples_controls_only() && stratum.subcohort == stratum.subcohort_cases {
+                return Err(CchError::NoSubcohortControls(s));
+            }
+        }
+        Ok(strata)
+    }
+
+    pub fn fit(&self, method: CchMethod) -> Result<CoxPHModel, CchError> {
+        let sample: Vec<&Subject> = self
+            .subjects
+            .iter()
+            .filter(|s| s.is_subcohort || s.is_case)
+            .collect();
+        if sample.is_empty() {
+            return Err(CchError::NoSubjects);
+        }
+        let strata = self.sampling_strata(&sample, method)?;
+        let stratum_of = |s: &Subject| if method.is_stratified() { s.stratum } else { 0 };
+
+        let mut records = CchRecords::new();
+        for (index, subject) in sample.iter().enumerate() {
+            let entry = subject.entry_time.unwrap_or(f64::NEG_INFINITY);
+            let stratum = &strata[stratum_of(subject)];
+            match method {
+                CchMethod::Prentice | CchMethod::SelfPrentice => {
+                    if subject.is_subcohort {
+                        records.push(subject, index, entry, subject.is_case, 1.0, 0.0);
+                    } else {
+                        let offset = if method == CchMethod::SelfPrentice {
+                            EXCLUDED_OFFSET
+                        } else {
+                            0.0
+                        };
+                        let start = entry_at_event(subject.time).max(entry);
+                        records.push(subject, index, start, true, 1.0, offset);
+                    }
+                }
+                CchMethod::IBorgan => {
+                    if subject.is_subcohort {
+                        let weight = 1.0 / stratum.sampling_fraction(false);
+                        records.push(subject, index, entry, false, weight, 0.0);
+                    }
+                    if subject.is_case {
+                        let start = entry_at_event(subject.time).max(entry);
+                        records.push(subject, index, start, true, 1.0, EXCLUDED_OFFSET);
+                    }
+                }
+                CchMethod::LinYing | CchMethod::IIBorgan => {
+                    let weight = if subject.is_case {
+                        1.0
+                    } else {
+                        1.0 / stratum.sampling_fraction(true)
+                    };
+                    records.push(subject, index, entry, subject.is_case, weight, 0.0);
+                }
             }
         }
+
+        let weights = records.weights.clone();
+        let subject_of = records.subject.clone();
+        let mut model = CoxPHModel::new_with_options(
+            records.covariates,
+            records.exit,
+            records.status,
+            None,
+            Some(records.weights),
+            Some(records.offsets),
+            CoxMethod::Breslow,
+        );
+        model.entry_times = Some(records.entry);
+        model.cluster = Some(records.subject);
         model.fit(100)?;
+
+        let nvar = model.coefficients().len();
+        let mut influence = Array2::<f64>::zeros((sample.len(), nvar));
+        for (r, row) in model.dfbeta().iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                influence[[subject_of[r], j]] += weights[r] * value;
+            }
+        }
+
+        let controls_only = method.samples_controls_only();
+        let sampled = |s: &Subject| s.is_subcohort && !(controls_only && s.is_case);
+        let mut variance = Array2::<f64>::zeros((nvar, nvar));
+        for (s, stratum) in strata.iter().enumerate() {
+            let members: Vec<usize> = (0..sample.len())
+                .filter(|&i| stratum_of(sample[i]) == s && sampled(sample[i]))
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let mut mean = Array1::<f64>::zeros(nvar);
+            for &i in &members {
+                mean += &influence.row(i);
+            }
+            mean /= members.len() as f64;
+            let scale = 1.0 - stratum.sampling_fraction(controls_only);
+            for &i in &members {
+                let centered = &influence.row(i) - &mean;
+                for j in 0..nvar {
+                    for k in 0..nvar {
+                        variance[[j, k]] += scale * centered[j] * centered[k];
+                    }
+                }
+            }
+        }
+        for (j, row) in model.naive_var().iter().enumerate() {
+            for (k, &value) in row.iter().enumerate() {
+                variance[[j, k]] += value;
+            }
+        }
+        model.set_robust_var(variance);
         Ok(model)
     }
 }
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CohortData {
+    #[staticmethod]
+    #[pyo3(name = "new", signature = (cohort_size=None, stratum_sizes=None))]
+    fn py_new(cohort_size: Option<usize>, stratum_sizes: Option<IndexArg>) -> Self {
+        let stratum_sizes = stratum_sizes.map(IndexArg::into_vec);
+        let mut data = Self::new();
+        data.cohort_size = cohort_size;
+        data.stratum_sizes = stratum_sizes;
+        data
+    }
+
+    #[getter]
+    fn get_cohort_size(&self) -> Option<usize> {
+        self.cohort_size
+    }
+
+    #[setter]
+    fn set_cohort_size(&mut self, cohort_size: Option<usize>) {
+        self.cohort_size = cohort_size;
+    }
+
+    #[getter]
+    fn get_stratum_sizes(&self) -> Option<Vec<usize>> {
+        self.stratum_sizes.clone()
+    }
+
+    #[setter]
+    fn set_stratum_sizes(&mut self, stratum_sizes: Option<Vec<usize>>) {
+        self.stratum_sizes = stratum_sizes;
+    }
+
+    #[pyo3(name = "add_subject")]
+    fn py_add_subject(&mut self, subject: Subject) {
+        self.add_subject(subject);
+    }
+
+    #[pyo3(name = "get_subject")]
+    fn py_get_subject(&self, id: usize) -> Subject {
+        self.get_subject(id)
+    }
+
+    #[pyo3(name = "fit")]
+    fn py_fit(&self, py: Python<'_>, method: CchMethod) -> PyResult<CoxPHModel> {
+        Ok(parallel::detach(py, || self.fit(method))?)
+    }
+}
@@ -1,6 +1,9 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use statrs::distribution::{ContinuousCDF, Gamma, Normal};
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn cipoisson_exact(k: u32, time: f64, p: f64) -> PyResult<(f64, f64)> {
     if time <= 0.0 || p <= 0.0 || p >= 1.0 {
@@ -25,6 +28,7 @@ pub fn cipoisson_exact(k: u32, time: f64, p: f64) -> PyResult<(f64, f64)> {
     Ok((lower_bound / time, upper_bound / time))
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn cipoisson_anscombe(k: u32, time: f64, p: f64) -> PyResult<(f64, f64)> {
     if time <= 0.0 || p <= 0.0 || p >= 1.0 {
@@ -50,6 +54,7 @@ pub fn cipoisson_anscombe(k: u32, time: f64, p: f64) -> PyResult<(f64, f64)> {
     Ok((lower_bound_poisson, upper_bound_poisson))
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn cipoisson(k: u32, time: f64, p: f64, method: String) -> PyResult<(f64, f64)> {
     match method.as_str() {
@@ -1,21 +1,17 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct FineGrayOutput {
-    #[pyo3(get)]
     pub row: Vec<usize>,
-    #[pyo3(get)]
     pub start: Vec<f64>,
-    #[pyo3(get)]
     pub end: Vec<f64>,
-    #[pyo3(get)]
     pub wt: Vec<f64>,
-    #[pyo3(get)]
     pub add: Vec<usize>,
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn finegray(
     tstart: Vec<f64>,
     tstop: Vec<f64>,
@@ -120,6 +116,7 @@ pub(crate) fn finegray_internal(
     }
 }
 
+#[cfg(feature = "python")]
 #[pymodule]
 #[pyo3(name = "finegray")]
 fn finegray_module(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
@@ -1,61 +1,83 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
+#[cfg(feature = "python")]
 #[pyfunction]
+#[allow(clippy::too_many_arguments)]
 pub fn agsurv5(
+    py: Python<'_>,
     n: usize,
     nvar: usize,
-    dd: Vec<i32>,
-    x1: Vec<f64>,
-    x2: Vec<f64>,
-    xsum: Vec<f64>,
-    xsum2: Vec<f64>,
+    dd: VectorArg<'_, i32>,
+    x1: VectorArg<'_, f64>,
+    x2: VectorArg<'_, f64>,
+    xsum: VectorArg<'_, f64>,
+    xsum2: VectorArg<'_, f64>,
 ) -> PyResult<Py<PyDict>> {
-    let dd_slice = &dd;
-    let x1_slice = &x1;
-    let x2_slice = &x2;
-    let xsum_slice = &xsum;
-    let xsum2_slice = &xsum2;
+    let dd = dd.as_slice();
+    let x1 = x1.as_slice();
+    let x2 = x2.as_slice();
+    let xsum = xsum.as_slice();
+    let xsum2 = xsum2.as_slice();
+    let (sum1, sum2, xbar) =
+        parallel::detach(py, || agsurv5_sums(nvar, &dd[..n], &x1, &x2, &xsum, &xsum2));
+
+    Python::attach(|py| {
+        let dict = PyDict::new(py);
+        dict.set_item("sum1", sum1)?;
+        dict.set_item("sum2", sum2)?;
+        dict.set_item("xbar", xbar)?;
+        Ok(dict.into())
+    })
+}
+
+/// Efron-weighted hazard, variance and covariate-mean increments at each
+/// event time. `xsum` and `xsum2` are column-major with `dd.len()` rows.
+pub(crate) fn agsurv5_sums(
+    nvar: usize,
+    dd: &[i32],
+    x1: &[f64],
+    x2: &[f64],
+    xsum: &[f64],
+    xsum2: &[f64],
+) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let n = dd.len();
     let mut sum1 = vec![0.0; n];
     let mut sum2 = vec![0.0; n];
     let mut xbar = vec![0.0; n * nvar];
     for i in 0..n {
-        let d = dd_slice[i] as f64;
+        let d = dd[i] as f64;
 
         if d == 1.0 {
-            let temp = 1.0 / x1_slice[i];
+            let temp = 1.0 / x1[i];
             sum1[i] = temp;
             sum2[i] = temp.powi(2);
 
             for k in 0..nvar {
                 let idx = i + n * k;
-                xbar[idx] = xsum_slice[idx] * temp.powi(2);
+                xbar[idx] = xsum[idx] * temp.powi(2);
             }
         } else {
-            let d_int = dd_slice[i];
-            let mut temp;
-
-            for j in 0..d_int {
+            for j in 0..dd[i] {
                 let j_f64 = j as f64;
-                temp = 1.0 / (x1_slice[i] - x2_slice[i] * j_f64 / d);
+                let temp = 1.0 / (x1[i] - x2[i] * j_f64 / d);
 
                 sum1[i] += temp / d;
                 sum2[i] += temp.powi(2) / d;
 
                 for k in 0..nvar {
                     let idx = i + n * k;
-                    let weighted_x = xsum_slice[idx] - xsum2_slice[idx] * j_f64 / d;
+                    let weighted_x = xsum[idx] - xsum2[idx] * j_f64 / d;
                     xbar[idx] += (weighted_x * temp.powi(2)) / d;
                 }
             }
         }
     }
-
-    Python::attach(|py| {
-        let dict = PyDict::new(py);
-        dict.set_item("sum1", sum1)?;
-        dict.set_item("sum2", sum2)?;
-        dict.set_item("xbar", xbar)?;
-        Ok(dict.into())
-    })
+    (sum1, sum2, xbar)
 }
@@ -1,6 +1,5 @@
 use ndarray::{Array2, ArrayView2};
 
-#[allow(dead_code)]
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn coxsurv2(
     otime: &[f64],
@@ -52,6 +51,7 @@ pub(crate) fn coxsurv2(
             for n_elem in n.iter_mut().skip(3).take(9) {
                 *n_elem = 0.0;
             }
+            xsum2.fill(0.0);
 
             while person2 >= 0 && trans[sort2[person2 as usize]] == current_trans {
                 let i2 = sort2[person2 as usize];
@@ -144,7 +144,7 @@ pub(crate) fn coxsurv2(
             }
 
             for k in 0..nvar {
-                xbar[[row_idx, k]] = if n[0] == 0.0 { 0.0 } else { xsum1[k] / n[3] };
+                xbar[[row_idx, k]] = if n[2] == 0.0 { 0.0 } else { xsum1[k] / n[2] };
                 xsum2_arr[[row_idx, k]] = xsum2[k];
             }
         }
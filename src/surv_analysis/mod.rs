@@ -5,9 +5,11 @@ pub mod coxsurv2;
 pub mod coxsurv3;
 pub mod coxsurv4;
 pub mod fastkm;
+pub mod multistate;
 pub mod nelson_aalen;
 pub mod survdiff2;
 pub mod survfit4;
 pub mod survfitaj;
 pub mod survfitkm;
 pub mod survfitresid;
+pub mod turnbull;
@@ -0,0 +1,485 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, VectorArg};
+use crate::surv_analysis::survfitaj::survfitaj_internal;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::{Array2, s};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyKeyError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MultiStateError {
+    #[error("id, tstart, tstop, from_state and to_state must be non-empty and of equal length")]
+    LengthMismatch,
+    #[error("{0} must have one entry per row")]
+    ArgumentLength(&'static str),
+    #[error("row {row} has an invalid interval ({tstart}, {tstop}]")]
+    InvalidInterval { row: usize, tstart: f64, tstop: f64 },
+    #[error("row {0} has a negative or non-finite weight")]
+    InvalidWeight(usize),
+    #[error("subject {0} has overlapping intervals")]
+    Overlap(usize),
+    #[error("subject {id} starts a row in state '{found}' after being in state '{expected}'")]
+    StateMismatch {
+        id: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("subject {0} appears in more than one group")]
+    GroupChange(usize),
+    #[error("no transitions between states were observed")]
+    NoTransitions,
+    #[error("Aalen-Johansen computation failed: {0}")]
+    Fit(String),
+}
+
+#[cfg(feature = "python")]
+impl From<MultiStateError> for PyErr {
+    fn from(err: MultiStateError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// Aalen-Johansen estimates for one group. State columns follow
+/// [`MultiStateFit::states`] and hazard columns [`MultiStateFit::transitions`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct MultiStateCurve {
+    pub group: Option<usize>,
+    pub time: Vec<f64>,
+    pub n_risk: Vec<Vec<f64>>,
+    pub n_event: Vec<Vec<f64>>,
+    pub n_censor: Vec<Vec<f64>>,
+    pub pstate: Vec<Vec<f64>>,
+    pub std_err: Vec<Vec<f64>>,
+    pub cumhaz: Vec<Vec<f64>>,
+    pub std_chaz: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct MultiStateFit {
+    pub states: Vec<String>,
+    pub transitions: Vec<(String, String)>,
+    pub curves: Vec<MultiStateCurve>,
+}
+
+impl MultiStateFit {
+    fn curve(&self, group: Option<usize>) -> Option<&MultiStateCurve> {
+        self.curves.iter().find(|c| c.group == group)
+    }
+
+    /// Occupancy probability of `state` and its standard error over time.
+    pub fn probability(&self, state: &str, group: Option<usize>) -> Option<(Vec<f64>, Vec<f64>)> {
+        let j = self.states.iter().position(|s| s == state)?;
+        let curve = self.curve(group)?;
+        Some((
+            curve.pstate.iter().map(|row| row[j]).collect(),
+            curve.std_err.iter().map(|row| row[j]).collect(),
+        ))
+    }
+
+    /// Cumulative hazard of the `from -> to` transition and its standard error.
+    pub fn hazard(
+        &self,
+        from: &str,
+        to: &str,
+        group: Option<usize>,
+    ) -> Option<(Vec<f64>, Vec<f64>)> {
+        let jk = self
+            .transitions
+            .iter()
+            .position(|(a, b)| a == from && b == to)?;
+        let curve = self.curve(group)?;
+        Some((
+            curve.cumhaz.iter().map(|row| row[jk]).collect(),
+            curve.std_chaz.iter().map(|row| row[jk]).collect(),
+        ))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MultiStateFit {
+    #[pyo3(name = "probability", signature = (state, group=None))]
+    fn probability_py(&self, state: &str, group: Option<usize>) -> PyResult<(Vec<f64>, Vec<f64>)> {
+        self.probability(state, group).ok_or_else(|| {
+            PyKeyError::new_err(format!("no curve for state '{state}' and group {group:?}"))
+        })
+    }
+
+    #[pyo3(name = "hazard", signature = (from_state, to_state, group=None))]
+    fn hazard_py(
+        &self,
+        from_state: &str,
+        to_state: &str,
+        group: Option<usize>,
+    ) -> PyResult<(Vec<f64>, Vec<f64>)> {
+        self.hazard(from_state, to_state, group).ok_or_else(|| {
+            PyKeyError::new_err(format!(
+                "no hazard for transition '{from_state}' -> '{to_state}' and group {group:?}"
+            ))
+        })
+    }
+}
+
+fn to_rows(arr: ndarray::ArrayView2<'_, f64>) -> Vec<Vec<f64>> {
+    arr.outer_iter().map(|row| row.to_vec()).collect()
+}
+
+fn sorted_by_time(rows: usize, time: impl Fn(usize) -> f64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..rows).collect();
+    order.sort_by(|&a, &b| time(a).partial_cmp(&time(b)).unwrap_or(Ordering::Equal));
+    order
+}
+
+/// Data for one group laid out the way `survfitaj_internal` expects.
+struct GroupInput<'a> {
+    rows: &'a [usize],
+    subject: Vec<usize>,
+    n_subjects: usize,
+}
+
+struct Layout<'a> {
+    tstart: &'a [f64],
+    tstop: &'a [f64],
+    from: &'a [usize],
+    to: &'a [usize],
+    weights: &'a [f64],
+    position: &'a [usize],
+    nstate: usize,
+    hindx: &'a Array2<usize>,
+    trmat: &'a Array2<usize>,
+}
+
+fn fit_group(
+    layout: &Layout<'_>,
+    group: Option<usize>,
+    input: &GroupInput<'_>,
+) -> Result<MultiStateCurve, MultiStateError> {
+    let rows = input.rows;
+    let nstate = layout.nstate;
+    let m = rows.len();
+
+    let mut y = Vec::with_capacity(3 * m);
+    for &r in rows {
+        let status = if layout.from[r] == layout.to[r] {
+            0.0
+        } else {
+            (layout.to[r] + 1) as f64
+        };
+        y.extend_from_slice(&[layout.tstart[r], layout.tstop[r], status]);
+    }
+    let cstate: Vec<usize> = rows.iter().map(|&r| layout.from[r]).collect();
+    let wt: Vec<f64> = rows.iter().map(|&r| layout.weights[r]).collect();
+    let position: Vec<usize> = rows.iter().map(|&r| layout.position[r]).collect();
+
+    let sort1 = sorted_by_time(m, |i| y[3 * i]);
+    let sort2 = sorted_by_time(m, |i| y[3 * i + 1]);
+    let t0 = y[3 * sort1[0]];
+
+    let mut utime: Vec<f64> = sort2.iter().map(|&i| y[3 * i + 1]).collect();
+    utime.dedup();
+
+    let mut p0 = vec![0.0; nstate];
+    let mut total = 0.0;
+    for i in 0..m {
+        if y[3 * i] == t0 {
+            p0[cstate[i]] += wt[i];
+            total += wt[i];
+        }
+    }
+    if total <= 0.0 {
+        return Err(MultiStateError::Fit(
+            "no weight at risk at the start time".to_string(),
+        ));
+    }
+    for p in &mut p0 {
+        *p /= total;
+    }
+
+    let ngrp = input.n_subjects;
+    let mut i0 = vec![0.0; ngrp * nstate];
+    for i in 0..m {
+        if y[3 * i] == t0 {
+            let g = input.subject[i];
+            for (j, &p) in p0.iter().enumerate() {
+                let indicator = if j == cstate[i] { 1.0 } else { 0.0 };
+                i0[g * nstate + j] += wt[i] * (indicator - p) / total;
+            }
+        }
+    }
+
+    let fit = survfitaj_internal(
+        &y,
+        &sort1,
+        &sort2,
+        &utime,
+        &cstate,
+        &wt,
+        &input.subject,
+        ngrp,
+        &p0,
+        &i0,
+        1,
+        false,
+        &position,
+        layout.hindx,
+        layout.trmat,
+        t0,
+    )
+    .map_err(|e| MultiStateError::Fit(e.to_string()))?;
+
+    let std_err = fit
+        .std_err
+        .unwrap_or_else(|| Array2::zeros((utime.len(), nstate)));
+    let std_chaz = fit
+        .std_chaz
+        .unwrap_or_else(|| Array2::zeros(fit.cumhaz.raw_dim()));
+
+    Ok(MultiStateCurve {
+        group,
+        time: utime,
+        n_risk: to_rows(fit.n_risk.slice(s![.., ..nstate])),
+        n_event: to_rows(fit.n_event.view()),
+        n_censor: to_rows(fit.n_censor.slice(s![.., ..nstate])),
+        pstate: to_rows(fit.pstate.view()),
+        std_err: to_rows(std_err.view()),
+        cumhaz: to_rows(fit.cumhaz.view()),
+        std_chaz: to_rows(std_chaz.view()),
+    })
+}
+
+/// Validated multi-state rows with states and observed transitions indexed.
+pub(crate) struct MultiStateData {
+    pub(crate) states: Vec<String>,
+    pub(crate) from: Vec<usize>,
+    pub(crate) to: Vec<usize>,
+    pub(crate) weights: Vec<f64>,
+    pub(crate) position: Vec<usize>,
+    pub(crate) transitions: Vec<(usize, usize)>,
+}
+
+impl MultiStateData {
+    pub(crate) fn transition_labels(&self) -> Vec<(String, String)> {
+        self.transitions
+            .iter()
+            .map(|&(j, k)| (self.states[j].clone(), self.states[k].clone()))
+            .collect()
+    }
+}
+
+pub(crate) fn prepare_multistate<S: AsRef<str>>(
+    id: &[usize],
+    tstart: &[f64],
+    tstop: &[f64],
+    from_state: &[S],
+    to_state: &[S],
+    weights: Option<&[f64]>,
+    group: Option<&[usize]>,
+) -> Result<MultiStateData, MultiStateError> {
+    let n = id.len();
+    if n == 0
+        || tstart.len() != n
+        || tstop.len() != n
+        || from_state.len() != n
+        || to_state.len() != n
+    {
+        return Err(MultiStateError::LengthMismatch);
+    }
+    if weights.is_some_and(|w| w.len() != n) {
+        return Err(MultiStateError::ArgumentLength("weights"));
+    }
+    if group.is_some_and(|g| g.len() != n) {
+        return Err(MultiStateError::ArgumentLength("group"));
+    }
+
+    let weights = weights.map_or_else(|| vec![1.0; n], <[f64]>::to_vec);
+    for row in 0..n {
+        if !(tstart[row].is_finite() && tstop[row].is_finite() && tstart[row] < tstop[row]) {
+            return Err(MultiStateError::InvalidInterval {
+                row,
+                tstart: tstart[row],
+                tstop: tstop[row],
+            });
+        }
+        if !(weights[row].is_finite() && weights[row] >= 0.0) {
+            return Err(MultiStateError::InvalidWeight(row));
+        }
+    }
+
+    let mut states: Vec<String> = Vec::new();
+    let mut state_index: HashMap<&str, usize> = HashMap::new();
+    for label in from_state.iter().chain(to_state) {
+        let label = label.as_ref();
+        if !state_index.contains_key(label) {
+            state_index.insert(label, states.len());
+            states.push(label.to_string());
+        }
+    }
+    let from: Vec<usize> = from_state.iter().map(|s| state_index[s.as_ref()]).collect();
+    let to: Vec<usize> = to_state.iter().map(|s| state_index[s.as_ref()]).collect();
+
+    let mut subjects: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (row, &subject) in id.iter().enumerate() {
+        subjects.entry(subject).or_default().push(row);
+    }
+    let mut position = vec![0usize; n];
+    for (&subject, rows) in &mut subjects {
+        rows.sort_by(|&a, &b| tstart[a].partial_cmp(&tstart[b]).unwrap_or(Ordering::Equal));
+        for pair in rows.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            if tstart[next] < tstop[prev] {
+                return Err(MultiStateError::Overlap(subject));
+            }
+            if from[next] != to[prev] {
+                return Err(MultiStateError::StateMismatch {
+                    id: subject,
+                    expected: states[to[prev]].clone(),
+                    found: states[from[next]].clone(),
+                });
+            }
+            if let Some(g) = group
+                && g[next] != g[prev]
+            {
+                return Err(MultiStateError::GroupChange(subject));
+            }
+        }
+        position[rows[0]] |= 1;
+        position[rows[rows.len() - 1]] |= 2;
+    }
+
+    let transitions: Vec<(usize, usize)> = from
+        .iter()
+        .zip(&to)
+        .filter(|(a, b)| a != b)
+        .map(|(&a, &b)| (a, b))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if transitions.is_empty() {
+        return Err(MultiStateError::NoTransitions);
+    }
+
+    Ok(MultiStateData {
+        states,
+        from,
+        to,
+        weights,
+        position,
+        transitions,
+    })
+}
+
+/// Aalen-Johansen state occupancy probabilities and transition hazards from
+/// counting-process data. Each row is an interval `(tstart, tstop]` that
+/// subject `id` spends in `from_state`, ending with a move to `to_state`; a
+/// row with `to_state == from_state` ends without a transition (censored if
+/// it is the subject's last row). States are ordered by first appearance in
+/// `from_state`, then `to_state`. Standard errors use the infinitesimal
+/// jackknife with subjects as clusters, and one curve is fitted per `group`.
+pub fn multistate_fit<S: AsRef<str>>(
+    id: &[usize],
+    tstart: &[f64],
+    tstop: &[f64],
+    from_state: &[S],
+    to_state: &[S],
+    weights: Option<&[f64]>,
+    group: Option<&[usize]>,
+) -> Result<MultiStateFit, MultiStateError> {
+    let data = prepare_multistate(id, tstart, tstop, from_state, to_state, weights, group)?;
+    let n = id.len();
+    let nstate = data.states.len();
+    let nhaz = data.transitions.len();
+    let mut hindx = Array2::zeros((nstate, nstate));
+    let mut trmat = Array2::zeros((nhaz, 2));
+    for (jk, &(j, k)) in data.transitions.iter().enumerate() {
+        hindx[[j, k]] = jk;
+        trmat[[jk, 0]] = j;
+        trmat[[jk, 1]] = k;
+    }
+
+    let mut groups: BTreeMap<Option<usize>, Vec<usize>> = BTreeMap::new();
+    for row in 0..n {
+        groups.entry(group.map(|g| g[row])).or_default().push(row);
+    }
+    let inputs: Vec<(Option<usize>, GroupInput<'_>)> = groups
+        .iter()
+        .map(|(&g, rows)| {
+            let mut local: BTreeMap<usize, usize> = BTreeMap::new();
+            for &r in rows {
+                let next = local.len();
+                local.entry(id[r]).or_insert(next);
+            }
+            let subject = rows.iter().map(|&r| local[&id[r]]).collect();
+            (
+                g,
+                GroupInput {
+                    rows,
+                    subject,
+                    n_subjects: local.len(),
+                },
+            )
+        })
+        .collect();
+
+    let layout = Layout {
+        tstart,
+        tstop,
+        from: &data.from,
+        to: &data.to,
+        weights: &data.weights,
+        position: &data.position,
+        nstate,
+        hindx: &hindx,
+        trmat: &trmat,
+    };
+    let curves = inputs
+        .par_iter()
+        .map(|(g, input)| fit_group(&layout, *g, input))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(MultiStateFit {
+        transitions: data.transition_labels(),
+        states: data.states,
+        curves,
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "multistate_fit", signature = (id, tstart, tstop, from_state, to_state, weights=None, group=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn multistate_fit_py(
+    py: Python<'_>,
+    id: IndexArg,
+    tstart: VectorArg<'_, f64>,
+    tstop: VectorArg<'_, f64>,
+    from_state: Vec<String>,
+    to_state: Vec<String>,
+    weights: Option<VectorArg<'_, f64>>,
+    group: Option<IndexArg>,
+) -> PyResult<MultiStateFit> {
+    let id = id.into_vec();
+    let tstart = tstart.as_slice();
+    let tstop = tstop.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let group = group.map(IndexArg::into_vec);
+    Ok(parallel::detach(py, || {
+        multistate_fit(
+            &id,
+            &tstart,
+            &tstop,
+            &from_state,
+            &to_state,
+            weights.as_deref(),
+            group.as_deref(),
+        )
+    })?)
+}
@@ -1,36 +1,43 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use rayon::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct NelsonAalenResult {
-    #[pyo3(get)]
     pub time: Vec<f64>,
-    #[pyo3(get)]
     pub cumulative_hazard: Vec<f64>,
-    #[pyo3(get)]
     pub variance: Vec<f64>,
-    #[pyo3(get)]
     pub ci_lower: Vec<f64>,
-    #[pyo3(get)]
     pub ci_upper: Vec<f64>,
-    #[pyo3(get)]
     pub n_risk: Vec<usize>,
-    #[pyo3(get)]
     pub n_events: Vec<usize>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NelsonAalenResult {
     #[new]
     fn new(
-        time: Vec<f64>,
-        cumulative_hazard: Vec<f64>,
-        variance: Vec<f64>,
-        ci_lower: Vec<f64>,
-        ci_upper: Vec<f64>,
-        n_risk: Vec<usize>,
-        n_events: Vec<usize>,
+        time: VectorArg<'_, f64>,
+        cumulative_hazard: VectorArg<'_, f64>,
+        variance: VectorArg<'_, f64>,
+        ci_lower: VectorArg<'_, f64>,
+        ci_upper: VectorArg<'_, f64>,
+        n_risk: IndexArg,
+        n_events: IndexArg,
     ) -> Self {
+        let time = time.into_vec();
+        let cumulative_hazard = cumulative_hazard.into_vec();
+        let variance = variance.into_vec();
+        let ci_lower = ci_lower.into_vec();
+        let ci_upper = ci_upper.into_vec();
+        let n_risk = n_risk.into_vec();
+        let n_events = n_events.into_vec();
         Self {
             time,
             cumulative_hazard,
@@ -167,50 +174,56 @@ pub fn nelson_aalen(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, weights=None, confidence_level=None))]
 pub fn nelson_aalen_estimator(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    weights: Option<VectorArg<'_, f64>>,
     confidence_level: Option<f64>,
 ) -> PyResult<NelsonAalenResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    let weights_ref = weights.as_deref();
-    Ok(nelson_aalen(&time, &status, weights_ref, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        let weights_ref = weights.as_deref();
+        Ok(nelson_aalen(&time, &status, weights_ref, conf))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct StratifiedKMResult {
-    #[pyo3(get)]
     pub strata: Vec<i32>,
-    #[pyo3(get)]
     pub times: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub survival: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub ci_lower: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub ci_upper: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub n_risk: Vec<Vec<usize>>,
-    #[pyo3(get)]
     pub n_events: Vec<Vec<usize>>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl StratifiedKMResult {
     #[new]
     fn new(
-        strata: Vec<i32>,
-        times: Vec<Vec<f64>>,
-        survival: Vec<Vec<f64>>,
-        ci_lower: Vec<Vec<f64>>,
-        ci_upper: Vec<Vec<f64>>,
+        strata: VectorArg<'_, i32>,
+        times: MatrixArg<'_, f64>,
+        survival: MatrixArg<'_, f64>,
+        ci_lower: MatrixArg<'_, f64>,
+        ci_upper: MatrixArg<'_, f64>,
         n_risk: Vec<Vec<usize>>,
         n_events: Vec<Vec<usize>>,
     ) -> Self {
+        let strata = strata.into_vec();
+        let times = times.into_rows();
+        let survival = survival.into_rows();
+        let ci_lower = ci_lower.into_rows();
+        let ci_upper = ci_upper.into_rows();
         Self {
             strata,
             times,
@@ -246,30 +259,27 @@ pub fn stratified_km(
     unique_strata.sort();
     unique_strata.dedup();
 
-    let mut all_times = Vec::new();
-    let mut all_survival = Vec::new();
-    let mut all_ci_lower = Vec::new();
-    let mut all_ci_upper = Vec::new();
-    let mut all_n_risk = Vec::new();
-    let mut all_n_events = Vec::new();
-
-    for &stratum in &unique_strata {
-        let mask: Vec<bool> = strata.iter().map(|&s| s == stratum).collect();
-        let stratum_time: Vec<f64> = time
-            .iter()
-            .zip(&mask)
-            .filter(|&(_, m)| *m)
-            .map(|(&t, _)| t)
-            .collect();
-        let stratum_status: Vec<i32> = status
-            .iter()
-            .zip(&mask)
-            .filter(|&(_, m)| *m)
-            .map(|(&s, _)| s)
-            .collect();
-
-        let result = kaplan_meier(&stratum_time, &stratum_status, None, confidence_level);
-
+    let results: Vec<KMResult> = unique_strata
+        .par_iter()
+        .map(|&stratum| {
+            let (stratum_time, stratum_status): (Vec<f64>, Vec<i32>) = time
+                .iter()
+                .zip(status)
+                .zip(strata)
+                .filter(|&(_, &s)| s == stratum)
+                .map(|((&t, &d), _)| (t, d))
+                .unzip();
+            kaplan_meier(&stratum_time, &stratum_status, None, confidence_level)
+        })
+        .collect();
+
+    let mut all_times = Vec::with_capacity(results.len());
+    let mut all_survival = Vec::with_capacity(results.len());
+    let mut all_ci_lower = Vec::with_capacity(results.len());
+    let mut all_ci_upper = Vec::with_capacity(results.len());
+    let mut all_n_risk = Vec::with_capacity(results.len());
+    let mut all_n_events = Vec::with_capacity(results.len());
+    for result in results {
         all_times.push(result.time);
         all_survival.push(result.survival);
         all_ci_lower.push(result.ci_lower);
@@ -418,14 +428,21 @@ fn kaplan_meier(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, strata, confidence_level=None))]
 pub fn stratified_kaplan_meier(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    strata: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    strata: VectorArg<'_, i32>,
     confidence_level: Option<f64>,
 ) -> PyResult<StratifiedKMResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(stratified_km(&time, &status, &strata, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let strata = strata.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(stratified_km(&time, &status, &strata, conf))
+    })
 }
@@ -1,37 +1,30 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use ndarray::{Array1, Array2, s};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use std::error::Error;
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 #[derive(Debug, Clone)]
 pub struct SurvFitAJ {
-    #[pyo3(get)]
     pub n_risk: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub n_event: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub n_censor: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub pstate: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub cumhaz: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub std_err: Option<Vec<Vec<f64>>>,
-    #[pyo3(get)]
     pub std_chaz: Option<Vec<Vec<f64>>>,
-    #[pyo3(get)]
     pub std_auc: Option<Vec<Vec<f64>>>,
-    #[pyo3(get)]
     pub influence: Option<Vec<Vec<f64>>>,
-    #[pyo3(get)]
     pub n_enter: Option<Vec<Vec<f64>>>,
-    #[pyo3(get)]
     pub n_transition: Vec<Vec<f64>>,
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
-struct SurvFitAJInternal {
+pub struct SurvFitAJInternal {
     pub n_risk: Array2<f64>,
     pub n_event: Array2<f64>,
     pub n_censor: Array2<f64>,
@@ -45,8 +38,8 @@ struct SurvFitAJInternal {
     pub n_transition: Array2<f64>,
 }
 
-impl SurvFitAJInternal {
-    fn into_python_result(self) -> SurvFitAJ {
+impl From<SurvFitAJInternal> for SurvFitAJ {
+    fn from(result: SurvFitAJInternal) -> Self {
         let array2_to_vec = |arr: Array2<f64>| -> Vec<Vec<f64>> {
             arr.outer_iter().map(|row| row.to_vec()).collect()
         };
@@ -55,23 +48,23 @@ impl SurvFitAJInternal {
             |opt: Option<Array2<f64>>| -> Option<Vec<Vec<f64>>> { opt.map(array2_to_vec) };
 
         SurvFitAJ {
-            n_risk: array2_to_vec(self.n_risk),
-            n_event: array2_to_vec(self.n_event),
-            n_censor: array2_to_vec(self.n_censor),
-            pstate: array2_to_vec(self.pstate),
-            cumhaz: array2_to_vec(self.cumhaz),
-            std_err: option_array2_to_vec(self.std_err),
-            std_chaz: option_array2_to_vec(self.std_chaz),
-            std_auc: option_array2_to_vec(self.std_auc),
-            influence: option_array2_to_vec(self.influence),
-            n_enter: option_array2_to_vec(self.n_enter),
-            n_transition: array2_to_vec(self.n_transition),
+            n_risk: array2_to_vec(result.n_risk),
+            n_event: array2_to_vec(result.n_event),
+            n_censor: array2_to_vec(result.n_censor),
+            pstate: array2_to_vec(result.pstate),
+            cumhaz: array2_to_vec(result.cumhaz),
+            std_err: option_array2_to_vec(result.std_err),
+            std_chaz: option_array2_to_vec(result.std_chaz),
+            std_auc: option_array2_to_vec(result.std_auc),
+            influence: option_array2_to_vec(result.influence),
+            n_enter: option_array2_to_vec(result.n_enter),
+            n_transition: array2_to_vec(result.n_transition),
         }
     }
 }
 
 #[allow(clippy::too_many_arguments)]
-fn survfitaj_internal(
+pub fn survfitaj_internal(
     y: &[f64],
     sort1: &[usize],
     sort2: &[usize],
@@ -111,14 +104,14 @@ fn survfitaj_internal(
     let mut phat = Array1::from_vec(p0.to_vec());
     let mut chaz = Array1::zeros(nhaz);
 
-    let mut person1 = nused - 1;
-    let mut person2 = nused - 1;
+    let mut person1 = nused;
+    let mut person2 = nused;
 
     for i in (0..ntime).rev() {
         let ctime = utime[i];
 
-        while person1 > 0 && y[sort1[person1] * 3] >= ctime {
-            let idx = sort1[person1];
+        while person1 > 0 && y[sort1[person1 - 1] * 3] >= ctime {
+            let idx = sort1[person1 - 1];
             let cs = cstate[idx];
             ntemp[cs] -= wt[idx];
             ntemp[cs + nstate] -= 1.0;
@@ -133,8 +126,8 @@ fn survfitaj_internal(
             person1 -= 1;
         }
 
-        while person2 > 0 && y[sort2[person2] * 3 + 1] >= ctime {
-            let idx = sort2[person2];
+        while person2 > 0 && y[sort2[person2 - 1] * 3 + 1] >= ctime {
+            let idx = sort2[person2 - 1];
             let cs = cstate[idx];
             ntemp[cs] += wt[idx];
             ntemp[cs + nstate] += 1.0;
@@ -155,8 +148,6 @@ fn survfitaj_internal(
         n_risk.row_mut(i).assign(&ntemp);
     }
 
-    let _person1 = 0;
-    let _person2 = 0;
     let mut u = if sefit > 0 {
         Some(Array2::from_shape_vec((ngrp, nstate), i0.to_vec())?)
     } else {
@@ -164,15 +155,18 @@ fn survfitaj_internal(
     };
 
     for i in 0..ntime {
+        // p(t) = p(t-) (I + dA): every transition at t starts from p(t-).
+        let pminus = phat.clone();
         for jk in 0..nhaz {
             if n_transition[[i, jk]] > 0.0 {
                 let j = trmat[[jk, 0]];
                 let k = trmat[[jk, 1]];
                 let haz = n_transition[[i, jk]] / n_risk[[i, j]];
                 chaz[jk] += haz;
-                let pj = phat[j];
-                phat[j] -= pj * haz;
-                phat[k] += pj * haz;
+                if j != k {
+                    phat[j] -= pminus[j] * haz;
+                    phat[k] += pminus[j] * haz;
+                }
             }
         }
 
@@ -207,6 +201,11 @@ fn survfitaj_internal(
         let mut person2_wg = 0;
 
         for i in 0..ntime {
+            let pminus = if i > 0 {
+                pstate.row(i - 1).to_owned()
+            } else {
+                Array1::from_vec(p0.to_vec())
+            };
             let delta = if i > 0 {
                 utime[i] - utime[i - 1]
             } else {
@@ -266,73 +265,71 @@ fn survfitaj_internal(
                 }
             }
 
-            if tdeath == 0 {
-                continue;
-            }
-
-            ucopy.assign(u);
-            for j in 0..nstate {
-                if h[[j, j]] != 0.0 {
-                    for k in 0..nstate {
-                        if k != j && h[[j, k]] != 0.0 {
-                            for g in 0..ngrp {
-                                u[[g, k]] += ucopy[[g, j]] * h[[j, k]];
+            if tdeath > 0 {
+                ucopy.assign(u);
+                for j in 0..nstate {
+                    if h[[j, j]] != 0.0 {
+                        for k in 0..nstate {
+                            if k != j && h[[j, k]] != 0.0 {
+                                for g in 0..ngrp {
+                                    u[[g, k]] += ucopy[[g, j]] * h[[j, k]];
+                                }
                             }
                         }
-                    }
-                    for g in 0..ngrp {
-                        u[[g, j]] += ucopy[[g, j]] * h[[j, j]];
+                        for g in 0..ngrp {
+                            u[[g, j]] += ucopy[[g, j]] * h[[j, j]];
+                        }
                     }
                 }
-            }
-
-            #[allow(clippy::needless_range_loop)]
-            for p in person2_wg..nused {
-                let idx = sort2[p];
-                if y[idx * 3 + 1] != utime[i] {
-                    break;
-                }
-                if y[idx * 3 + 2] > 0.0 {
-                    let j = cstate[idx];
-                    let k = y[idx * 3 + 2] as usize - 1;
-                    let g = grp[idx];
-                    let term = wt[idx] * phat[j] / n_risk[[i, j]];
 
-                    u[[g, j]] -= term;
-                    u[[g, k]] += term;
+                #[allow(clippy::needless_range_loop)]
+                for p in person2_wg..nused {
+                    let idx = sort2[p];
+                    if y[idx * 3 + 1] != utime[i] {
+                        break;
+                    }
+                    if y[idx * 3 + 2] > 0.0 {
+                        let j = cstate[idx];
+                        let k = y[idx * 3 + 2] as usize - 1;
+                        let g = grp[idx];
+                        let term = wt[idx] * pminus[j] / n_risk[[i, j]];
+
+                        u[[g, j]] -= term;
+                        u[[g, k]] += term;
+                    }
                 }
-            }
-
-            for jk in 0..nhaz {
-                if n_transition[[i, jk]] > 0.0 {
-                    let j = trmat[[jk, 0]];
-                    let k = trmat[[jk, 1]];
-                    let haz = n_transition[[i, jk]] / n_risk[[i, j]];
-                    let htemp = haz / n_risk[[i, j]];
 
-                    for g in 0..ngrp {
-                        if wg[[g, j]] > 0.0 {
-                            c[[g, jk]] -= wg[[g, j]] * htemp;
-                        }
-                    }
+                for jk in 0..nhaz {
+                    if n_transition[[i, jk]] > 0.0 {
+                        let j = trmat[[jk, 0]];
+                        let k = trmat[[jk, 1]];
+                        let haz = n_transition[[i, jk]] / n_risk[[i, j]];
+                        let htemp = haz / n_risk[[i, j]];
 
-                    if j != k {
                         for g in 0..ngrp {
                             if wg[[g, j]] > 0.0 {
-                                let term = wg[[g, j]] * phat[j] * htemp;
-                                u[[g, j]] += term;
-                                u[[g, k]] -= term;
+                                c[[g, jk]] -= wg[[g, j]] * htemp;
+                            }
+                        }
+
+                        if j != k {
+                            for g in 0..ngrp {
+                                if wg[[g, j]] > 0.0 {
+                                    let term = wg[[g, j]] * pminus[j] * htemp;
+                                    u[[g, j]] += term;
+                                    u[[g, k]] -= term;
+                                }
                             }
                         }
                     }
                 }
-            }
 
-            for j in 0..nstate {
-                se1[j] = u.column(j).mapv(|x| x.powi(2)).sum().sqrt();
-            }
-            for jk in 0..nhaz {
-                se2[jk] = c.column(jk).mapv(|x: f64| x.powi(2)).sum().sqrt();
+                for j in 0..nstate {
+                    se1[j] = u.column(j).mapv(|x| x.powi(2)).sum().sqrt();
+                }
+                for jk in 0..nhaz {
+                    se2[jk] = c.column(jk).mapv(|x: f64| x.powi(2)).sum().sqrt();
+                }
             }
 
             for j in 0..nstate {
@@ -372,26 +369,38 @@ fn survfitaj_internal(
     })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[allow(clippy::too_many_arguments)]
 pub fn survfitaj(
-    y: Vec<f64>,
-    sort1: Vec<usize>,
-    sort2: Vec<usize>,
-    utime: Vec<f64>,
-    cstate: Vec<usize>,
-    wt: Vec<f64>,
-    grp: Vec<usize>,
+    py: Python<'_>,
+    y: VectorArg<'_, f64>,
+    sort1: IndexArg,
+    sort2: IndexArg,
+    utime: VectorArg<'_, f64>,
+    cstate: IndexArg,
+    wt: VectorArg<'_, f64>,
+    grp: IndexArg,
     ngrp: usize,
-    p0: Vec<f64>,
-    i0: Vec<f64>,
+    p0: VectorArg<'_, f64>,
+    i0: VectorArg<'_, f64>,
     sefit: i32,
     entry: bool,
-    position: Vec<usize>,
+    position: IndexArg,
     hindx: Vec<Vec<usize>>,
     trmat: Vec<Vec<usize>>,
     t0: f64,
 ) -> PyResult<SurvFitAJ> {
+    let y = y.as_slice();
+    let sort1 = sort1.into_vec();
+    let sort2 = sort2.into_vec();
+    let utime = utime.as_slice();
+    let cstate = cstate.into_vec();
+    let wt = wt.as_slice();
+    let grp = grp.into_vec();
+    let p0 = p0.as_slice();
+    let i0 = i0.as_slice();
+    let position = position.into_vec();
     let hindx_array = Array2::from_shape_vec(
         (hindx.len(), hindx[0].len()),
         hindx.into_iter().flatten().collect(),
@@ -404,25 +413,27 @@ pub fn survfitaj(
     )
     .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid trmat array: {}", e)))?;
 
-    let result = survfitaj_internal(
-        &y,
-        &sort1,
-        &sort2,
-        &utime,
-        &cstate,
-        &wt,
-        &grp,
-        ngrp,
-        &p0,
-        &i0,
-        sefit,
-        entry,
-        &position,
-        &hindx_array,
-        &trmat_array,
-        t0,
-    )
-    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("survfitaj failed: {}", e)))?;
-
-    Ok(result.into_python_result())
+    let result = parallel::detach(py, || {
+        survfitaj_internal(
+            &y,
+            &sort1,
+            &sort2,
+            &utime,
+            &cstate,
+            &wt,
+            &grp,
+            ngrp,
+            &p0,
+            &i0,
+            sefit,
+            entry,
+            &position,
+            &hindx_array,
+            &trmat_array,
+            t0,
+        )
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("survfitaj failed: {}", e)))
+    })?;
+
+    Ok(result.into())
 }
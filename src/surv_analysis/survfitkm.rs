@@ -1,41 +1,47 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::utilities::validation::clamp_probability;
+#[cfg(feature = "python")]
 use crate::utilities::validation::{
-    clamp_probability, validate_length, validate_no_nan, validate_non_empty, validate_non_negative,
+    validate_length, validate_no_nan, validate_non_empty, validate_non_negative,
 };
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct SurvFitKMOutput {
-    #[pyo3(get)]
     pub time: Vec<f64>,
-    #[pyo3(get)]
     pub n_risk: Vec<f64>,
-    #[pyo3(get)]
     pub n_event: Vec<f64>,
-    #[pyo3(get)]
     pub n_censor: Vec<f64>,
-    #[pyo3(get)]
     pub estimate: Vec<f64>,
-    #[pyo3(get)]
     pub std_err: Vec<f64>,
-    #[pyo3(get)]
     pub conf_lower: Vec<f64>,
-    #[pyo3(get)]
     pub conf_upper: Vec<f64>,
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[allow(clippy::too_many_arguments)]
 #[pyo3(signature = (time, status, weights=None, entry_times=None, position=None, reverse=None, computation_type=None))]
 pub fn survfitkm(
-    time: Vec<f64>,
-    status: Vec<f64>,
-    weights: Option<Vec<f64>>,
-    entry_times: Option<Vec<f64>>,
-    position: Option<Vec<i32>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    entry_times: Option<VectorArg<'_, f64>>,
+    position: Option<VectorArg<'_, i32>>,
     reverse: Option<bool>,
     computation_type: Option<i32>,
 ) -> PyResult<SurvFitKMOutput> {
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let entry_times = entry_times.as_ref().map(VectorArg::as_slice);
+    let position = position.as_ref().map(VectorArg::as_slice);
     validate_non_empty(&time, "time")?;
     validate_length(time.len(), status.len(), "status")?;
     validate_non_negative(&time, "time")?;
@@ -48,7 +54,7 @@ pub fn survfitkm(
             validate_non_negative(&w, "weights")?;
             w
         }
-        None => vec![1.0; time.len()],
+        None => vec![1.0; time.len()].into(),
     };
 
     let position = match position {
@@ -56,25 +62,27 @@ pub fn survfitkm(
             validate_length(time.len(), p.len(), "position")?;
             p
         }
-        None => vec![0; time.len()],
+        None => vec![0; time.len()].into(),
     };
 
     if let Some(ref entry) = entry_times {
         validate_length(time.len(), entry.len(), "entry_times")?;
     }
 
-    let _reverse = reverse.unwrap_or(false);
+    let reverse = reverse.unwrap_or(false);
     let _computation_type = computation_type.unwrap_or(0);
 
-    Ok(survfitkm_internal(
-        &time,
-        &status,
-        &weights,
-        entry_times.as_deref(),
-        &position,
-        _reverse,
-        _computation_type,
-    ))
+    Ok(parallel::detach(py, || {
+        survfitkm_internal(
+            &time,
+            &status,
+            &weights,
+            entry_times.as_deref(),
+            &position,
+            reverse,
+            _computation_type,
+        )
+    }))
 }
 
 pub fn survfitkm_internal(
@@ -82,61 +90,70 @@ pub fn survfitkm_internal(
     status: &[f64],
     weights: &[f64],
     _entry_times: Option<&[f64]>,
-    position: &[i32],
-    _reverse: bool,
+    _position: &[i32],
+    reverse: bool,
     _computation_type: i32,
 ) -> SurvFitKMOutput {
-    let mut unique_times = Vec::new();
-    for (&t, &s) in time.iter().zip(status) {
-        if s > 0.0 && !unique_times.contains(&t) {
-            unique_times.push(t);
-        }
-    }
-    unique_times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-    let dtime = unique_times;
-
-    let ntime = dtime.len();
-    let mut n_risk = vec![0.0; ntime];
-    let mut n_event = vec![0.0; ntime];
-    let mut n_censor = vec![0.0; ntime];
-    let mut estimate = vec![1.0; ntime];
-    let mut std_err = vec![0.0; ntime];
-
-    let mut current_risk = weights.iter().sum();
+    let mut order: Vec<usize> = (0..time.len()).collect();
+    order.sort_by(|&a, &b| {
+        time[a]
+            .partial_cmp(&time[b])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut dtime = Vec::new();
+    let mut n_risk = Vec::new();
+    let mut n_event = Vec::new();
+    let mut n_censor = Vec::new();
+    let mut estimate = Vec::new();
+    let mut std_err = Vec::new();
+
+    let mut current_risk: f64 = weights.iter().sum();
     let mut current_estimate = 1.0;
     let mut cumulative_variance = 0.0;
 
-    for (i, &t) in dtime.iter().enumerate().rev() {
+    let mut start = 0;
+    while start < order.len() {
+        let t = time[order[start]];
+        let mut end = start;
+        let mut deaths = 0.0;
         let mut censored = 0.0;
-        let mut weighted_events = 0.0;
-        let weighted_risk = current_risk;
-
-        for (j, (&time_j, &status_j)) in time.iter().zip(status).enumerate() {
-            if (time_j - t).abs() < 1e-9 {
-                if status_j > 0.0 {
-                    weighted_events += weights[j];
-                } else if position[j] & 2 != 0 {
-                    censored += 1.0;
-                }
+        while end < order.len() && (time[order[end]] - t).abs() < 1e-9 {
+            let j = order[end];
+            if status[j] > 0.0 {
+                deaths += weights[j];
+            } else {
+                censored += weights[j];
             }
+            end += 1;
         }
 
-        if i < ntime - 1 {
-            current_risk -= weighted_events + censored;
-        }
-
-        n_risk[i] = weighted_risk;
-        n_event[i] = weighted_events;
-        n_censor[i] = censored;
-
-        if weighted_risk > 0.0 && weighted_events > 0.0 {
-            let hazard = weighted_events / weighted_risk;
-            current_estimate *= 1.0 - hazard;
-            cumulative_variance += hazard / (weighted_risk - weighted_events);
+        // With reverse=true the censoring distribution is estimated; deaths at a
+        // tied time are taken to occur before the censorings.
+        let (events, others, weighted_risk) = if reverse {
+            (censored, deaths, current_risk - deaths)
+        } else {
+            (deaths, censored, current_risk)
+        };
+
+        if events > 0.0 {
+            if weighted_risk > 0.0 {
+                let hazard = events / weighted_risk;
+                current_estimate *= 1.0 - hazard;
+                if weighted_risk > events {
+                    cumulative_variance += hazard / (weighted_risk - events);
+                }
+            }
+            dtime.push(t);
+            n_risk.push(weighted_risk);
+            n_event.push(events);
+            n_censor.push(others);
+            estimate.push(current_estimate);
+            std_err.push((current_estimate * current_estimate * cumulative_variance).sqrt());
         }
 
-        estimate[i] = current_estimate;
-        std_err[i] = (current_estimate * current_estimate * cumulative_variance).sqrt();
+        current_risk -= deaths + censored;
+        start = end;
     }
 
     let z = 1.96;
@@ -193,6 +210,7 @@ fn process_entry_times(entry_times: Option<&[f64]>, position: &[i32]) -> Vec<f64
     entry_vec
 }
 
+#[cfg(feature = "python")]
 #[pymodule]
 #[pyo3(name = "survfitkm")]
 fn survfitkm_module(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
@@ -0,0 +1,462 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::validation::bootstrap::bootstrap_sample_indices;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TurnbullError {
+    #[error("lower and upper must be non-empty and of equal length")]
+    LengthMismatch,
+    #[error("weights must have the same length as lower")]
+    WeightsLength,
+    #[error("observation {index} has an invalid interval ({lower}, {upper}]")]
+    InvalidInterval {
+        index: usize,
+        lower: f64,
+        upper: f64,
+    },
+    #[error("confidence_level must be between 0 and 1")]
+    InvalidConfidenceLevel,
+}
+
+#[cfg(feature = "python")]
+impl From<TurnbullError> for PyErr {
+    fn from(err: TurnbullError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct TurnbullResult {
+    pub time: Vec<f64>,
+    pub interval_lower: Vec<f64>,
+    pub probability: Vec<f64>,
+    pub n_risk: Vec<f64>,
+    pub n_event: Vec<f64>,
+    pub estimate: Vec<f64>,
+    pub std_err: Vec<f64>,
+    pub conf_lower: Vec<f64>,
+    pub conf_upper: Vec<f64>,
+    pub log_likelihood: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+pub struct TurnbullConfig {
+    pub max_iter: usize,
+    pub tol: f64,
+    pub n_bootstrap: usize,
+    pub confidence_level: f64,
+    pub seed: Option<u64>,
+}
+
+impl Default for TurnbullConfig {
+    fn default() -> Self {
+        Self {
+            max_iter: 1000,
+            tol: 1e-9,
+            n_bootstrap: 200,
+            confidence_level: 0.95,
+            seed: None,
+        }
+    }
+}
+
+/// Endpoint ordering key. At a tied value the closed left end of an exact
+/// observation comes first, then right ends, then open left ends.
+#[derive(Clone, Copy, Debug)]
+struct Endpoint {
+    value: f64,
+    kind: u8,
+}
+
+impl Endpoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value
+            .partial_cmp(&other.value)
+            .unwrap_or(Ordering::Equal)
+            .then(self.kind.cmp(&other.kind))
+    }
+}
+
+const CLOSED_LEFT: u8 = 0;
+const RIGHT: u8 = 1;
+const OPEN_LEFT: u8 = 2;
+
+struct Npmle {
+    lower: Vec<Endpoint>,
+    upper: Vec<Endpoint>,
+    probability: Vec<f64>,
+    log_likelihood: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+fn endpoints(lower: f64, upper: f64) -> (Endpoint, Endpoint) {
+    let kind = if lower == upper {
+        CLOSED_LEFT
+    } else {
+        OPEN_LEFT
+    };
+    (
+        Endpoint { value: lower, kind },
+        Endpoint {
+            value: upper,
+            kind: RIGHT,
+        },
+    )
+}
+
+fn turnbull_intervals(lower: &[f64], upper: &[f64]) -> (Vec<Endpoint>, Vec<Endpoint>) {
+    let mut all: Vec<Endpoint> = lower
+        .iter()
+        .zip(upper)
+        .flat_map(|(&l, &u)| {
+            let (left, right) = endpoints(l, u);
+            [left, right]
+        })
+        .collect();
+    all.sort_by(|a, b| a.cmp(b));
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    for pair in all.windows(2) {
+        if pair[0].kind != RIGHT && pair[1].kind == RIGHT {
+            left.push(pair[0]);
+            right.push(pair[1]);
+        }
+    }
+    (left, right)
+}
+
+fn log_likelihood(ranges: &[(usize, usize)], weights: &[f64], cumulative: &[f64]) -> f64 {
+    ranges
+        .iter()
+        .zip(weights)
+        .map(|(&(l, r), &w)| {
+            let below = if l == 0 { 0.0 } else { cumulative[l - 1] };
+            w * (cumulative[r] - below).max(1e-300).ln()
+        })
+        .sum()
+}
+
+fn cumulative_sum(probability: &[f64]) -> Vec<f64> {
+    let mut total = 0.0;
+    let mut cumulative: Vec<f64> = probability
+        .iter()
+        .map(|p| {
+            total += p;
+            total
+        })
+        .collect();
+    if let Some(last) = cumulative.last_mut() {
+        *last = 1.0;
+    }
+    cumulative
+}
+
+fn pava(values: &mut [f64], weights: &[f64]) {
+    let mut blocks: Vec<(f64, f64, usize)> = Vec::with_capacity(values.len());
+    for (&v, &w) in values.iter().zip(weights) {
+        blocks.push((v, w, 1));
+        while blocks.len() > 1 {
+            let (v2, w2, n2) = blocks[blocks.len() - 1];
+            let (v1, w1, n1) = blocks[blocks.len() - 2];
+            if v1 <= v2 {
+                break;
+            }
+            blocks.truncate(blocks.len() - 2);
+            blocks.push(((v1 * w1 + v2 * w2) / (w1 + w2), w1 + w2, n1 + n2));
+        }
+    }
+    let mut k = 0;
+    for (v, _, n) in blocks {
+        values[k..k + n].fill(v);
+        k += n;
+    }
+}
+
+fn icm_step(ranges: &[(usize, usize)], weights: &[f64], cumulative: &mut [f64], loglik: &mut f64) {
+    let m = cumulative.len();
+    if m < 2 {
+        return;
+    }
+    let free = m - 1;
+    let mut gradient = vec![0.0; free];
+    let mut curvature = vec![0.0; free];
+    for (&(l, r), &w) in ranges.iter().zip(weights) {
+        let below = if l == 0 { 0.0 } else { cumulative[l - 1] };
+        let d = (cumulative[r] - below).max(1e-300);
+        if r < free {
+            gradient[r] += w / d;
+            curvature[r] += w / (d * d);
+        }
+        if l > 0 {
+            gradient[l - 1] -= w / d;
+            curvature[l - 1] += w / (d * d);
+        }
+    }
+
+    let mut proposal: Vec<f64> = (0..free)
+        .map(|k| {
+            if curvature[k] > 0.0 {
+                cumulative[k] + gradient[k] / curvature[k]
+            } else {
+                cumulative[k]
+            }
+        })
+        .collect();
+    let pava_weights: Vec<f64> = curvature.iter().map(|c| c.max(1e-12)).collect();
+    pava(&mut proposal, &pava_weights);
+    for value in &mut proposal {
+        *value = value.clamp(0.0, 1.0);
+    }
+
+    let mut step = 1.0;
+    for _ in 0..30 {
+        let mut candidate = cumulative.to_vec();
+        for k in 0..free {
+            candidate[k] += step * (proposal[k] - cumulative[k]);
+        }
+        let value = log_likelihood(ranges, weights, &candidate);
+        if value > *loglik {
+            cumulative.copy_from_slice(&candidate);
+            *loglik = value;
+            return;
+        }
+        step /= 2.0;
+    }
+}
+
+fn em_step(ranges: &[(usize, usize)], weights: &[f64], probability: &mut [f64]) {
+    let total: f64 = weights.iter().sum();
+    let mut cumulative = vec![0.0; probability.len() + 1];
+    for (j, &p) in probability.iter().enumerate() {
+        cumulative[j + 1] = cumulative[j] + p;
+    }
+    let mut expected = vec![0.0; probability.len()];
+    for (&(l, r), &w) in ranges.iter().zip(weights) {
+        let d = cumulative[r + 1] - cumulative[l];
+        if d <= 0.0 {
+            continue;
+        }
+        for j in l..=r {
+            expected[j] += w * probability[j] / d;
+        }
+    }
+    for (p, e) in probability.iter_mut().zip(expected) {
+        *p = e / total;
+    }
+}
+
+fn fit_npmle(lower: &[f64], upper: &[f64], weights: &[f64], max_iter: usize, tol: f64) -> Npmle {
+    let (left, right) = turnbull_intervals(lower, upper);
+    let m = left.len();
+    let ranges: Vec<(usize, usize)> = lower
+        .iter()
+        .zip(upper)
+        .map(|(&l, &u)| {
+            let (obs_left, obs_right) = endpoints(l, u);
+            let first = left
+                .iter()
+                .position(|e| e.cmp(&obs_left) != Ordering::Less)
+                .unwrap_or(m);
+            let last = right
+                .iter()
+                .rposition(|e| e.cmp(&obs_right) != Ordering::Greater)
+                .unwrap_or(0);
+            (first.min(last), last)
+        })
+        .collect();
+
+    let mut probability = vec![1.0 / m as f64; m];
+    let mut loglik = log_likelihood(&ranges, weights, &cumulative_sum(&probability));
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iter {
+        iterations += 1;
+        let previous = loglik;
+
+        let mut cumulative = cumulative_sum(&probability);
+        icm_step(&ranges, weights, &mut cumulative, &mut loglik);
+        let mut below = 0.0;
+        for (p, &c) in probability.iter_mut().zip(&cumulative) {
+            *p = (c - below).max(0.0);
+            below = c;
+        }
+
+        em_step(&ranges, weights, &mut probability);
+        loglik = log_likelihood(&ranges, weights, &cumulative_sum(&probability));
+        if (loglik - previous).abs() <= tol {
+            converged = true;
+            break;
+        }
+    }
+
+    Npmle {
+        lower: left,
+        upper: right,
+        probability,
+        log_likelihood: loglik,
+        iterations,
+        converged,
+    }
+}
+
+fn survival_at(fit: &Npmle, t: f64) -> f64 {
+    let mass: f64 = fit
+        .upper
+        .iter()
+        .zip(&fit.probability)
+        .filter(|(e, _)| e.value <= t)
+        .map(|(_, p)| p)
+        .sum();
+    (1.0 - mass).clamp(0.0, 1.0)
+}
+
+/// Turnbull nonparametric maximum likelihood estimate of the survival curve
+/// for interval-censored data, fitted with the EM-ICM algorithm. Each
+/// observation is the interval (lower, upper]; lower == upper is an exact
+/// time and upper = inf is right censored. Confidence bands are bootstrap
+/// percentile intervals.
+pub fn turnbull(
+    lower: &[f64],
+    upper: &[f64],
+    weights: Option<&[f64]>,
+    config: &TurnbullConfig,
+) -> Result<TurnbullResult, TurnbullError> {
+    let n = lower.len();
+    if n == 0 || upper.len() != n {
+        return Err(TurnbullError::LengthMismatch);
+    }
+    let default_weights = vec![1.0; n];
+    let weights = weights.unwrap_or(&default_weights);
+    if weights.len() != n {
+        return Err(TurnbullError::WeightsLength);
+    }
+    if !(config.confidence_level > 0.0 && config.confidence_level < 1.0) {
+        return Err(TurnbullError::InvalidConfidenceLevel);
+    }
+    for i in 0..n {
+        if lower[i].is_nan()
+            || upper[i].is_nan()
+            || lower[i] > upper[i]
+            || lower[i] == f64::INFINITY
+        {
+            return Err(TurnbullError::InvalidInterval {
+                index: i,
+                lower: lower[i],
+                upper: upper[i],
+            });
+        }
+    }
+
+    let fit = fit_npmle(lower, upper, weights, config.max_iter, config.tol);
+    let m = fit.probability.len();
+    let total: f64 = weights.iter().sum();
+    let time: Vec<f64> = fit.upper.iter().map(|e| e.value).collect();
+
+    let mut estimate = Vec::with_capacity(m);
+    let mut n_risk = Vec::with_capacity(m);
+    let mut n_event = Vec::with_capacity(m);
+    let mut surv = 1.0;
+    for &p in &fit.probability {
+        n_risk.push(total * surv);
+        n_event.push(total * p);
+        surv = (surv - p).max(0.0);
+        estimate.push(surv);
+    }
+
+    let seed = config.seed.unwrap_or(42);
+    let replicates: Vec<Vec<f64>> = (0..config.n_bootstrap)
+        .into_par_iter()
+        .map(|b| {
+            let indices = bootstrap_sample_indices(n, seed, b);
+            let boot_lower: Vec<f64> = indices.iter().map(|&i| lower[i]).collect();
+            let boot_upper: Vec<f64> = indices.iter().map(|&i| upper[i]).collect();
+            let boot_weights: Vec<f64> = indices.iter().map(|&i| weights[i]).collect();
+            let boot = fit_npmle(
+                &boot_lower,
+                &boot_upper,
+                &boot_weights,
+                config.max_iter,
+                config.tol,
+            );
+            time.iter().map(|&t| survival_at(&boot, t)).collect()
+        })
+        .collect();
+
+    let alpha = 1.0 - config.confidence_level;
+    let mut std_err = vec![f64::NAN; m];
+    let mut conf_lower = vec![f64::NAN; m];
+    let mut conf_upper = vec![f64::NAN; m];
+    let b = replicates.len();
+    if b > 1 {
+        for j in 0..m {
+            let mut values: Vec<f64> = replicates.iter().map(|r| r[j]).collect();
+            let mean = values.iter().sum::<f64>() / b as f64;
+            std_err[j] =
+                (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (b - 1) as f64).sqrt();
+            values.sort_by(|a, c| a.partial_cmp(c).unwrap_or(Ordering::Equal));
+            conf_lower[j] = values[((alpha / 2.0) * b as f64) as usize];
+            conf_upper[j] = values[(((1.0 - alpha / 2.0) * b as f64) as usize).min(b - 1)];
+        }
+    }
+
+    Ok(TurnbullResult {
+        time,
+        interval_lower: fit.lower.iter().map(|e| e.value).collect(),
+        probability: fit.probability,
+        n_risk,
+        n_event,
+        estimate,
+        std_err,
+        conf_lower,
+        conf_upper,
+        log_likelihood: fit.log_likelihood,
+        iterations: fit.iterations,
+        converged: fit.converged,
+    })
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(
+    name = "turnbull",
+    signature = (lower, upper, weights=None, n_bootstrap=200, confidence_level=0.95, seed=None, max_iter=1000, tol=1e-9)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn turnbull_py(
+    py: Python<'_>,
+    lower: VectorArg<'_, f64>,
+    upper: VectorArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
+    n_bootstrap: usize,
+    confidence_level: f64,
+    seed: Option<u64>,
+    max_iter: usize,
+    tol: f64,
+) -> PyResult<TurnbullResult> {
+    let lower = lower.as_slice();
+    let upper = upper.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let config = TurnbullConfig {
+        max_iter,
+        tol,
+        n_bootstrap,
+        confidence_level,
+        seed,
+    };
+    Ok(parallel::detach(py, || {
+        turnbull(&lower, &upper, weights.as_deref(), &config)
+    })?)
+}
@@ -3,5 +3,3 @@ pub mod multicheck;
 pub mod r_validation_tests;
 pub mod unit_tests;
 pub mod validation_tests;
-pub mod zph1;
-pub mod zph2;
@@ -1,10 +1,15 @@
 #[cfg(test)]
 mod tests {
+    use crate::regression::agfit5::agfit5;
+    use crate::regression::coxph::{CoxMethod, CoxPHModel};
+    use crate::regression::frailty::{FrailtyDistribution, FrailtyError, coxph_frailty};
+    use crate::regression::survreg6::{SurvivalFit, survreg};
     use crate::surv_analysis::nelson_aalen::{nelson_aalen, stratified_km};
     use crate::validation::landmark::{compute_hazard_ratio, compute_survival_at_times};
     use crate::validation::logrank::{WeightType, weighted_logrank_test};
     use crate::validation::power::sample_size_logrank;
     use crate::validation::rmst::compute_rmst;
+    use ndarray::Array2;
 
     const LOOSE_TOLERANCE: f64 = 0.05;
 
@@ -57,6 +62,23 @@ mod tests {
         )
     }
 
+    fn coxph_test1() -> (Vec<Vec<f64>>, Vec<f64>, Vec<u8>, Vec<usize>) {
+        (
+            vec![
+                vec![0.0],
+                vec![2.0],
+                vec![1.0],
+                vec![1.0],
+                vec![1.0],
+                vec![0.0],
+                vec![0.0],
+            ],
+            vec![4.0, 3.0, 1.0, 1.0, 2.0, 2.0, 3.0],
+            vec![1, 1, 1, 0, 1, 1, 0],
+            vec![0, 0, 0, 0, 1, 1, 1],
+        )
+    }
+
     fn ovarian_data() -> (Vec<f64>, Vec<i32>, Vec<i32>) {
         (
             vec![
@@ -73,6 +95,43 @@ mod tests {
         )
     }
 
+    /// The kidney data of the R survival package: recurrence times of two
+    /// catheter infections for each of 38 patients, with age and sex as the
+    /// covariates.
+    fn kidney() -> (Vec<usize>, Vec<f64>, Vec<u8>, Array2<f64>) {
+        let time = vec![
+            8.0, 16.0, 23.0, 13.0, 22.0, 28.0, 447.0, 318.0, 30.0, 12.0, 24.0, 245.0, 7.0, 9.0,
+            511.0, 30.0, 53.0, 196.0, 15.0, 154.0, 7.0, 333.0, 141.0, 8.0, 96.0, 38.0, 149.0, 70.0,
+            536.0, 25.0, 17.0, 4.0, 185.0, 177.0, 292.0, 114.0, 22.0, 159.0, 15.0, 108.0, 152.0,
+            562.0, 402.0, 24.0, 13.0, 66.0, 39.0, 46.0, 12.0, 40.0, 113.0, 201.0, 132.0, 156.0,
+            34.0, 30.0, 2.0, 25.0, 130.0, 26.0, 27.0, 58.0, 5.0, 43.0, 152.0, 30.0, 190.0, 5.0,
+            119.0, 8.0, 54.0, 16.0, 6.0, 78.0, 63.0, 8.0,
+        ];
+        let status = vec![
+            1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0, 0, 1,
+            0, 1, 0, 1, 1, 1, 1, 0, 0, 1, 0, 1, 1, 1, 0, 1, 1, 1, 0, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 0, 1, 1, 1, 1, 0, 1, 1, 0, 0, 0, 1, 1, 0,
+        ];
+        let age = vec![
+            28.0, 28.0, 48.0, 48.0, 32.0, 32.0, 31.0, 32.0, 10.0, 10.0, 16.0, 17.0, 51.0, 51.0,
+            55.0, 56.0, 69.0, 69.0, 51.0, 52.0, 44.0, 44.0, 34.0, 34.0, 35.0, 35.0, 42.0, 42.0,
+            17.0, 17.0, 60.0, 60.0, 60.0, 60.0, 43.0, 44.0, 53.0, 53.0, 44.0, 44.0, 46.0, 47.0,
+            30.0, 30.0, 62.0, 63.0, 42.0, 43.0, 43.0, 43.0, 57.0, 58.0, 10.0, 10.0, 52.0, 52.0,
+            53.0, 53.0, 54.0, 54.0, 56.0, 56.0, 50.0, 51.0, 57.0, 57.0, 44.0, 45.0, 22.0, 22.0,
+            42.0, 42.0, 52.0, 52.0, 60.0, 60.0,
+        ];
+        let sex = vec![
+            1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 2.0,
+            2.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0,
+            2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 1.0, 1.0, 2.0,
+            2.0, 2.0, 2.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0,
+            2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 1.0, 1.0,
+        ];
+        let id = (0..76).map(|i| i / 2 + 1).collect();
+        let x = Array2::from_shape_fn((76, 2), |(i, j)| if j == 0 { age[i] } else { sex[i] });
+        (id, time, status, x)
+    }
+
     #[test]
     fn test_r_aml_kaplan_meier_maintained() {
         let (time, status) = aml_maintained();
@@ -193,6 +252,77 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_r_ovarian_flexsurv_standard_errors() {
+        // flexsurvreg(Surv(time, status) ~ rx, dist = "gengamma" / "gompertz"),
+        // with the reference from a separate high-precision implementation of
+        // flexsurv's likelihoods, maximised by Newton's method.
+        let (time, status, group) = ovarian_data();
+        let status: Vec<f64> = status.iter().map(|&s| f64::from(s)).collect();
+        let covariates: Vec<Vec<f64>> =
+            group.iter().map(|&g| vec![1.0, f64::from(g - 1)]).collect();
+        let fit = |distribution: &str| {
+            survreg(
+                time.clone(),
+                status.clone(),
+                covariates.clone(),
+                None,
+                None,
+                None,
+                None,
+                Some(distribution),
+                Some(100),
+                Some(1e-9),
+                None,
+            )
+            .unwrap()
+        };
+        let se = |fit: &SurvivalFit| -> Vec<f64> {
+            (0..fit.coefficients.len())
+                .map(|j| fit.variance_matrix[j][j].sqrt())
+                .collect()
+        };
+
+        // mu, rx, log(sigma), Q
+        let gengamma = fit("gengamma");
+        assert_eq!(gengamma.convergence_flag, 0);
+        assert!(approx_eq(gengamma.log_likelihood, -95.4881308263, 1e-7));
+        for (got, want) in gengamma.coefficients.iter().zip([
+            7.329508657,
+            -1.097190703,
+            -0.7457945217,
+            1.857771452,
+        ]) {
+            assert!(approx_eq(*got, want, 1e-5), "{got} vs {want}");
+        }
+        for (got, want) in
+            se(&gengamma)
+                .iter()
+                .zip([0.4480146311, 0.4308722569, 1.246334404, 2.62955111])
+        {
+            assert!(approx_eq(*got, want, 1e-5), "{got} vs {want}");
+        }
+
+        // log(rate), rx, shape
+        let gompertz = fit("gompertz");
+        assert_eq!(gompertz.convergence_flag, 0);
+        assert!(approx_eq(gompertz.log_likelihood, -96.1310986826, 1e-7));
+        for (got, want) in
+            gompertz
+                .coefficients
+                .iter()
+                .zip([-7.51312167, 1.136459715, 0.0003511675753])
+        {
+            assert!(approx_eq(*got, want, 1e-6), "{got} vs {want}");
+        }
+        for (got, want) in se(&gompertz)
+            .iter()
+            .zip([0.6121236622, 0.6703233061, 0.001163609537])
+        {
+            assert!(approx_eq(*got, want, 1e-6), "{got} vs {want}");
+        }
+    }
+
     #[test]
     fn test_r_ovarian_logrank() {
         let (time, status, group) = ovarian_data();
@@ -429,4 +559,281 @@ mod tests {
         assert!(result.rmst > 0.0);
         assert!(result.rmst < 350.0);
     }
+
+    #[test]
+    fn test_r_coxph_efron_ties() {
+        let (x, time, status, _) = coxph_test1();
+        let mut model = CoxPHModel::new_with_data(x, time, status);
+        model.fit(20).unwrap();
+
+        assert!(approx_eq(model.coefficients()[0][0], 0.4608, 1e-3));
+    }
+
+    #[test]
+    fn test_r_coxph_stratified_efron() {
+        let (x, time, status, sex) = coxph_test1();
+        let mut model = CoxPHModel::new_with_options(
+            x.clone(),
+            time.clone(),
+            status.clone(),
+            Some(sex.clone()),
+            None,
+            None,
+            CoxMethod::Efron,
+        );
+        model.fit(20).unwrap();
+        let beta = model.coefficients()[0][0];
+        assert!(approx_eq(beta, 0.8023, 1e-3));
+
+        let mut breslow = CoxPHModel::new_with_options(
+            x,
+            time,
+            status,
+            Some(sex),
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        breslow.fit(20).unwrap();
+        assert!(approx_eq(breslow.coefficients()[0][0], 0.7357, 1e-3));
+
+        let baselines = model.baseline_hazards();
+        assert_eq!(baselines.len(), 2);
+        assert_eq!(baselines[0].time, vec![1.0, 3.0, 4.0]);
+        assert_eq!(baselines[1].time, vec![2.0]);
+
+        let denom = beta.exp() + 2.0;
+        let efron_step = 1.0 / denom + 1.0 / (denom - (beta.exp() + 1.0) / 2.0);
+        assert!(approx_eq(
+            baselines[1].cumulative_hazard[0],
+            efron_step,
+            1e-8
+        ));
+
+        let (_, curves) = model
+            .survival_curve(
+                vec![vec![0.0], vec![0.0]],
+                Some(vec![2.5]),
+                Some(vec![0, 1]),
+            )
+            .unwrap();
+        assert!(approx_eq(curves[1][0], (-efron_step).exp(), 1e-8));
+        assert!(curves[0][0] > curves[1][0]);
+        assert!(
+            model
+                .survival_curve(vec![vec![0.0]], None, Some(vec![5]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_r_coxph_counting_process() {
+        let start = vec![1.0, 2.0, 5.0, 2.0, 1.0, 7.0, 3.0, 4.0, 8.0, 8.0];
+        let stop = vec![2.0, 3.0, 6.0, 7.0, 8.0, 9.0, 9.0, 9.0, 14.0, 17.0];
+        let event = vec![1, 1, 1, 1, 1, 1, 1, 0, 0, 0];
+        let x: Vec<Vec<f64>> = [1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0]
+            .iter()
+            .map(|&v| vec![v])
+            .collect();
+
+        let mut breslow = CoxPHModel::new_with_options(
+            x.clone(),
+            stop.clone(),
+            event.clone(),
+            None,
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        breslow.entry_times = Some(start.clone());
+        breslow.fit(20).unwrap();
+        assert!(approx_eq(breslow.coefficients()[0][0], -0.08453, 1e-3));
+
+        let baseline = &breslow.baseline_hazards()[0];
+        assert_eq!(baseline.time, vec![2.0, 3.0, 6.0, 7.0, 8.0, 9.0]);
+        assert!(approx_eq(baseline.cumulative_hazard[5], 2.0267, 1e-2));
+
+        let mut yy = start.clone();
+        yy.extend(&stop);
+        yy.extend(event.iter().map(|&e| f64::from(e)));
+        let covar: Vec<f64> = x.iter().map(|row| row[0]).collect();
+        let legacy = agfit5(
+            10,
+            1,
+            0,
+            &yy,
+            &covar,
+            &[0.0; 10],
+            &[1.0; 10],
+            &[1; 10],
+            &[],
+            0,
+            0,
+            &[],
+            20,
+            1e-9,
+        )
+        .unwrap();
+        assert!(approx_eq(legacy.coefficients[0], -0.08453, 1e-3));
+
+        let mut efron = CoxPHModel::new_with_data(x, stop, event);
+        efron.entry_times = Some(start);
+        efron.fit(20).unwrap();
+        assert!(approx_eq(efron.coefficients()[0][0], -0.02111, 1e-3));
+    }
+
+    #[test]
+    fn test_r_coxph_frailty_kidney() {
+        let (id, time, status, x) = kidney();
+        let fit = |entry: Option<&[f64]>,
+                   stop: &[f64],
+                   status: &[u8],
+                   id: &[usize],
+                   x: &Array2<f64>,
+                   distribution,
+                   theta| {
+            coxph_frailty(
+                x.view(),
+                stop,
+                status,
+                id,
+                None,
+                None,
+                entry,
+                distribution,
+                theta,
+                CoxMethod::Efron,
+            )
+            .unwrap()
+        };
+
+        // coxph(Surv(time, status) ~ age + sex + frailty(id), kidney)
+        let gamma = fit(
+            None,
+            &time,
+            &status,
+            &id,
+            &x,
+            FrailtyDistribution::Gamma,
+            None,
+        );
+        assert!(gamma.converged);
+        assert_eq!(gamma.groups.len(), 38);
+        assert!(approx_eq(gamma.theta, 0.408, 1e-3));
+        assert!(approx_eq(
+            gamma.integrated_log_likelihood.unwrap(),
+            -181.6,
+            0.05
+        ));
+
+        // coxph(Surv(time, status) ~ age + sex, kidney): a vanishing frailty
+        // variance gives back the Cox fit.
+        let cox = [0.00203, -0.82931];
+        let cox_se = [0.00925, 0.29895];
+        for distribution in [FrailtyDistribution::Gamma, FrailtyDistribution::Gaussian] {
+            let fixed = fit(None, &time, &status, &id, &x, distribution, Some(1e-6));
+            for j in 0..2 {
+                assert!(approx_eq(fixed.coefficients[j], cox[j], 1e-4));
+                assert!(approx_eq(fixed.std_errors[j], cox_se[j], 1e-4));
+            }
+        }
+
+        // Splitting every row at half its time into (start, stop] intervals
+        // leaves the fit unchanged.
+        let mut split = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for i in 0..76 {
+            let half = time[i] / 2.0;
+            split.0.extend([0.0, half]);
+            split.1.extend([half, time[i]]);
+            split.2.extend([0, status[i]]);
+            split.3.extend([id[i], id[i]]);
+            split.4.extend([i, i]);
+        }
+        let xs = Array2::from_shape_fn((152, 2), |(k, j)| x[[split.4[k], j]]);
+        for distribution in [FrailtyDistribution::Gamma, FrailtyDistribution::Gaussian] {
+            let whole = fit(None, &time, &status, &id, &x, distribution, Some(0.5));
+            let parts = fit(
+                Some(&split.0),
+                &split.1,
+                &split.2,
+                &split.3,
+                &xs,
+                distribution,
+                Some(0.5),
+            );
+            for j in 0..2 {
+                assert!(approx_eq(
+                    whole.coefficients[j],
+                    parts.coefficients[j],
+                    1e-8
+                ));
+                assert!(approx_eq(whole.std_errors[j], parts.std_errors[j], 1e-8));
+            }
+            for (a, b) in whole.frailties.iter().zip(&parts.frailties) {
+                assert!(approx_eq(*a, *b, 1e-8));
+            }
+            assert!(approx_eq(whole.log_likelihood, parts.log_likelihood, 1e-8));
+        }
+        assert!(matches!(
+            coxph_frailty(
+                x.view(),
+                &time,
+                &status,
+                &id,
+                None,
+                None,
+                Some(&time),
+                FrailtyDistribution::Gamma,
+                None,
+                CoxMethod::Efron,
+            ),
+            Err(FrailtyError::InvalidInterval(0))
+        ));
+    }
+
+    #[test]
+    fn test_r_coxph_pspline_kidney() {
+        let (_, time, status, x) = kidney();
+        let names: Vec<String> = ["time", "status", "age"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let data = Array2::from_shape_fn((76, 3), |(i, j)| match j {
+            0 => time[i],
+            1 => f64::from(status[i]),
+            _ => x[[i, 0]],
+        });
+        let mut model = CoxPHModel::from_formula(
+            "Surv(time, status) ~ pspline(age)",
+            data.view(),
+            &names,
+            None,
+            CoxMethod::Efron,
+        )
+        .unwrap();
+        assert_eq!(model.penalized_terms[0].columns, 12);
+        model.fit(30).unwrap();
+        assert!(model.converged());
+
+        // coxph(Surv(time, status) ~ pspline(age), kidney) with the 4 df met
+        // exactly instead of to within R's default eps = 0.1, from a separate
+        // implementation of R's basis and second-difference penalty.
+        assert!(approx_eq(model.penalty_theta()[0], 0.853353, 1e-5));
+        assert!(approx_eq(model.log_likelihood(), -186.537309, 1e-5));
+        assert!(approx_eq(
+            model.null_log_likelihood().unwrap(),
+            -187.902762,
+            1e-5
+        ));
+        let beta = &model.coefficients()[0];
+        let se = model.standard_errors(false);
+        for (j, (b, s)) in [
+            (0, (-5.946834, 5.783492)),
+            (5, (-5.534424, 5.036825)),
+            (11, (-5.259363, 5.122971)),
+        ] {
+            assert!(approx_eq(beta[j], b, 1e-5));
+            assert!(approx_eq(se[j], s, 1e-5));
+        }
+    }
 }
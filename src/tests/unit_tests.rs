@@ -2,10 +2,36 @@
 mod tests {
     use crate::matrix::chinv2::chinv2;
     use crate::matrix::cholesky2::cholesky2;
+    use crate::regression::clogit::{
+        ClogitDataSet, ClogitError, ClogitMethod, ConditionalLogisticRegression,
+    };
+    use crate::regression::coxnet::{CoxNetConfig, CoxNetError, coxnet, cv_coxnet};
+    use crate::regression::coxph::{ConfType, CoxMethod, CoxPHError, CoxPHModel, Subject};
+    use crate::regression::coxph_tt::TimeTransform;
+    use crate::regression::frailty::{FrailtyDistribution, FrailtyError, coxph_frailty};
+    use crate::regression::multistate_cox::{MultiStateCoxError, multistate_coxph};
+    use crate::regression::survreg6::{
+        DistributionType, SurvivalFit, survreg, survreg_formula, survreg_interval,
+    };
+    use crate::specialized::brier::survival_brier;
+    use crate::specialized::cch::{CchError, CchMethod, CohortData};
+    use crate::surv_analysis::multistate::{MultiStateError, multistate_fit};
     use crate::surv_analysis::survdiff2::{
         SurvDiffInput, SurvDiffOutput, SurvDiffParams, survdiff2_internal,
     };
+    use crate::surv_analysis::turnbull::{TurnbullConfig, turnbull};
+    use crate::utilities::formula::{Formula, FormulaError, PenalizedTerm, model_frame};
+    use crate::utilities::parallel::{install, num_threads, set_num_threads};
+    use crate::utilities::persistence::{ModelFormat, Persist, PersistError};
     use crate::utilities::survsplit::survsplit;
+    use crate::validation::bootstrap::{
+        BootstrapCiMethod, BootstrapConfig, Resampler, bootstrap_cox,
+    };
+    use crate::validation::cox_zph::{ZphTransform, cox_zph};
+    use crate::validation::crossval::CVConfig;
+    use crate::validation::zph1::zph1;
+    use crate::validation::zph2::zph2;
+    use ndarray::{Array1, Array2, ArrayView1, array};
 
     #[test]
     fn test_survdiff2_standard() {
@@ -431,4 +457,1653 @@ mod tests {
         assert_eq!(matrix[1], 0.0);
         assert_eq!(matrix[2], 0.0);
     }
+
+    #[test]
+    fn test_coxph_model_fit_rust_api() {
+        let covariates = vec![
+            vec![1.5],
+            vec![1.0],
+            vec![0.5],
+            vec![2.0],
+            vec![0.2],
+            vec![1.8],
+        ];
+        let times = vec![5.0, 4.0, 3.0, 1.0, 6.0, 2.0];
+        let status = vec![1, 0, 1, 1, 1, 1];
+        let mut model = CoxPHModel::new_with_data(covariates, times, status);
+        model.fit(20).unwrap();
+
+        let coefs = model.coefficients();
+        assert_eq!(coefs.len(), 1);
+        assert!((coefs[0][0] - 1.784395).abs() < 1e-4);
+        assert!((model.naive_var()[0][0] - 1.551172).abs() < 1e-3);
+        assert_eq!(model.risk_scores.len(), 6);
+    }
+
+    #[test]
+    fn test_coxph_model_fit_no_data() {
+        let mut model = CoxPHModel::new();
+        assert!(matches!(model.fit(10), Err(CoxPHError::NoData)));
+    }
+
+    #[test]
+    fn test_coxph_weights_match_replicated_rows() {
+        let covariates = vec![vec![0.5], vec![1.0], vec![1.5], vec![2.0], vec![0.2]];
+        let times = vec![5.0, 4.0, 3.0, 1.0, 6.0];
+        let status = vec![1, 0, 1, 1, 1];
+
+        let mut weighted = CoxPHModel::new_with_options(
+            covariates.clone(),
+            times.clone(),
+            status.clone(),
+            None,
+            Some(vec![2.0, 1.0, 1.0, 1.0, 3.0]),
+            None,
+            CoxMethod::Breslow,
+        );
+        weighted.fit(20).unwrap();
+
+        let mut rep_cov = covariates.clone();
+        let mut rep_times = times.clone();
+        let mut rep_status = status.clone();
+        for (i, copies) in [(0, 1), (4, 2)] {
+            for _ in 0..copies {
+                rep_cov.push(covariates[i].clone());
+                rep_times.push(times[i]);
+                rep_status.push(status[i]);
+            }
+        }
+        let mut replicated = CoxPHModel::new_with_options(
+            rep_cov,
+            rep_times,
+            rep_status,
+            None,
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        replicated.fit(20).unwrap();
+
+        assert!((weighted.coefficients()[0][0] - replicated.coefficients()[0][0]).abs() < 1e-6);
+
+        let mut bad = CoxPHModel::new_with_options(
+            covariates,
+            times,
+            status,
+            None,
+            Some(vec![1.0; 3]),
+            None,
+            CoxMethod::Efron,
+        );
+        assert!(matches!(
+            bad.fit(20),
+            Err(CoxPHError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_coxph_split_intervals_match_unsplit() {
+        let covariates = vec![vec![0.5], vec![1.0], vec![1.5], vec![0.2], vec![2.0]];
+        let times = vec![5.0, 4.0, 3.0, 1.0, 6.0];
+        let status = vec![1, 0, 1, 1, 1];
+
+        let mut unsplit =
+            CoxPHModel::new_with_data(covariates.clone(), times.clone(), status.clone());
+        unsplit.fit(20).unwrap();
+
+        let mut split_cov = Vec::new();
+        let mut entry = Vec::new();
+        let mut exit = Vec::new();
+        let mut split_status = Vec::new();
+        for i in 0..times.len() {
+            if times[i] > 2.5 {
+                split_cov.push(covariates[i].clone());
+                entry.push(0.0);
+                exit.push(2.5);
+                split_status.push(0);
+                split_cov.push(covariates[i].clone());
+                entry.push(2.5);
+            } else {
+                split_cov.push(covariates[i].clone());
+                entry.push(0.0);
+            }
+            exit.push(times[i]);
+            split_status.push(status[i]);
+        }
+        let mut split = CoxPHModel::new_with_data(split_cov, exit, split_status);
+        split.entry_times = Some(entry);
+        split.fit(20).unwrap();
+
+        assert!((unsplit.coefficients()[0][0] - split.coefficients()[0][0]).abs() < 1e-6);
+        assert_eq!(
+            unsplit.baseline_hazards()[0].time,
+            split.baseline_hazards()[0].time
+        );
+        let total: f64 = split.martingale_residuals().iter().sum();
+        assert!(total.abs() < 1e-6);
+
+        split.entry_times = Some(vec![0.0; 3]);
+        assert!(matches!(
+            split.fit(20),
+            Err(CoxPHError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_coxph_robust_variance() {
+        let covariates: Vec<Vec<f64>> = [0.5, 1.0, 1.5, 2.0, 0.2, 1.8, 0.7, 0.1]
+            .iter()
+            .map(|&v| vec![v])
+            .collect();
+        let times = vec![5.0, 4.0, 3.0, 1.0, 6.0, 2.0, 7.0, 8.0];
+        let status = vec![1, 0, 1, 1, 1, 1, 0, 1];
+
+        let mut model =
+            CoxPHModel::new_with_data(covariates.clone(), times.clone(), status.clone());
+        model.fit(50).unwrap();
+        assert!((model.coefficients()[0][0] - 3.4909).abs() < 1e-2);
+        assert!((model.naive_var()[0][0] - 3.1980).abs() < 1e-2);
+        assert!((model.robust_var()[0][0] - 1.2907).abs() < 1e-2);
+
+        let total: f64 = model.score_residuals().iter().map(|r| r[0]).sum();
+        assert!(total.abs() < 1e-4);
+
+        let naive_se = model.standard_errors(false)[0];
+        let robust_se = model.standard_errors(true)[0];
+        let (_, lower, upper) = model.hazard_ratios_with_ci(0.95, true).unwrap();
+        assert!(((upper[0] / lower[0]).ln() - 2.0 * 1.959964 * robust_se).abs() < 1e-5);
+        let (_, lower, upper) = model.hazard_ratios_with_ci(0.8, false).unwrap();
+        assert!(((upper[0] / lower[0]).ln() - 2.0 * 1.281552 * naive_se).abs() < 1e-5);
+        for level in [0.0, 1.0, f64::NAN] {
+            assert!(matches!(
+                model.hazard_ratios_with_ci(level, false),
+                Err(CoxPHError::InvalidConfidenceLevel(_))
+            ));
+        }
+        assert!(robust_se < naive_se);
+        assert_eq!(model.wald_test(true).df, 1);
+
+        let mut clustered = CoxPHModel::new_with_data(covariates, times, status);
+        clustered.cluster = Some(vec![10, 10, 11, 11, 12, 12, 13, 13]);
+        clustered.fit(50).unwrap();
+        assert!((clustered.robust_var()[0][0] - 0.8489).abs() < 1e-2);
+        assert!((clustered.naive_var()[0][0] - model.naive_var()[0][0]).abs() < 1e-10);
+    }
+
+    fn cch_cohort(subcohort: &[bool]) -> CohortData {
+        let x = [0.5, 1.0, 1.5, 2.0, 0.2, 1.8, 0.7, 0.1];
+        let times = [5.0, 4.0, 3.0, 1.0, 6.0, 2.0, 7.0, 8.0];
+        let status = [true, false, true, true, true, true, false, true];
+        let mut data = CohortData::new();
+        for i in 0..x.len() {
+            data.add_subject(Subject::new_with_time(
+                i,
+                vec![x[i]],
+                status[i],
+                subcohort[i],
+                0,
+                times[i],
+                None,
+            ));
+        }
+        data
+    }
+
+    #[test]
+    fn test_cch_full_cohort_matches_cox() {
+        let mut data = cch_cohort(&[true; 8]);
+        data.cohort_size = Some(8);
+        data.stratum_sizes = Some(vec![8]);
+        for method in [
+            CchMethod::Prentice,
+            CchMethod::SelfPrentice,
+            CchMethod::LinYing,
+            CchMethod::IBorgan,
+            CchMethod::IIBorgan,
+        ] {
+            let model = data.fit(method).unwrap();
+            assert!((model.coefficients()[0][0] - 3.4909).abs() < 1e-2);
+            let naive = model.naive_var()[0][0];
+            assert!((naive - 3.1980).abs() < 1e-2);
+            assert!((model.robust_var()[0][0] - naive).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_cch_subcohort_variance() {
+        let subcohort = [false, true, true, true, false, true, true, true];
+        let mut data = cch_cohort(&subcohort);
+        assert!(matches!(
+            data.fit(CchMethod::Prentice),
+            Err(CchError::MissingCohortSize)
+        ));
+        data.cohort_size = Some(6);
+        assert!(matches!(
+            data.fit(CchMethod::Prentice),
+            Err(CchError::CohortTooSmall { .. })
+        ));
+        data.cohort_size = Some(20);
+
+        let prentice = data.fit(CchMethod::Prentice).unwrap();
+        let self_prentice = data.fit(CchMethod::SelfPrentice).unwrap();
+        let lin_ying = data.fit(CchMethod::LinYing).unwrap();
+        for model in [&prentice, &self_prentice, &lin_ying] {
+            assert!(model.coefficients()[0][0].is_finite());
+            assert!(model.robust_var()[0][0] > model.naive_var()[0][0]);
+        }
+        assert!((prentice.coefficients()[0][0] - 3.1696).abs() < 2e-2);
+        assert!((self_prentice.coefficients()[0][0] - 2.8438).abs() < 2e-2);
+        assert!(matches!(
+            data.fit(CchMethod::IBorgan),
+            Err(CchError::MissingStratumSize(0))
+        ));
+    }
+
+    #[test]
+    fn test_formula_model_frame() {
+        let names: Vec<String> = ["time", "status", "x", "g", "s", "id", "o"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let data = array![
+            [1.0, 1.0, 0.5, 1.0, 0.0, 7.0, 0.1],
+            [2.0, 0.0, 1.5, 2.0, 1.0, 7.0, 0.2],
+            [3.0, 1.0, 2.5, 3.0, 0.0, 9.0, 0.3],
+            [4.0, 1.0, 3.5, 2.0, 1.0, 8.0, 0.4],
+        ];
+        let frame = model_frame(
+            "Surv(time, status) ~ x * factor(g) + strata(s) + cluster(id) + offset(o) - 1",
+            data.view(),
+            &names,
+        )
+        .unwrap();
+        assert_eq!(
+            frame.column_names,
+            vec!["x", "g1", "g2", "g3", "x:g2", "x:g3"]
+        );
+        assert_eq!(frame.covariates[1], vec![1.5, 0.0, 1.0, 0.0, 1.5, 0.0]);
+        assert_eq!(frame.covariates[2], vec![2.5, 0.0, 0.0, 1.0, 0.0, 2.5]);
+        assert_eq!(frame.status, Some(vec![1.0, 0.0, 1.0, 1.0]));
+        assert_eq!(frame.strata, Some(vec![0, 1, 0, 1]));
+        assert_eq!(frame.cluster, Some(vec![0, 0, 1, 2]));
+        assert_eq!(frame.offset, Some(vec![0.1, 0.2, 0.3, 0.4]));
+        assert!(!frame.intercept);
+
+        let sum = model_frame("time ~ factor(g, contrast = \"sum\")", data.view(), &names).unwrap();
+        assert_eq!(sum.covariates[2], vec![-1.0, -1.0]);
+
+        let spline = model_frame("time ~ bs(x, df = 5)", data.view(), &names).unwrap();
+        assert_eq!(spline.column_names.len(), 5);
+        for row in &spline.covariates {
+            let total: f64 = row.iter().sum();
+            assert!((0.0..=1.0 + 1e-12).contains(&total));
+        }
+        let cubic = model_frame("time ~ bs(x)", data.view(), &names).unwrap();
+        assert_eq!(cubic.column_names, vec!["bs(x)1", "bs(x)2", "bs(x)3"]);
+        for (value, expected) in cubic.covariates[1]
+            .iter()
+            .zip([4.0 / 9.0, 2.0 / 9.0, 1.0 / 27.0])
+        {
+            assert!((value - expected).abs() < 1e-12);
+        }
+        let helmert = model_frame("time ~ factor(g, \"helmert\") + x + 0", data.view(), &names);
+        assert_eq!(helmert.unwrap().covariates[3], vec![0.0, 1.0, 0.0, 3.5]);
+
+        let penalized = model_frame("time ~ x + pspline(x, df = 3)", data.view(), &names).unwrap();
+        assert_eq!(penalized.column_names.len(), 1 + 9);
+        assert_eq!(penalized.column_names[1], "ps(x)2");
+        assert_eq!(
+            penalized.penalized,
+            vec![PenalizedTerm {
+                name: "pspline(x)".to_string(),
+                first: 1,
+                columns: 9,
+                df: 3.0,
+            }]
+        );
+        for row in &penalized.covariates {
+            let total: f64 = row[1..].iter().sum();
+            assert!(total > 0.0 && total <= 1.0 + 1e-12);
+        }
+        assert!(matches!(
+            penalized.unpenalized(),
+            Err(FormulaError::PenalizedTerm(_))
+        ));
+        assert!(matches!(
+            model_frame("time ~ pspline(x, theta = 0.5)", data.view(), &names),
+            Err(FormulaError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            model_frame("time ~ x:pspline(x)", data.view(), &names),
+            Err(FormulaError::InvalidTerm(_))
+        ));
+        assert!(matches!(
+            model_frame("time ~ ridge(x)", data.view(), &names),
+            Err(FormulaError::PenalizedTerm(_))
+        ));
+
+        let formula = Formula::parse("Surv(start, stop, event) ~ a + b + a:b - a:b").unwrap();
+        assert_eq!(formula.terms.len(), 2);
+        assert!(Formula::parse("time ~ factor(g").is_err());
+        assert!(model_frame("time ~ missing", data.view(), &names).is_err());
+    }
+
+    #[test]
+    fn test_coxph_from_formula_matches_matrix_api() {
+        let x = [0.5, 1.0, 1.5, 2.0, 0.2, 1.8, 0.7, 0.1];
+        let times = [5.0, 4.0, 3.0, 1.0, 6.0, 2.0, 7.0, 8.0];
+        let status = [1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 1.0];
+        let data = Array2::from_shape_fn((8, 3), |(i, j)| [times[i], status[i], x[i]][j]);
+        let names = vec!["time".to_string(), "status".to_string(), "x".to_string()];
+
+        let mut model = CoxPHModel::from_formula(
+            "Surv(time, status) ~ x",
+            data.view(),
+            &names,
+            None,
+            CoxMethod::Efron,
+        )
+        .unwrap();
+        model.fit(50).unwrap();
+        assert_eq!(model.covariate_names, vec!["x"]);
+        assert!((model.coefficients()[0][0] - 3.4909).abs() < 1e-2);
+        assert!(model.summary().contains("x "));
+
+        let clogit =
+            ClogitDataSet::from_formula("status ~ x + strata(time)", data.view(), &names).unwrap();
+        assert_eq!(clogit.get_num_observations(), 8);
+        assert_eq!(clogit.get_num_covariates(), 1);
+    }
+
+    fn survreg_intercept_only(distribution: &str) -> crate::regression::survreg6::SurvivalFit {
+        let time = vec![2.0, 3.0, 5.0, 7.0, 8.0, 11.0, 13.0, 15.0, 17.0, 20.0];
+        let status = vec![1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0];
+        let covariates = vec![vec![1.0]; time.len()];
+        survreg(
+            time,
+            status,
+            covariates,
+            None,
+            None,
+            None,
+            None,
+            Some(distribution),
+            Some(100),
+            Some(1e-9),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_survreg_exponential_and_weibull() {
+        let exponential = survreg_intercept_only("exponential");
+        assert_eq!(exponential.distribution, DistributionType::Exponential);
+        assert_eq!(exponential.scale, vec![1.0]);
+        assert!((exponential.coefficients[0] - (101.0f64 / 7.0).ln()).abs() < 1e-5);
+        assert!((exponential.log_likelihood + 25.684473).abs() < 1e-4);
+
+        let weibull = survreg_intercept_only("weibull");
+        assert!((weibull.coefficients[0] - 2.628568).abs() < 1e-4);
+        assert!((weibull.scale[0] - 0.645924).abs() < 1e-4);
+        assert!((weibull.log_likelihood + 24.886001).abs() < 1e-4);
+        assert!((exponential.aic() - 53.368946).abs() < 1e-3);
+        assert!((weibull.aic() - 53.772002).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_survreg_quantiles_match_survival() {
+        for distribution in [
+            "weibull",
+            "lognormal",
+            "loglogistic",
+            "gaussian",
+            "gengamma",
+            "gompertz",
+        ] {
+            let fit = survreg_intercept_only(distribution);
+            let x = vec![vec![1.0]];
+            let median = fit.predict_quantile(&x, 0.5, None).unwrap()[0];
+            let surv = fit.predict_survival(&x, &[median], None).unwrap();
+            assert!(
+                (surv[0][0] - 0.5).abs() < 1e-6,
+                "{distribution}: S(median) = {}",
+                surv[0][0]
+            );
+        }
+
+        let weibull = survreg_intercept_only("weibull");
+        let gengamma = survreg_intercept_only("gengamma");
+        assert_eq!(gengamma.coefficients.len(), 3);
+        assert!(gengamma.shape.is_some());
+        assert!(gengamma.log_likelihood >= weibull.log_likelihood - 1e-6);
+        for fit in [&gengamma, &survreg_intercept_only("gompertz")] {
+            assert_eq!(fit.convergence_flag, 0);
+            assert!(fit.score_vector.iter().all(|u| u.abs() < 1e-3));
+        }
+    }
+
+    #[test]
+    fn test_survreg_interval_censored() {
+        let lower = vec![1.0, 2.0, 4.0, 3.0, 6.0, 5.0, 7.0, 8.0, 2.0, 1.0, 9.0, 0.0];
+        let upper = vec![3.0, 5.0, 4.0, 3.0, 9.0, 5.0, 10.0, 8.0, 4.0, 2.0, 12.0, 2.0];
+        let status = vec![3.0, 3.0, 1.0, 2.0, 3.0, 0.0, 3.0, 0.0, 3.0, 3.0, 3.0, 3.0];
+        let x = [0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0];
+        let covariates: Vec<Vec<f64>> = x.iter().map(|&v| vec![1.0, v]).collect();
+        let fit = |distribution: &str| {
+            survreg_interval(
+                lower.clone(),
+                upper.clone(),
+                status.clone(),
+                covariates.clone(),
+                None,
+                None,
+                None,
+                None,
+                Some(distribution),
+                Some(100),
+                Some(1e-10),
+                None,
+            )
+            .unwrap()
+        };
+
+        let weibull = fit("weibull");
+        assert_eq!(weibull.convergence_flag, 0);
+        assert!((weibull.coefficients[0] - 1.656027).abs() < 1e-4);
+        assert!((weibull.coefficients[1] - 0.145145).abs() < 1e-4);
+        assert!((weibull.scale[0] - 0.719015).abs() < 1e-4);
+        assert!((weibull.log_likelihood + 19.040605).abs() < 1e-4);
+
+        let exponential = fit("exponential");
+        assert!((exponential.coefficients[0] - 1.675219).abs() < 1e-4);
+        assert!((exponential.log_likelihood + 19.573756).abs() < 1e-4);
+
+        let gengamma = fit("gengamma");
+        assert!(gengamma.log_likelihood >= weibull.log_likelihood - 1e-6);
+
+        let missing_upper = survreg(
+            lower.clone(),
+            status.clone(),
+            covariates.clone(),
+            None,
+            None,
+            None,
+            None,
+            Some("weibull"),
+            None,
+            None,
+            None,
+        );
+        assert!(missing_upper.is_err());
+
+        let names: Vec<String> = ["lower", "upper", "status", "left", "right", "x"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let rows: Vec<Vec<f64>> = (0..lower.len())
+            .map(|i| {
+                let (left, right) = match status[i] {
+                    0.0 => (lower[i], f64::NAN),
+                    1.0 => (lower[i], lower[i]),
+                    2.0 => (f64::NAN, lower[i]),
+                    _ => (lower[i], upper[i]),
+                };
+                vec![lower[i], upper[i], status[i], left, right, x[i]]
+            })
+            .collect();
+        let data = Array2::from_shape_vec((rows.len(), 6), rows.concat()).unwrap();
+        let formula_fit = |formula: &str| {
+            survreg_formula(
+                formula,
+                data.view(),
+                &names,
+                None,
+                Some("weibull"),
+                Some(100),
+                Some(1e-10),
+                None,
+            )
+        };
+        for formula in [
+            "Surv(lower, upper, status, type = \"interval\") ~ x",
+            "Surv(left, right, type = \"interval2\") ~ x",
+        ] {
+            let fit = formula_fit(formula).unwrap();
+            assert!((fit.coefficients[1] - weibull.coefficients[1]).abs() < 1e-6);
+            assert!((fit.log_likelihood - weibull.log_likelihood).abs() < 1e-6);
+        }
+        assert!(formula_fit("Surv(lower, upper, status) ~ x").is_err());
+        assert!(formula_fit("Surv(left, right, status, type = \"interval2\") ~ x").is_err());
+    }
+
+    #[test]
+    fn test_turnbull_matches_kaplan_meier_for_right_censored_data() {
+        let time = [1.0, 2.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        let status = [1, 1, 0, 1, 0, 1, 1, 0];
+        let upper: Vec<f64> = time
+            .iter()
+            .zip(status)
+            .map(|(&t, d)| if d == 1 { t } else { f64::INFINITY })
+            .collect();
+        let config = TurnbullConfig {
+            n_bootstrap: 0,
+            ..Default::default()
+        };
+        let fit = turnbull(&time, &upper, None, &config).unwrap();
+        assert!(fit.converged);
+        assert_eq!(fit.time, vec![1.0, 2.0, 3.0, 5.0, 6.0, f64::INFINITY]);
+        let expected = [0.875, 0.75, 0.6, 0.4, 0.2, 0.0];
+        for (est, exp) in fit.estimate.iter().zip(expected) {
+            assert!((est - exp).abs() < 1e-6, "{est} vs {exp}");
+        }
+        assert!((fit.log_likelihood + 11.682825).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_turnbull_interval_censored() {
+        let lower = [0.0, 1.0, 2.0, 3.0, 0.0, 4.0, 1.0, 5.0, 2.0, 6.0];
+        let upper = [
+            2.0,
+            3.0,
+            5.0,
+            3.0,
+            1.0,
+            f64::INFINITY,
+            4.0,
+            f64::INFINITY,
+            2.0,
+            8.0,
+        ];
+        let config = TurnbullConfig {
+            n_bootstrap: 50,
+            seed: Some(7),
+            ..Default::default()
+        };
+        let fit = turnbull(&lower, &upper, None, &config).unwrap();
+        assert_eq!(fit.interval_lower, vec![0.0, 2.0, 3.0, 4.0, 6.0]);
+        assert_eq!(fit.time, vec![1.0, 2.0, 3.0, 5.0, 8.0]);
+        let expected = [0.134413, 0.256174, 0.309413, 0.0, 0.3];
+        for (p, exp) in fit.probability.iter().zip(expected) {
+            assert!((p - exp).abs() < 1e-4, "{p} vs {exp}");
+        }
+        assert!((fit.log_likelihood + 11.406698).abs() < 1e-5);
+        for j in 0..fit.time.len() {
+            assert!(fit.std_err[j].is_finite());
+            assert!(0.0 <= fit.conf_lower[j] && fit.conf_lower[j] <= fit.conf_upper[j]);
+            assert!(fit.conf_upper[j] <= 1.0);
+        }
+
+        let again = turnbull(&lower, &upper, None, &config).unwrap();
+        assert_eq!(fit.conf_lower, again.conf_lower);
+        assert!(turnbull(&[2.0], &[1.0], None, &config).is_err());
+    }
+
+    #[test]
+    fn test_coxnet_path_and_cross_validation() {
+        let x1 = [0.5, 1.0, 1.5, 2.0, 0.2, 1.8, 0.7, 0.1, 1.2, 0.9, 0.3, 1.6];
+        let x2 = [1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0];
+        let time = [5.0, 4.0, 3.0, 1.0, 6.0, 2.0, 7.0, 8.0, 2.5, 9.0, 4.5, 3.5];
+        let status = [1, 0, 1, 1, 1, 1, 0, 1, 1, 0, 1, 1];
+        let covariates = Array2::from_shape_fn((12, 2), |(i, j)| [x1[i], x2[i]][j]);
+
+        let path = coxnet(
+            &time,
+            &status,
+            covariates.view(),
+            None,
+            &CoxNetConfig {
+                n_lambda: 20,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(path.lambdas.len(), 20);
+        assert_eq!(path.df[0], 0);
+        assert!(path.df[19] > 0);
+        assert!(path.log_likelihood[19] > path.null_log_likelihood);
+        let eta = path.linear_predictors(covariates.view(), 19).unwrap();
+        let beta = &path.coefficients[19];
+        assert!((eta[0] - (0.5 * beta[0] + beta[1])).abs() < 1e-12);
+        assert!(matches!(
+            path.linear_predictors(Array2::ones((1, 1)).view(), 19),
+            Err(CoxNetError::DimensionMismatch)
+        ));
+
+        let unpenalized = coxnet(
+            &time,
+            &status,
+            covariates.view(),
+            None,
+            &CoxNetConfig {
+                lambdas: Some(vec![1e-9]),
+                tol: 1e-12,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut cox = CoxPHModel::new_with_covariate_matrix(
+            covariates.clone(),
+            time.to_vec(),
+            status.iter().map(|&s| s as u8).collect(),
+            None,
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        cox.fit(50).unwrap();
+        let expected = &cox.coefficients()[0];
+        assert!((expected[0] - 1.840710).abs() < 1e-4);
+        for (b, e) in unpenalized.coefficients[0].iter().zip(expected) {
+            assert!((b - e).abs() < 1e-4, "{b} vs {e}");
+        }
+
+        let cv = cv_coxnet(
+            &time,
+            &status,
+            covariates.view(),
+            None,
+            &CoxNetConfig {
+                n_lambda: 20,
+                alpha: 0.5,
+                ..Default::default()
+            },
+            &CVConfig {
+                n_folds: 3,
+                shuffle: true,
+                seed: Some(3),
+            },
+        )
+        .unwrap();
+        assert_eq!(cv.cv_mean.len(), 20);
+        assert!(cv.lambda_1se >= cv.lambda_min);
+        assert!(cv.cv_mean.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_cox_zph_matches_grambsch_therneau() {
+        let covariates = vec![
+            vec![0.5, 1.0],
+            vec![1.2, 0.0],
+            vec![0.3, 1.0],
+            vec![0.9, 1.0],
+            vec![-0.4, 0.0],
+            vec![0.1, 1.0],
+            vec![-0.8, 0.0],
+            vec![0.6, 0.0],
+            vec![-1.1, 1.0],
+            vec![-0.2, 0.0],
+            vec![-0.5, 1.0],
+            vec![-1.3, 0.0],
+        ];
+        let times = vec![
+            2.0, 3.0, 5.0, 7.0, 8.0, 10.0, 12.0, 13.0, 15.0, 17.0, 19.0, 22.0,
+        ];
+        let status = vec![1, 1, 0, 1, 1, 0, 1, 1, 1, 0, 1, 1];
+        let mut model = CoxPHModel::new_with_data(covariates, times, status);
+        model.fit(50).unwrap();
+
+        let identity = cox_zph(&model, &ZphTransform::Identity).unwrap();
+        assert!((identity.chi2_values[0] - 0.620262).abs() < 1e-3);
+        assert!((identity.chi2_values[1] - 0.160076).abs() < 1e-3);
+        assert!((identity.global_chi2 - 0.780802).abs() < 1e-3);
+        assert_eq!(identity.global_df, 2);
+        assert!((identity.scaled_residuals[0][0] - 1.461038).abs() < 1e-3);
+        assert!((identity.scaled_residuals[0][1] - 2.588955).abs() < 1e-3);
+
+        let km = cox_zph(&model, &ZphTransform::KaplanMeier).unwrap();
+        assert!((km.chi2_values[0] - 0.515607).abs() < 1e-3);
+        assert!((km.p_values[1] - 0.728405).abs() < 1e-3);
+        assert!((km.transformed_times[1] - 1.0 / 12.0).abs() < 1e-10);
+
+        // Ranks of the nine event times only, not of all twelve times.
+        let rank = cox_zph(&model, &ZphTransform::Rank).unwrap();
+        assert!((rank.chi2_values[0] - 0.552413).abs() < 1e-3);
+        assert!((rank.chi2_values[1] - 0.079613).abs() < 1e-3);
+        assert!((rank.global_chi2 - 0.617699).abs() < 1e-3);
+        assert_eq!(
+            rank.transformed_times,
+            (1..=9).map(f64::from).collect::<Vec<_>>()
+        );
+
+        assert!(cox_zph(&CoxPHModel::new(), &ZphTransform::Rank).is_err());
+    }
+
+    #[test]
+    fn test_zph2_matches_zph1_on_split_intervals() {
+        let x = [0.5, 1.2, 0.3, 0.9, -0.4, 0.1, -0.8, 0.6];
+        let time = [2.0, 3.0, 5.0, 5.0, 8.0, 10.0, 12.0, 13.0];
+        let status = [1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0];
+        let strata = [0, 1, 0, 1, 0, 1, 0, 1];
+        let eta: Vec<f64> = x.iter().map(|v| 0.7 * v).collect();
+        let gt: Vec<f64> = time.iter().map(|t| t - 6.0).collect();
+        let order = |key: &[f64]| -> Array1<usize> {
+            let mut order: Vec<usize> = (0..key.len()).collect();
+            order.sort_by(|&i, &j| strata[i].cmp(&strata[j]).then(key[i].total_cmp(&key[j])));
+            Array1::from(order)
+        };
+        let covar = Array2::from_shape_vec((8, 1), x.to_vec()).unwrap();
+        let weights = Array1::from_elem(8, 1.0);
+        let strata_view = Array1::from(strata.to_vec());
+
+        let mut start = Vec::new();
+        let mut stop = Vec::new();
+        let mut split_status = Vec::new();
+        let mut rows = Vec::new();
+        for i in 0..8 {
+            if time[i] > 4.0 {
+                start.extend([0.0, 4.0]);
+                stop.extend([4.0, time[i]]);
+                split_status.extend([0.0, status[i]]);
+                rows.extend([i, i]);
+            } else {
+                start.push(0.0);
+                stop.push(time[i]);
+                split_status.push(status[i]);
+                rows.push(i);
+            }
+        }
+        let split_gt: Vec<f64> = rows.iter().map(|&i| gt[i]).collect();
+        let split_covar = Array2::from_shape_fn((rows.len(), 1), |(r, _)| x[rows[r]]);
+        let split_strata: Array1<i32> = rows.iter().map(|&i| strata[i]).collect();
+        let split_eta: Array1<f64> = rows.iter().map(|&i| eta[i]).collect();
+        let split_sort = |key: &[f64]| -> Array1<usize> {
+            let mut order: Vec<usize> = (0..key.len()).collect();
+            order.sort_by(|&i, &j| {
+                split_strata[i]
+                    .cmp(&split_strata[j])
+                    .then(key[i].total_cmp(&key[j]))
+            });
+            Array1::from(order)
+        };
+
+        for method in [0, 1] {
+            let right = zph1(
+                ArrayView1::from(&gt),
+                (ArrayView1::from(&time), ArrayView1::from(&status)),
+                covar.view(),
+                ArrayView1::from(&eta),
+                weights.view(),
+                strata_view.view(),
+                method,
+                order(&time).view(),
+            );
+            let counting = zph2(
+                ArrayView1::from(&split_gt),
+                (
+                    ArrayView1::from(&start),
+                    ArrayView1::from(&stop),
+                    ArrayView1::from(&split_status),
+                ),
+                split_covar.view(),
+                split_eta.view(),
+                Array1::from_elem(rows.len(), 1.0).view(),
+                split_strata.view(),
+                method,
+                split_sort(&start).view(),
+                split_sort(&stop).view(),
+            );
+            for (a, b) in right.u.iter().zip(counting.u.iter()) {
+                assert!((a - b).abs() < 1e-10, "method {method}: u {a} vs {b}");
+            }
+            for (a, b) in right.imat.iter().zip(counting.imat.iter()) {
+                assert!((a - b).abs() < 1e-10, "method {method}: imat {a} vs {b}");
+            }
+            let total: f64 = right.schoen.iter().sum();
+            assert!((total - right.u[0]).abs() < 1e-10);
+            assert_eq!(right.schoen.nrows(), 6);
+            for (a, b) in right.schoen.iter().zip(counting.schoen.iter()) {
+                assert!((a - b).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fitted_models_round_trip_through_json_and_binary() {
+        let covariates = vec![
+            vec![0.5, 1.0],
+            vec![1.0, 0.0],
+            vec![0.2, 0.0],
+            vec![2.0, 1.0],
+            vec![1.5, 1.0],
+            vec![1.8, 0.0],
+            vec![0.9, 0.0],
+            vec![1.2, 1.0],
+        ];
+        let times = vec![5.0, 4.0, 3.0, 1.0, 6.0, 2.0, 7.0, 3.5];
+        let status = vec![1, 0, 1, 1, 1, 1, 0, 1];
+        let mut model = CoxPHModel::new_with_options(
+            covariates.clone(),
+            times,
+            status,
+            Some(vec![0, 1, 0, 1, 0, 1, 0, 1]),
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        model.fit(20).unwrap();
+
+        let from_json = CoxPHModel::from_json(&model.to_json().unwrap()).unwrap();
+        let from_bytes = CoxPHModel::from_bytes(&model.to_bytes().unwrap()).unwrap();
+        for restored in [&from_json, &from_bytes] {
+            assert_eq!(restored.coefficients(), model.coefficients());
+            assert_eq!(restored.naive_var(), model.naive_var());
+            assert_eq!(restored.strata, model.strata);
+            assert_eq!(restored.method, CoxMethod::Breslow);
+            assert_eq!(restored.baseline_hazards().len(), 2);
+            for (a, b) in restored
+                .baseline_hazards()
+                .iter()
+                .zip(model.baseline_hazards())
+            {
+                assert_eq!(a.time, b.time);
+                assert_eq!(a.cumulative_hazard, b.cumulative_hazard);
+            }
+            assert_eq!(
+                restored.predict(covariates.clone()),
+                model.predict(covariates.clone())
+            );
+        }
+
+        let path = std::env::temp_dir().join("survival_persist_test.bin");
+        let fit = survreg_intercept_only("weibull");
+        fit.save(&path, ModelFormat::Binary).unwrap();
+        let loaded = SurvivalFit::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.coefficients, fit.coefficients);
+        assert_eq!(loaded.variance_matrix, fit.variance_matrix);
+        assert_eq!(loaded.distribution, DistributionType::Weibull);
+
+        assert!(matches!(
+            SurvivalFit::from_json(&model.to_json().unwrap()),
+            Err(PersistError::WrongModelType { .. })
+        ));
+        assert!(ModelFormat::parse("yaml").is_err());
+    }
+
+    #[test]
+    fn test_matrix_entry_points_match_row_api() {
+        let covariates = vec![
+            vec![0.5, 1.0],
+            vec![1.0, 0.0],
+            vec![1.5, 1.0],
+            vec![2.0, 0.0],
+            vec![0.2, 1.0],
+            vec![1.8, 0.0],
+            vec![0.9, 1.0],
+            vec![1.2, 0.0],
+        ];
+        let times = vec![5.0, 4.0, 3.0, 1.0, 6.0, 2.0, 7.0, 3.5];
+        let status = vec![1, 0, 1, 1, 1, 1, 0, 1];
+        let column_major = ndarray::Array2::from_shape_fn((8, 2), |(i, j)| covariates[i][j])
+            .reversed_axes()
+            .as_standard_layout()
+            .reversed_axes()
+            .to_owned();
+        assert!(!column_major.is_standard_layout());
+
+        let mut rows = CoxPHModel::new_with_data(covariates.clone(), times.clone(), status.clone());
+        rows.fit(20).unwrap();
+        let mut matrix = CoxPHModel::new_with_covariate_matrix(
+            column_major.clone(),
+            times,
+            status,
+            None,
+            None,
+            None,
+            CoxMethod::default(),
+        );
+        matrix.fit(20).unwrap();
+        assert_eq!(rows.coefficients(), matrix.coefficients());
+        assert_eq!(
+            rows.predict(covariates.clone()),
+            matrix.predict_matrix(column_major.view())
+        );
+
+        let (curve_times, curves) = rows.survival_curve(covariates.clone(), None, None).unwrap();
+        let (matrix_times, matrix_curves) = matrix
+            .survival_curve_matrix(column_major.view(), None, None)
+            .unwrap();
+        assert_eq!(curve_times, matrix_times);
+        assert_eq!(matrix_curves.dim(), (8, curve_times.len()));
+        for (row, curve) in matrix_curves.outer_iter().zip(&curves) {
+            assert_eq!(row.to_vec(), *curve);
+        }
+        assert!(
+            matrix
+                .cumulative_hazard_matrix(ndarray::Array2::zeros((1, 3)).view(), None)
+                .is_err()
+        );
+
+        let fit = survreg_intercept_only("weibull");
+        let ones = ndarray::Array2::ones((3, 1));
+        let eta = fit.linear_predictors_matrix(ones.view()).unwrap();
+        assert_eq!(eta, fit.linear_predictors(&vec![vec![1.0]; 3]).unwrap());
+    }
+
+    #[test]
+    fn test_parallel_results_do_not_depend_on_thread_count() {
+        let n = 60;
+        let covariates = ndarray::Array2::from_shape_fn((n, 2), |(i, j)| {
+            if j == 0 {
+                ((i * 7) % 11) as f64 / 10.0
+            } else {
+                (i % 2) as f64
+            }
+        });
+        let times: Vec<f64> = (0..n).map(|i| 1.0 + ((i * 13) % 17) as f64).collect();
+        let status: Vec<u8> = (0..n).map(|i| u8::from(i % 4 != 0)).collect();
+        let strata: Vec<usize> = (0..n).map(|i| i % 3).collect();
+
+        let run = |threads: usize| {
+            set_num_threads(Some(threads)).unwrap();
+            assert_eq!(num_threads(), threads);
+            install(|| {
+                let mut model = CoxPHModel::new_with_covariate_matrix(
+                    covariates.clone(),
+                    times.clone(),
+                    status.clone(),
+                    Some(strata.clone()),
+                    None,
+                    None,
+                    CoxMethod::Efron,
+                );
+                model.fit(20).unwrap();
+                (model.coefficients(), model.standard_errors(false))
+            })
+        };
+        let serial = run(1);
+        let parallel = run(4);
+        set_num_threads(None).unwrap();
+
+        let bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+        assert_eq!(serial.0.len(), parallel.0.len());
+        for (a, b) in serial.0.iter().zip(&parallel.0) {
+            assert_eq!(bits(a), bits(b));
+        }
+        assert_eq!(bits(&serial.1), bits(&parallel.1));
+    }
+
+    #[test]
+    fn test_bootstrap_cox_stratified_cluster_intervals() {
+        let n = 60;
+        let x: Vec<f64> = (0..n).map(|i| ((i * 7) % 11) as f64 / 10.0).collect();
+        let time: Vec<f64> = (0..n)
+            .map(|i| 1.0 + ((i * 13) % 17) as f64 + x[i])
+            .collect();
+        let status: Vec<i32> = (0..n).map(|i| i32::from(i % 4 != 0)).collect();
+        let strata: Vec<usize> = (0..n).map(|i| i / 20).collect();
+        let cluster: Vec<usize> = (0..n).map(|i| i / 2).collect();
+
+        let resampler = Resampler::new(n, Some(&strata), Some(&cluster)).unwrap();
+        let rows = resampler.replicate(11, 3);
+        for s in 0..3 {
+            assert_eq!(rows.iter().filter(|&&i| strata[i] == s).count(), 20);
+        }
+        assert_eq!(rows, resampler.replicate(11, 3));
+        assert_ne!(rows, resampler.replicate(11, 4));
+        assert!(Resampler::new(4, Some(&[0, 1, 1, 1]), Some(&[0, 0, 1, 2])).is_err());
+
+        let covariates = ndarray::Array2::from_shape_vec((1, n), x.clone()).unwrap();
+        let mut reference = CoxPHModel::new_with_covariate_matrix(
+            ndarray::Array2::from_shape_vec((n, 1), x).unwrap(),
+            time.clone(),
+            status.iter().map(|&d| d as u8).collect(),
+            None,
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        reference.fit(25).unwrap();
+
+        for method in [
+            BootstrapCiMethod::Percentile,
+            BootstrapCiMethod::Bca,
+            BootstrapCiMethod::PercentileT,
+        ] {
+            let config = BootstrapConfig {
+                n_bootstrap: 200,
+                seed: Some(3),
+                ci_method: method,
+                strata: Some(strata.clone()),
+                cluster: Some(cluster.clone()),
+                ..Default::default()
+            };
+            let result = bootstrap_cox(&time, &status, &covariates, None, &config).unwrap();
+            let beta = result.coefficients[0];
+            assert!((beta - reference.coefficients()[0][0]).abs() < 1e-4);
+            assert!(result.ci_lower[0] < beta && beta < result.ci_upper[0]);
+            assert!(result.std_errors[0] > 0.0);
+            assert_eq!(result.ci_method, method.name());
+
+            let again = bootstrap_cox(&time, &status, &covariates, None, &config).unwrap();
+            assert_eq!(result.bootstrap_samples, again.bootstrap_samples);
+            assert_eq!(result.ci_lower, again.ci_lower);
+        }
+
+        // Dropping the only row with x = 1 leaves a singular refit, which
+        // must fail BCa rather than shrink the jackknife.
+        let time: Vec<f64> = (1..=12).map(f64::from).collect();
+        let status = vec![1; 12];
+        let mut x = vec![0.0; 12];
+        x[3] = 1.0;
+        let covariates = ndarray::Array2::from_shape_vec((1, 12), x).unwrap();
+        let config = BootstrapConfig {
+            n_bootstrap: 50,
+            ci_method: BootstrapCiMethod::Bca,
+            ..Default::default()
+        };
+        let err = bootstrap_cox(&time, &status, &covariates, None, &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "BCa jackknife refit failed for 1 of 12 units"
+        );
+
+        assert_eq!(BootstrapCiMethod::parse("BCa"), Ok(BootstrapCiMethod::Bca));
+        assert!(BootstrapCiMethod::parse("normal").is_err());
+    }
+
+    #[test]
+    fn test_multistate_fit_competing_risks() {
+        let n = 10;
+        let id: Vec<usize> = (0..n).collect();
+        let tstart = vec![0.0; n];
+        let tstop: Vec<f64> = (1..=n).map(|i| i as f64).collect();
+        let from = vec!["alive"; n];
+        let to: Vec<&str> = (0..n)
+            .map(|i| if i % 3 == 0 { "relapse" } else { "death" })
+            .collect();
+        let group: Vec<usize> = (0..n).map(|i| i % 2).collect();
+
+        let fit = multistate_fit(&id, &tstart, &tstop, &from, &to, None, None).unwrap();
+        assert_eq!(fit.states, vec!["alive", "relapse", "death"]);
+        assert_eq!(fit.transitions.len(), 2);
+        let (p, se) = fit.probability("relapse", None).unwrap();
+        assert!((p[n - 1] - 0.4).abs() < 1e-12);
+        assert!((se[n - 1] - (0.4_f64 * 0.6 / n as f64).sqrt()).abs() < 1e-12);
+        let (alive, _) = fit.probability("alive", None).unwrap();
+        assert!(alive[n - 1].abs() < 1e-12);
+        let (chaz, _) = fit.hazard("alive", "death", None).unwrap();
+        assert!(chaz[0].abs() < 1e-12 && (chaz[1] - 1.0 / 9.0).abs() < 1e-12);
+
+        let grouped = multistate_fit(&id, &tstart, &tstop, &from, &to, None, Some(&group)).unwrap();
+        assert_eq!(grouped.curves.len(), 2);
+        assert!(grouped.probability("death", Some(1)).is_some());
+        assert!(grouped.probability("death", None).is_none());
+
+        let chained = multistate_fit(
+            &[0, 0],
+            &[0.0, 1.0],
+            &[1.0, 2.0],
+            &["alive", "relapse"],
+            &["alive", "death"],
+            None,
+            None,
+        );
+        assert!(matches!(
+            chained,
+            Err(MultiStateError::StateMismatch { id: 0, .. })
+        ));
+        let overlap = multistate_fit(
+            &[0, 0],
+            &[0.0, 0.5],
+            &[1.0, 2.0],
+            &["alive", "alive"],
+            &["alive", "death"],
+            None,
+            None,
+        );
+        assert!(matches!(overlap, Err(MultiStateError::Overlap(0))));
+        let censored = vec!["alive"; n];
+        assert!(matches!(
+            multistate_fit(&id, &tstart, &tstop, &from, &censored, None, None),
+            Err(MultiStateError::NoTransitions)
+        ));
+    }
+
+    #[test]
+    fn test_multistate_coxph_illness_death() {
+        let id = vec![0, 0, 1, 2, 2, 3, 4, 5, 5, 6, 7, 7, 8, 9, 10, 11, 11];
+        let tstart = vec![
+            0.0, 2.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 5.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 4.0,
+        ];
+        let tstop = vec![
+            2.0, 6.0, 4.0, 3.0, 8.0, 7.0, 5.0, 5.0, 9.0, 6.0, 1.0, 7.0, 9.0, 2.5, 8.0, 4.0, 10.0,
+        ];
+        let from = vec![
+            "healthy", "ill", "healthy", "healthy", "ill", "healthy", "healthy", "healthy", "ill",
+            "healthy", "healthy", "ill", "healthy", "healthy", "healthy", "healthy", "ill",
+        ];
+        let to = vec![
+            "ill", "dead", "dead", "ill", "ill", "healthy", "dead", "ill", "dead", "dead", "ill",
+            "dead", "healthy", "dead", "ill", "ill", "ill",
+        ];
+        let x = [
+            0.5, 1.2, 0.1, 0.8, -0.4, 1.5, -0.9, 0.3, 0.7, -1.1, 0.0, -0.6,
+        ];
+        let covariates = Array2::from_shape_fn((id.len(), 1), |(r, _)| x[id[r]]);
+
+        let fit = multistate_coxph(
+            &id,
+            &tstart,
+            &tstop,
+            &from,
+            &to,
+            covariates.view(),
+            None,
+            None,
+            CoxMethod::Efron,
+            20,
+        )
+        .unwrap();
+        assert!(fit.converged);
+        assert!(fit.iterations > 0 && fit.iterations <= 20);
+        assert_eq!(fit.states, vec!["healthy", "ill", "dead"]);
+        assert_eq!(fit.transitions.len(), 3);
+        assert_eq!(fit.coefficient_names[0], "x1:healthy->ill");
+        assert_eq!(fit.n_events, vec![6, 4, 3]);
+
+        let rows: Vec<usize> = (0..id.len()).filter(|&r| from[r] == "healthy").collect();
+        let mut single = CoxPHModel::new_with_options(
+            rows.iter().map(|&r| covariates.row(r).to_vec()).collect(),
+            rows.iter().map(|&r| tstop[r]).collect(),
+            rows.iter().map(|&r| u8::from(to[r] == "ill")).collect(),
+            None,
+            None,
+            None,
+            CoxMethod::Efron,
+        );
+        single.entry_times = Some(rows.iter().map(|&r| tstart[r]).collect());
+        single.fit(20).unwrap();
+        assert!((fit.coefficients[0] - single.coefficients()[0][0]).abs() < 1e-8);
+        assert_eq!(fit.transition_coefficients[0], vec![fit.coefficients[0]]);
+
+        let (times, pstate) = fit.predict(&[0.5], "healthy").unwrap();
+        assert_eq!(times.len(), pstate.len());
+        for (prev, row) in pstate.iter().zip(pstate.iter().skip(1)) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert!(row[0] <= prev[0] + 1e-12 && row[2] >= prev[2] - 1e-12);
+        }
+        let (_, from_ill) = fit.predict(&[0.5], "ill").unwrap();
+        assert!(from_ill.iter().all(|row| row[0] == 0.0));
+
+        let shared = multistate_coxph(
+            &id,
+            &tstart,
+            &tstop,
+            &from,
+            &to,
+            covariates.view(),
+            Some(&[true]),
+            None,
+            CoxMethod::Breslow,
+            20,
+        )
+        .unwrap();
+        assert_eq!(shared.coefficient_names, vec!["x1"]);
+        assert!(
+            shared
+                .transition_coefficients
+                .iter()
+                .all(|row| row[0] == shared.coefficients[0])
+        );
+        assert!(matches!(
+            shared.predict(&[0.5], "cured"),
+            Err(MultiStateCoxError::UnknownState(_))
+        ));
+
+        let truncated = multistate_coxph(
+            &id,
+            &tstart,
+            &tstop,
+            &from,
+            &to,
+            covariates.view(),
+            None,
+            None,
+            CoxMethod::Efron,
+            1,
+        )
+        .unwrap();
+        assert!(!truncated.converged);
+        assert_eq!(truncated.iterations, 1);
+    }
+
+    #[test]
+    fn test_clogit_conditional_likelihood() {
+        let rows = [
+            (0, 1, 0.5, 1.0),
+            (0, 0, 1.2, 0.0),
+            (0, 0, -0.3, 1.0),
+            (1, 1, 1.5, 0.0),
+            (1, 0, 0.2, 1.0),
+            (1, 0, 0.9, 0.0),
+            (2, 1, 0.1, 1.0),
+            (2, 0, 0.7, 0.0),
+            (2, 0, -0.8, 1.0),
+            (300, 1, 1.1, 1.0),
+            (300, 1, -0.2, 0.0),
+            (300, 0, 0.4, 1.0),
+            (300, 0, -1.0, 0.0),
+            (300, 0, 0.6, 1.0),
+            (301, 1, 0.3, 0.0),
+            (301, 0, -0.5, 1.0),
+            (301, 0, 1.4, 0.0),
+            (301, 0, 0.0, 0.0),
+            (302, 0, 0.8, 1.0),
+            (302, 0, 0.2, 0.0),
+        ];
+        let mut data = ClogitDataSet::new();
+        for (stratum, case, x1, x2) in rows {
+            data.add_observation(case, stratum, vec![x1, x2]);
+        }
+
+        let mut exact = ConditionalLogisticRegression::new(data.clone(), 50, 1e-10);
+        exact.fit().unwrap();
+        assert!(exact.converged);
+        assert!((exact.coefficients[0] - 0.613141).abs() < 1e-5);
+        assert!((exact.coefficients[1] - 0.135020).abs() < 1e-5);
+        let se = exact.std_errors();
+        assert!((se[0] - 0.727625).abs() < 1e-4);
+        assert!((se[1] - 0.996490).abs() < 1e-4);
+        assert!((exact.log_likelihood + 6.599711).abs() < 1e-6);
+        assert!((exact.null_log_likelihood + 6.984716).abs() < 1e-6);
+        let lrt = exact.likelihood_ratio_test();
+        assert!((lrt.statistic - 2.0 * (6.984716 - 6.599711)).abs() < 1e-5);
+        assert_eq!(lrt.df, 2);
+        assert!(exact.wald_test().statistic > 0.0 && exact.score_test().statistic > 0.0);
+
+        for (method, beta, loglik) in [
+            (ClogitMethod::Breslow, [0.541652, 0.049341], -7.557844),
+            (ClogitMethod::Efron, [0.571424, 0.038969], -7.297701),
+        ] {
+            let mut fit =
+                ConditionalLogisticRegression::with_method(data.clone(), 50, 1e-10, method);
+            fit.fit().unwrap();
+            assert!((fit.coefficients[0] - beta[0]).abs() < 1e-5);
+            assert!((fit.coefficients[1] - beta[1]).abs() < 1e-5);
+            assert!((fit.log_likelihood - loglik).abs() < 1e-6);
+        }
+
+        let mut large = ClogitDataSet::new();
+        for i in 0..40 {
+            large.add_observation(u8::from(i % 2 == 0), 1000, vec![i as f64 / 10.0]);
+        }
+        let mut too_large = ConditionalLogisticRegression::new(large, 50, 1e-8);
+        assert!(matches!(
+            too_large.fit(),
+            Err(ClogitError::ExactTooLarge { stratum: 1000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_coxph_coefficient_table_and_global_tests() {
+        let group = [1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        let times: Vec<f64> = (1..=12).map(f64::from).collect();
+        let status = vec![1, 1, 0, 1, 1, 1, 0, 1, 1, 0, 1, 1];
+        let mut model = CoxPHModel::new_with_data(
+            group.iter().map(|&g| vec![g]).collect(),
+            times,
+            status.clone(),
+        );
+        assert!(!model.converged());
+        model.fit(20).unwrap();
+        assert!(model.converged());
+        assert!(model.iterations().unwrap() > 0);
+
+        let null: f64 = -(0..12)
+            .filter(|&i| status[i] == 1)
+            .map(|i| ((12 - i) as f64).ln())
+            .sum::<f64>();
+        assert!((model.null_log_likelihood().unwrap() - null).abs() < 1e-10);
+        let lrt = model.likelihood_ratio_test();
+        assert!((lrt.statistic - 2.0 * (model.log_likelihood() - null)).abs() < 1e-10);
+        assert_eq!(lrt.df, 1);
+        let score = model.score_test();
+        assert!((score.statistic - 0.0804215).abs() < 1e-6);
+        assert!(score.p_value > 0.7 && score.p_value < 0.8);
+
+        let table = model.coefficient_table(0.95, false);
+        let row = &table[0];
+        let se = model.standard_errors(false)[0];
+        assert_eq!(row.name, "var0");
+        assert!((row.se - se).abs() < 1e-12);
+        assert!((row.z - row.coef / se).abs() < 1e-12);
+        assert!((row.lower - (row.coef - 1.959964 * se).exp()).abs() < 1e-5);
+        assert!((row.upper - (row.coef + 1.959964 * se).exp()).abs() < 1e-5);
+        assert!((row.p_value - model.wald_test(false).p_value).abs() < 1e-6);
+        assert!(model.summary().contains("ScoreTest"));
+    }
+
+    #[test]
+    fn test_coxph_brier_score_matches_survival_brier() {
+        let times = vec![
+            1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0,
+        ];
+        let status = vec![1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0];
+        let x = [
+            0.5, 1.2, -0.3, 0.8, 0.1, 1.5, -1.0, 0.4, -0.6, 1.1, -0.2, 0.9, 0.0, -0.8,
+        ];
+        let strata: Vec<usize> = (0..14).map(|i| i % 2).collect();
+        let offsets: Vec<f64> = (0..14).map(|i| 0.1 * f64::from(i % 3)).collect();
+        let mut model = CoxPHModel::new_with_options(
+            x.iter().map(|&v| vec![v]).collect(),
+            times.clone(),
+            status.clone(),
+            Some(strata.clone()),
+            None,
+            Some(offsets.clone()),
+            CoxMethod::Efron,
+        );
+        model.fit(30).unwrap();
+
+        let result = model.brier_score(None).unwrap();
+        assert_eq!(result.times, vec![1.0, 2.0, 4.0, 5.0, 7.0, 8.0, 10.0]);
+        let covariates = Array2::from_shape_fn((14, 1), |(i, _)| x[i]);
+        let (_, curves) = model
+            .survival_curve_matrix(covariates.view(), Some(result.times.clone()), Some(strata))
+            .unwrap();
+        let mut predictions = curves;
+        for (mut row, offset) in predictions.outer_iter_mut().zip(&offsets) {
+            row.mapv_inplace(|s| s.powf(offset.exp()));
+        }
+        let status: Vec<i32> = status.iter().map(|&s| i32::from(s)).collect();
+        let expected = survival_brier(&times, &status, predictions.view(), &result.times).unwrap();
+        for (a, b) in result.brier.iter().zip(&expected.brier) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert!((result.integrated_brier - expected.integrated_brier).abs() < 1e-12);
+        assert!((result.integrated_ipa - expected.integrated_ipa).abs() < 1e-12);
+        assert!(result.brier.iter().all(|b| (0.0..=1.0).contains(b)));
+
+        let at = model.brier_score(Some(vec![3.0, 6.0])).unwrap();
+        assert_eq!(at.times, vec![3.0, 6.0]);
+        assert!(matches!(
+            model.brier_score(Some(vec![6.0, 3.0])),
+            Err(CoxPHError::Brier(_))
+        ));
+        model.entry_times = Some(vec![0.0; 14]);
+        model.fit(30).unwrap();
+        assert!(matches!(
+            model.brier_score(None),
+            Err(CoxPHError::CountingProcess(_))
+        ));
+        assert!(matches!(
+            CoxPHModel::new().brier_score(None),
+            Err(CoxPHError::NotFitted)
+        ));
+    }
+
+    #[test]
+    fn test_coxph_predict_survival_standard_errors() {
+        let times = [
+            1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0,
+        ];
+        let status = vec![1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0];
+        let x = [
+            0.5, 1.2, -0.3, 0.8, 0.1, 1.5, -1.0, 0.4, -0.6, 1.1, -0.2, 0.9, 0.0, -0.8,
+        ];
+        let mut model =
+            CoxPHModel::new_with_data(x.iter().map(|&v| vec![v]).collect(), times.to_vec(), status);
+        model.fit(30).unwrap();
+        assert!((model.coefficients()[0][0] - 0.809637).abs() < 1e-5);
+
+        let newdata = ndarray::Array2::from_shape_vec((2, 1), vec![0.5, -1.0]).unwrap();
+        let at = vec![0.5, 2.5, 4.0, 7.0];
+        let log = model
+            .predict_survival(
+                newdata.view(),
+                Some(at.clone()),
+                None,
+                true,
+                ConfType::Log,
+                0.95,
+            )
+            .unwrap();
+        let expected = [
+            [
+                (0.23727377, 0.13916484),
+                (0.47583807, 0.22055877),
+                (0.86243938, 0.35350623),
+            ],
+            [
+                (0.07043986, 0.07298944),
+                (0.14126285, 0.13447791),
+                (0.25603383, 0.23198305),
+            ],
+        ];
+        for (pred, rows) in log.iter().zip(&expected) {
+            let se = pred.std_err.as_ref().unwrap();
+            assert_eq!(pred.cumulative_hazard[0], 0.0);
+            assert_eq!(se[0], 0.0);
+            for (j, &(h, e)) in rows.iter().enumerate() {
+                assert!((pred.cumulative_hazard[j + 1] - h).abs() < 1e-6);
+                assert!((se[j + 1] - e).abs() < 1e-6);
+                assert!((pred.survival[j + 1] - (-h).exp()).abs() < 1e-6);
+            }
+        }
+        let (_, curves) = model
+            .survival_curve_matrix(newdata.view(), Some(at.clone()), None)
+            .unwrap();
+        for (pred, curve) in log.iter().zip(curves.outer_iter()) {
+            for (a, b) in pred.survival.iter().zip(curve) {
+                assert!((a - b).abs() < 1e-10);
+            }
+        }
+        let lower = log[0].lower.as_ref().unwrap();
+        let upper = log[0].upper.as_ref().unwrap();
+        assert!((lower[2] - 0.40327954).abs() < 1e-6);
+        assert!((upper[2] - 0.95738389).abs() < 1e-6);
+        assert_eq!(upper[1], 1.0);
+
+        let loglog = model
+            .predict_survival(
+                newdata.view(),
+                Some(at.clone()),
+                None,
+                true,
+                ConfType::LogLog,
+                0.95,
+            )
+            .unwrap();
+        assert!((loglog[0].lower.as_ref().unwrap()[2] - 0.30717598).abs() < 1e-6);
+        assert!((loglog[0].upper.as_ref().unwrap()[2] - 0.82544837).abs() < 1e-6);
+        let plain = model
+            .predict_survival(
+                newdata.view(),
+                Some(at),
+                None,
+                true,
+                ConfType::parse("plain").unwrap(),
+                0.95,
+            )
+            .unwrap();
+        assert!((plain[0].lower.as_ref().unwrap()[3] - 0.12965356).abs() < 1e-6);
+        assert!((plain[0].upper.as_ref().unwrap()[3] - 0.71460861).abs() < 1e-6);
+
+        let point = model
+            .predict_survival(newdata.view(), None, None, false, ConfType::Log, 0.95)
+            .unwrap();
+        assert!(point[0].std_err.is_none() && point[0].lower.is_none());
+        assert!(matches!(
+            ConfType::parse("logit"),
+            Err(CoxPHError::InvalidConfType(_))
+        ));
+
+        let mut delayed = CoxPHModel::new_with_options(
+            x.iter().map(|&v| vec![v]).collect(),
+            times.iter().map(|t| t + 1.0).collect(),
+            vec![1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 1],
+            Some((0..14).map(|i| i % 2).collect()),
+            None,
+            None,
+            CoxMethod::Breslow,
+        );
+        delayed.entry_times = Some((0..14).map(|i| f64::from(i % 3)).collect());
+        delayed.fit(30).unwrap();
+        let strata = vec![0, 1];
+        let preds = delayed
+            .predict_survival(
+                newdata.view(),
+                None,
+                Some(strata.clone()),
+                true,
+                ConfType::Log,
+                0.95,
+            )
+            .unwrap();
+        let (_, curves) = delayed
+            .survival_curve_matrix(newdata.view(), None, Some(strata))
+            .unwrap();
+        for (pred, curve) in preds.iter().zip(curves.outer_iter()) {
+            for (a, b) in pred.survival.iter().zip(curve) {
+                assert!((a - b).abs() < 1e-10);
+            }
+            assert!(pred.std_err.as_ref().unwrap().iter().all(|e| e.is_finite()));
+        }
+    }
+
+    #[test]
+    fn test_coxph_time_transform_expansion() {
+        let times = vec![
+            1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0,
+        ];
+        let status = vec![1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0];
+        let x = [
+            0.5, 1.2, -0.3, 0.8, 0.1, 1.5, -1.0, 0.4, -0.6, 1.1, -0.2, 0.9, 0.0, -0.8,
+        ];
+        let base = CoxPHModel::new_with_data(x.iter().map(|&v| vec![v]).collect(), times, status);
+
+        let fit = |transform: TimeTransform| {
+            let mut model = base.with_time_transforms(&[(0, transform)]).unwrap();
+            model.fit(30).unwrap();
+            model
+        };
+        let log = fit(TimeTransform::Log);
+        assert_eq!(log.covariate_names, vec!["var0", "var0:log(t)"]);
+        assert_eq!(log.n_events(), 9);
+        assert!(log.cluster.is_none());
+        let source = log.source_rows.as_ref().unwrap();
+        assert_eq!(source.len(), log.event_times.len());
+        assert!(source.windows(2).all(|w| w[0] <= w[1]));
+        let beta = &log.coefficients()[0];
+        let se = log.standard_errors(false);
+        assert!((beta[0] - 0.276732).abs() < 1e-5);
+        assert!((beta[1] - 0.448269).abs() < 1e-5);
+        assert!((se[0] - 1.008748).abs() < 1e-5);
+        assert!((se[1] - 0.757004).abs() < 1e-5);
+        assert!((log.log_likelihood() + 16.272150).abs() < 1e-5);
+
+        let custom = fit(TimeTransform::Custom(std::sync::Arc::new(|x, t| {
+            Ok(x.iter().zip(t).map(|(x, t)| x * t.ln()).collect())
+        })));
+        assert_eq!(custom.covariate_names[1], "tt(var0)");
+        assert!((custom.log_likelihood() - log.log_likelihood()).abs() < 1e-10);
+
+        let step = fit(TimeTransform::Step(vec![3.5]));
+        let beta = &step.coefficients()[0];
+        assert!((beta[0] - 0.389459).abs() < 1e-5);
+        assert!((beta[1] - 0.710696).abs() < 1e-5);
+        assert!((step.log_likelihood() + 16.210862).abs() < 1e-5);
+
+        assert!(matches!(
+            base.with_time_transforms(&[(1, TimeTransform::Log)]),
+            Err(CoxPHError::TimeTransform(_))
+        ));
+    }
+
+    #[test]
+    fn test_coxph_frailty_gamma_and_gaussian() {
+        let times = [
+            0.4, 9.1, 17.2, 37.95, 1.76, 12.5, 20.6, 43.05, 3.12, 15.9, 24.0, 4.65, 4.48, 19.3,
+            27.4, 9.75, 5.84, 22.7, 30.8, 14.85, 7.2, 26.1, 5.2, 19.95,
+        ];
+        let status: Vec<u8> = (0..24).map(|i| u8::from(i % 5 != 3)).collect();
+        let covariates = Array2::from_shape_fn((24, 1), |(i, _)| ((i * 7) % 11) as f64 / 5.0 - 1.0);
+        let group: Vec<usize> = (0..24).map(|i| 10 * (i % 4)).collect();
+        let fit = |distribution, theta| {
+            coxph_frailty(
+                covariates.view(),
+                &times,
+                &status,
+                &group,
+                None,
+                None,
+                None,
+                distribution,
+                theta,
+                CoxMethod::Breslow,
+            )
+            .unwrap()
+        };
+
+        // A fixed variance is used as given, and the gamma integrated
+        // likelihood is maximised at the estimated one.
+        let fixed = fit(FrailtyDistribution::Gamma, Some(0.5));
+        assert_eq!(fixed.theta, 0.5);
+        let gamma = fit(FrailtyDistribution::Gamma, None);
+        assert_eq!(gamma.groups, vec![0, 10, 20, 30]);
+        assert!(gamma.converged);
+        let integrated = |theta: f64| {
+            fit(FrailtyDistribution::Gamma, Some(theta))
+                .integrated_log_likelihood
+                .unwrap()
+        };
+        let best = gamma.integrated_log_likelihood.unwrap();
+        assert!(best >= integrated(gamma.theta * 0.8) && best >= integrated(gamma.theta * 1.25));
+        assert!(gamma.df > 1.0 && gamma.df < 5.0);
+
+        // The Gaussian variance solves the REML equation on the diagonal
+        // frailty information.
+        let gaussian = fit(FrailtyDistribution::Gaussian, None);
+        assert!(gaussian.converged);
+        assert!(gaussian.integrated_log_likelihood.is_none());
+        let reml = gaussian
+            .frailties
+            .iter()
+            .zip(&gaussian.frailty_std_errors)
+            .map(|(b, se)| b * b + se * se)
+            .sum::<f64>()
+            / 4.0;
+        assert!((gaussian.theta - reml).abs() < 1e-4);
+        assert!(
+            (gaussian.aic - (-2.0 * gaussian.log_likelihood + 2.0 * gaussian.df)).abs() < 1e-10
+        );
+
+        let frailty_only = coxph_frailty(
+            Array2::zeros((24, 0)).view(),
+            &times,
+            &status,
+            &group,
+            None,
+            None,
+            None,
+            FrailtyDistribution::Gaussian,
+            Some(1.0),
+            CoxMethod::Efron,
+        )
+        .unwrap();
+        assert!(frailty_only.converged && frailty_only.coefficients.is_empty());
+        assert!(frailty_only.frailty_std_errors.iter().all(|se| *se > 0.0));
+
+        assert!(matches!(
+            FrailtyDistribution::parse("weibull"),
+            Err(FrailtyError::UnknownDistribution(_))
+        ));
+        assert!(matches!(
+            coxph_frailty(
+                covariates.view(),
+                &times,
+                &status,
+                &group,
+                None,
+                None,
+                None,
+                FrailtyDistribution::Gamma,
+                Some(0.0),
+                CoxMethod::Efron,
+            ),
+            Err(FrailtyError::InvalidTheta(_))
+        ));
+    }
 }
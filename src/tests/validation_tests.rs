@@ -1,5 +1,7 @@
 #[cfg(test)]
 mod tests {
+    use crate::concordance::concordance_index::{ConcordanceIndexError, concordance_index};
+    use crate::specialized::brier::survival_brier;
     use crate::surv_analysis::nelson_aalen::{nelson_aalen, stratified_km};
     use crate::validation::calibration::{calibration_curve, stratify_risk, time_dependent_auc};
     use crate::validation::landmark::{
@@ -11,6 +13,7 @@ mod tests {
     use crate::validation::rmst::{
         compare_rmst, compute_cumulative_incidence, compute_rmst, compute_survival_quantile,
     };
+    use ndarray::Array2;
 
     const TOLERANCE: f64 = 1e-4;
     const LOOSE_TOLERANCE: f64 = 1e-2;
@@ -425,7 +428,7 @@ mod tests {
         let status = vec![1, 1, 0, 0];
         let risk_score = vec![0.9, 0.8, 0.2, 0.1];
 
-        let result = time_dependent_auc(&time, &status, &risk_score, &[2.5]);
+        let result = time_dependent_auc(&time, &status, &risk_score, &[2.5], None);
 
         assert!(result.auc[0] > 0.8);
     }
@@ -436,11 +439,104 @@ mod tests {
         let status = vec![1, 1, 1, 0, 0, 0];
         let risk_score = vec![0.5, 0.5, 0.5, 0.5, 0.5, 0.5];
 
-        let result = time_dependent_auc(&time, &status, &risk_score, &[3.5]);
+        let result = time_dependent_auc(&time, &status, &risk_score, &[3.5], None);
 
         assert!(approx_eq(result.auc[0], 0.5, 0.1));
     }
 
+    #[test]
+    fn test_td_auc_ipcw_with_standard_errors() {
+        let time: Vec<f64> = (1..=12).map(f64::from).collect();
+        let status = vec![1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0];
+        let risk_score = vec![2.1, 1.4, 1.9, 0.7, 1.6, 1.2, 0.3, 1.0, 0.2, 0.9, 0.4, 0.1];
+
+        let result = time_dependent_auc(&time, &status, &risk_score, &[4.5, 8.5], Some(0.95));
+
+        assert!(approx_eq(result.auc[0], 0.828125, 1e-6));
+        assert!(approx_eq(result.auc[1], 0.953903, 1e-6));
+        assert!(approx_eq(result.std_err[0], 0.150879, 1e-6));
+        assert!(approx_eq(result.std_err[1], 0.057935, 1e-6));
+        assert!(approx_eq(
+            result.conf_lower[1],
+            0.953903 - 1.959964 * 0.057935,
+            1e-5
+        ));
+        assert_eq!(result.conf_upper[1], 1.0);
+    }
+
+    #[test]
+    fn test_concordance_index_uno_with_paired_comparison() {
+        let time = vec![
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 3.0, 6.0,
+        ];
+        let status = vec![1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1];
+        let first = vec![
+            2.1, 1.4, 1.9, 0.7, 1.6, 1.2, 0.3, 1.0, 0.2, 0.9, 0.4, 0.1, 1.0, 1.2,
+        ];
+        let second = vec![
+            1.0, 2.0, 0.5, 1.5, 0.2, 1.2, 0.8, 0.9, 0.3, 1.1, 0.4, 0.6, 1.0, 0.7,
+        ];
+        let scores = Array2::from_shape_fn((14, 2), |(i, k)| [&first, &second][k][i]);
+
+        let full = concordance_index(&time, &status, scores.view(), None).unwrap();
+        assert!(approx_eq(full.concordance[0], 0.852133, 1e-6));
+        assert!(approx_eq(full.concordance[1], 0.587075, 1e-6));
+        assert!(approx_eq(full.std_err[0], 0.088630, 1e-6));
+        assert!(approx_eq(full.comparisons[0].std_err, 0.149343, 1e-6));
+        assert!(approx_eq(full.comparisons[0].p_value, 0.075928, 1e-6));
+
+        let truncated = concordance_index(&time, &status, scores.view(), Some(8.5)).unwrap();
+        assert!(approx_eq(truncated.concordance[0], 0.897743, 1e-6));
+        assert!(approx_eq(truncated.concordance[1], 0.701955, 1e-6));
+        assert!(approx_eq(truncated.std_err[1], 0.120980, 1e-6));
+
+        assert!(concordance_index(&time, &status, scores.view(), Some(0.0)).is_err());
+
+        // Observations in rows: a third column gives a third C statistic and
+        // leaves the first two unchanged, and the transpose is rejected.
+        let negated: Vec<f64> = first.iter().map(|v| -v).collect();
+        let three = Array2::from_shape_fn((14, 3), |(i, k)| [&first, &second, &negated][k][i]);
+        let result = concordance_index(&time, &status, three.view(), None).unwrap();
+        assert_eq!(result.concordance.len(), 3);
+        assert_eq!(result.comparisons.len(), 3);
+        assert!(approx_eq(result.concordance[0], full.concordance[0], 1e-12));
+        assert!(approx_eq(result.concordance[1], full.concordance[1], 1e-12));
+        assert!(approx_eq(
+            result.concordance[2],
+            1.0 - full.concordance[0],
+            1e-12
+        ));
+        assert!(matches!(
+            concordance_index(&time, &status, three.t(), None),
+            Err(ConcordanceIndexError::LengthMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_survival_brier_ipcw_and_reference() {
+        let time: Vec<f64> = (1..=12).map(f64::from).collect();
+        let status = vec![1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0];
+        let rates: [f64; 12] = [
+            0.30, 0.10, 0.25, 0.15, 0.12, 0.14, 0.05, 0.10, 0.06, 0.08, 0.07, 0.04,
+        ];
+        let eval_times = [2.5, 5.5, 8.5];
+        let predictions =
+            Array2::from_shape_fn((12, 3), |(i, k)| (-rates[i] * eval_times[k]).exp());
+
+        let result = survival_brier(&time, &status, predictions.view(), &eval_times).unwrap();
+
+        assert!(approx_eq(result.brier[1], 0.117886, 1e-6));
+        assert!(approx_eq(result.brier[2], 0.128026, 1e-6));
+        assert!(approx_eq(result.reference_brier[1], 0.195556, 1e-6));
+        assert!(approx_eq(result.reference_brier[2], 0.249992, 1e-6));
+        assert!(approx_eq(result.integrated_brier, 0.109760, 1e-6));
+        assert!(approx_eq(result.integrated_ipa, 0.388090, 1e-6));
+
+        let mut bad = predictions.clone();
+        bad[[0, 0]] = 1.5;
+        assert!(survival_brier(&time, &status, bad.view(), &eval_times).is_err());
+    }
+
     #[test]
     fn test_single_observation() {
         let time = vec![5.0];
@@ -1,413 +1,379 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use itertools::Itertools;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
 
 #[allow(clippy::too_many_arguments)]
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn agexact(
+    py: Python<'_>,
     mut maxiter: i32,
     nused: i32,
     nvar: i32,
-    start: Vec<f64>,
-    stop: Vec<f64>,
-    event: Vec<i32>,
-    mut covar: Vec<f64>,
-    offset: Vec<f64>,
-    strata: Vec<i32>,
-    mut means: Vec<f64>,
-    mut beta: Vec<f64>,
-    mut u: Vec<f64>,
-    mut imat: Vec<f64>,
-    mut loglik: Vec<f64>,
-    mut work: Vec<f64>,
-    mut work2: Vec<i32>,
+    start: VectorArg<'_, f64>,
+    stop: VectorArg<'_, f64>,
+    event: VectorArg<'_, i32>,
+    covar: VectorArg<'_, f64>,
+    offset: VectorArg<'_, f64>,
+    strata: VectorArg<'_, i32>,
+    means: VectorArg<'_, f64>,
+    beta: VectorArg<'_, f64>,
+    u: VectorArg<'_, f64>,
+    imat: VectorArg<'_, f64>,
+    loglik: VectorArg<'_, f64>,
+    work: VectorArg<'_, f64>,
+    work2: VectorArg<'_, i32>,
     eps: f64,
     tol_chol: f64,
-    nocenter: Vec<i32>,
+    nocenter: VectorArg<'_, i32>,
 ) -> PyResult<Py<PyDict>> {
-    let n = nused as usize;
-    let nvar_usize = nvar as usize;
-    let p = nvar_usize;
+    let start = start.as_slice();
+    let stop = stop.as_slice();
+    let event = event.as_slice();
+    let mut covar = covar.into_vec();
+    let offset = offset.as_slice();
+    let strata = strata.as_slice();
+    let mut means = means.into_vec();
+    let mut beta = beta.into_vec();
+    let mut u = u.into_vec();
+    let mut imat = imat.into_vec();
+    let mut loglik = loglik.into_vec();
+    let mut work = work.into_vec();
+    let mut work2 = work2.into_vec();
+    let nocenter = nocenter.as_slice();
+    let (maxiter, flag, sctest) = parallel::detach(py, || {
+        let n = nused as usize;
+        let nvar_usize = nvar as usize;
+        let p = nvar_usize;
+
+        let (cmat, rest) = work.split_at_mut(p * p);
+        let (a, rest) = rest.split_at_mut(p);
+        let (newbeta, rest) = rest.split_at_mut(p);
+        let (score, _) = rest.split_at_mut(n);
+
+        let _index = &mut work2[0..n];
+        let atrisk = &mut work2[n..2 * n];
 
-    let (cmat, rest) = work.split_at_mut(p * p);
-    let (a, rest) = rest.split_at_mut(p);
-    let (newbeta, rest) = rest.split_at_mut(p);
-    let (score, newvar) = rest.split_at_mut(n);
-
-    let _index = &mut work2[0..n];
-    let atrisk = &mut work2[n..2 * n];
-
-    #[allow(clippy::needless_range_loop)]
-    for i in 0..nvar_usize {
-        if nocenter[i] == 0 {
-            means[i] = 0.0;
-        } else {
-            let mut sum = 0.0;
-            #[allow(clippy::needless_range_loop)]
-            for j in 0..n {
-                sum += covar[i * n + j];
+        #[allow(clippy::needless_range_loop)]
+        for i in 0..nvar_usize {
+            if nocenter[i] == 0 {
+                means[i] = 0.0;
+            } else {
+                let mut sum = 0.0;
+                #[allow(clippy::needless_range_loop)]
+                for j in 0..n {
+                    sum += covar[i * n + j];
+                }
+                means[i] = sum / n as f64;
+                let mean_val = means[i];
+                #[allow(clippy::needless_range_loop)]
+                for j in 0..n {
+                    covar[i * n + j] -= mean_val;
+                }
             }
-            means[i] = sum / n as f64;
-            let mean_val = means[i];
+        }
+
+        #[allow(clippy::needless_range_loop)]
+        for person in 0..n {
+            let mut zbeta = 0.0;
             #[allow(clippy::needless_range_loop)]
-            for j in 0..n {
-                covar[i * n + j] -= mean_val;
+            for i in 0..nvar_usize {
+                zbeta += beta[i] * covar[i * n + person];
             }
+            score[person] = (zbeta + offset[person]).exp();
         }
-    }
 
-    #[allow(clippy::needless_range_loop)]
-    for person in 0..n {
-        let mut zbeta = 0.0;
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..nvar_usize {
-            zbeta += beta[i] * covar[i * n + person];
+        if loglik.len() < 2 {
+            loglik.resize(2, 0.0);
         }
-        score[person] = (zbeta + offset[person]).exp();
-    }
+        loglik[1] = 0.0;
+        u.fill(0.0);
+        imat.fill(0.0);
 
-    if loglik.len() < 2 {
-        loglik.resize(2, 0.0);
-    }
-    loglik[1] = 0.0;
-    u.fill(0.0);
-    imat.fill(0.0);
-
-    let mut person = 0;
-    while person < n {
-        if event[person] == 0 {
-            person += 1;
-        } else {
-            let time = stop[person];
-            let mut deaths = 0;
-            let mut nrisk = 0;
-            let mut k = person;
-
-            while k < n {
-                if stop[k] == time {
-                    deaths += event[k];
-                }
-                if start[k] < time {
-                    atrisk[nrisk] = k as i32;
-                    nrisk += 1;
-                }
-                if strata[k] == 1 {
-                    break;
+        let mut person = 0;
+        while person < n {
+            if event[person] == 0 {
+                person += 1;
+            } else {
+                let time = stop[person];
+                let mut deaths = 0;
+                let mut nrisk = 0;
+                let mut k = person;
+
+                while k < n {
+                    if stop[k] == time {
+                        deaths += event[k];
+                    }
+                    if start[k] < time {
+                        atrisk[nrisk] = k as i32;
+                        nrisk += 1;
+                    }
+                    if strata[k] == 1 {
+                        break;
+                    }
+                    k += 1;
                 }
-                k += 1;
-            }
 
-            let mut denom = 0.0;
-            a.fill(0.0);
-            cmat.fill(0.0);
+                let rows: Vec<usize> = atrisk[..nrisk].iter().map(|&k| k as usize).collect();
+                let denom = exact_set_moments(&score[..n], &covar, &rows, deaths as usize, a, cmat);
 
-            if deaths == 1 {
+                loglik[1] -= denom.ln();
                 #[allow(clippy::needless_range_loop)]
-                for l in 0..nrisk {
-                    let k = atrisk[l] as usize;
-                    let weight = score[k];
-                    denom += weight;
+                for i in 0..nvar_usize {
+                    u[i] -= a[i] / denom;
                     #[allow(clippy::needless_range_loop)]
-                    for i in 0..nvar_usize {
-                        let covar_ik = covar[i * n + k];
-                        a[i] += weight * covar_ik;
-                        #[allow(clippy::needless_range_loop)]
-                        for j in 0..=i {
-                            let covar_jk = covar[j * n + k];
-                            cmat[i * p + j] += weight * covar_ik * covar_jk;
-                        }
+                    for j in 0..=i {
+                        let cmat_ij = cmat[i * p + j];
+                        let term = (cmat_ij - a[i] * a[j] / denom) / denom;
+                        imat[j * p + i] += term;
                     }
                 }
-            } else {
-                let combinations = init_doloop(0, nrisk, deaths as usize);
-                for indices in combinations {
-                    newvar.fill(0.0);
-                    let mut weight = 1.0;
-                    for &idx in &indices {
-                        let k = atrisk[idx] as usize;
-                        weight *= score[k];
+
+                let mut k = person;
+                while k < n && stop[k] == time {
+                    if event[k] == 1 {
+                        loglik[1] += score[k].ln();
                         #[allow(clippy::needless_range_loop)]
                         for i in 0..nvar_usize {
-                            newvar[i] += covar[i * n + k];
+                            u[i] += covar[i * n + k];
                         }
                     }
-                    denom += weight;
-                    #[allow(clippy::needless_range_loop)]
-                    for i in 0..nvar_usize {
-                        a[i] += weight * newvar[i];
-                        #[allow(clippy::needless_range_loop)]
-                        for j in 0..=i {
-                            cmat[i * p + j] += weight * newvar[i] * newvar[j];
-                        }
+                    person += 1;
+                    if strata[k] == 1 {
+                        break;
                     }
+                    k += 1;
                 }
             }
+        }
+
+        loglik[0] = loglik[1];
+        let mut a_copy = a.to_vec();
+        let _ = cholesky2(&mut imat[..p * p], p, tol_chol);
+        chsolve2(&mut imat[..p * p], p, &mut a_copy);
+        let sctest = a_copy.iter().zip(u.iter()).map(|(a, u)| a * u).sum::<f64>();
 
-            loglik[1] -= denom.ln();
+        if maxiter == 0 {
+            chinv2(&mut imat[..p * p], p);
             #[allow(clippy::needless_range_loop)]
-            for i in 0..nvar_usize {
-                u[i] -= a[i] / denom;
+            for i in 0..p {
                 #[allow(clippy::needless_range_loop)]
-                for j in 0..=i {
-                    let cmat_ij = cmat[i * p + j];
-                    let term = (cmat_ij - a[i] * a[j] / denom) / denom;
-                    imat[j * p + i] += term;
+                for j in 0..i {
+                    imat[i * p + j] = imat[j * p + i];
                 }
             }
+            let final_flag = 0;
+            (maxiter, final_flag, sctest)
+        } else {
+            let mut iter = 0;
+            let mut halving = false;
+            let mut newbeta_vec = newbeta.to_vec();
+            let mut newlk = 0.0;
+
+            while iter < maxiter {
+                iter += 1;
+                newlk = 0.0;
+                u.fill(0.0);
+                imat.fill(0.0);
 
-            let mut k = person;
-            while k < n && stop[k] == time {
-                if event[k] == 1 {
-                    loglik[1] += score[k].ln();
+                #[allow(clippy::needless_range_loop)]
+                for person in 0..n {
+                    let mut zbeta = 0.0;
                     #[allow(clippy::needless_range_loop)]
                     for i in 0..nvar_usize {
-                        u[i] += covar[i * n + k];
+                        zbeta += newbeta_vec[i] * covar[i * n + person];
                     }
+                    score[person] = (zbeta + offset[person]).exp();
                 }
-                person += 1;
-                if strata[k] == 1 {
-                    break;
-                }
-                k += 1;
-            }
-        }
-    }
-
-    loglik[0] = loglik[1];
-    let mut a_copy = a.to_vec();
-    let _ = cholesky2(&mut imat[..p * p], p, tol_chol);
-    chsolve2(&mut imat[..p * p], p, &mut a_copy);
-    let sctest = a_copy.iter().zip(u.iter()).map(|(a, u)| a * u).sum::<f64>();
-
-    if maxiter == 0 {
-        chinv2(&mut imat[..p * p], p);
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..p {
-            #[allow(clippy::needless_range_loop)]
-            for j in 0..i {
-                imat[i * p + j] = imat[j * p + i];
-            }
-        }
-        let final_flag = 0;
-        Python::attach(|py| {
-            let dict = PyDict::new(py);
-            dict.set_item("maxiter", maxiter)?;
-            dict.set_item("covar", covar.to_vec())?;
-            dict.set_item("means", means.to_vec())?;
-            dict.set_item("beta", beta.to_vec())?;
-            dict.set_item("u", u.to_vec())?;
-            dict.set_item("imat", imat.to_vec())?;
-            dict.set_item("loglik", loglik.to_vec())?;
-            dict.set_item("flag", final_flag)?;
-            dict.set_item("sctest", sctest)?;
-            Ok(dict.into())
-        })
-    } else {
-        let mut iter = 0;
-        let mut halving = false;
-        let mut newbeta_vec = newbeta.to_vec();
-        let mut newlk = 0.0;
-
-        while iter < maxiter {
-            iter += 1;
-            newlk = 0.0;
-            u.fill(0.0);
-            imat.fill(0.0);
 
-            #[allow(clippy::needless_range_loop)]
-            for person in 0..n {
-                let mut zbeta = 0.0;
-                #[allow(clippy::needless_range_loop)]
-                for i in 0..nvar_usize {
-                    zbeta += newbeta_vec[i] * covar[i * n + person];
-                }
-                score[person] = (zbeta + offset[person]).exp();
-            }
-
-            let mut person = 0;
-            while person < n {
-                if event[person] == 0 {
-                    person += 1;
-                } else {
-                    let time = stop[person];
-                    let mut deaths = 0;
-                    let mut nrisk = 0;
-                    let mut k = person;
-
-                    while k < n {
-                        if stop[k] == time {
-                            deaths += event[k];
-                        }
-                        if start[k] < time {
-                            atrisk[nrisk] = k as i32;
-                            nrisk += 1;
-                        }
-                        if strata[k] == 1 {
-                            break;
+                let mut person = 0;
+                while person < n {
+                    if event[person] == 0 {
+                        person += 1;
+                    } else {
+                        let time = stop[person];
+                        let mut deaths = 0;
+                        let mut nrisk = 0;
+                        let mut k = person;
+
+                        while k < n {
+                            if stop[k] == time {
+                                deaths += event[k];
+                            }
+                            if start[k] < time {
+                                atrisk[nrisk] = k as i32;
+                                nrisk += 1;
+                            }
+                            if strata[k] == 1 {
+                                break;
+                            }
+                            k += 1;
                         }
-                        k += 1;
-                    }
 
-                    let mut denom = 0.0;
-                    a.fill(0.0);
-                    cmat.fill(0.0);
+                        let rows: Vec<usize> =
+                            atrisk[..nrisk].iter().map(|&k| k as usize).collect();
+                        let denom =
+                            exact_set_moments(&score[..n], &covar, &rows, deaths as usize, a, cmat);
 
-                    if deaths == 1 {
+                        newlk -= denom.ln();
                         #[allow(clippy::needless_range_loop)]
-                        for l in 0..nrisk {
-                            let k = atrisk[l] as usize;
-                            let weight = score[k];
-                            denom += weight;
+                        for i in 0..nvar_usize {
+                            u[i] -= a[i] / denom;
                             #[allow(clippy::needless_range_loop)]
-                            for i in 0..nvar_usize {
-                                let covar_ik = covar[i * n + k];
-                                a[i] += weight * covar_ik;
-                                #[allow(clippy::needless_range_loop)]
-                                for j in 0..=i {
-                                    cmat[i * p + j] += weight * covar_ik * covar[j * n + k];
-                                }
+                            for j in 0..=i {
+                                let cmat_ij = cmat[i * p + j];
+                                let term = (cmat_ij - a[i] * a[j] / denom) / denom;
+                                imat[j * p + i] += term;
                             }
                         }
-                    } else {
-                        let combinations = init_doloop(0, nrisk, deaths as usize);
-                        for indices in combinations {
-                            newvar.fill(0.0);
-                            let mut weight = 1.0;
-                            for &idx in &indices {
-                                let k = atrisk[idx] as usize;
-                                weight *= score[k];
+
+                        let mut k = person;
+                        while k < n && stop[k] == time {
+                            if event[k] == 1 {
+                                newlk += score[k].ln();
                                 #[allow(clippy::needless_range_loop)]
                                 for i in 0..nvar_usize {
-                                    newvar[i] += covar[i * n + k];
+                                    u[i] += covar[i * n + k];
                                 }
                             }
-                            denom += weight;
-                            #[allow(clippy::needless_range_loop)]
-                            for i in 0..nvar_usize {
-                                a[i] += weight * newvar[i];
-                                #[allow(clippy::needless_range_loop)]
-                                for j in 0..=i {
-                                    cmat[i * p + j] += weight * newvar[i] * newvar[j];
-                                }
+                            person += 1;
+                            if strata[k] == 1 {
+                                break;
                             }
+                            k += 1;
                         }
                     }
+                }
 
-                    newlk -= denom.ln();
+                if (1.0 - (loglik[1] / newlk)).abs() <= eps && !halving {
+                    loglik[1] = newlk;
+                    chinv2(&mut imat[..p * p], p);
                     #[allow(clippy::needless_range_loop)]
-                    for i in 0..nvar_usize {
-                        u[i] -= a[i] / denom;
+                    for i in 0..p {
                         #[allow(clippy::needless_range_loop)]
-                        for j in 0..=i {
-                            let cmat_ij = cmat[i * p + j];
-                            let term = (cmat_ij - a[i] * a[j] / denom) / denom;
-                            imat[j * p + i] += term;
+                        for j in 0..i {
+                            imat[i * p + j] = imat[j * p + i];
                         }
                     }
+                    beta.copy_from_slice(&newbeta_vec);
+                    maxiter = iter;
+                    return (maxiter, 0, sctest);
+                } else {
+                    if iter == maxiter {
+                        break;
+                    }
 
-                    let mut k = person;
-                    while k < n && stop[k] == time {
-                        if event[k] == 1 {
-                            newlk += score[k].ln();
-                            #[allow(clippy::needless_range_loop)]
-                            for i in 0..nvar_usize {
-                                u[i] += covar[i * n + k];
-                            }
+                    if newlk < loglik[1] {
+                        halving = true;
+                        #[allow(clippy::needless_range_loop)]
+                        for i in 0..nvar_usize {
+                            newbeta_vec[i] = (newbeta_vec[i] + beta[i]) / 2.0;
                         }
-                        person += 1;
-                        if strata[k] == 1 {
-                            break;
+                    } else {
+                        halving = false;
+                        loglik[1] = newlk;
+                        let _flag_check = cholesky2(&mut imat[..p * p], p, tol_chol);
+                        let mut u_copy = u.to_vec();
+                        chsolve2(&mut imat[..p * p], p, &mut u_copy);
+
+                        beta[..nvar_usize].copy_from_slice(&newbeta_vec[..nvar_usize]);
+                        #[allow(clippy::needless_range_loop)]
+                        for i in 0..nvar_usize {
+                            newbeta_vec[i] += u_copy[i];
                         }
-                        k += 1;
                     }
                 }
             }
 
-            if (1.0 - (loglik[1] / newlk)).abs() <= eps && !halving {
-                loglik[1] = newlk;
-                chinv2(&mut imat[..p * p], p);
+            loglik[1] = newlk;
+            chinv2(&mut imat[..p * p], p);
+            #[allow(clippy::needless_range_loop)]
+            for i in 0..p {
                 #[allow(clippy::needless_range_loop)]
-                for i in 0..p {
-                    #[allow(clippy::needless_range_loop)]
-                    for j in 0..i {
-                        imat[i * p + j] = imat[j * p + i];
-                    }
-                }
-                beta.copy_from_slice(&newbeta_vec);
-                maxiter = iter;
-                return Python::attach(|py| {
-                    let dict = PyDict::new(py);
-                    dict.set_item("maxiter", maxiter)?;
-                    dict.set_item("covar", covar.to_vec())?;
-                    dict.set_item("means", means.to_vec())?;
-                    dict.set_item("beta", beta.to_vec())?;
-                    dict.set_item("u", u.to_vec())?;
-                    dict.set_item("imat", imat.to_vec())?;
-                    dict.set_item("loglik", loglik.to_vec())?;
-                    dict.set_item("flag", 0)?;
-                    dict.set_item("sctest", sctest)?;
-                    Ok(dict.into())
-                });
-            } else {
-                if iter == maxiter {
-                    break;
+                for j in 0..i {
+                    imat[i * p + j] = imat[j * p + i];
                 }
+            }
+            beta.copy_from_slice(&newbeta_vec);
+            let final_flag = 1000;
 
-                if newlk < loglik[1] {
-                    halving = true;
-                    #[allow(clippy::needless_range_loop)]
-                    for i in 0..nvar_usize {
-                        newbeta_vec[i] = (newbeta_vec[i] + beta[i]) / 2.0;
-                    }
-                } else {
-                    halving = false;
-                    loglik[1] = newlk;
-                    let _flag_check = cholesky2(&mut imat[..p * p], p, tol_chol);
-                    let mut u_copy = u.to_vec();
-                    chsolve2(&mut imat[..p * p], p, &mut u_copy);
+            (maxiter, final_flag, sctest)
+        }
+    });
+
+    let dict = PyDict::new(py);
+    dict.set_item("maxiter", maxiter)?;
+    dict.set_item("covar", covar)?;
+    dict.set_item("means", means)?;
+    dict.set_item("beta", beta)?;
+    dict.set_item("u", u)?;
+    dict.set_item("imat", imat)?;
+    dict.set_item("loglik", loglik)?;
+    dict.set_item("flag", flag)?;
+    dict.set_item("sctest", sctest)?;
+    Ok(dict.into())
+}
 
-                    beta[..nvar_usize].copy_from_slice(&newbeta_vec[..nvar_usize]);
-                    #[allow(clippy::needless_range_loop)]
-                    for i in 0..nvar_usize {
-                        newbeta_vec[i] += u_copy[i];
-                    }
+/// Sums `exp(sum of z'beta)` over every way `deaths` of `rows` can be the
+/// events, returning the total and filling `a` with its first moment and the
+/// lower triangle of `cmat` with its second moment. `covar` is stored one
+/// variable after another, each of length `score.len()`.
+pub(crate) fn exact_set_moments(
+    score: &[f64],
+    covar: &[f64],
+    rows: &[usize],
+    deaths: usize,
+    a: &mut [f64],
+    cmat: &mut [f64],
+) -> f64 {
+    let n = score.len();
+    let p = a.len();
+    let mut denom = 0.0;
+    a.fill(0.0);
+    cmat.fill(0.0);
+
+    if deaths == 1 {
+        for &k in rows {
+            let weight = score[k];
+            denom += weight;
+            for i in 0..p {
+                let covar_ik = covar[i * n + k];
+                a[i] += weight * covar_ik;
+                for j in 0..=i {
+                    cmat[i * p + j] += weight * covar_ik * covar[j * n + k];
                 }
             }
         }
-
-        loglik[1] = newlk;
-        chinv2(&mut imat[..p * p], p);
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..p {
-            #[allow(clippy::needless_range_loop)]
-            for j in 0..i {
-                imat[i * p + j] = imat[j * p + i];
+    } else {
+        let mut newvar = vec![0.0; p];
+        for indices in rows.iter().combinations(deaths) {
+            newvar.fill(0.0);
+            let mut weight = 1.0;
+            for &&k in &indices {
+                weight *= score[k];
+                for (i, v) in newvar.iter_mut().enumerate() {
+                    *v += covar[i * n + k];
+                }
+            }
+            denom += weight;
+            for i in 0..p {
+                a[i] += weight * newvar[i];
+                for j in 0..=i {
+                    cmat[i * p + j] += weight * newvar[i] * newvar[j];
+                }
             }
         }
-        beta.copy_from_slice(&newbeta_vec);
-        let final_flag = 1000;
-
-        Python::attach(|py| {
-            let dict = PyDict::new(py);
-            dict.set_item("maxiter", maxiter)?;
-            dict.set_item("covar", covar.to_vec())?;
-            dict.set_item("means", means.to_vec())?;
-            dict.set_item("beta", beta.to_vec())?;
-            dict.set_item("u", u.to_vec())?;
-            dict.set_item("imat", imat.to_vec())?;
-            dict.set_item("loglik", loglik.to_vec())?;
-            dict.set_item("flag", final_flag)?;
-            dict.set_item("sctest", sctest)?;
-            Ok(dict.into())
-        })
     }
+    denom
 }
 
-#[allow(clippy::too_many_arguments)]
-fn init_doloop(start: usize, end: usize, k: usize) -> Vec<Vec<usize>> {
-    (start..end).combinations(k).collect()
-}
-
+#[cfg(feature = "python")]
 fn cholesky2(matrix: &mut [f64], n: usize, tol: f64) -> i32 {
     #[allow(clippy::needless_range_loop)]
     for i in 0..n {
@@ -434,6 +400,7 @@ fn cholesky2(matrix: &mut [f64], n: usize, tol: f64) -> i32 {
     0
 }
 
+#[cfg(feature = "python")]
 fn chsolve2(chol: &mut [f64], n: usize, b: &mut [f64]) {
     #[allow(clippy::needless_range_loop)]
     for i in 0..n {
@@ -454,6 +421,7 @@ fn chsolve2(chol: &mut [f64], n: usize, b: &mut [f64]) {
     }
 }
 
+#[cfg(feature = "python")]
 fn chinv2(chol: &mut [f64], n: usize) {
     #[allow(clippy::needless_range_loop)]
     for i in 0..n {
@@ -1,6 +1,36 @@
-#[allow(dead_code)]
-enum ClusterVariable {
-    Character(String),
-    Factor(Vec<String>),
-    Numeric(f64),
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub fn cluster<T: Hash + Eq>(ids: &[T]) -> Vec<usize> {
+    let mut codes: HashMap<&T, usize> = HashMap::new();
+    ids.iter()
+        .map(|id| {
+            let next = codes.len();
+            *codes.entry(id).or_insert(next)
+        })
+        .collect()
+}
+
+pub fn robust_variance(
+    dfbeta: &Array2<f64>,
+    weights: &[f64],
+    cluster: Option<&[usize]>,
+) -> Array2<f64> {
+    let n = dfbeta.nrows();
+    let nvar = dfbeta.ncols();
+
+    let ngroup = match cluster {
+        Some(c) => c.iter().max().map_or(0, |&m| m + 1),
+        None => n,
+    };
+    let mut grouped = Array2::<f64>::zeros((ngroup, nvar));
+    for i in 0..n {
+        let g = cluster.map_or(i, |c| c[i]);
+        for j in 0..nvar {
+            grouped[[g, j]] += weights[i] * dfbeta[[i, j]];
+        }
+    }
+
+    grouped.t().dot(&grouped)
 }
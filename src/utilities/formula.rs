@@ -0,0 +1,787 @@
+use crate::core::pspline::pspline_basis;
+#[cfg(feature = "python")]
+use crate::python::arrays::MatrixArg;
+use crate::utilities::cluster::cluster;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::ArrayView2;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FormulaError {
+    #[error("formula must have the form 'response ~ terms'")]
+    MissingTilde,
+    #[error("unbalanced parentheses in formula")]
+    UnbalancedParentheses,
+    #[error("invalid response '{0}'")]
+    InvalidResponse(String),
+    #[error("invalid term '{0}'")]
+    InvalidTerm(String),
+    #[error("invalid argument '{0}'")]
+    InvalidArgument(String),
+    #[error("unknown variable '{0}'")]
+    UnknownVariable(String),
+    #[error("penalized term '{0}' is not supported, use pspline() for a penalized spline")]
+    PenalizedTerm(String),
+    #[error("data has {got} columns, expected {expected}")]
+    DimensionMismatch { expected: usize, got: usize },
+}
+
+#[cfg(feature = "python")]
+impl From<FormulaError> for PyErr {
+    fn from(err: FormulaError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contrast {
+    Treatment,
+    Sum,
+    Helmert,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Component {
+    Numeric(String),
+    Factor {
+        variable: String,
+        contrast: Contrast,
+    },
+    BSpline {
+        variable: String,
+        df: usize,
+    },
+    PSpline {
+        variable: String,
+        df: f64,
+    },
+}
+
+impl Component {
+    fn label(&self) -> String {
+        match self {
+            Component::Numeric(name) => name.clone(),
+            Component::Factor { variable, .. } => format!("factor({})", variable),
+            Component::BSpline { variable, .. } => format!("bs({})", variable),
+            Component::PSpline { variable, .. } => format!("pspline({})", variable),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub components: Vec<Component>,
+}
+
+impl Term {
+    fn key(&self) -> Vec<String> {
+        let mut key: Vec<String> = self.components.iter().map(|c| c.label()).collect();
+        key.sort();
+        key
+    }
+}
+
+/// Layout of a `Surv()` response, following the `type` argument of R's
+/// `Surv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(eq, eq_int))]
+pub enum SurvType {
+    /// `Surv(time, event)`
+    Right,
+    /// `Surv(start, stop, event)`
+    Counting,
+    /// `Surv(time, time2, event, type = "interval")` with event codes 0
+    /// (right), 1 (exact), 2 (left) and 3 (interval censored).
+    Interval,
+    /// `Surv(left, right, type = "interval2")` where a missing or infinite
+    /// bound marks a left- or right-censored observation.
+    Interval2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    Variable(String),
+    Surv {
+        kind: SurvType,
+        start: Option<String>,
+        stop: String,
+        event: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    pub response: Response,
+    pub terms: Vec<Term>,
+    pub intercept: bool,
+    pub strata: Vec<String>,
+    pub cluster: Option<String>,
+    pub offsets: Vec<String>,
+}
+
+/// Covariate columns `first..first + columns` holding the basis of a
+/// `pspline()` term, to be penalized down to `df` degrees of freedom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct PenalizedTerm {
+    pub name: String,
+    pub first: usize,
+    pub columns: usize,
+    pub df: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct ModelFrame {
+    pub time: Vec<f64>,
+    pub start: Option<Vec<f64>>,
+    pub status: Option<Vec<f64>>,
+    pub surv_type: Option<SurvType>,
+    pub covariates: Vec<Vec<f64>>,
+    pub column_names: Vec<String>,
+    pub penalized: Vec<PenalizedTerm>,
+    pub intercept: bool,
+    pub strata: Option<Vec<usize>>,
+    pub cluster: Option<Vec<usize>>,
+    pub offset: Option<Vec<f64>>,
+}
+
+impl ModelFrame {
+    /// Fails on `pspline()` terms, for models without penalized fits.
+    pub fn unpenalized(self) -> Result<Self, FormulaError> {
+        match self.penalized.first() {
+            Some(term) => Err(FormulaError::PenalizedTerm(term.name.clone())),
+            None => Ok(self),
+        }
+    }
+}
+
+fn split_top_level(s: &str, separators: &[char]) -> Result<Vec<(char, String)>, FormulaError> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    let mut sep = '+';
+    for ch in s.chars() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth < 0 {
+                    return Err(FormulaError::UnbalancedParentheses);
+                }
+            }
+            _ => {}
+        }
+        if depth == 0 && separators.contains(&ch) {
+            parts.push((sep, current.trim().to_string()));
+            current.clear();
+            sep = ch;
+        } else {
+            current.push(ch);
+        }
+    }
+    if depth != 0 {
+        return Err(FormulaError::UnbalancedParentheses);
+    }
+    parts.push((sep, current.trim().to_string()));
+    Ok(parts)
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+fn parse_call(s: &str) -> Result<Option<(String, Vec<String>)>, FormulaError> {
+    let Some(open) = s.find('(') else {
+        return Ok(None);
+    };
+    if !s.ends_with(')') {
+        return Ok(None);
+    }
+    let name = s[..open].trim();
+    if !is_identifier(name) {
+        return Ok(None);
+    }
+    let args = split_top_level(&s[open + 1..s.len() - 1], &[','])?
+        .into_iter()
+        .map(|(_, arg)| arg)
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    Ok(Some((name.to_string(), args)))
+}
+
+fn argument_value(arg: &str) -> &str {
+    let value = match arg.split_once('=') {
+        Some((_, value)) => value.trim(),
+        None => arg.trim(),
+    };
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+fn variable_arg(arg: &str) -> Result<String, FormulaError> {
+    if is_identifier(arg) {
+        Ok(arg.to_string())
+    } else {
+        Err(FormulaError::InvalidTerm(arg.to_string()))
+    }
+}
+
+fn parse_component(s: &str) -> Result<Component, FormulaError> {
+    match parse_call(s)? {
+        None if is_identifier(s) => Ok(Component::Numeric(s.to_string())),
+        None => Err(FormulaError::InvalidTerm(s.to_string())),
+        Some((name, args)) => match (name.as_str(), args.as_slice()) {
+            ("factor", [variable]) => Ok(Component::Factor {
+                variable: variable_arg(variable)?,
+                contrast: Contrast::Treatment,
+            }),
+            ("factor", [variable, contrast]) => {
+                let contrast = match argument_value(contrast) {
+                    "treatment" | "contr.treatment" => Contrast::Treatment,
+                    "sum" | "contr.sum" => Contrast::Sum,
+                    "helmert" | "contr.helmert" => Contrast::Helmert,
+                    other => return Err(FormulaError::InvalidArgument(other.to_string())),
+                };
+                Ok(Component::Factor {
+                    variable: variable_arg(variable)?,
+                    contrast,
+                })
+            }
+            ("bs", [variable]) => Ok(Component::BSpline {
+                variable: variable_arg(variable)?,
+                df: 3,
+            }),
+            ("bs", [variable, df]) => {
+                let df = argument_value(df)
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&df| df >= 3)
+                    .ok_or_else(|| FormulaError::InvalidArgument(df.clone()))?;
+                Ok(Component::BSpline {
+                    variable: variable_arg(variable)?,
+                    df,
+                })
+            }
+            ("pspline", [variable]) => Ok(Component::PSpline {
+                variable: variable_arg(variable)?,
+                df: 4.0,
+            }),
+            ("pspline", [variable, df]) => {
+                let df = df
+                    .split_once('=')
+                    .is_none_or(|(key, _)| key.trim() == "df")
+                    .then(|| argument_value(df).parse::<f64>().ok())
+                    .flatten()
+                    .filter(|&df| df > 1.0 && df.is_finite())
+                    .ok_or_else(|| FormulaError::InvalidArgument(df.clone()))?;
+                Ok(Component::PSpline {
+                    variable: variable_arg(variable)?,
+                    df,
+                })
+            }
+            ("ridge" | "frailty", _) => Err(FormulaError::PenalizedTerm(s.to_string())),
+            _ => Err(FormulaError::InvalidTerm(s.to_string())),
+        },
+    }
+}
+
+fn parse_response(s: &str) -> Result<Response, FormulaError> {
+    let invalid = || FormulaError::InvalidResponse(s.to_string());
+    match parse_call(s)? {
+        None if is_identifier(s) => Ok(Response::Variable(s.to_string())),
+        Some((name, args)) if name == "Surv" => {
+            let (typed, variables): (Vec<&String>, Vec<&String>) = args.iter().partition(|arg| {
+                arg.split_once('=')
+                    .is_some_and(|(key, _)| key.trim() == "type")
+            });
+            let kind = match typed.as_slice() {
+                [] => None,
+                [arg] => Some(match argument_value(arg) {
+                    "right" => SurvType::Right,
+                    "counting" => SurvType::Counting,
+                    "interval" => SurvType::Interval,
+                    "interval2" => SurvType::Interval2,
+                    other => return Err(FormulaError::InvalidArgument(other.to_string())),
+                }),
+                _ => return Err(invalid()),
+            };
+            let variables = variables
+                .into_iter()
+                .map(|arg| variable_arg(arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            let (kind, start, stop, event) = match (kind, variables.as_slice()) {
+                (None | Some(SurvType::Right), [time, event]) => {
+                    (SurvType::Right, None, time, Some(event))
+                }
+                (None | Some(SurvType::Counting), [start, stop, event]) => {
+                    (SurvType::Counting, Some(start), stop, Some(event))
+                }
+                (Some(SurvType::Interval), [time, time2, event]) => {
+                    (SurvType::Interval, Some(time), time2, Some(event))
+                }
+                (Some(SurvType::Interval2), [left, right]) => {
+                    (SurvType::Interval2, Some(left), right, None)
+                }
+                _ => return Err(invalid()),
+            };
+            Ok(Response::Surv {
+                kind,
+                start: start.cloned(),
+                stop: stop.clone(),
+                event: event.cloned(),
+            })
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn expand_term(s: &str) -> Result<Vec<Term>, FormulaError> {
+    let factors: Vec<Vec<Component>> = split_top_level(s, &['*'])?
+        .into_iter()
+        .map(|(_, part)| {
+            split_top_level(&part, &[':'])?
+                .into_iter()
+                .map(|(_, c)| parse_component(&c))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut subsets: Vec<Vec<usize>> = (1..(1usize << factors.len()))
+        .map(|mask| {
+            (0..factors.len())
+                .filter(|&i| mask & (1 << i) != 0)
+                .collect()
+        })
+        .collect();
+    subsets.sort_by_key(|subset| subset.len());
+
+    Ok(subsets
+        .into_iter()
+        .map(|subset| Term {
+            components: subset
+                .into_iter()
+                .flat_map(|i| factors[i].iter().cloned())
+                .collect(),
+        })
+        .collect())
+}
+
+impl Formula {
+    pub fn parse(formula: &str) -> Result<Self, FormulaError> {
+        let (lhs, rhs) = formula.split_once('~').ok_or(FormulaError::MissingTilde)?;
+        let response = parse_response(lhs.trim())?;
+
+        let mut parsed = Formula {
+            response,
+            terms: Vec::new(),
+            intercept: true,
+            strata: Vec::new(),
+            cluster: None,
+            offsets: Vec::new(),
+        };
+        let mut removed = Vec::new();
+
+        for (sign, text) in split_top_level(rhs, &['+', '-'])? {
+            if text.is_empty() {
+                if sign == '-' {
+                    return Err(FormulaError::InvalidTerm(text));
+                }
+                continue;
+            }
+            match (sign, text.as_str()) {
+                ('+', "1") => parsed.intercept = true,
+                ('+', "0") | ('-', "1") => parsed.intercept = false,
+                _ => {}
+            }
+            if text == "0" || text == "1" {
+                continue;
+            }
+
+            if let Some((name, args)) = parse_call(&text)?
+                && matches!(name.as_str(), "strata" | "cluster" | "offset")
+            {
+                if sign == '-' || args.is_empty() {
+                    return Err(FormulaError::InvalidTerm(text));
+                }
+                let variables = args
+                    .iter()
+                    .map(|arg| variable_arg(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match name.as_str() {
+                    "strata" => parsed.strata.extend(variables),
+                    "offset" => parsed.offsets.extend(variables),
+                    _ => {
+                        if variables.len() != 1 || parsed.cluster.is_some() {
+                            return Err(FormulaError::InvalidTerm(text));
+                        }
+                        parsed.cluster = variables.into_iter().next();
+                    }
+                }
+                continue;
+            }
+
+            for term in expand_term(&text)? {
+                if sign == '-' {
+                    removed.push(term.key());
+                } else if !parsed.terms.iter().any(|t| t.key() == term.key()) {
+                    parsed.terms.push(term);
+                }
+            }
+        }
+        parsed.terms.retain(|term| !removed.contains(&term.key()));
+        Ok(parsed)
+    }
+
+    pub fn model_frame(
+        &self,
+        data: ArrayView2<f64>,
+        variable_names: &[String],
+    ) -> Result<ModelFrame, FormulaError> {
+        let n = data.nrows();
+        let frame = DataColumns::new(data, variable_names)?;
+
+        let (surv_type, start, time, status) = match &self.response {
+            Response::Variable(name) => (None, None, frame.column(name)?, None),
+            Response::Surv {
+                kind: SurvType::Interval2,
+                start: Some(left),
+                stop: right,
+                ..
+            } => {
+                let bound = |name: &str, missing: f64| -> Result<Vec<f64>, FormulaError> {
+                    Ok(frame
+                        .column(name)?
+                        .into_iter()
+                        .map(|v| if v.is_nan() { missing } else { v })
+                        .collect())
+                };
+                (
+                    Some(SurvType::Interval2),
+                    Some(bound(left, f64::NEG_INFINITY)?),
+                    bound(right, f64::INFINITY)?,
+                    Some(vec![3.0; n]),
+                )
+            }
+            Response::Surv {
+                kind,
+                start,
+                stop,
+                event,
+            } => (
+                Some(*kind),
+                start.as_ref().map(|s| frame.column(s)).transpose()?,
+                frame.column(stop)?,
+                event.as_ref().map(|e| frame.column(e)).transpose()?,
+            ),
+        };
+
+        let mut column_names = Vec::new();
+        let mut columns: Vec<Vec<f64>> = Vec::new();
+        let mut penalized = Vec::new();
+        // Without an intercept the first factor is coded by indicators of
+        // all of its levels, as in R's model.matrix().
+        let mut full_factor = !self.intercept;
+        for term in &self.terms {
+            if let [component @ Component::PSpline { df, .. }] = term.components.as_slice() {
+                let (cnames, cvalues) = frame.expand(component, false)?;
+                penalized.push(PenalizedTerm {
+                    name: component.label(),
+                    first: columns.len(),
+                    columns: cvalues.len(),
+                    df: *df,
+                });
+                column_names.extend(cnames);
+                columns.extend(cvalues);
+                continue;
+            }
+            let full =
+                full_factor && matches!(term.components.as_slice(), [Component::Factor { .. }]);
+            full_factor &= !full;
+            let mut names = vec![String::new()];
+            let mut values = vec![vec![1.0; n]];
+            for component in &term.components {
+                if matches!(component, Component::PSpline { .. }) {
+                    return Err(FormulaError::InvalidTerm(component.label()));
+                }
+                let (cnames, cvalues) = frame.expand(component, full)?;
+                let mut next_names = Vec::new();
+                let mut next_values = Vec::new();
+                for (cname, cvalue) in cnames.iter().zip(&cvalues) {
+                    for (name, value) in names.iter().zip(&values) {
+                        next_names.push(if name.is_empty() {
+                            cname.clone()
+                        } else {
+                            format!("{}:{}", name, cname)
+                        });
+                        next_values.push(value.iter().zip(cvalue).map(|(a, b)| a * b).collect());
+                    }
+                }
+                names = next_names;
+                values = next_values;
+            }
+            column_names.extend(names);
+            columns.extend(values);
+        }
+        let covariates = (0..n)
+            .map(|i| columns.iter().map(|column| column[i]).collect())
+            .collect();
+
+        let strata = if self.strata.is_empty() {
+            None
+        } else {
+            let keys = self
+                .strata
+                .iter()
+                .map(|name| frame.column(name))
+                .collect::<Result<Vec<_>, _>>()?;
+            let rows: Vec<Vec<u64>> = (0..n)
+                .map(|i| keys.iter().map(|key| key[i].to_bits()).collect())
+                .collect();
+            Some(cluster(&rows))
+        };
+
+        let cluster_ids = self
+            .cluster
+            .as_ref()
+            .map(|name| {
+                let ids: Vec<u64> = frame.column(name)?.iter().map(|v| v.to_bits()).collect();
+                Ok(cluster(&ids))
+            })
+            .transpose()?;
+
+        let offset = if self.offsets.is_empty() {
+            None
+        } else {
+            let mut total = vec![0.0; n];
+            for name in &self.offsets {
+                for (t, v) in total.iter_mut().zip(frame.column(name)?) {
+                    *t += v;
+                }
+            }
+            Some(total)
+        };
+
+        Ok(ModelFrame {
+            time,
+            start,
+            status,
+            surv_type,
+            covariates,
+            column_names,
+            penalized,
+            intercept: self.intercept,
+            strata,
+            cluster: cluster_ids,
+            offset,
+        })
+    }
+}
+
+pub fn model_frame(
+    formula: &str,
+    data: ArrayView2<f64>,
+    variable_names: &[String],
+) -> Result<ModelFrame, FormulaError> {
+    Formula::parse(formula)?.model_frame(data, variable_names)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "model_frame")]
+pub fn model_frame_py(
+    py: Python<'_>,
+    formula: &str,
+    data: MatrixArg<'_, f64>,
+    variable_names: Vec<String>,
+) -> PyResult<ModelFrame> {
+    let data = data.view();
+    Ok(parallel::detach(py, || {
+        model_frame(formula, data, &variable_names)
+    })?)
+}
+
+struct DataColumns<'a> {
+    data: ArrayView2<'a, f64>,
+    index: HashMap<&'a str, usize>,
+}
+
+impl<'a> DataColumns<'a> {
+    fn new(data: ArrayView2<'a, f64>, variable_names: &'a [String]) -> Result<Self, FormulaError> {
+        if data.ncols() != variable_names.len() {
+            return Err(FormulaError::DimensionMismatch {
+                expected: variable_names.len(),
+                got: data.ncols(),
+            });
+        }
+        let index = variable_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+        Ok(Self { data, index })
+    }
+
+    fn column(&self, name: &str) -> Result<Vec<f64>, FormulaError> {
+        let j = *self
+            .index
+            .get(name)
+            .ok_or_else(|| FormulaError::UnknownVariable(name.to_string()))?;
+        Ok(self.data.column(j).to_vec())
+    }
+
+    /// Columns of `component`; with `full` a factor gets one indicator per
+    /// level instead of its contrasts.
+    fn expand(
+        &self,
+        component: &Component,
+        full: bool,
+    ) -> Result<(Vec<String>, Vec<Vec<f64>>), FormulaError> {
+        match component {
+            Component::Factor { variable, .. } if full => {
+                let x = self.column(variable)?;
+                let levels = factor_levels(&x);
+                let names = levels
+                    .iter()
+                    .map(|level| format!("{}{}", variable, level))
+                    .collect();
+                let columns = levels
+                    .iter()
+                    .map(|&level| {
+                        x.iter()
+                            .map(|&v| {
+                                if v.is_nan() {
+                                    f64::NAN
+                                } else {
+                                    f64::from(u8::from(v == level))
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+                Ok((names, columns))
+            }
+            Component::Numeric(name) => Ok((vec![name.clone()], vec![self.column(name)?])),
+            Component::Factor { variable, contrast } => {
+                let x = self.column(variable)?;
+                let levels = factor_levels(&x);
+                let k = levels.len();
+                let level_of = |v: f64| levels.iter().position(|&l| l == v);
+                let coding = |level: usize, column: usize| -> f64 {
+                    match contrast {
+                        Contrast::Treatment => f64::from(u8::from(level == column + 1)),
+                        Contrast::Sum if level == k - 1 => -1.0,
+                        Contrast::Sum => f64::from(u8::from(level == column)),
+                        Contrast::Helmert if level <= column => -1.0,
+                        Contrast::Helmert if level == column + 1 => (column + 1) as f64,
+                        Contrast::Helmert => 0.0,
+                    }
+                };
+                let names = (0..k.saturating_sub(1))
+                    .map(|c| match contrast {
+                        Contrast::Treatment => format!("{}{}", variable, levels[c + 1]),
+                        _ => format!("{}{}", variable, c + 1),
+                    })
+                    .collect();
+                let columns = (0..k.saturating_sub(1))
+                    .map(|c| {
+                        x.iter()
+                            .map(|&v| level_of(v).map_or(f64::NAN, |level| coding(level, c)))
+                            .collect()
+                    })
+                    .collect();
+                Ok((names, columns))
+            }
+            Component::BSpline { variable, df } => {
+                let x = self.column(variable)?;
+                let basis = bspline_basis(&x, *df)
+                    .ok_or_else(|| FormulaError::InvalidArgument(format!("bs({})", variable)))?;
+                let names = (1..=*df)
+                    .map(|j| format!("bs({}){}", variable, j))
+                    .collect();
+                Ok((names, basis))
+            }
+            Component::PSpline { variable, df } => {
+                let x = self.column(variable)?;
+                let basis = pspline_basis(&x, *df).ok_or_else(|| {
+                    FormulaError::InvalidArgument(format!("pspline({})", variable))
+                })?;
+                let names = (2..basis.len() + 2)
+                    .map(|j| format!("ps({}){}", variable, j))
+                    .collect();
+                Ok((names, basis))
+            }
+        }
+    }
+}
+
+fn factor_levels(x: &[f64]) -> Vec<f64> {
+    let mut levels: Vec<f64> = x.iter().copied().filter(|v| !v.is_nan()).collect();
+    levels.sort_by(f64::total_cmp);
+    levels.dedup();
+    levels
+}
+
+fn bspline_basis(x: &[f64], df: usize) -> Option<Vec<Vec<f64>>> {
+    const DEGREE: usize = 3;
+    let mut sorted: Vec<f64> = x.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(f64::total_cmp);
+    let (&lo, &hi) = (sorted.first()?, sorted.last()?);
+    if df < DEGREE || hi <= lo {
+        return None;
+    }
+
+    // Interior knots at the quantiles of x, as in R's bs().
+    let interior = df - DEGREE;
+    let quantile = |p: f64| {
+        let h = p * (sorted.len() - 1) as f64;
+        let k = h.floor() as usize;
+        let upper = sorted[(k + 1).min(sorted.len() - 1)];
+        sorted[k] + (h - k as f64) * (upper - sorted[k])
+    };
+    let mut knots = vec![lo; DEGREE + 1];
+    knots.extend((1..=interior).map(|k| quantile(k as f64 / (interior + 1) as f64)));
+    knots.extend(std::iter::repeat_n(hi, DEGREE + 1));
+    let last_span = knots.len() - DEGREE - 2;
+
+    let mut columns = vec![vec![0.0; x.len()]; df];
+    for (i, &v) in x.iter().enumerate() {
+        if !v.is_finite() {
+            columns.iter_mut().for_each(|column| column[i] = f64::NAN);
+            continue;
+        }
+        let mut b: Vec<f64> = (0..knots.len() - 1)
+            .map(|s| {
+                let inside = knots[s] <= v && v < knots[s + 1];
+                f64::from(u8::from(inside || (s == last_span && v == hi)))
+            })
+            .collect();
+        for degree in 1..=DEGREE {
+            for s in 0..knots.len() - degree - 1 {
+                let left = knots[s + degree] - knots[s];
+                let right = knots[s + degree + 1] - knots[s + 1];
+                let mut value = 0.0;
+                if left > 0.0 {
+                    value += (v - knots[s]) / left * b[s];
+                }
+                if right > 0.0 {
+                    value += (knots[s + degree + 1] - v) / right * b[s + 1];
+                }
+                b[s] = value;
+            }
+        }
+        for (j, column) in columns.iter_mut().enumerate() {
+            column[i] = b[j + 1];
+        }
+    }
+    Some(columns)
+}
@@ -2,6 +2,9 @@ pub mod agexact;
 pub mod cluster;
 pub mod collapse;
 pub mod doloop;
+pub mod formula;
+pub mod parallel;
+pub mod persistence;
 pub mod survsplit;
 pub mod tmerge;
 pub mod validation;
@@ -0,0 +1,74 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyRuntimeError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+#[derive(Error, Debug)]
+pub enum ParallelError {
+    #[error("failed to build thread pool: {0}")]
+    Build(#[from] ThreadPoolBuildError),
+}
+
+#[cfg(feature = "python")]
+impl From<ParallelError> for PyErr {
+    fn from(err: ParallelError) -> PyErr {
+        PyRuntimeError::new_err(err.to_string())
+    }
+}
+
+fn configured_pool() -> Option<Arc<ThreadPool>> {
+    POOL.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Sets the number of worker threads used by the parallel estimators. `None`
+/// or `0` falls back to rayon's global pool (one thread per logical CPU, or
+/// `RAYON_NUM_THREADS`). Work is always split the same way, so results do not
+/// depend on this setting.
+pub fn set_num_threads(num_threads: Option<usize>) -> Result<(), ParallelError> {
+    let pool = match num_threads {
+        Some(n) if n > 0 => Some(Arc::new(ThreadPoolBuilder::new().num_threads(n).build()?)),
+        _ => None,
+    };
+    *POOL.write().unwrap_or_else(|e| e.into_inner()) = pool;
+    Ok(())
+}
+
+pub fn num_threads() -> usize {
+    configured_pool().map_or_else(rayon::current_num_threads, |pool| {
+        pool.current_num_threads()
+    })
+}
+
+/// Runs `op` on the configured thread pool so that any parallel iterators it
+/// uses honour [`set_num_threads`].
+pub fn install<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    match configured_pool() {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Releases the GIL and runs `op` on the configured thread pool.
+#[cfg(feature = "python")]
+pub fn detach<R: Send>(py: Python<'_>, op: impl FnOnce() -> R + Send) -> R {
+    py.detach(|| install(op))
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "set_num_threads", signature = (num_threads=None))]
+pub fn set_num_threads_py(num_threads: Option<usize>) -> PyResult<()> {
+    Ok(set_num_threads(num_threads)?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "get_num_threads")]
+pub fn num_threads_py() -> usize {
+    num_threads()
+}
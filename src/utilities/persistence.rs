@@ -0,0 +1,369 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIOError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::{Deserialize, Serialize, forward_to_deserialize_any};
+use serde_json::Value;
+use std::path::Path;
+use thiserror::Error;
+
+/// Version of the on-disk model format. Bump when a persisted struct changes
+/// in a way older readers cannot handle.
+pub const FORMAT_VERSION: u32 = 1;
+
+const BINARY_MAGIC: &[u8; 8] = b"SURVMODL";
+
+#[derive(Error, Debug)]
+pub enum PersistError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("binary encoding error: {0}")]
+    Encode(#[from] bincode::error::EncodeError),
+    #[error("binary decoding error: {0}")]
+    Decode(#[from] bincode::error::DecodeError),
+    #[error("unsupported model format version {found}, this build reads up to {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("expected a saved {expected}, found {found}")]
+    WrongModelType { expected: String, found: String },
+    #[error("unknown model format '{0}', expected 'json' or 'binary'")]
+    UnknownFormat(String),
+}
+
+#[cfg(feature = "python")]
+impl From<PersistError> for PyErr {
+    fn from(err: PersistError) -> PyErr {
+        match err {
+            PersistError::Io(_) => PyIOError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+    Json,
+    Binary,
+}
+
+impl ModelFormat {
+    pub fn parse(name: &str) -> Result<Self, PersistError> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "binary" | "bin" => Ok(Self::Binary),
+            _ => Err(PersistError::UnknownFormat(name.to_string())),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    format_version: u32,
+    model_type: String,
+    model: T,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    format_version: u32,
+    model_type: String,
+}
+
+fn check_header(header: &Header, expected: &str) -> Result<(), PersistError> {
+    if header.format_version > FORMAT_VERSION {
+        return Err(PersistError::UnsupportedVersion {
+            found: header.format_version,
+            supported: FORMAT_VERSION,
+        });
+    }
+    if header.model_type != expected {
+        return Err(PersistError::WrongModelType {
+            expected: expected.to_string(),
+            found: header.model_type.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// JSON has no representation for NaN or infinity, so serde_json writes them
+/// as `null`. This deserializer reads a `null` where a float is expected back
+/// as NaN.
+struct NullAsNan(Value);
+
+impl<'de> Deserializer<'de> for NullAsNan {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(values) => visitor.visit_seq(NullAsNanSeq(values.into_iter())),
+            Value::Object(map) => visitor.visit_map(NullAsNanMap {
+                entries: map.into_iter(),
+                value: None,
+            }),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_f64(f64::NAN),
+            other => other.deserialize_f64(visitor),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_f32(f32::NAN),
+            other => other.deserialize_f32(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct NullAsNanSeq(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for NullAsNanSeq {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(NullAsNan(value)))
+            .transpose()
+    }
+}
+
+struct NullAsNanMap {
+    entries: serde_json::map::IntoIter,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for NullAsNanMap {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        seed.deserialize(NullAsNan(self.value.take().unwrap_or(Value::Null)))
+    }
+}
+
+/// Versioned JSON and binary serialization for fitted models. The binary
+/// format is an 8-byte magic header followed by a bincode-encoded envelope;
+/// `load` detects which format a file uses. JSON stores NaN and infinite
+/// values as `null`, which load back as NaN; the binary format is exact.
+pub trait Persist: Serialize + DeserializeOwned {
+    const MODEL_TYPE: &'static str;
+
+    fn to_json(&self) -> Result<String, PersistError> {
+        Ok(serde_json::to_string_pretty(&Envelope {
+            format_version: FORMAT_VERSION,
+            model_type: Self::MODEL_TYPE.to_string(),
+            model: self,
+        })?)
+    }
+
+    fn from_json(json: &str) -> Result<Self, PersistError> {
+        let header: Header = serde_json::from_str(json)?;
+        check_header(&header, Self::MODEL_TYPE)?;
+        let value: Value = serde_json::from_str(json)?;
+        let envelope = Envelope::<Self>::deserialize(NullAsNan(value))?;
+        Ok(envelope.model)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, PersistError> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        let header = (FORMAT_VERSION, Self::MODEL_TYPE.to_string());
+        bytes.extend(bincode::serde::encode_to_vec(
+            &header,
+            bincode::config::standard(),
+        )?);
+        bytes.extend(bincode::serde::encode_to_vec(
+            self,
+            bincode::config::standard(),
+        )?);
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, PersistError> {
+        let body = bytes.strip_prefix(BINARY_MAGIC.as_slice()).ok_or_else(|| {
+            PersistError::WrongModelType {
+                expected: Self::MODEL_TYPE.to_string(),
+                found: "data without a model header".to_string(),
+            }
+        })?;
+        let ((format_version, model_type), read): ((u32, String), usize) =
+            bincode::serde::decode_from_slice(body, bincode::config::standard())?;
+        check_header(
+            &Header {
+                format_version,
+                model_type,
+            },
+            Self::MODEL_TYPE,
+        )?;
+        let (model, _): (Self, usize) =
+            bincode::serde::decode_from_slice(&body[read..], bincode::config::standard())?;
+        Ok(model)
+    }
+
+    fn save(&self, path: impl AsRef<Path>, format: ModelFormat) -> Result<(), PersistError> {
+        let bytes = match format {
+            ModelFormat::Json => self.to_json()?.into_bytes(),
+            ModelFormat::Binary => self.to_bytes()?,
+        };
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    fn load(path: impl AsRef<Path>) -> Result<Self, PersistError> {
+        let bytes = std::fs::read(path)?;
+        if bytes.starts_with(BINARY_MAGIC) {
+            Self::from_bytes(&bytes)
+        } else {
+            Self::from_json(&String::from_utf8_lossy(&bytes))
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+pub(crate) fn py_save<T: Persist>(model: &T, path: &str, format: &str) -> PyResult<()> {
+    Ok(model.save(path, ModelFormat::parse(format)?)?)
+}
+
+#[cfg(feature = "python")]
+pub(crate) fn py_to_bytes<'py, T: Persist>(
+    py: Python<'py>,
+    model: &T,
+) -> PyResult<Bound<'py, PyBytes>> {
+    Ok(PyBytes::new(py, &model.to_bytes()?))
+}
+
+/// `__reduce__` implementation that pickles through `type(model).from_bytes`.
+#[cfg(feature = "python")]
+pub(crate) fn py_reduce<'py, T: Persist>(
+    slf: &Bound<'py, PyAny>,
+    model: &T,
+) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyBytes>,))> {
+    let constructor = slf.get_type().getattr("from_bytes")?;
+    Ok((constructor, (py_to_bytes(slf.py(), model)?,)))
+}
+
+/// Implements [`Persist`] for a model type and, with the `python` feature,
+/// its `save`, `load`, `to_json`, `from_json`, `to_bytes` and `from_bytes`
+/// methods and pickling support.
+macro_rules! impl_persist {
+    ($model:ident) => {
+        impl $crate::utilities::persistence::Persist for $model {
+            const MODEL_TYPE: &'static str = stringify!($model);
+        }
+
+        #[cfg(feature = "python")]
+        #[pyo3::pymethods]
+        impl $model {
+            #[pyo3(name = "save", signature = (path, format = "json"))]
+            fn py_save(&self, path: &str, format: &str) -> pyo3::PyResult<()> {
+                $crate::utilities::persistence::py_save(self, path, format)
+            }
+
+            #[staticmethod]
+            #[pyo3(name = "load")]
+            fn py_load(path: &str) -> pyo3::PyResult<Self> {
+                Ok(<Self as $crate::utilities::persistence::Persist>::load(
+                    path,
+                )?)
+            }
+
+            #[pyo3(name = "to_json")]
+            fn py_to_json(&self) -> pyo3::PyResult<String> {
+                Ok(<Self as $crate::utilities::persistence::Persist>::to_json(
+                    self,
+                )?)
+            }
+
+            #[staticmethod]
+            #[pyo3(name = "from_json")]
+            fn py_from_json(json: &str) -> pyo3::PyResult<Self> {
+                Ok(<Self as $crate::utilities::persistence::Persist>::from_json(json)?)
+            }
+
+            #[pyo3(name = "to_bytes")]
+            fn py_to_bytes<'py>(
+                &self,
+                py: pyo3::Python<'py>,
+            ) -> pyo3::PyResult<pyo3::Bound<'py, pyo3::types::PyBytes>> {
+                $crate::utilities::persistence::py_to_bytes(py, self)
+            }
+
+            #[staticmethod]
+            #[pyo3(name = "from_bytes")]
+            fn py_from_bytes(data: &[u8]) -> pyo3::PyResult<Self> {
+                Ok(<Self as $crate::utilities::persistence::Persist>::from_bytes(data)?)
+            }
+
+            fn __reduce__<'py>(
+                slf: &pyo3::Bound<'py, Self>,
+            ) -> pyo3::PyResult<(
+                pyo3::Bound<'py, pyo3::PyAny>,
+                (pyo3::Bound<'py, pyo3::types::PyBytes>,),
+            )> {
+                $crate::utilities::persistence::py_reduce(
+                    pyo3::Bound::as_any(slf),
+                    &*pyo3::Bound::borrow(slf),
+                )
+            }
+        }
+    };
+}
+pub(crate) use impl_persist;
@@ -1,21 +1,17 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 #[derive(Clone)]
 pub struct SplitResult {
-    #[pyo3(get)]
     pub row: Vec<usize>,
-    #[pyo3(get)]
     pub interval: Vec<usize>,
-    #[pyo3(get)]
     pub start: Vec<f64>,
-    #[pyo3(get)]
     pub end: Vec<f64>,
-    #[pyo3(get)]
     pub censor: Vec<bool>,
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn survsplit(tstart: Vec<f64>, tstop: Vec<f64>, cut: Vec<f64>) -> SplitResult {
     let n = tstart.len();
     let ncut = cut.len();
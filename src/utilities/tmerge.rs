@@ -1,6 +1,7 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn tmerge(
     id: Vec<i32>,
     time1: Vec<f64>,
@@ -48,7 +49,7 @@ pub fn tmerge(
     result
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn tmerge2(id: Vec<i32>, time1: Vec<f64>, nid: Vec<i32>, ntime: Vec<f64>) -> Vec<usize> {
     let n1 = id.len();
     let n2 = nid.len();
@@ -78,7 +79,7 @@ pub fn tmerge2(id: Vec<i32>, time1: Vec<f64>, nid: Vec<i32>, ntime: Vec<f64>) ->
     result
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn tmerge3(id: Vec<i32>, miss: Vec<bool>) -> Vec<usize> {
     let n = id.len();
     let mut result = vec![0; n];
@@ -1,4 +1,6 @@
+#[cfg(feature = "python")]
 use pyo3::PyErr;
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyValueError;
 use std::fmt;
 
@@ -75,6 +77,7 @@ impl fmt::Display for ValidationError {
 
 impl std::error::Error for ValidationError {}
 
+#[cfg(feature = "python")]
 impl From<ValidationError> for PyErr {
     fn from(err: ValidationError) -> PyErr {
         PyValueError::new_err(err.to_string())
@@ -1,46 +1,100 @@
-use ndarray::Array2;
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::{Array1, Array2};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
+use statrs::distribution::{ContinuousCDF, Normal};
+use std::collections::BTreeMap;
+
+type BootstrapError = Box<dyn std::error::Error + Send + Sync>;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct BootstrapResult {
-    #[pyo3(get)]
     pub coefficients: Vec<f64>,
-    #[pyo3(get)]
     pub std_errors: Vec<f64>,
-    #[pyo3(get)]
     pub ci_lower: Vec<f64>,
-    #[pyo3(get)]
     pub ci_upper: Vec<f64>,
-    #[pyo3(get)]
     pub bootstrap_samples: Vec<Vec<f64>>,
+    pub ci_method: String,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl BootstrapResult {
     #[new]
+    #[pyo3(signature = (coefficients, std_errors, ci_lower, ci_upper, bootstrap_samples, ci_method=String::from("percentile")))]
     fn new(
-        coefficients: Vec<f64>,
-        std_errors: Vec<f64>,
-        ci_lower: Vec<f64>,
-        ci_upper: Vec<f64>,
-        bootstrap_samples: Vec<Vec<f64>>,
+        coefficients: VectorArg<'_, f64>,
+        std_errors: VectorArg<'_, f64>,
+        ci_lower: VectorArg<'_, f64>,
+        ci_upper: VectorArg<'_, f64>,
+        bootstrap_samples: MatrixArg<'_, f64>,
+        ci_method: String,
     ) -> Self {
+        let coefficients = coefficients.into_vec();
+        let std_errors = std_errors.into_vec();
+        let ci_lower = ci_lower.into_vec();
+        let ci_upper = ci_upper.into_vec();
+        let bootstrap_samples = bootstrap_samples.into_rows();
         Self {
             coefficients,
             std_errors,
             ci_lower,
             ci_upper,
             bootstrap_samples,
+            ci_method,
         }
     }
 }
 
+/// How confidence intervals are formed from the bootstrap replicates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BootstrapCiMethod {
+    #[default]
+    Percentile,
+    /// Bias-corrected and accelerated; the acceleration comes from a
+    /// leave-one-unit-out jackknife, and every unit's refit must succeed.
+    Bca,
+    /// Studentized intervals using each replicate's model-based standard error.
+    PercentileT,
+}
+
+impl BootstrapCiMethod {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "percentile" => Ok(Self::Percentile),
+            "bca" => Ok(Self::Bca),
+            "percentile_t" | "percentile-t" | "studentized" => Ok(Self::PercentileT),
+            other => Err(format!(
+                "unknown ci_method '{other}', expected 'percentile', 'bca' or 'percentile_t'"
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Percentile => "percentile",
+            Self::Bca => "bca",
+            Self::PercentileT => "percentile_t",
+        }
+    }
+}
+
+/// `strata` restricts resampling to within strata (the fitted model is not
+/// stratified); `cluster` resamples whole subjects instead of rows.
 pub struct BootstrapConfig {
     pub n_bootstrap: usize,
     pub confidence_level: f64,
     pub seed: Option<u64>,
+    pub ci_method: BootstrapCiMethod,
+    pub strata: Option<Vec<usize>>,
+    pub cluster: Option<Vec<usize>>,
 }
 
 impl Default for BootstrapConfig {
@@ -49,263 +103,398 @@ impl Default for BootstrapConfig {
             n_bootstrap: 1000,
             confidence_level: 0.95,
             seed: None,
+            ci_method: BootstrapCiMethod::Percentile,
+            strata: None,
+            cluster: None,
         }
     }
 }
 
-fn simple_rng(seed: u64, index: usize) -> u64 {
-    let mut state = seed.wrapping_add(index as u64);
-    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
-    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
-    state
+/// Replicate `iteration` draws from its own ChaCha stream of `seed`, so
+/// replicates are independent and reproducible regardless of scheduling.
+fn replicate_rng(seed: u64, iteration: usize) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(iteration as u64);
+    rng
 }
 
-fn bootstrap_sample_indices(n: usize, seed: u64, iteration: usize) -> Vec<usize> {
-    let mut indices = Vec::with_capacity(n);
-    for i in 0..n {
-        let rng_val = simple_rng(seed.wrapping_add(iteration as u64), i);
-        indices.push((rng_val as usize) % n);
+pub(crate) fn bootstrap_sample_indices(n: usize, seed: u64, iteration: usize) -> Vec<usize> {
+    if n == 0 {
+        return Vec::new();
     }
-    indices
+    let mut rng = replicate_rng(seed, iteration);
+    (0..n).map(|_| rng.gen_range(0..n)).collect()
 }
 
-pub fn bootstrap_cox(
-    time: &[f64],
-    status: &[i32],
-    covariates: &Array2<f64>,
-    weights: Option<&[f64]>,
-    config: &BootstrapConfig,
-) -> Result<BootstrapResult, Box<dyn std::error::Error + Send + Sync>> {
-    use crate::regression::coxfit6::{CoxFit, Method as CoxMethod};
-    use ndarray::Array1;
+/// Resampling units (single rows or clusters of rows) grouped by stratum.
+pub(crate) struct Resampler {
+    units: Vec<Vec<usize>>,
+    strata: Vec<Vec<usize>>,
+}
 
-    let n = time.len();
-    let nvar = covariates.nrows();
+impl Resampler {
+    pub(crate) fn new(
+        n: usize,
+        strata: Option<&[usize]>,
+        cluster: Option<&[usize]>,
+    ) -> Result<Self, BootstrapError> {
+        if strata.is_some_and(|s| s.len() != n) {
+            return Err("strata must have one entry per observation".into());
+        }
+        if cluster.is_some_and(|c| c.len() != n) {
+            return Err("cluster must have one entry per observation".into());
+        }
 
-    let default_weights: Vec<f64> = vec![1.0; n];
-    let weights = weights.unwrap_or(&default_weights);
+        let units: Vec<Vec<usize>> = match cluster {
+            Some(cluster) => {
+                let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+                for (i, &c) in cluster.iter().enumerate() {
+                    groups.entry(c).or_default().push(i);
+                }
+                groups.into_values().collect()
+            }
+            None => (0..n).map(|i| vec![i]).collect(),
+        };
+
+        let mut by_stratum: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (u, rows) in units.iter().enumerate() {
+            let stratum = strata.map_or(0, |s| s[rows[0]]);
+            if let Some(s) = strata
+                && rows.iter().any(|&i| s[i] != stratum)
+            {
+                return Err("a cluster cannot span more than one stratum".into());
+            }
+            by_stratum.entry(stratum).or_default().push(u);
+        }
 
-    let mut sorted_indices: Vec<usize> = (0..n).collect();
-    sorted_indices.sort_by(|&a, &b| {
-        time[b]
-            .partial_cmp(&time[a])
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+        Ok(Self {
+            units,
+            strata: by_stratum.into_values().collect(),
+        })
+    }
 
-    let sorted_time: Vec<f64> = sorted_indices.iter().map(|&i| time[i]).collect();
-    let sorted_status: Vec<i32> = sorted_indices.iter().map(|&i| status[i]).collect();
-    let sorted_weights: Vec<f64> = sorted_indices.iter().map(|&i| weights[i]).collect();
+    pub(crate) fn n_units(&self) -> usize {
+        self.units.len()
+    }
 
-    let mut sorted_covariates = Array2::zeros((n, nvar));
-    for (new_idx, &orig_idx) in sorted_indices.iter().enumerate() {
-        for var in 0..nvar {
-            sorted_covariates[[new_idx, var]] = covariates[[var, orig_idx]];
+    /// Rows of replicate `iteration`: each stratum keeps its number of units.
+    pub(crate) fn replicate(&self, seed: u64, iteration: usize) -> Vec<usize> {
+        let mut rng = replicate_rng(seed, iteration);
+        let mut rows = Vec::new();
+        for stratum in &self.strata {
+            for _ in 0..stratum.len() {
+                let unit = stratum[rng.gen_range(0..stratum.len())];
+                rows.extend_from_slice(&self.units[unit]);
+            }
         }
+        rows
     }
 
-    let initial_beta: Vec<f64> = vec![0.0; nvar];
-
-    let time_arr = Array1::from_vec(sorted_time.clone());
-    let status_arr = Array1::from_vec(sorted_status.clone());
-    let strata_arr = Array1::from_elem(n, 0i32);
-    let offset_arr = Array1::from_elem(n, 0.0);
-    let weights_arr = Array1::from_vec(sorted_weights.clone());
-
-    let mut original_fit = CoxFit::new(
-        time_arr,
-        status_arr,
-        sorted_covariates.clone(),
-        strata_arr,
-        offset_arr,
-        weights_arr,
-        CoxMethod::Breslow,
-        25,
-        1e-9,
-        1e-9,
-        vec![true; nvar],
-        initial_beta.clone(),
-    )?;
-
-    original_fit.fit()?;
-    let (original_beta, _, _, _, _, _, _, _) = original_fit.results();
+    fn without_unit(&self, unit: usize) -> Vec<usize> {
+        self.units
+            .iter()
+            .enumerate()
+            .filter(|&(u, _)| u != unit)
+            .flat_map(|(_, rows)| rows.iter().copied())
+            .collect()
+    }
+}
 
-    let seed = config.seed.unwrap_or(42);
+struct Estimate {
+    coefficients: Vec<f64>,
+    std_errors: Vec<f64>,
+}
 
-    let bootstrap_coefs: Vec<Vec<f64>> = (0..config.n_bootstrap)
-        .into_par_iter()
-        .filter_map(|b| {
-            let indices = bootstrap_sample_indices(n, seed, b);
+fn sorted(mut values: Vec<f64>) -> Vec<f64> {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    values
+}
 
-            let boot_time: Vec<f64> = indices.iter().map(|&i| sorted_time[i]).collect();
-            let boot_status: Vec<i32> = indices.iter().map(|&i| sorted_status[i]).collect();
-            let boot_weights: Vec<f64> = indices.iter().map(|&i| sorted_weights[i]).collect();
+fn order_statistic(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let idx = (p * sorted.len() as f64).max(0.0) as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
 
-            let mut boot_covariates = Array2::zeros((n, nvar));
-            for (new_idx, &orig_idx) in indices.iter().enumerate() {
-                for var in 0..nvar {
-                    boot_covariates[[new_idx, var]] = sorted_covariates[[orig_idx, var]];
-                }
-            }
+fn jackknife_acceleration(jackknife: &[f64]) -> f64 {
+    if jackknife.is_empty() {
+        return 0.0;
+    }
+    let mean = jackknife.iter().sum::<f64>() / jackknife.len() as f64;
+    let (num, den) = jackknife.iter().fold((0.0, 0.0), |(num, den), &theta| {
+        let d = mean - theta;
+        (num + d.powi(3), den + d * d)
+    });
+    if den > 0.0 {
+        num / (6.0 * den.powf(1.5))
+    } else {
+        0.0
+    }
+}
 
-            let mut boot_indices: Vec<usize> = (0..n).collect();
-            boot_indices.sort_by(|&a, &b| {
-                boot_time[b]
-                    .partial_cmp(&boot_time[a])
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
-
-            let resorted_time: Vec<f64> = boot_indices.iter().map(|&i| boot_time[i]).collect();
-            let resorted_status: Vec<i32> = boot_indices.iter().map(|&i| boot_status[i]).collect();
-            let resorted_weights: Vec<f64> =
-                boot_indices.iter().map(|&i| boot_weights[i]).collect();
-
-            let mut resorted_covariates = Array2::zeros((n, nvar));
-            for (new_idx, &orig_idx) in boot_indices.iter().enumerate() {
-                for var in 0..nvar {
-                    resorted_covariates[[new_idx, var]] = boot_covariates[[orig_idx, var]];
-                }
-            }
+/// Runs the replicates, then forms standard errors and intervals. `fit` maps
+/// row indices into the original data to an estimate, or `None` if that
+/// resample could not be fitted.
+fn resample<F>(
+    estimate: Estimate,
+    resampler: &Resampler,
+    config: &BootstrapConfig,
+    fit: F,
+) -> Result<BootstrapResult, BootstrapError>
+where
+    F: Fn(&[usize]) -> Option<Estimate> + Sync,
+{
+    let ncoef = estimate.coefficients.len();
+    let seed = config.seed.unwrap_or(42);
 
-            let time_arr = Array1::from_vec(resorted_time);
-            let status_arr = Array1::from_vec(resorted_status);
-            let strata_arr = Array1::from_elem(n, 0i32);
-            let offset_arr = Array1::from_elem(n, 0.0);
-            let weights_arr = Array1::from_vec(resorted_weights);
-
-            match CoxFit::new(
-                time_arr,
-                status_arr,
-                resorted_covariates,
-                strata_arr,
-                offset_arr,
-                weights_arr,
-                CoxMethod::Breslow,
-                25,
-                1e-9,
-                1e-9,
-                vec![true; nvar],
-                initial_beta.clone(),
-            ) {
-                Ok(mut fit) => {
-                    if fit.fit().is_ok() {
-                        let (beta, _, _, _, _, _, _, _) = fit.results();
-                        Some(beta)
-                    } else {
-                        None
-                    }
-                }
-                Err(_) => None,
-            }
-        })
+    let replicates: Vec<Estimate> = (0..config.n_bootstrap)
+        .into_par_iter()
+        .filter_map(|b| fit(&resampler.replicate(seed, b)))
+        .filter(|rep| rep.coefficients.len() == ncoef)
         .collect();
 
-    let actual_n_bootstrap = bootstrap_coefs.len();
+    let actual_n_bootstrap = replicates.len();
     if actual_n_bootstrap == 0 {
         return Err("All bootstrap iterations failed".into());
     }
 
-    let mut means = vec![0.0; nvar];
-    for coefs in &bootstrap_coefs {
-        for (i, &c) in coefs.iter().enumerate() {
-            means[i] += c;
-        }
-    }
-    for m in &mut means {
-        *m /= actual_n_bootstrap as f64;
-    }
-
-    let mut std_errors = vec![0.0; nvar];
-    for coefs in &bootstrap_coefs {
-        for (i, &c) in coefs.iter().enumerate() {
-            std_errors[i] += (c - means[i]).powi(2);
+    let jackknife: Vec<Vec<f64>> = if config.ci_method == BootstrapCiMethod::Bca {
+        let refits: Vec<Option<Vec<f64>>> = (0..resampler.n_units())
+            .into_par_iter()
+            .map(|u| {
+                fit(&resampler.without_unit(u))
+                    .filter(|rep| rep.coefficients.len() == ncoef)
+                    .map(|rep| rep.coefficients)
+            })
+            .collect();
+        let failed = refits.iter().filter(|rep| rep.is_none()).count();
+        if failed > 0 {
+            return Err(format!(
+                "BCa jackknife refit failed for {failed} of {} units",
+                refits.len()
+            )
+            .into());
         }
-    }
-    for se in &mut std_errors {
-        *se = (*se / (actual_n_bootstrap - 1) as f64).sqrt();
-    }
+        refits.into_iter().flatten().collect()
+    } else {
+        Vec::new()
+    };
 
+    let normal = Normal::new(0.0, 1.0).expect("standard normal parameters are valid");
     let alpha = 1.0 - config.confidence_level;
-    let lower_percentile = (alpha / 2.0 * actual_n_bootstrap as f64) as usize;
-    let upper_percentile = ((1.0 - alpha / 2.0) * actual_n_bootstrap as f64) as usize;
+    let b = actual_n_bootstrap as f64;
 
-    let mut ci_lower = vec![0.0; nvar];
-    let mut ci_upper = vec![0.0; nvar];
+    let mut std_errors = vec![0.0; ncoef];
+    let mut ci_lower = vec![0.0; ncoef];
+    let mut ci_upper = vec![0.0; ncoef];
 
-    for var in 0..nvar {
-        let mut var_coefs: Vec<f64> = bootstrap_coefs.iter().map(|c| c[var]).collect();
-        var_coefs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        ci_lower[var] = var_coefs[lower_percentile.min(actual_n_bootstrap - 1)];
-        ci_upper[var] = var_coefs[upper_percentile.min(actual_n_bootstrap - 1)];
+    for var in 0..ncoef {
+        let theta_hat = estimate.coefficients[var];
+        let draws: Vec<f64> = replicates.iter().map(|r| r.coefficients[var]).collect();
+
+        let mean = draws.iter().sum::<f64>() / b;
+        let ss: f64 = draws.iter().map(|&c| (c - mean).powi(2)).sum();
+        std_errors[var] = (ss / (actual_n_bootstrap.max(2) - 1) as f64).sqrt();
+
+        (ci_lower[var], ci_upper[var]) = match config.ci_method {
+            BootstrapCiMethod::Percentile => {
+                let draws = sorted(draws);
+                (
+                    order_statistic(&draws, alpha / 2.0),
+                    order_statistic(&draws, 1.0 - alpha / 2.0),
+                )
+            }
+            BootstrapCiMethod::Bca => {
+                let below = draws.iter().filter(|&&c| c < theta_hat).count() as f64;
+                let z0 = normal.inverse_cdf((below / b).clamp(0.5 / b, 1.0 - 0.5 / b));
+                let jack: Vec<f64> = jackknife.iter().map(|c| c[var]).collect();
+                let a = jackknife_acceleration(&jack);
+                let adjusted = |p: f64| {
+                    let z = z0 + normal.inverse_cdf(p);
+                    normal.cdf(z0 + z / (1.0 - a * z))
+                };
+                let draws = sorted(draws);
+                (
+                    order_statistic(&draws, adjusted(alpha / 2.0)),
+                    order_statistic(&draws, adjusted(1.0 - alpha / 2.0)),
+                )
+            }
+            BootstrapCiMethod::PercentileT => {
+                let se_hat = estimate.std_errors.get(var).copied().unwrap_or(f64::NAN);
+                let t = sorted(
+                    replicates
+                        .iter()
+                        .filter_map(|r| {
+                            let se = *r.std_errors.get(var)?;
+                            (se.is_finite() && se > 0.0)
+                                .then(|| (r.coefficients[var] - theta_hat) / se)
+                        })
+                        .collect(),
+                );
+                (
+                    theta_hat - order_statistic(&t, 1.0 - alpha / 2.0) * se_hat,
+                    theta_hat - order_statistic(&t, alpha / 2.0) * se_hat,
+                )
+            }
+        };
     }
 
     Ok(BootstrapResult {
-        coefficients: original_beta,
+        coefficients: estimate.coefficients,
         std_errors,
         ci_lower,
         ci_upper,
-        bootstrap_samples: bootstrap_coefs,
+        bootstrap_samples: replicates.into_iter().map(|r| r.coefficients).collect(),
+        ci_method: config.ci_method.name().to_string(),
+    })
+}
+
+/// Fits a Breslow Cox model to the given rows of the data (covariates are
+/// `nvar x n`), ordering them by ascending time as `CoxFit` expects.
+fn fit_cox_rows(
+    time: &[f64],
+    status: &[i32],
+    covariates: &Array2<f64>,
+    weights: &[f64],
+    rows: &[usize],
+) -> Result<Estimate, BootstrapError> {
+    use crate::regression::coxfit6::{CoxFit, Method as CoxMethod};
+
+    let n = rows.len();
+    let nvar = covariates.nrows();
+
+    let mut order = rows.to_vec();
+    order.sort_by(|&a, &b| {
+        time[a]
+            .partial_cmp(&time[b])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut sorted_covariates = Array2::zeros((n, nvar));
+    for (new_idx, &orig_idx) in order.iter().enumerate() {
+        for var in 0..nvar {
+            sorted_covariates[[new_idx, var]] = covariates[[var, orig_idx]];
+        }
+    }
+
+    let mut fit = CoxFit::new(
+        order.iter().map(|&i| time[i]).collect::<Array1<f64>>(),
+        order.iter().map(|&i| status[i]).collect::<Array1<i32>>(),
+        sorted_covariates,
+        Array1::from_elem(n, 0i32),
+        Array1::from_elem(n, 0.0),
+        order.iter().map(|&i| weights[i]).collect::<Array1<f64>>(),
+        CoxMethod::Breslow,
+        25,
+        1e-9,
+        1e-9,
+        vec![true; nvar],
+        vec![0.0; nvar],
+    )?;
+    fit.fit()?;
+    let (beta, _, _, imat, _, _, _, _) = fit.results();
+    let std_errors = (0..nvar).map(|i| imat[[i, i]].sqrt()).collect();
+
+    Ok(Estimate {
+        coefficients: beta,
+        std_errors,
+    })
+}
+
+pub fn bootstrap_cox(
+    time: &[f64],
+    status: &[i32],
+    covariates: &Array2<f64>,
+    weights: Option<&[f64]>,
+    config: &BootstrapConfig,
+) -> Result<BootstrapResult, BootstrapError> {
+    let n = time.len();
+
+    let default_weights: Vec<f64> = vec![1.0; n];
+    let weights = weights.unwrap_or(&default_weights);
+
+    let resampler = Resampler::new(n, config.strata.as_deref(), config.cluster.as_deref())?;
+    let all_rows: Vec<usize> = (0..n).collect();
+    let estimate = fit_cox_rows(time, status, covariates, weights, &all_rows)?;
+
+    resample(estimate, &resampler, config, |rows| {
+        fit_cox_rows(time, status, covariates, weights, rows).ok()
     })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-#[pyo3(signature = (time, status, covariates, weights=None, n_bootstrap=None, confidence_level=None, seed=None))]
+#[pyo3(signature = (time, status, covariates, weights=None, n_bootstrap=None, confidence_level=None, seed=None, strata=None, cluster=None, ci_method=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn bootstrap_cox_ci(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    covariates: Vec<Vec<f64>>,
-    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    covariates: MatrixArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
     n_bootstrap: Option<usize>,
     confidence_level: Option<f64>,
     seed: Option<u64>,
+    strata: Option<IndexArg>,
+    cluster: Option<IndexArg>,
+    ci_method: Option<&str>,
 ) -> PyResult<BootstrapResult> {
-    let n = time.len();
-    let nvar = if !covariates.is_empty() {
-        covariates[0].len()
-    } else {
-        0
-    };
-
-    let cov_array = if nvar > 0 {
-        let flat: Vec<f64> = covariates.into_iter().flatten().collect();
-        let temp = Array2::from_shape_vec((n, nvar), flat)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
-        temp.t().to_owned()
-    } else {
-        Array2::zeros((0, n))
-    };
-
-    let config = BootstrapConfig {
-        n_bootstrap: n_bootstrap.unwrap_or(1000),
-        confidence_level: confidence_level.unwrap_or(0.95),
-        seed,
-    };
-
-    let weights_ref = weights.as_deref();
-
-    bootstrap_cox(&time, &status, &cov_array, weights_ref, &config)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let covariates = covariates.view();
+    let ci_method = ci_method
+        .map(BootstrapCiMethod::parse)
+        .transpose()
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?
+        .unwrap_or_default();
+    let strata = strata.map(IndexArg::into_vec);
+    let cluster = cluster.map(IndexArg::into_vec);
+    parallel::detach(py, || {
+        let n = time.len();
+
+        let cov_array = if covariates.nrows() > 0 && covariates.ncols() > 0 {
+            if covariates.nrows() != n {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "covariates must have one row per observation",
+                ));
+            }
+            covariates.t().to_owned()
+        } else {
+            Array2::zeros((0, n))
+        };
+
+        let config = BootstrapConfig {
+            n_bootstrap: n_bootstrap.unwrap_or(1000),
+            confidence_level: confidence_level.unwrap_or(0.95),
+            seed,
+            ci_method,
+            strata,
+            cluster,
+        };
+
+        let weights_ref = weights.as_deref();
+
+        bootstrap_cox(&time, &status, &cov_array, weights_ref, &config)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    })
 }
 
-pub fn bootstrap_survreg(
+fn fit_survreg_rows(
     time: &[f64],
     status: &[f64],
-    covariates: &Array2<f64>,
+    cov_vecs: &[Vec<f64>],
     distribution: &str,
-    config: &BootstrapConfig,
-) -> Result<BootstrapResult, Box<dyn std::error::Error + Send + Sync>> {
+    rows: &[usize],
+) -> Result<Estimate, BootstrapError> {
     use crate::regression::survreg6::survreg;
 
-    let n = time.len();
-    let nvar = covariates.ncols();
-
-    let cov_vecs: Vec<Vec<f64>> = (0..n)
-        .map(|i| (0..nvar).map(|j| covariates[[j, i]]).collect())
-        .collect();
-
-    let original = survreg(
-        time.to_vec(),
-        status.to_vec(),
-        cov_vecs.clone(),
+    let fit = survreg(
+        rows.iter().map(|&i| time[i]).collect(),
+        rows.iter().map(|&i| status[i]).collect(),
+        rows.iter().map(|&i| cov_vecs[i].clone()).collect(),
         None,
         None,
         None,
@@ -315,133 +504,97 @@ pub fn bootstrap_survreg(
         Some(1e-5),
         Some(1e-9),
     )?;
-
-    let seed = config.seed.unwrap_or(42);
-    let dist = distribution.to_string();
-
-    let bootstrap_coefs: Vec<Vec<f64>> = (0..config.n_bootstrap)
-        .into_par_iter()
-        .filter_map(|b| {
-            let indices = bootstrap_sample_indices(n, seed, b);
-
-            let boot_time: Vec<f64> = indices.iter().map(|&i| time[i]).collect();
-            let boot_status: Vec<f64> = indices.iter().map(|&i| status[i]).collect();
-            let boot_covariates: Vec<Vec<f64>> =
-                indices.iter().map(|&i| cov_vecs[i].clone()).collect();
-
-            match survreg(
-                boot_time,
-                boot_status,
-                boot_covariates,
-                None,
-                None,
-                None,
-                None,
-                Some(&dist),
-                Some(25),
-                Some(1e-5),
-                Some(1e-9),
-            ) {
-                Ok(result) => Some(result.coefficients),
-                Err(_) => None,
-            }
+    let std_errors = (0..fit.coefficients.len())
+        .map(|i| {
+            fit.variance_matrix
+                .get(i)
+                .and_then(|row| row.get(i))
+                .map_or(f64::NAN, |v| v.sqrt())
         })
         .collect();
 
-    let actual_n_bootstrap = bootstrap_coefs.len();
-    if actual_n_bootstrap == 0 {
-        return Err("All bootstrap iterations failed".into());
-    }
-
-    let ncoef = original.coefficients.len();
-    let mut means = vec![0.0; ncoef];
-    for coefs in &bootstrap_coefs {
-        for (i, &c) in coefs.iter().enumerate() {
-            if i < ncoef {
-                means[i] += c;
-            }
-        }
-    }
-    for m in &mut means {
-        *m /= actual_n_bootstrap as f64;
-    }
-
-    let mut std_errors = vec![0.0; ncoef];
-    for coefs in &bootstrap_coefs {
-        for (i, &c) in coefs.iter().enumerate() {
-            if i < ncoef {
-                std_errors[i] += (c - means[i]).powi(2);
-            }
-        }
-    }
-    for se in &mut std_errors {
-        *se = (*se / (actual_n_bootstrap - 1) as f64).sqrt();
-    }
+    Ok(Estimate {
+        coefficients: fit.coefficients,
+        std_errors,
+    })
+}
 
-    let alpha = 1.0 - config.confidence_level;
-    let lower_percentile = (alpha / 2.0 * actual_n_bootstrap as f64) as usize;
-    let upper_percentile = ((1.0 - alpha / 2.0) * actual_n_bootstrap as f64) as usize;
+pub fn bootstrap_survreg(
+    time: &[f64],
+    status: &[f64],
+    covariates: &Array2<f64>,
+    distribution: &str,
+    config: &BootstrapConfig,
+) -> Result<BootstrapResult, BootstrapError> {
+    let n = time.len();
+    let nvar = covariates.nrows();
 
-    let mut ci_lower = vec![0.0; ncoef];
-    let mut ci_upper = vec![0.0; ncoef];
+    let cov_vecs: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..nvar).map(|j| covariates[[j, i]]).collect())
+        .collect();
 
-    for var in 0..ncoef {
-        let mut var_coefs: Vec<f64> = bootstrap_coefs
-            .iter()
-            .filter_map(|c| c.get(var).copied())
-            .collect();
-        if var_coefs.is_empty() {
-            continue;
-        }
-        var_coefs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        ci_lower[var] = var_coefs[lower_percentile.min(var_coefs.len() - 1)];
-        ci_upper[var] = var_coefs[upper_percentile.min(var_coefs.len() - 1)];
-    }
+    let resampler = Resampler::new(n, config.strata.as_deref(), config.cluster.as_deref())?;
+    let all_rows: Vec<usize> = (0..n).collect();
+    let estimate = fit_survreg_rows(time, status, &cov_vecs, distribution, &all_rows)?;
 
-    Ok(BootstrapResult {
-        coefficients: original.coefficients,
-        std_errors,
-        ci_lower,
-        ci_upper,
-        bootstrap_samples: bootstrap_coefs,
+    resample(estimate, &resampler, config, |rows| {
+        fit_survreg_rows(time, status, &cov_vecs, distribution, rows).ok()
     })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-#[pyo3(signature = (time, status, covariates, distribution=None, n_bootstrap=None, confidence_level=None, seed=None))]
+#[pyo3(signature = (time, status, covariates, distribution=None, n_bootstrap=None, confidence_level=None, seed=None, strata=None, cluster=None, ci_method=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn bootstrap_survreg_ci(
-    time: Vec<f64>,
-    status: Vec<f64>,
-    covariates: Vec<Vec<f64>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, f64>,
+    covariates: MatrixArg<'_, f64>,
     distribution: Option<&str>,
     n_bootstrap: Option<usize>,
     confidence_level: Option<f64>,
     seed: Option<u64>,
+    strata: Option<IndexArg>,
+    cluster: Option<IndexArg>,
+    ci_method: Option<&str>,
 ) -> PyResult<BootstrapResult> {
-    let n = time.len();
-    let nvar = if !covariates.is_empty() {
-        covariates[0].len()
-    } else {
-        0
-    };
-
-    let cov_array = if nvar > 0 {
-        let flat: Vec<f64> = covariates.into_iter().flatten().collect();
-        let temp = Array2::from_shape_vec((n, nvar), flat)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
-        temp.t().to_owned()
-    } else {
-        Array2::zeros((0, n))
-    };
-
-    let config = BootstrapConfig {
-        n_bootstrap: n_bootstrap.unwrap_or(1000),
-        confidence_level: confidence_level.unwrap_or(0.95),
-        seed,
-    };
-
-    let dist = distribution.unwrap_or("weibull");
-
-    bootstrap_survreg(&time, &status, &cov_array, dist, &config)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let covariates = covariates.view();
+    let ci_method = ci_method
+        .map(BootstrapCiMethod::parse)
+        .transpose()
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?
+        .unwrap_or_default();
+    let strata = strata.map(IndexArg::into_vec);
+    let cluster = cluster.map(IndexArg::into_vec);
+    parallel::detach(py, || {
+        let n = time.len();
+
+        let cov_array = if covariates.nrows() > 0 && covariates.ncols() > 0 {
+            if covariates.nrows() != n {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "covariates must have one row per observation",
+                ));
+            }
+            covariates.t().to_owned()
+        } else {
+            Array2::zeros((0, n))
+        };
+
+        let config = BootstrapConfig {
+            n_bootstrap: n_bootstrap.unwrap_or(1000),
+            confidence_level: confidence_level.unwrap_or(0.95),
+            seed,
+            ci_method,
+            strata,
+            cluster,
+        };
+
+        let dist = distribution.unwrap_or("weibull");
+
+        bootstrap_survreg(&time, &status, &cov_array, dist, &config)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    })
 }
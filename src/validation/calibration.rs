@@ -1,40 +1,46 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+use crate::surv_analysis::survfitkm::survfitkm_internal;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::ArrayView2;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use rayon::prelude::*;
+use statrs::distribution::{ContinuousCDF, Normal};
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct CalibrationResult {
-    #[pyo3(get)]
     pub risk_groups: Vec<f64>,
-    #[pyo3(get)]
     pub predicted: Vec<f64>,
-    #[pyo3(get)]
     pub observed: Vec<f64>,
-    #[pyo3(get)]
     pub n_per_group: Vec<usize>,
-    #[pyo3(get)]
     pub hosmer_lemeshow_stat: f64,
-    #[pyo3(get)]
     pub hosmer_lemeshow_pvalue: f64,
-    #[pyo3(get)]
     pub calibration_slope: f64,
-    #[pyo3(get)]
     pub calibration_intercept: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl CalibrationResult {
     #[new]
     #[allow(clippy::too_many_arguments)]
     fn new(
-        risk_groups: Vec<f64>,
-        predicted: Vec<f64>,
-        observed: Vec<f64>,
-        n_per_group: Vec<usize>,
+        risk_groups: VectorArg<'_, f64>,
+        predicted: VectorArg<'_, f64>,
+        observed: VectorArg<'_, f64>,
+        n_per_group: IndexArg,
         hosmer_lemeshow_stat: f64,
         hosmer_lemeshow_pvalue: f64,
         calibration_slope: f64,
         calibration_intercept: f64,
     ) -> Self {
+        let risk_groups = risk_groups.into_vec();
+        let predicted = predicted.into_vec();
+        let observed = observed.into_vec();
+        let n_per_group = n_per_group.into_vec();
         Self {
             risk_groups,
             predicted,
@@ -264,43 +270,50 @@ fn calibration_regression(predicted: &[f64], observed: &[f64]) -> (f64, f64) {
     (slope, intercept)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (predicted_risk, observed_event, n_groups=None))]
 pub fn calibration(
-    predicted_risk: Vec<f64>,
-    observed_event: Vec<i32>,
+    py: Python<'_>,
+    predicted_risk: VectorArg<'_, f64>,
+    observed_event: VectorArg<'_, i32>,
     n_groups: Option<usize>,
 ) -> PyResult<CalibrationResult> {
-    let n_groups = n_groups.unwrap_or(10);
-    Ok(calibration_curve(
-        &predicted_risk,
-        &observed_event,
-        n_groups,
-    ))
+    let predicted_risk = predicted_risk.as_slice();
+    let observed_event = observed_event.as_slice();
+    parallel::detach(py, || {
+        let n_groups = n_groups.unwrap_or(10);
+        Ok(calibration_curve(
+            &predicted_risk,
+            &observed_event,
+            n_groups,
+        ))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct PredictionResult {
-    #[pyo3(get)]
     pub linear_predictor: Vec<f64>,
-    #[pyo3(get)]
     pub risk_score: Vec<f64>,
-    #[pyo3(get)]
     pub survival_prob: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub times: Vec<f64>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl PredictionResult {
     #[new]
     fn new(
-        linear_predictor: Vec<f64>,
-        risk_score: Vec<f64>,
-        survival_prob: Vec<Vec<f64>>,
-        times: Vec<f64>,
+        linear_predictor: VectorArg<'_, f64>,
+        risk_score: VectorArg<'_, f64>,
+        survival_prob: MatrixArg<'_, f64>,
+        times: VectorArg<'_, f64>,
     ) -> Self {
+        let linear_predictor = linear_predictor.into_vec();
+        let risk_score = risk_score.into_vec();
+        let survival_prob = survival_prob.into_rows();
+        let times = times.into_vec();
         Self {
             linear_predictor,
             risk_score,
@@ -312,12 +325,12 @@ impl PredictionResult {
 
 pub fn predict_survival(
     coef: &[f64],
-    x: &[Vec<f64>],
+    x: ArrayView2<f64>,
     baseline_hazard: &[f64],
     baseline_times: &[f64],
     pred_times: &[f64],
 ) -> PredictionResult {
-    let n = x.len();
+    let n = x.nrows();
     let n_times = pred_times.len();
 
     let mut linear_predictor = Vec::with_capacity(n);
@@ -332,7 +345,7 @@ pub fn predict_survival(
         })
         .collect();
 
-    for xi in x {
+    for xi in x.outer_iter() {
         let lp: f64 = coef.iter().zip(xi).map(|(&c, &xij)| c * xij).sum();
         let rs = lp.exp();
 
@@ -378,49 +391,59 @@ fn interpolate_cumhaz(times: &[f64], cumhaz: &[f64], t: f64) -> f64 {
     cumhaz[cumhaz.len() - 1]
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (coef, x, baseline_hazard, baseline_times, pred_times))]
 pub fn predict_cox(
-    coef: Vec<f64>,
-    x: Vec<Vec<f64>>,
-    baseline_hazard: Vec<f64>,
-    baseline_times: Vec<f64>,
-    pred_times: Vec<f64>,
+    py: Python<'_>,
+    coef: VectorArg<'_, f64>,
+    x: MatrixArg<'_, f64>,
+    baseline_hazard: VectorArg<'_, f64>,
+    baseline_times: VectorArg<'_, f64>,
+    pred_times: VectorArg<'_, f64>,
 ) -> PyResult<PredictionResult> {
-    Ok(predict_survival(
-        &coef,
-        &x,
-        &baseline_hazard,
-        &baseline_times,
-        &pred_times,
-    ))
+    let coef = coef.as_slice();
+    let x = x.view();
+    let baseline_hazard = baseline_hazard.as_slice();
+    let baseline_times = baseline_times.as_slice();
+    let pred_times = pred_times.as_slice();
+    parallel::detach(py, || {
+        Ok(predict_survival(
+            &coef,
+            x,
+            &baseline_hazard,
+            &baseline_times,
+            &pred_times,
+        ))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct RiskStratificationResult {
-    #[pyo3(get)]
     pub risk_groups: Vec<usize>,
-    #[pyo3(get)]
     pub cutpoints: Vec<f64>,
-    #[pyo3(get)]
     pub group_sizes: Vec<usize>,
-    #[pyo3(get)]
     pub group_event_rates: Vec<f64>,
-    #[pyo3(get)]
     pub group_median_risk: Vec<f64>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl RiskStratificationResult {
     #[new]
     fn new(
-        risk_groups: Vec<usize>,
-        cutpoints: Vec<f64>,
-        group_sizes: Vec<usize>,
-        group_event_rates: Vec<f64>,
-        group_median_risk: Vec<f64>,
+        risk_groups: IndexArg,
+        cutpoints: VectorArg<'_, f64>,
+        group_sizes: IndexArg,
+        group_event_rates: VectorArg<'_, f64>,
+        group_median_risk: VectorArg<'_, f64>,
     ) -> Self {
+        let risk_groups = risk_groups.into_vec();
+        let cutpoints = cutpoints.into_vec();
+        let group_sizes = group_sizes.into_vec();
+        let group_event_rates = group_event_rates.into_vec();
+        let group_median_risk = group_median_risk.into_vec();
         Self {
             risk_groups,
             cutpoints,
@@ -512,82 +535,244 @@ pub fn stratify_risk(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (risk_scores, events, n_groups=None))]
 pub fn risk_stratification(
-    risk_scores: Vec<f64>,
-    events: Vec<i32>,
+    py: Python<'_>,
+    risk_scores: VectorArg<'_, f64>,
+    events: VectorArg<'_, i32>,
     n_groups: Option<usize>,
 ) -> PyResult<RiskStratificationResult> {
-    let n_groups = n_groups.unwrap_or(3);
-    Ok(stratify_risk(&risk_scores, &events, n_groups))
+    let risk_scores = risk_scores.as_slice();
+    let events = events.as_slice();
+    parallel::detach(py, || {
+        let n_groups = n_groups.unwrap_or(3);
+        Ok(stratify_risk(&risk_scores, &events, n_groups))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct TdAUCResult {
-    #[pyo3(get)]
     pub times: Vec<f64>,
-    #[pyo3(get)]
     pub auc: Vec<f64>,
-    #[pyo3(get)]
+    pub std_err: Vec<f64>,
+    pub conf_lower: Vec<f64>,
+    pub conf_upper: Vec<f64>,
     pub integrated_auc: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TdAUCResult {
     #[new]
-    fn new(times: Vec<f64>, auc: Vec<f64>, integrated_auc: f64) -> Self {
+    fn new(
+        times: VectorArg<'_, f64>,
+        auc: VectorArg<'_, f64>,
+        std_err: VectorArg<'_, f64>,
+        conf_lower: VectorArg<'_, f64>,
+        conf_upper: VectorArg<'_, f64>,
+        integrated_auc: f64,
+    ) -> Self {
+        let times = times.into_vec();
+        let auc = auc.into_vec();
+        let std_err = std_err.into_vec();
+        let conf_lower = conf_lower.into_vec();
+        let conf_upper = conf_upper.into_vec();
         Self {
             times,
             auc,
+            std_err,
+            conf_lower,
+            conf_upper,
             integrated_auc,
         }
     }
 }
 
+/// Reverse Kaplan-Meier estimate of the censoring distribution, stored as the
+/// censoring times with their risk sets, counts and survival G(u).
+pub(crate) struct CensoringDistribution {
+    time: Vec<f64>,
+    n_risk: Vec<f64>,
+    n_event: Vec<f64>,
+    survival: Vec<f64>,
+}
+
+impl CensoringDistribution {
+    pub(crate) fn new(time: &[f64], status: &[i32]) -> Self {
+        let status: Vec<f64> = status.iter().map(|&s| f64::from(s)).collect();
+        let weights = vec![1.0; time.len()];
+        let position = vec![0; time.len()];
+        let fit = survfitkm_internal(time, &status, &weights, None, &position, true, 0);
+        Self {
+            time: fit.time,
+            n_risk: fit.n_risk,
+            n_event: fit.n_event,
+            survival: fit.estimate,
+        }
+    }
+
+    /// G(t) when `inclusive`, otherwise G(t-).
+    pub(crate) fn at(&self, t: f64, inclusive: bool) -> f64 {
+        let k = if inclusive {
+            self.time.partition_point(|&u| u <= t)
+        } else {
+            self.time.partition_point(|&u| u < t)
+        };
+        if k == 0 { 1.0 } else { self.survival[k - 1] }
+    }
+}
+
+/// Weighted share of `sorted` values below `x`, counting ties as one half.
+/// `cumulative[k]` is the total weight of the first `k` sorted values.
+fn share_below(sorted: &[f64], cumulative: &[f64], x: f64) -> f64 {
+    let lo = sorted.partition_point(|&v| v < x);
+    let hi = sorted.partition_point(|&v| v <= x);
+    cumulative[lo] + 0.5 * (cumulative[hi] - cumulative[lo])
+}
+
+fn sorted_with_cumulative(mut pairs: Vec<(f64, f64)>) -> (Vec<f64>, Vec<f64>) {
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut cumulative = Vec::with_capacity(pairs.len() + 1);
+    cumulative.push(0.0);
+    for &(_, w) in &pairs {
+        cumulative.push(cumulative.last().unwrap() + w);
+    }
+    (pairs.into_iter().map(|(v, _)| v).collect(), cumulative)
+}
+
+/// Cumulative/dynamic AUC at `t` with inverse-probability-of-censoring weights
+/// (Uno et al., 2007). Returns the estimate and its influence-function standard
+/// error, which includes the contribution from estimating the censoring
+/// distribution.
+fn ipcw_auc_at(
+    time: &[f64],
+    status: &[i32],
+    risk_score: &[f64],
+    censoring: &CensoringDistribution,
+    t: f64,
+) -> (f64, f64) {
+    let n = time.len();
+    let cases: Vec<usize> = (0..n).filter(|&i| time[i] <= t && status[i] == 1).collect();
+    let controls: Vec<usize> = (0..n).filter(|&j| time[j] > t).collect();
+    if cases.is_empty() || controls.is_empty() {
+        return (0.5, f64::NAN);
+    }
+
+    let case_weight: Vec<f64> = cases
+        .iter()
+        .map(|&i| 1.0 / censoring.at(time[i], false))
+        .collect();
+    let control_weight = 1.0 / censoring.at(t, true);
+    let s1: f64 = case_weight.iter().sum();
+    let s2 = control_weight * controls.len() as f64;
+
+    let (control_scores, control_cum) =
+        sorted_with_cumulative(controls.iter().map(|&j| (risk_score[j], 1.0)).collect());
+    let (case_scores, case_cum) = sorted_with_cumulative(
+        cases
+            .iter()
+            .zip(&case_weight)
+            .map(|(&i, &w)| (risk_score[i], w))
+            .collect(),
+    );
+
+    let below: Vec<f64> = cases
+        .iter()
+        .map(|&i| share_below(&control_scores, &control_cum, risk_score[i]) * control_weight)
+        .collect();
+    let auc = case_weight
+        .iter()
+        .zip(&below)
+        .map(|(w, a)| w * a)
+        .sum::<f64>()
+        / (s1 * s2);
+
+    let mut influence = vec![0.0; n];
+    let mut case_terms: Vec<(f64, f64)> = Vec::with_capacity(cases.len());
+    for ((&i, &w), &a) in cases.iter().zip(&case_weight).zip(&below) {
+        let c = w * (a - auc * s2) / (s1 * s2);
+        influence[i] += c;
+        case_terms.push((time[i], c));
+    }
+    for &j in &controls {
+        let above = s1 - share_below(&case_scores, &case_cum, risk_score[j]);
+        influence[j] += control_weight * (above - auc * s1) / (s1 * s2);
+    }
+
+    // Censoring martingale term: sum_i c_i * int_{u < T_i} dM_C,k(u) / Y(u).
+    case_terms.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let case_times: Vec<f64> = case_terms.iter().map(|&(u, _)| u).collect();
+    let mut suffix = vec![0.0; case_terms.len() + 1];
+    for k in (0..case_terms.len()).rev() {
+        suffix[k] = suffix[k + 1] + case_terms[k].1;
+    }
+    let later_cases = |u: f64| suffix[case_times.partition_point(|&v| v <= u)];
+    let mut compensator = Vec::with_capacity(censoring.time.len());
+    let mut running = 0.0;
+    for ((&u, &y), &d) in censoring
+        .time
+        .iter()
+        .zip(&censoring.n_risk)
+        .zip(&censoring.n_event)
+    {
+        running += d / (y * y) * later_cases(u);
+        compensator.push(running);
+    }
+    for k in 0..n {
+        let m = censoring.time.partition_point(|&u| u <= time[k]);
+        let mut term = if m > 0 { -compensator[m - 1] } else { 0.0 };
+        if status[k] != 1 && m > 0 && censoring.time[m - 1] == time[k] {
+            term += later_cases(time[k]) / censoring.n_risk[m - 1];
+        }
+        influence[k] += term;
+    }
+
+    let std_err = influence.iter().map(|v| v * v).sum::<f64>().sqrt();
+    (auc, std_err)
+}
+
 pub fn time_dependent_auc(
     time: &[f64],
     status: &[i32],
     risk_score: &[f64],
     eval_times: &[f64],
+    confidence_level: Option<f64>,
 ) -> TdAUCResult {
     let n = time.len();
     if n == 0 || eval_times.is_empty() {
         return TdAUCResult {
             times: vec![],
             auc: vec![],
+            std_err: vec![],
+            conf_lower: vec![],
+            conf_upper: vec![],
             integrated_auc: 0.0,
         };
     }
 
-    let mut auc_values = Vec::with_capacity(eval_times.len());
-
-    for &t in eval_times {
-        let mut concordant = 0.0;
-        let mut discordant = 0.0;
-
-        for i in 0..n {
-            if time[i] <= t && status[i] == 1 {
-                for j in 0..n {
-                    if time[j] > t {
-                        if risk_score[i] > risk_score[j] {
-                            concordant += 1.0;
-                        } else if risk_score[i] < risk_score[j] {
-                            discordant += 1.0;
-                        } else {
-                            concordant += 0.5;
-                            discordant += 0.5;
-                        }
-                    }
-                }
-            }
-        }
-
-        let total = concordant + discordant;
-        let auc = if total > 0.0 { concordant / total } else { 0.5 };
-        auc_values.push(auc);
-    }
+    let censoring = CensoringDistribution::new(time, status);
+    let confidence_level = confidence_level.unwrap_or(0.95);
+    let z = Normal::new(0.0, 1.0)
+        .map(|d| d.inverse_cdf(0.5 + confidence_level / 2.0))
+        .unwrap_or(1.96);
+
+    let (auc_values, std_err): (Vec<f64>, Vec<f64>) = eval_times
+        .par_iter()
+        .map(|&t| ipcw_auc_at(time, status, risk_score, &censoring, t))
+        .unzip();
+    let conf_lower = auc_values
+        .iter()
+        .zip(&std_err)
+        .map(|(auc, se)| (auc - z * se).clamp(0.0, 1.0))
+        .collect();
+    let conf_upper = auc_values
+        .iter()
+        .zip(&std_err)
+        .map(|(auc, se)| (auc + z * se).clamp(0.0, 1.0))
+        .collect();
 
     let integrated = if auc_values.len() > 1 {
         let mut sum = 0.0;
@@ -611,17 +796,35 @@ pub fn time_dependent_auc(
     TdAUCResult {
         times: eval_times.to_vec(),
         auc: auc_values,
+        std_err,
+        conf_lower,
+        conf_upper,
         integrated_auc: integrated,
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-#[pyo3(signature = (time, status, risk_score, eval_times))]
+#[pyo3(signature = (time, status, risk_score, eval_times, confidence_level=None))]
 pub fn td_auc(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    risk_score: Vec<f64>,
-    eval_times: Vec<f64>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    risk_score: VectorArg<'_, f64>,
+    eval_times: VectorArg<'_, f64>,
+    confidence_level: Option<f64>,
 ) -> PyResult<TdAUCResult> {
-    Ok(time_dependent_auc(&time, &status, &risk_score, &eval_times))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let risk_score = risk_score.as_slice();
+    let eval_times = eval_times.as_slice();
+    parallel::detach(py, || {
+        Ok(time_dependent_auc(
+            &time,
+            &status,
+            &risk_score,
+            &eval_times,
+            confidence_level,
+        ))
+    })
 }
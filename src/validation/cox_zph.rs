@@ -0,0 +1,364 @@
+use crate::regression::coxph::{CoxMethod, CoxPHModel};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use crate::validation::zph1::{ZphResult, zph1};
+use crate::validation::zph2::zph2;
+use ndarray::{Array1, Array2, ArrayView1, s};
+use ndarray_linalg::Solve;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use statrs::distribution::{ChiSquared, ContinuousCDF};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CoxZphError {
+    #[error("the Cox model must be fitted before testing proportional hazards")]
+    NotFitted,
+    #[error("the model has no events")]
+    NoEvents,
+    #[error("unknown transform '{0}', expected 'km', 'rank', 'identity' or 'log'")]
+    UnknownTransform(String),
+    #[error("transformed times have length {got}, expected {expected}")]
+    TransformLength { expected: usize, got: usize },
+    #[error("the log transform requires positive times")]
+    NonPositiveTime,
+}
+
+#[cfg(feature = "python")]
+impl From<CoxZphError> for PyErr {
+    fn from(err: CoxZphError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// Time scale g(t) against which the scaled Schoenfeld residuals are tested.
+#[derive(Debug, Clone)]
+pub enum ZphTransform {
+    KaplanMeier,
+    /// Ranks of the event times among the events.
+    Rank,
+    Identity,
+    Log,
+    /// Transformed values of each observation's time, in the model's row order.
+    Custom(Vec<f64>),
+}
+
+impl ZphTransform {
+    pub fn parse(name: &str) -> Result<Self, CoxZphError> {
+        match name.to_lowercase().as_str() {
+            "km" => Ok(Self::KaplanMeier),
+            "rank" => Ok(Self::Rank),
+            "identity" => Ok(Self::Identity),
+            "log" => Ok(Self::Log),
+            _ => Err(CoxZphError::UnknownTransform(name.to_string())),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::KaplanMeier => "km",
+            Self::Rank => "rank",
+            Self::Identity => "identity",
+            Self::Log => "log",
+            Self::Custom(_) => "custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all))]
+pub struct CoxZphResult {
+    pub variable_names: Vec<String>,
+    pub chi2_values: Vec<f64>,
+    pub df: Vec<usize>,
+    pub p_values: Vec<f64>,
+    pub global_chi2: f64,
+    pub global_df: usize,
+    pub global_p_value: f64,
+    pub transform: String,
+    pub times: Vec<f64>,
+    pub transformed_times: Vec<f64>,
+    pub scaled_residuals: Vec<Vec<f64>>,
+}
+
+/// Left-continuous Kaplan-Meier `1 - S(t-)` at each of `times`, allowing for
+/// delayed entry.
+fn km_transform(entry: Option<&[f64]>, stop: &[f64], status: &[u8]) -> Vec<f64> {
+    let sorted = |values: &mut Vec<f64>| {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    };
+    let mut stops = stop.to_vec();
+    sorted(&mut stops);
+    let mut entries = entry.map(<[f64]>::to_vec).unwrap_or_default();
+    sorted(&mut entries);
+    let mut event_times: Vec<f64> = stop
+        .iter()
+        .zip(status)
+        .filter(|&(_, &s)| s != 0)
+        .map(|(&t, _)| t)
+        .collect();
+    sorted(&mut event_times);
+
+    let mut steps: Vec<(f64, f64)> = Vec::new();
+    let mut surv = 1.0;
+    let mut k = 0;
+    while k < event_times.len() {
+        let t = event_times[k];
+        let d = event_times[k..].iter().take_while(|&&v| v == t).count();
+        let n_risk = (stops.len() - stops.partition_point(|&v| v < t)) as f64
+            - (entries.len() - entries.partition_point(|&v| v < t)) as f64;
+        if n_risk > 0.0 {
+            surv *= 1.0 - d as f64 / n_risk;
+        }
+        steps.push((t, surv));
+        k += d;
+    }
+
+    stop.iter()
+        .map(|&t| {
+            let m = steps.partition_point(|&(u, _)| u < t);
+            if m == 0 { 0.0 } else { 1.0 - steps[m - 1].1 }
+        })
+        .collect()
+}
+
+fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| {
+        values[a]
+            .partial_cmp(&values[b])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Ranks of the event times among the events only, as R's
+/// `rank(y[event, ny - 1])`. A censored observation takes the rank of the
+/// events at its time, which is the value used for that risk set; at other
+/// times it is never read.
+fn event_ranks(times: &[f64], status: &[u8]) -> Vec<f64> {
+    let (event_times, _): (Vec<f64>, Vec<u8>) =
+        times.iter().zip(status).filter(|&(_, &s)| s != 0).unzip();
+    let ranks = average_ranks(&event_times);
+    let mut by_time: Vec<(f64, f64)> = event_times.into_iter().zip(ranks).collect();
+    by_time.sort_by(|a, b| a.0.total_cmp(&b.0));
+    times
+        .iter()
+        .map(|&t| {
+            let k = by_time.partition_point(|&(u, _)| u < t);
+            match by_time.get(k) {
+                Some(&(u, rank)) if u == t => rank,
+                _ => 0.0,
+            }
+        })
+        .collect()
+}
+
+fn quadratic_form(imat: &Array2<f64>, u: &Array1<f64>, index: &[usize]) -> f64 {
+    let sub = Array2::from_shape_fn((index.len(), index.len()), |(i, j)| {
+        imat[(index[i], index[j])]
+    });
+    let rhs: Array1<f64> = index.iter().map(|&i| u[i]).collect();
+    sub.solve(&rhs).map(|w| w.dot(&rhs)).unwrap_or(f64::NAN)
+}
+
+fn chi2_p_value(statistic: f64, df: usize) -> f64 {
+    ChiSquared::new(df as f64)
+        .map(|dist| dist.sf(statistic))
+        .unwrap_or(f64::NAN)
+}
+
+/// Grambsch-Therneau test of proportional hazards for a fitted Cox model, as
+/// in R's `cox.zph`. Each covariate is tested for a coefficient that varies
+/// linearly in the transformed time g(t), along with a global test over all
+/// covariates. The scaled Schoenfeld residuals plotted against
+/// `transformed_times` estimate beta(t).
+pub fn cox_zph(model: &CoxPHModel, transform: &ZphTransform) -> Result<CoxZphResult, CoxZphError> {
+    let covariates = model.covariate_matrix();
+    let n = model.event_times.len();
+    let nvar = covariates.ncols();
+    let beta = model.coefficients().into_iter().next().unwrap_or_default();
+    let var = model.naive_var();
+    if n == 0 || nvar == 0 || beta.len() != nvar || var.len() != nvar {
+        return Err(CoxZphError::NotFitted);
+    }
+    let status = &model.censoring;
+    let ndead = status.iter().filter(|&&s| s != 0).count();
+    if ndead == 0 {
+        return Err(CoxZphError::NoEvents);
+    }
+
+    let times = &model.event_times;
+    let ttimes = match transform {
+        ZphTransform::KaplanMeier => km_transform(model.entry_times.as_deref(), times, status),
+        ZphTransform::Rank => event_ranks(times, status),
+        ZphTransform::Identity => times.clone(),
+        ZphTransform::Log => {
+            if times.iter().any(|&t| t <= 0.0) {
+                return Err(CoxZphError::NonPositiveTime);
+            }
+            times.iter().map(|t| t.ln()).collect()
+        }
+        ZphTransform::Custom(values) => {
+            if values.len() != n {
+                return Err(CoxZphError::TransformLength {
+                    expected: n,
+                    got: values.len(),
+                });
+            }
+            values.clone()
+        }
+    };
+    let event_mean = ttimes
+        .iter()
+        .zip(status)
+        .filter(|&(_, &s)| s != 0)
+        .map(|(&g, _)| g)
+        .sum::<f64>()
+        / ndead as f64;
+    let gtime: Array1<f64> = ttimes.iter().map(|g| g - event_mean).collect();
+
+    let eta: Array1<f64> = model.risk_scores.iter().map(|r| r.ln()).collect();
+    let weights = Array1::from(model.weights.clone());
+    let strata: Array1<i32> = model.strata.iter().map(|&s| s as i32).collect();
+    let stop = Array1::from(times.clone());
+    let status_f64: Array1<f64> = status.iter().map(|&s| f64::from(s)).collect();
+    let method = match model.method {
+        CoxMethod::Breslow => 0,
+        CoxMethod::Efron => 1,
+    };
+    let sort_by = |key: &[f64]| -> Array1<usize> {
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&i, &j| {
+            model.strata[i].cmp(&model.strata[j]).then_with(|| {
+                key[i]
+                    .partial_cmp(&key[j])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+        Array1::from(order)
+    };
+    let sort2 = sort_by(times);
+
+    let ZphResult {
+        mut u,
+        imat,
+        schoen,
+        ..
+    } = match &model.entry_times {
+        Some(entry) => {
+            let start = Array1::from(entry.clone());
+            zph2(
+                gtime.view(),
+                (start.view(), stop.view(), status_f64.view()),
+                covariates.view(),
+                eta.view(),
+                weights.view(),
+                strata.view(),
+                method,
+                sort_by(entry).view(),
+                sort2.view(),
+            )
+        }
+        None => zph1(
+            gtime.view(),
+            (stop.view(), status_f64.view()),
+            covariates.view(),
+            eta.view(),
+            weights.view(),
+            strata.view(),
+            method,
+            sort2.view(),
+        ),
+    };
+
+    // The score for beta is zero at the fitted coefficients.
+    u.slice_mut(s![..nvar]).fill(0.0);
+    let mut chi2_values = Vec::with_capacity(nvar);
+    let mut p_values = Vec::with_capacity(nvar);
+    for j in 0..nvar {
+        let index: Vec<usize> = (0..nvar).chain(std::iter::once(nvar + j)).collect();
+        let chi2 = quadratic_form(&imat, &u, &index);
+        chi2_values.push(chi2);
+        p_values.push(chi2_p_value(chi2, 1));
+    }
+    let global_index: Vec<usize> = (0..2 * nvar).collect();
+    let global_chi2 = quadratic_form(&imat, &u, &global_index);
+
+    let event_rows: Vec<usize> = sort2.iter().copied().filter(|&i| status[i] != 0).collect();
+    let scaled_residuals = schoen
+        .outer_iter()
+        .map(|row| scale_residual(row, &var, &beta, ndead as f64))
+        .collect();
+
+    Ok(CoxZphResult {
+        variable_names: (0..nvar)
+            .map(|i| {
+                model
+                    .covariate_names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("var{}", i))
+            })
+            .collect(),
+        chi2_values,
+        df: vec![1; nvar],
+        p_values,
+        global_chi2,
+        global_df: nvar,
+        global_p_value: chi2_p_value(global_chi2, nvar),
+        transform: transform.name().to_string(),
+        times: event_rows.iter().map(|&i| times[i]).collect(),
+        transformed_times: event_rows.iter().map(|&i| ttimes[i]).collect(),
+        scaled_residuals,
+    })
+}
+
+fn scale_residual(row: ArrayView1<f64>, var: &[Vec<f64>], beta: &[f64], ndead: f64) -> Vec<f64> {
+    (0..beta.len())
+        .map(|j| {
+            beta[j]
+                + ndead
+                    * row
+                        .iter()
+                        .zip(var)
+                        .map(|(r, v_row)| r * v_row[j])
+                        .sum::<f64>()
+        })
+        .collect()
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "cox_zph", signature = (model, transform=None))]
+pub fn cox_zph_py(
+    py: Python<'_>,
+    model: PyRef<'_, CoxPHModel>,
+    transform: Option<Bound<'_, PyAny>>,
+) -> PyResult<CoxZphResult> {
+    let transform = match transform {
+        None => ZphTransform::KaplanMeier,
+        Some(t) if t.is_callable() => {
+            let values: Vec<f64> = t.call1((model.event_times.clone(),))?.extract()?;
+            ZphTransform::Custom(values)
+        }
+        Some(t) => ZphTransform::parse(&t.extract::<String>()?)?,
+    };
+    let model = &*model;
+    Ok(parallel::detach(py, || cox_zph(model, &transform))?)
+}
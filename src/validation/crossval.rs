@@ -1,29 +1,33 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
 use ndarray::Array2;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use rayon::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct CVResult {
-    #[pyo3(get)]
     pub fold_scores: Vec<f64>,
-    #[pyo3(get)]
     pub mean_score: f64,
-    #[pyo3(get)]
     pub std_score: f64,
-    #[pyo3(get)]
     pub fold_coefficients: Vec<Vec<f64>>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl CVResult {
     #[new]
     fn new(
-        fold_scores: Vec<f64>,
+        fold_scores: VectorArg<'_, f64>,
         mean_score: f64,
         std_score: f64,
-        fold_coefficients: Vec<Vec<f64>>,
+        fold_coefficients: MatrixArg<'_, f64>,
     ) -> Self {
+        let fold_scores = fold_scores.into_vec();
+        let fold_coefficients = fold_coefficients.into_rows();
         Self {
             fold_scores,
             mean_score,
@@ -59,7 +63,12 @@ fn simple_shuffle(indices: &mut [usize], seed: u64) {
     }
 }
 
-fn create_folds(n: usize, n_folds: usize, shuffle: bool, seed: Option<u64>) -> Vec<Vec<usize>> {
+pub(crate) fn create_folds(
+    n: usize,
+    n_folds: usize,
+    shuffle: bool,
+    seed: Option<u64>,
+) -> Vec<Vec<usize>> {
     let mut indices: Vec<usize> = (0..n).collect();
 
     if shuffle {
@@ -248,43 +257,49 @@ pub fn cv_cox(
     })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, covariates, weights=None, n_folds=None, shuffle=None, seed=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn cv_cox_concordance(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    covariates: Vec<Vec<f64>>,
-    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    covariates: MatrixArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
     n_folds: Option<usize>,
     shuffle: Option<bool>,
     seed: Option<u64>,
 ) -> PyResult<CVResult> {
-    let n = time.len();
-    let nvar = if !covariates.is_empty() {
-        covariates[0].len()
-    } else {
-        0
-    };
-
-    let cov_array = if nvar > 0 {
-        let flat: Vec<f64> = covariates.into_iter().flatten().collect();
-        let temp = Array2::from_shape_vec((n, nvar), flat)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
-        temp.t().to_owned()
-    } else {
-        Array2::zeros((0, n))
-    };
-
-    let config = CVConfig {
-        n_folds: n_folds.unwrap_or(5),
-        shuffle: shuffle.unwrap_or(true),
-        seed,
-    };
-
-    let weights_ref = weights.as_deref();
-
-    cv_cox(&time, &status, &cov_array, weights_ref, &config)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    let covariates = covariates.view();
+    parallel::detach(py, || {
+        let n = time.len();
+
+        let cov_array = if covariates.nrows() > 0 && covariates.ncols() > 0 {
+            if covariates.nrows() != n {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "covariates must have one row per observation",
+                ));
+            }
+            covariates.t().to_owned()
+        } else {
+            Array2::zeros((0, n))
+        };
+
+        let config = CVConfig {
+            n_folds: n_folds.unwrap_or(5),
+            shuffle: shuffle.unwrap_or(true),
+            seed,
+        };
+
+        let weights_ref = weights.as_deref();
+
+        cv_cox(&time, &status, &cov_array, weights_ref, &config)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    })
 }
 
 pub fn cv_survreg(
@@ -383,41 +398,46 @@ pub fn cv_survreg(
     })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, covariates, distribution=None, n_folds=None, shuffle=None, seed=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn cv_survreg_loglik(
-    time: Vec<f64>,
-    status: Vec<f64>,
-    covariates: Vec<Vec<f64>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, f64>,
+    covariates: MatrixArg<'_, f64>,
     distribution: Option<&str>,
     n_folds: Option<usize>,
     shuffle: Option<bool>,
     seed: Option<u64>,
 ) -> PyResult<CVResult> {
-    let n = time.len();
-    let nvar = if !covariates.is_empty() {
-        covariates[0].len()
-    } else {
-        0
-    };
-
-    let cov_array = if nvar > 0 {
-        let flat: Vec<f64> = covariates.into_iter().flatten().collect();
-        let temp = Array2::from_shape_vec((n, nvar), flat)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
-        temp.t().to_owned()
-    } else {
-        Array2::zeros((0, n))
-    };
-
-    let config = CVConfig {
-        n_folds: n_folds.unwrap_or(5),
-        shuffle: shuffle.unwrap_or(true),
-        seed,
-    };
-
-    let dist = distribution.unwrap_or("weibull");
-
-    cv_survreg(&time, &status, &cov_array, dist, &config)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let covariates = covariates.view();
+    parallel::detach(py, || {
+        let n = time.len();
+
+        let cov_array = if covariates.nrows() > 0 && covariates.ncols() > 0 {
+            if covariates.nrows() != n {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "covariates must have one row per observation",
+                ));
+            }
+            covariates.t().to_owned()
+        } else {
+            Array2::zeros((0, n))
+        };
+
+        let config = CVConfig {
+            n_folds: n_folds.unwrap_or(5),
+            shuffle: shuffle.unwrap_or(true),
+            seed,
+        };
+
+        let dist = distribution.unwrap_or("weibull");
+
+        cv_survreg(&time, &status, &cov_array, dist, &config)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    })
 }
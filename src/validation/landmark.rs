@@ -1,22 +1,22 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct LandmarkResult {
-    #[pyo3(get)]
     pub landmark_time: f64,
-    #[pyo3(get)]
     pub n_at_risk: usize,
-    #[pyo3(get)]
     pub n_excluded: usize,
-    #[pyo3(get)]
     pub time: Vec<f64>,
-    #[pyo3(get)]
     pub status: Vec<i32>,
-    #[pyo3(get)]
     pub original_indices: Vec<usize>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl LandmarkResult {
     #[new]
@@ -24,10 +24,13 @@ impl LandmarkResult {
         landmark_time: f64,
         n_at_risk: usize,
         n_excluded: usize,
-        time: Vec<f64>,
-        status: Vec<i32>,
-        original_indices: Vec<usize>,
+        time: VectorArg<'_, f64>,
+        status: VectorArg<'_, i32>,
+        original_indices: IndexArg,
     ) -> Self {
+        let time = time.into_vec();
+        let status = status.into_vec();
+        let original_indices = original_indices.into_vec();
         Self {
             landmark_time,
             n_at_risk,
@@ -68,32 +71,31 @@ pub fn compute_landmark(time: &[f64], status: &[i32], landmark_time: f64) -> Lan
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn landmark_analysis(
-    time: Vec<f64>,
-    status: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
     landmark_time: f64,
 ) -> PyResult<LandmarkResult> {
-    Ok(compute_landmark(&time, &status, landmark_time))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    parallel::detach(py, || Ok(compute_landmark(&time, &status, landmark_time)))
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct ConditionalSurvivalResult {
-    #[pyo3(get)]
     pub given_time: f64,
-    #[pyo3(get)]
     pub target_time: f64,
-    #[pyo3(get)]
     pub conditional_survival: f64,
-    #[pyo3(get)]
     pub ci_lower: f64,
-    #[pyo3(get)]
     pub ci_upper: f64,
-    #[pyo3(get)]
     pub n_at_risk: usize,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl ConditionalSurvivalResult {
     #[new]
@@ -225,42 +227,43 @@ pub fn compute_conditional_survival(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, given_time, target_time, confidence_level=None))]
 pub fn conditional_survival(
-    time: Vec<f64>,
-    status: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
     given_time: f64,
     target_time: f64,
     confidence_level: Option<f64>,
 ) -> PyResult<ConditionalSurvivalResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compute_conditional_survival(
-        &time,
-        &status,
-        given_time,
-        target_time,
-        conf,
-    ))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compute_conditional_survival(
+            &time,
+            &status,
+            given_time,
+            target_time,
+            conf,
+        ))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct HazardRatioResult {
-    #[pyo3(get)]
     pub hazard_ratio: f64,
-    #[pyo3(get)]
     pub ci_lower: f64,
-    #[pyo3(get)]
     pub ci_upper: f64,
-    #[pyo3(get)]
     pub se_log_hr: f64,
-    #[pyo3(get)]
     pub z_statistic: f64,
-    #[pyo3(get)]
     pub p_value: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl HazardRatioResult {
     #[new]
@@ -441,35 +444,37 @@ pub fn compute_hazard_ratio(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, group, confidence_level=None))]
 pub fn hazard_ratio(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    group: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    group: VectorArg<'_, i32>,
     confidence_level: Option<f64>,
 ) -> PyResult<HazardRatioResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compute_hazard_ratio(&time, &status, &group, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let group = group.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compute_hazard_ratio(&time, &status, &group, conf))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct SurvivalAtTimeResult {
-    #[pyo3(get)]
     pub time: f64,
-    #[pyo3(get)]
     pub survival: f64,
-    #[pyo3(get)]
     pub ci_lower: f64,
-    #[pyo3(get)]
     pub ci_upper: f64,
-    #[pyo3(get)]
     pub n_at_risk: usize,
-    #[pyo3(get)]
     pub n_events: usize,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl SurvivalAtTimeResult {
     #[new]
@@ -609,56 +614,64 @@ pub fn compute_survival_at_times(
     results
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, eval_times, confidence_level=None))]
 pub fn survival_at_times(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    eval_times: Vec<f64>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    eval_times: VectorArg<'_, f64>,
     confidence_level: Option<f64>,
 ) -> PyResult<Vec<SurvivalAtTimeResult>> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compute_survival_at_times(&time, &status, &eval_times, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let eval_times = eval_times.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compute_survival_at_times(&time, &status, &eval_times, conf))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct LifeTableResult {
-    #[pyo3(get)]
     pub interval_start: Vec<f64>,
-    #[pyo3(get)]
     pub interval_end: Vec<f64>,
-    #[pyo3(get)]
     pub n_at_risk: Vec<f64>,
-    #[pyo3(get)]
     pub n_deaths: Vec<f64>,
-    #[pyo3(get)]
     pub n_censored: Vec<f64>,
-    #[pyo3(get)]
     pub n_effective: Vec<f64>,
-    #[pyo3(get)]
     pub hazard: Vec<f64>,
-    #[pyo3(get)]
     pub survival: Vec<f64>,
-    #[pyo3(get)]
     pub se_survival: Vec<f64>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl LifeTableResult {
     #[new]
     #[allow(clippy::too_many_arguments)]
     fn new(
-        interval_start: Vec<f64>,
-        interval_end: Vec<f64>,
-        n_at_risk: Vec<f64>,
-        n_deaths: Vec<f64>,
-        n_censored: Vec<f64>,
-        n_effective: Vec<f64>,
-        hazard: Vec<f64>,
-        survival: Vec<f64>,
-        se_survival: Vec<f64>,
+        interval_start: VectorArg<'_, f64>,
+        interval_end: VectorArg<'_, f64>,
+        n_at_risk: VectorArg<'_, f64>,
+        n_deaths: VectorArg<'_, f64>,
+        n_censored: VectorArg<'_, f64>,
+        n_effective: VectorArg<'_, f64>,
+        hazard: VectorArg<'_, f64>,
+        survival: VectorArg<'_, f64>,
+        se_survival: VectorArg<'_, f64>,
     ) -> Self {
+        let interval_start = interval_start.into_vec();
+        let interval_end = interval_end.into_vec();
+        let n_at_risk = n_at_risk.into_vec();
+        let n_deaths = n_deaths.into_vec();
+        let n_censored = n_censored.into_vec();
+        let n_effective = n_effective.into_vec();
+        let hazard = hazard.into_vec();
+        let survival = survival.into_vec();
+        let se_survival = se_survival.into_vec();
         Self {
             interval_start,
             interval_end,
@@ -764,7 +777,16 @@ pub fn compute_life_table(time: &[f64], status: &[i32], breaks: &[f64]) -> LifeT
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-pub fn life_table(time: Vec<f64>, status: Vec<i32>, breaks: Vec<f64>) -> PyResult<LifeTableResult> {
-    Ok(compute_life_table(&time, &status, &breaks))
+pub fn life_table(
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    breaks: VectorArg<'_, f64>,
+) -> PyResult<LifeTableResult> {
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let breaks = breaks.as_slice();
+    parallel::detach(py, || Ok(compute_life_table(&time, &status, &breaks)))
 }
@@ -1,24 +1,23 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::VectorArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct LogRankResult {
-    #[pyo3(get)]
     pub statistic: f64,
-    #[pyo3(get)]
     pub p_value: f64,
-    #[pyo3(get)]
     pub df: usize,
-    #[pyo3(get)]
     pub observed: Vec<f64>,
-    #[pyo3(get)]
     pub expected: Vec<f64>,
-    #[pyo3(get)]
     pub variance: f64,
-    #[pyo3(get)]
     pub weight_type: String,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl LogRankResult {
     #[new]
@@ -26,11 +25,13 @@ impl LogRankResult {
         statistic: f64,
         p_value: f64,
         df: usize,
-        observed: Vec<f64>,
-        expected: Vec<f64>,
+        observed: VectorArg<'_, f64>,
+        expected: VectorArg<'_, f64>,
         variance: f64,
         weight_type: String,
     ) -> Self {
+        let observed = observed.into_vec();
+        let expected = expected.into_vec();
         Self {
             statistic,
             p_value,
@@ -295,52 +296,64 @@ fn weight_name(weight_type: &WeightType) -> String {
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, group, weight_type=None))]
 pub fn logrank_test(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    group: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    group: VectorArg<'_, i32>,
     weight_type: Option<&str>,
 ) -> PyResult<LogRankResult> {
-    let wt = match weight_type {
-        Some("wilcoxon") | Some("Wilcoxon") => WeightType::Wilcoxon,
-        Some("tarone-ware") | Some("TaroneWare") => WeightType::TaroneWare,
-        Some("peto-peto") | Some("PetoPeto") | Some("peto") => WeightType::PetoPeto,
-        _ => WeightType::LogRank,
-    };
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let group = group.as_slice();
+    parallel::detach(py, || {
+        let wt = match weight_type {
+            Some("wilcoxon") | Some("Wilcoxon") => WeightType::Wilcoxon,
+            Some("tarone-ware") | Some("TaroneWare") => WeightType::TaroneWare,
+            Some("peto-peto") | Some("PetoPeto") | Some("peto") => WeightType::PetoPeto,
+            _ => WeightType::LogRank,
+        };
 
-    Ok(weighted_logrank_test(&time, &status, &group, wt))
+        Ok(weighted_logrank_test(&time, &status, &group, wt))
+    })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, group, p, q))]
 pub fn fleming_harrington_test(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    group: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    group: VectorArg<'_, i32>,
     p: f64,
     q: f64,
 ) -> PyResult<LogRankResult> {
-    Ok(weighted_logrank_test(
-        &time,
-        &status,
-        &group,
-        WeightType::FlemingHarrington { p, q },
-    ))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let group = group.as_slice();
+    parallel::detach(py, || {
+        Ok(weighted_logrank_test(
+            &time,
+            &status,
+            &group,
+            WeightType::FlemingHarrington { p, q },
+        ))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct TrendTestResult {
-    #[pyo3(get)]
     pub statistic: f64,
-    #[pyo3(get)]
     pub p_value: f64,
-    #[pyo3(get)]
     pub trend_direction: String,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TrendTestResult {
     #[new]
@@ -468,14 +481,22 @@ pub fn logrank_trend_test(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, group, scores=None))]
 pub fn logrank_trend(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    group: Vec<i32>,
-    scores: Option<Vec<f64>>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    group: VectorArg<'_, i32>,
+    scores: Option<VectorArg<'_, f64>>,
 ) -> PyResult<TrendTestResult> {
-    let scores_ref = scores.as_deref();
-    Ok(logrank_trend_test(&time, &status, &group, scores_ref))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let group = group.as_slice();
+    let scores = scores.as_ref().map(VectorArg::as_slice);
+    parallel::detach(py, || {
+        let scores_ref = scores.as_deref();
+        Ok(logrank_trend_test(&time, &status, &group, scores_ref))
+    })
 }
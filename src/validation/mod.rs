@@ -1,8 +1,11 @@
 pub mod bootstrap;
 pub mod calibration;
+pub mod cox_zph;
 pub mod crossval;
 pub mod landmark;
 pub mod logrank;
 pub mod power;
 pub mod rmst;
 pub mod tests;
+pub mod zph1;
+pub mod zph2;
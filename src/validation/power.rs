@@ -1,36 +1,36 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::IndexArg;
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct SampleSizeResult {
-    #[pyo3(get)]
     pub n_total: usize,
-    #[pyo3(get)]
     pub n_events: usize,
-    #[pyo3(get)]
     pub n_per_group: Vec<usize>,
-    #[pyo3(get)]
     pub power: f64,
-    #[pyo3(get)]
     pub alpha: f64,
-    #[pyo3(get)]
     pub hazard_ratio: f64,
-    #[pyo3(get)]
     pub method: String,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl SampleSizeResult {
     #[new]
     fn new(
         n_total: usize,
         n_events: usize,
-        n_per_group: Vec<usize>,
+        n_per_group: IndexArg,
         power: f64,
         alpha: f64,
         hazard_ratio: f64,
         method: String,
     ) -> Self {
+        let n_per_group = n_per_group.into_vec();
         Self {
             n_total,
             n_events,
@@ -210,38 +210,44 @@ pub fn power_logrank(
     norm_cdf(z - z_alpha)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (hazard_ratio, power=None, alpha=None, allocation_ratio=None, sided=None))]
 pub fn sample_size_survival(
+    py: Python<'_>,
     hazard_ratio: f64,
     power: Option<f64>,
     alpha: Option<f64>,
     allocation_ratio: Option<f64>,
     sided: Option<usize>,
 ) -> PyResult<SampleSizeResult> {
-    let power = power.unwrap_or(0.8);
-    let alpha = alpha.unwrap_or(0.05);
-    let allocation_ratio = allocation_ratio.unwrap_or(1.0);
-    let sided = sided.unwrap_or(2);
-
-    if hazard_ratio <= 0.0 || hazard_ratio == 1.0 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "hazard_ratio must be positive and not equal to 1",
-        ));
-    }
+    parallel::detach(py, || {
+        let power = power.unwrap_or(0.8);
+        let alpha = alpha.unwrap_or(0.05);
+        let allocation_ratio = allocation_ratio.unwrap_or(1.0);
+        let sided = sided.unwrap_or(2);
+
+        if hazard_ratio <= 0.0 || hazard_ratio == 1.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "hazard_ratio must be positive and not equal to 1",
+            ));
+        }
 
-    Ok(sample_size_logrank(
-        hazard_ratio,
-        power,
-        alpha,
-        allocation_ratio,
-        sided,
-    ))
+        Ok(sample_size_logrank(
+            hazard_ratio,
+            power,
+            alpha,
+            allocation_ratio,
+            sided,
+        ))
+    })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (hazard_ratio, prob_event, power=None, alpha=None, allocation_ratio=None, sided=None))]
 pub fn sample_size_survival_freedman(
+    py: Python<'_>,
     hazard_ratio: f64,
     prob_event: f64,
     power: Option<f64>,
@@ -249,76 +255,78 @@ pub fn sample_size_survival_freedman(
     allocation_ratio: Option<f64>,
     sided: Option<usize>,
 ) -> PyResult<SampleSizeResult> {
-    let power = power.unwrap_or(0.8);
-    let alpha = alpha.unwrap_or(0.05);
-    let allocation_ratio = allocation_ratio.unwrap_or(1.0);
-    let sided = sided.unwrap_or(2);
-
-    if hazard_ratio <= 0.0 || hazard_ratio == 1.0 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "hazard_ratio must be positive and not equal to 1",
-        ));
-    }
+    parallel::detach(py, || {
+        let power = power.unwrap_or(0.8);
+        let alpha = alpha.unwrap_or(0.05);
+        let allocation_ratio = allocation_ratio.unwrap_or(1.0);
+        let sided = sided.unwrap_or(2);
+
+        if hazard_ratio <= 0.0 || hazard_ratio == 1.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "hazard_ratio must be positive and not equal to 1",
+            ));
+        }
 
-    if prob_event <= 0.0 || prob_event >= 1.0 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "prob_event must be between 0 and 1",
-        ));
-    }
+        if prob_event <= 0.0 || prob_event >= 1.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "prob_event must be between 0 and 1",
+            ));
+        }
 
-    Ok(sample_size_freedman(
-        hazard_ratio,
-        power,
-        alpha,
-        prob_event,
-        allocation_ratio,
-        sided,
-    ))
+        Ok(sample_size_freedman(
+            hazard_ratio,
+            power,
+            alpha,
+            prob_event,
+            allocation_ratio,
+            sided,
+        ))
+    })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (n_events, hazard_ratio, alpha=None, allocation_ratio=None, sided=None))]
 pub fn power_survival(
+    py: Python<'_>,
     n_events: usize,
     hazard_ratio: f64,
     alpha: Option<f64>,
     allocation_ratio: Option<f64>,
     sided: Option<usize>,
 ) -> PyResult<f64> {
-    let alpha = alpha.unwrap_or(0.05);
-    let allocation_ratio = allocation_ratio.unwrap_or(1.0);
-    let sided = sided.unwrap_or(2);
-
-    if hazard_ratio <= 0.0 || hazard_ratio == 1.0 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "hazard_ratio must be positive and not equal to 1",
-        ));
-    }
+    parallel::detach(py, || {
+        let alpha = alpha.unwrap_or(0.05);
+        let allocation_ratio = allocation_ratio.unwrap_or(1.0);
+        let sided = sided.unwrap_or(2);
+
+        if hazard_ratio <= 0.0 || hazard_ratio == 1.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "hazard_ratio must be positive and not equal to 1",
+            ));
+        }
 
-    Ok(power_logrank(
-        n_events,
-        hazard_ratio,
-        alpha,
-        allocation_ratio,
-        sided,
-    ))
+        Ok(power_logrank(
+            n_events,
+            hazard_ratio,
+            alpha,
+            allocation_ratio,
+            sided,
+        ))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct AccrualResult {
-    #[pyo3(get)]
     pub n_total: usize,
-    #[pyo3(get)]
     pub accrual_time: f64,
-    #[pyo3(get)]
     pub followup_time: f64,
-    #[pyo3(get)]
     pub study_duration: f64,
-    #[pyo3(get)]
     pub expected_events: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl AccrualResult {
     #[new]
@@ -372,9 +380,12 @@ pub fn expected_events_exponential(
     n1 * prob_event(lambda1) + n2 * prob_event(lambda2)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (n_total, hazard_control, hazard_ratio, accrual_time, followup_time, allocation_ratio=None, dropout_rate=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn expected_events(
+    py: Python<'_>,
     n_total: usize,
     hazard_control: f64,
     hazard_ratio: f64,
@@ -383,24 +394,26 @@ pub fn expected_events(
     allocation_ratio: Option<f64>,
     dropout_rate: Option<f64>,
 ) -> PyResult<AccrualResult> {
-    let allocation_ratio = allocation_ratio.unwrap_or(1.0);
-    let dropout_rate = dropout_rate.unwrap_or(0.0);
+    parallel::detach(py, || {
+        let allocation_ratio = allocation_ratio.unwrap_or(1.0);
+        let dropout_rate = dropout_rate.unwrap_or(0.0);
 
-    let events = expected_events_exponential(
-        n_total,
-        hazard_control,
-        hazard_ratio,
-        accrual_time,
-        followup_time,
-        allocation_ratio,
-        dropout_rate,
-    );
+        let events = expected_events_exponential(
+            n_total,
+            hazard_control,
+            hazard_ratio,
+            accrual_time,
+            followup_time,
+            allocation_ratio,
+            dropout_rate,
+        );
 
-    Ok(AccrualResult {
-        n_total,
-        accrual_time,
-        followup_time,
-        study_duration: accrual_time + followup_time,
-        expected_events: events,
+        Ok(AccrualResult {
+            n_total,
+            accrual_time,
+            followup_time,
+            study_duration: accrual_time + followup_time,
+            expected_events: events,
+        })
     })
 }
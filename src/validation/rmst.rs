@@ -1,22 +1,22 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{IndexArg, MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct RMSTResult {
-    #[pyo3(get)]
     pub rmst: f64,
-    #[pyo3(get)]
     pub variance: f64,
-    #[pyo3(get)]
     pub se: f64,
-    #[pyo3(get)]
     pub ci_lower: f64,
-    #[pyo3(get)]
     pub ci_upper: f64,
-    #[pyo3(get)]
     pub tau: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl RMSTResult {
     #[new]
@@ -33,30 +33,21 @@ impl RMSTResult {
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct RMSTComparisonResult {
-    #[pyo3(get)]
     pub rmst_diff: f64,
-    #[pyo3(get)]
     pub rmst_ratio: f64,
-    #[pyo3(get)]
     pub diff_se: f64,
-    #[pyo3(get)]
     pub diff_ci_lower: f64,
-    #[pyo3(get)]
     pub diff_ci_upper: f64,
-    #[pyo3(get)]
     pub ratio_ci_lower: f64,
-    #[pyo3(get)]
     pub ratio_ci_upper: f64,
-    #[pyo3(get)]
     pub p_value: f64,
-    #[pyo3(get)]
     pub rmst_group1: RMSTResult,
-    #[pyo3(get)]
     pub rmst_group2: RMSTResult,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl RMSTComparisonResult {
     #[new]
@@ -338,44 +329,54 @@ pub fn compare_rmst(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, tau, confidence_level=None))]
 pub fn rmst(
-    time: Vec<f64>,
-    status: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
     tau: f64,
     confidence_level: Option<f64>,
 ) -> PyResult<RMSTResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compute_rmst(&time, &status, tau, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compute_rmst(&time, &status, tau, conf))
+    })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, group, tau, confidence_level=None))]
 pub fn rmst_comparison(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    group: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    group: VectorArg<'_, i32>,
     tau: f64,
     confidence_level: Option<f64>,
 ) -> PyResult<RMSTComparisonResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compare_rmst(&time, &status, &group, tau, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let group = group.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compare_rmst(&time, &status, &group, tau, conf))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct MedianSurvivalResult {
-    #[pyo3(get)]
     pub median: Option<f64>,
-    #[pyo3(get)]
     pub ci_lower: Option<f64>,
-    #[pyo3(get)]
     pub ci_upper: Option<f64>,
-    #[pyo3(get)]
     pub quantile: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl MedianSurvivalResult {
     #[new]
@@ -495,44 +496,51 @@ pub fn compute_survival_quantile(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, quantile=None, confidence_level=None))]
 pub fn survival_quantile(
-    time: Vec<f64>,
-    status: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
     quantile: Option<f64>,
     confidence_level: Option<f64>,
 ) -> PyResult<MedianSurvivalResult> {
-    let q = quantile.unwrap_or(0.5);
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compute_survival_quantile(&time, &status, q, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    parallel::detach(py, || {
+        let q = quantile.unwrap_or(0.5);
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compute_survival_quantile(&time, &status, q, conf))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct CumulativeIncidenceResult {
-    #[pyo3(get)]
     pub time: Vec<f64>,
-    #[pyo3(get)]
     pub cif: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub variance: Vec<Vec<f64>>,
-    #[pyo3(get)]
     pub event_types: Vec<i32>,
-    #[pyo3(get)]
     pub n_risk: Vec<usize>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl CumulativeIncidenceResult {
     #[new]
     fn new(
-        time: Vec<f64>,
-        cif: Vec<Vec<f64>>,
-        variance: Vec<Vec<f64>>,
-        event_types: Vec<i32>,
-        n_risk: Vec<usize>,
+        time: VectorArg<'_, f64>,
+        cif: MatrixArg<'_, f64>,
+        variance: MatrixArg<'_, f64>,
+        event_types: VectorArg<'_, i32>,
+        n_risk: IndexArg,
     ) -> Self {
+        let time = time.into_vec();
+        let cif = cif.into_rows();
+        let variance = variance.into_rows();
+        let event_types = event_types.into_vec();
+        let n_risk = n_risk.into_vec();
         Self {
             time,
             cif,
@@ -647,33 +655,31 @@ pub fn compute_cumulative_incidence(time: &[f64], status: &[i32]) -> CumulativeI
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn cumulative_incidence(
-    time: Vec<f64>,
-    status: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
 ) -> PyResult<CumulativeIncidenceResult> {
-    Ok(compute_cumulative_incidence(&time, &status))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    parallel::detach(py, || Ok(compute_cumulative_incidence(&time, &status)))
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct NNTResult {
-    #[pyo3(get)]
     pub nnt: f64,
-    #[pyo3(get)]
     pub nnt_ci_lower: f64,
-    #[pyo3(get)]
     pub nnt_ci_upper: f64,
-    #[pyo3(get)]
     pub absolute_risk_reduction: f64,
-    #[pyo3(get)]
     pub arr_ci_lower: f64,
-    #[pyo3(get)]
     pub arr_ci_upper: f64,
-    #[pyo3(get)]
     pub time_horizon: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NNTResult {
     #[new]
@@ -826,15 +832,22 @@ fn compute_survival_at_time(
     (surv, variance)
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (time, status, group, time_horizon, confidence_level=None))]
 pub fn number_needed_to_treat(
-    time: Vec<f64>,
-    status: Vec<i32>,
-    group: Vec<i32>,
+    py: Python<'_>,
+    time: VectorArg<'_, f64>,
+    status: VectorArg<'_, i32>,
+    group: VectorArg<'_, i32>,
     time_horizon: f64,
     confidence_level: Option<f64>,
 ) -> PyResult<NNTResult> {
-    let conf = confidence_level.unwrap_or(0.95);
-    Ok(compute_nnt(&time, &status, &group, time_horizon, conf))
+    let time = time.as_slice();
+    let status = status.as_slice();
+    let group = group.as_slice();
+    parallel::detach(py, || {
+        let conf = confidence_level.unwrap_or(0.95);
+        Ok(compute_nnt(&time, &status, &group, time_horizon, conf))
+    })
 }
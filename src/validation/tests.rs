@@ -1,18 +1,22 @@
+#[cfg(feature = "python")]
+use crate::python::arrays::{MatrixArg, VectorArg};
+#[cfg(feature = "python")]
+use crate::utilities::parallel;
+use ndarray::ArrayView2;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use statrs::distribution::{ChiSquared, ContinuousCDF};
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct TestResult {
-    #[pyo3(get)]
     pub statistic: f64,
-    #[pyo3(get)]
     pub df: usize,
-    #[pyo3(get)]
     pub p_value: f64,
-    #[pyo3(get)]
     pub test_name: String,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TestResult {
     #[new]
@@ -37,97 +41,7 @@ fn chi2_sf(x: f64, df: usize) -> f64 {
     if x <= 0.0 || df == 0 {
         return 1.0;
     }
-
-    let k = df as f64 / 2.0;
-    let x_half = x / 2.0;
-
-    let ln_gamma_k = ln_gamma(k);
-    let regularized_gamma = lower_incomplete_gamma(k, x_half) / ln_gamma_k.exp();
-
-    1.0 - regularized_gamma
-}
-
-fn ln_gamma(x: f64) -> f64 {
-    let coeffs = [
-        76.18009172947146,
-        -86.50532032941677,
-        24.01409824083091,
-        -1.231739572450155,
-        0.1208650973866179e-2,
-        -0.5395239384953e-5,
-    ];
-
-    let y = x;
-    let tmp = x + 5.5;
-    let tmp = tmp - (x + 0.5) * tmp.ln();
-
-    let mut ser = 1.000000000190015;
-    for (j, &c) in coeffs.iter().enumerate() {
-        ser += c / (y + 1.0 + j as f64);
-    }
-
-    -tmp + (2.5066282746310005 * ser / x).ln()
-}
-
-fn lower_incomplete_gamma(a: f64, x: f64) -> f64 {
-    if x < 0.0 || a <= 0.0 {
-        return 0.0;
-    }
-
-    if x < a + 1.0 {
-        gamma_series(a, x)
-    } else {
-        ln_gamma(a).exp() - gamma_continued_fraction(a, x)
-    }
-}
-
-fn gamma_series(a: f64, x: f64) -> f64 {
-    let eps = 1e-10;
-    let max_iter = 100;
-
-    let mut sum = 1.0 / a;
-    let mut term = sum;
-
-    for n in 1..max_iter {
-        term *= x / (a + n as f64);
-        sum += term;
-        if term.abs() < eps * sum.abs() {
-            break;
-        }
-    }
-
-    sum * (-x + a * x.ln() - ln_gamma(a)).exp()
-}
-
-fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
-    let eps = 1e-10;
-    let max_iter = 100;
-
-    let mut b = x + 1.0 - a;
-    let mut c = 1.0 / 1e-30;
-    let mut d = 1.0 / b;
-    let mut h = d;
-
-    for i in 1..max_iter {
-        let an = -(i as f64) * (i as f64 - a);
-        b += 2.0;
-        d = an * d + b;
-        if d.abs() < 1e-30 {
-            d = 1e-30;
-        }
-        c = b + an / c;
-        if c.abs() < 1e-30 {
-            c = 1e-30;
-        }
-        d = 1.0 / d;
-        let del = d * c;
-        h *= del;
-        if (del - 1.0).abs() < eps {
-            break;
-        }
-    }
-
-    (-x + a * x.ln() - ln_gamma(a)).exp() * h
+    ChiSquared::new(df as f64).map_or(f64::NAN, |chi2| chi2.sf(x))
 }
 
 pub fn likelihood_ratio_test(loglik_full: f64, loglik_reduced: f64, df: usize) -> TestResult {
@@ -163,6 +77,28 @@ pub fn wald_test(coefficients: &[f64], std_errors: &[f64]) -> TestResult {
     }
 }
 
+pub fn wald_test_matrix(coefficients: &[f64], variance_matrix: &[Vec<f64>]) -> TestResult {
+    let n = coefficients.len();
+
+    let inv_var = invert_matrix(variance_matrix);
+
+    let mut statistic = 0.0;
+    for i in 0..n {
+        for j in 0..n {
+            statistic += coefficients[i] * inv_var[i][j] * coefficients[j];
+        }
+    }
+
+    let p_value = chi2_sf(statistic, n);
+
+    TestResult {
+        statistic,
+        df: n,
+        p_value,
+        test_name: "WaldTest".to_string(),
+    }
+}
+
 pub fn score_test(score_vector: &[f64], information_matrix: &[Vec<f64>]) -> TestResult {
     let n = score_vector.len();
 
@@ -229,62 +165,75 @@ fn invert_matrix(matrix: &[Vec<f64>]) -> Vec<Vec<f64>> {
     aug.iter().map(|row| row[n..].to_vec()).collect()
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn lrt_test(loglik_full: f64, loglik_reduced: f64, df: usize) -> PyResult<TestResult> {
     Ok(likelihood_ratio_test(loglik_full, loglik_reduced, df))
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-pub fn wald_test_py(coefficients: Vec<f64>, std_errors: Vec<f64>) -> PyResult<TestResult> {
-    if coefficients.len() != std_errors.len() {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "coefficients and std_errors must have the same length",
-        ));
-    }
-    Ok(wald_test(&coefficients, &std_errors))
+pub fn wald_test_py(
+    py: Python<'_>,
+    coefficients: VectorArg<'_, f64>,
+    std_errors: VectorArg<'_, f64>,
+) -> PyResult<TestResult> {
+    let coefficients = coefficients.as_slice();
+    let std_errors = std_errors.as_slice();
+    parallel::detach(py, || {
+        if coefficients.len() != std_errors.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "coefficients and std_errors must have the same length",
+            ));
+        }
+        Ok(wald_test(&coefficients, &std_errors))
+    })
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn score_test_py(
-    score_vector: Vec<f64>,
-    information_matrix: Vec<Vec<f64>>,
+    py: Python<'_>,
+    score_vector: VectorArg<'_, f64>,
+    information_matrix: MatrixArg<'_, f64>,
 ) -> PyResult<TestResult> {
-    if score_vector.len() != information_matrix.len() {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "score_vector length must match information_matrix dimensions",
-        ));
-    }
-    Ok(score_test(&score_vector, &information_matrix))
+    let score_vector = score_vector.as_slice();
+    let information_matrix = information_matrix.into_rows();
+    parallel::detach(py, || {
+        if score_vector.len() != information_matrix.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "score_vector length must match information_matrix dimensions",
+            ));
+        }
+        Ok(score_test(&score_vector, &information_matrix))
+    })
 }
 
 #[derive(Debug, Clone)]
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all))]
 pub struct ProportionalityTest {
-    #[pyo3(get)]
     pub variable_names: Vec<String>,
-    #[pyo3(get)]
     pub chi2_values: Vec<f64>,
-    #[pyo3(get)]
     pub p_values: Vec<f64>,
-    #[pyo3(get)]
     pub global_chi2: f64,
-    #[pyo3(get)]
     pub global_df: usize,
-    #[pyo3(get)]
     pub global_p_value: f64,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl ProportionalityTest {
     #[new]
     fn new(
         variable_names: Vec<String>,
-        chi2_values: Vec<f64>,
-        p_values: Vec<f64>,
+        chi2_values: VectorArg<'_, f64>,
+        p_values: VectorArg<'_, f64>,
         global_chi2: f64,
         global_df: usize,
         global_p_value: f64,
     ) -> Self {
+        let chi2_values = chi2_values.into_vec();
+        let p_values = p_values.into_vec();
         Self {
             variable_names,
             chi2_values,
@@ -297,16 +246,11 @@ impl ProportionalityTest {
 }
 
 pub fn proportional_hazards_test(
-    schoenfeld_residuals: &[Vec<f64>],
+    schoenfeld_residuals: ArrayView2<f64>,
     event_times: &[f64],
     _weights: Option<&[f64]>,
 ) -> ProportionalityTest {
-    let n_events = schoenfeld_residuals.len();
-    let n_vars = if n_events > 0 {
-        schoenfeld_residuals[0].len()
-    } else {
-        0
-    };
+    let (n_events, n_vars) = schoenfeld_residuals.dim();
 
     if n_events == 0 || n_vars == 0 {
         return ProportionalityTest {
@@ -335,11 +279,7 @@ pub fn proportional_hazards_test(
     for var in 0..n_vars {
         let residuals: Vec<f64> = sorted_indices
             .iter()
-            .filter_map(|&i| {
-                schoenfeld_residuals
-                    .get(i)
-                    .and_then(|row| row.get(var).copied())
-            })
+            .map(|&i| schoenfeld_residuals[[i, var]])
             .collect();
 
         let mean_rank = (n_events as f64 + 1.0) / 2.0;
@@ -383,16 +323,23 @@ pub fn proportional_hazards_test(
     }
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn ph_test(
-    schoenfeld_residuals: Vec<Vec<f64>>,
-    event_times: Vec<f64>,
-    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+    schoenfeld_residuals: MatrixArg<'_, f64>,
+    event_times: VectorArg<'_, f64>,
+    weights: Option<VectorArg<'_, f64>>,
 ) -> PyResult<ProportionalityTest> {
-    let weights_ref = weights.as_deref();
-    Ok(proportional_hazards_test(
-        &schoenfeld_residuals,
-        &event_times,
-        weights_ref,
-    ))
+    let schoenfeld_residuals = schoenfeld_residuals.view();
+    let event_times = event_times.as_slice();
+    let weights = weights.as_ref().map(VectorArg::as_slice);
+    parallel::detach(py, || {
+        let weights_ref = weights.as_deref();
+        Ok(proportional_hazards_test(
+            schoenfeld_residuals,
+            &event_times,
+            weights_ref,
+        ))
+    })
 }
@@ -1,9 +1,6 @@
-#![allow(dead_code)]
-#![allow(clippy::absurd_extreme_comparisons)]
 use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
 
-#[allow(clippy::too_many_arguments)]
-fn update_used(
+pub(crate) fn update_used(
     used: &mut Array2<i32>,
     stratum: i32,
     start: usize,
@@ -30,6 +27,10 @@ pub struct ZphResult {
     pub used: Array2<i32>,
 }
 
+/// Score vector and information matrix for the test of `beta(t) = beta + theta * g(t)`
+/// in a right-censored Cox model, along with the Schoenfeld residuals in
+/// (stratum, time) order. `sort` orders the observations by stratum and then
+/// by time.
 #[allow(clippy::too_many_arguments)]
 pub fn zph1(
     gt: ArrayView1<f64>,
@@ -41,7 +42,6 @@ pub fn zph1(
     method: i32,
     sort: ArrayView1<usize>,
 ) -> ZphResult {
-    let nused = y.0.len();
     let nvar = covar.ncols();
     let nevent = y.1.iter().filter(|&&s| s != 0.0).count();
     let nstrat = strata.iter().max().map(|&s| s + 1).unwrap_or(0) as usize;
@@ -53,7 +53,6 @@ pub fn zph1(
 
     let mut current_stratum = -1;
     let mut k = 0;
-    let mut _ndead = 0;
     for (i, &idx) in sort.iter().enumerate() {
         let stratum = strata[idx];
         if stratum != current_stratum {
@@ -62,9 +61,7 @@ pub fn zph1(
             }
             current_stratum = stratum;
             k = i;
-            _ndead = 0;
         }
-        _ndead += y.1[idx] as usize;
     }
     if current_stratum != -1 {
         update_used(&mut used, current_stratum, k, sort.len(), &covar, &sort);
@@ -72,12 +69,12 @@ pub fn zph1(
 
     let mut centered_covar = covar.to_owned();
     for mut col in centered_covar.columns_mut() {
-        let mean = col.mean().unwrap();
+        let mean = col.mean().unwrap_or(0.0);
         col -= mean;
     }
 
     let mut cstrat = -1;
-    let mut ip = nused - 1;
+    let mut ip = sort.len();
     let mut denom = 0.0;
     let mut a = Array1::zeros(nvar);
     let mut cmat = Array2::zeros((nvar, nvar));
@@ -85,9 +82,8 @@ pub fn zph1(
     let mut cmat2 = Array2::zeros((nvar, nvar));
     let mut nevent_counter = nevent;
 
-    #[allow(unused_comparisons)]
-    while ip >= 0 {
-        let person = sort[ip];
+    while ip > 0 {
+        let person = sort[ip - 1];
         if strata[person] != cstrat {
             cstrat = strata[person];
             denom = 0.0;
@@ -99,12 +95,10 @@ pub fn zph1(
         let timewt = gt[person];
         let mut ndead_current = 0;
         let mut deadwt = 0.0;
-        let mut _denom2 = 0.0;
+        let mut denom2 = 0.0;
 
-        let _ip_start = ip;
-        #[allow(unused_comparisons)]
-        while ip >= 0 {
-            let p = sort[ip];
+        while ip > 0 {
+            let p = sort[ip - 1];
             if y.0[p] != dtime || strata[p] != cstrat {
                 break;
             }
@@ -113,21 +107,21 @@ pub fn zph1(
                 denom += risk;
                 for i in 0..nvar {
                     a[i] += risk * centered_covar[(p, i)];
-                    for j in 0..=i {
+                    for j in 0..nvar {
                         cmat[(i, j)] += risk * centered_covar[(p, i)] * centered_covar[(p, j)];
                     }
                 }
             } else {
                 ndead_current += 1;
                 deadwt += weights[p];
-                _denom2 += risk;
+                denom2 += risk;
                 nevent_counter -= 1;
                 for i in 0..nvar {
                     schoen[(nevent_counter, i)] = centered_covar[(p, i)];
                     u[i] += weights[p] * centered_covar[(p, i)];
                     u[i + nvar] += timewt * weights[p] * centered_covar[(p, i)];
                     a2[i] += risk * centered_covar[(p, i)];
-                    for j in 0..=i {
+                    for j in 0..nvar {
                         cmat2[(i, j)] += risk * centered_covar[(p, i)] * centered_covar[(p, j)];
                     }
                 }
@@ -136,46 +130,33 @@ pub fn zph1(
         }
 
         if ndead_current > 0 {
-            match method {
-                0 => process_breslow(
-                    &mut u,
-                    &mut imat,
-                    &mut schoen,
-                    nevent_counter,
-                    ndead_current,
-                    deadwt,
-                    timewt,
-                    &a,
-                    &mut a2,
-                    &cmat,
-                    &mut cmat2,
-                    &mut denom,
-                    nvar,
-                ),
+            denom += denom2;
+            a += &a2;
+            cmat += &cmat2;
+            let xbar = match method {
+                0 => process_breslow(&mut u, &mut imat, deadwt, timewt, &a, &cmat, denom, nvar),
                 _ => process_efron(
                     &mut u,
                     &mut imat,
-                    &mut schoen,
-                    nevent_counter,
                     ndead_current,
                     deadwt,
                     timewt,
                     &a,
-                    &mut a2,
+                    &a2,
                     &cmat,
-                    &mut cmat2,
-                    &mut denom,
+                    &cmat2,
+                    denom,
+                    denom2,
                     nvar,
                 ),
+            };
+            for row in nevent_counter..nevent_counter + ndead_current {
+                for i in 0..nvar {
+                    schoen[(row, i)] -= xbar[i];
+                }
             }
-        }
-    }
-
-    for i in 0..nvar {
-        for j in 0..i {
-            imat[(i, j)] = imat[(j, i)];
-            imat[(i, j + nvar)] = imat[(j, i + nvar)];
-            imat[(i + nvar, j + nvar)] = imat[(j + nvar, i + nvar)];
+            a2.fill(0.0);
+            cmat2.fill(0.0);
         }
     }
 
@@ -187,77 +168,71 @@ pub fn zph1(
     }
 }
 
+/// Adds the contribution of one death time to the score and information,
+/// where `a`, `cmat` and `denom` are sums over the whole risk set including
+/// the deaths. Returns the covariate mean at that time.
 #[allow(clippy::too_many_arguments)]
-fn process_breslow(
+pub(crate) fn process_breslow(
     u: &mut Array1<f64>,
     imat: &mut Array2<f64>,
-    _schoen: &mut Array2<f64>,
-    _nevent_counter: usize,
-    _ndead_current: usize,
     deadwt: f64,
     timewt: f64,
     a: &Array1<f64>,
-    a2: &mut Array1<f64>,
     cmat: &Array2<f64>,
-    cmat2: &mut Array2<f64>,
-    denom: &mut f64,
+    denom: f64,
     nvar: usize,
-) {
-    let wt = deadwt / *denom;
-    for i in 0..nvar {
-        u[i] -= wt * a[i];
-        u[i + nvar] -= timewt * wt * a[i];
-        for j in 0..=i {
-            imat[(i, j)] += wt * cmat[(i, j)];
-            imat[(i, j + nvar)] += timewt * wt * cmat[(i, j)];
-            imat[(i + nvar, j + nvar)] += timewt * timewt * wt * cmat[(i, j)];
-        }
-    }
-    *denom += a2.iter().sum::<f64>();
+) -> Array1<f64> {
+    let xbar = a / denom;
     for i in 0..nvar {
-        a2[i] = 0.0;
-        for j in 0..=i {
-            cmat2[(i, j)] = 0.0;
+        u[i] -= deadwt * xbar[i];
+        u[i + nvar] -= timewt * deadwt * xbar[i];
+        for j in 0..nvar {
+            let v = cmat[(i, j)] / denom - xbar[i] * xbar[j];
+            imat[(i, j)] += deadwt * v;
+            imat[(i, j + nvar)] += timewt * deadwt * v;
+            imat[(i + nvar, j)] += timewt * deadwt * v;
+            imat[(i + nvar, j + nvar)] += timewt * timewt * deadwt * v;
         }
     }
+    xbar
 }
 
+/// Efron version of [`process_breslow`], where `a2`, `cmat2` and `denom2` are
+/// the sums over the tied deaths alone. Returns the covariate mean averaged
+/// over the tied deaths.
 #[allow(clippy::too_many_arguments)]
-fn process_efron(
+pub(crate) fn process_efron(
     u: &mut Array1<f64>,
     imat: &mut Array2<f64>,
-    _schoen: &mut Array2<f64>,
-    _nevent_counter: usize,
     ndead_current: usize,
     deadwt: f64,
     timewt: f64,
     a: &Array1<f64>,
-    a2: &mut Array1<f64>,
+    a2: &Array1<f64>,
     cmat: &Array2<f64>,
-    cmat2: &mut Array2<f64>,
-    denom: &mut f64,
+    cmat2: &Array2<f64>,
+    denom: f64,
+    denom2: f64,
     nvar: usize,
-) {
+) -> Array1<f64> {
+    let wt = deadwt / ndead_current as f64;
+    let mut mean = Array1::zeros(nvar);
     for k in 0..ndead_current {
         let temp = k as f64 / ndead_current as f64;
-        let d2 = *denom - temp * a2.iter().sum::<f64>();
-        let wt = deadwt / (ndead_current as f64 * d2);
+        let d2 = denom - temp * denom2;
+        let xbar = (a - &(a2 * temp)) / d2;
         for i in 0..nvar {
-            u[i] -= wt * (a[i] - temp * a2[i]);
-            u[i + nvar] -= timewt * wt * (a[i] - temp * a2[i]);
-            for j in 0..=i {
-                imat[(i, j)] += wt * (cmat[(i, j)] - temp * cmat2[(i, j)]);
-                imat[(i, j + nvar)] += timewt * wt * (cmat[(i, j)] - temp * cmat2[(i, j)]);
-                imat[(i + nvar, j + nvar)] +=
-                    timewt * timewt * wt * (cmat[(i, j)] - temp * cmat2[(i, j)]);
+            u[i] -= wt * xbar[i];
+            u[i + nvar] -= timewt * wt * xbar[i];
+            for j in 0..nvar {
+                let v = (cmat[(i, j)] - temp * cmat2[(i, j)]) / d2 - xbar[i] * xbar[j];
+                imat[(i, j)] += wt * v;
+                imat[(i, j + nvar)] += timewt * wt * v;
+                imat[(i + nvar, j)] += timewt * wt * v;
+                imat[(i + nvar, j + nvar)] += timewt * timewt * wt * v;
             }
         }
+        mean += &xbar;
     }
-    *denom += a2.iter().sum::<f64>();
-    for i in 0..nvar {
-        a2[i] = 0.0;
-        for j in 0..=i {
-            cmat2[(i, j)] = 0.0;
-        }
-    }
+    mean / ndead_current as f64
 }
@@ -0,0 +1,275 @@
+use crate::validation::zph1::{ZphResult, process_breslow, process_efron, update_used};
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+
+/// Counting-process version of [`zph1`](crate::validation::zph1::zph1) for
+/// `(start, stop]` data. `sort1` orders the observations by stratum and start
+/// time, `sort2` by stratum and stop time.
+#[allow(clippy::too_many_arguments)]
+pub fn zph2(
+    gt: ArrayView1<f64>,
+    y: (ArrayView1<f64>, ArrayView1<f64>, ArrayView1<f64>),
+    covar: ArrayView2<f64>,
+    eta: ArrayView1<f64>,
+    weights: ArrayView1<f64>,
+    strata: ArrayView1<i32>,
+    method: i32,
+    sort1: ArrayView1<usize>,
+    sort2: ArrayView1<usize>,
+) -> ZphResult {
+    let nused = y.0.len();
+    let nvar = covar.ncols();
+    let nevent = y.2.iter().filter(|&&s| s != 0.0).count();
+    let nstrat = strata.iter().max().map(|&s| s + 1).unwrap_or(0) as usize;
+
+    let mut u = Array1::zeros(2 * nvar);
+    let mut imat = Array2::zeros((2 * nvar, 2 * nvar));
+    let mut schoen = Array2::zeros((nevent, nvar));
+    let mut used = Array2::zeros((nstrat, nvar));
+
+    let mut current_stratum = -1;
+    let mut k = 0;
+    for (i, &idx) in sort2.iter().enumerate() {
+        let stratum = strata[idx];
+        if stratum != current_stratum {
+            if current_stratum != -1 {
+                update_used(&mut used, current_stratum, k, i, &covar, &sort2);
+            }
+            current_stratum = stratum;
+            k = i;
+        }
+    }
+    if current_stratum != -1 {
+        update_used(&mut used, current_stratum, k, sort2.len(), &covar, &sort2);
+    }
+
+    let mut centered_covar = covar.to_owned();
+    for mut col in centered_covar.columns_mut() {
+        let mean = col.mean().unwrap_or(0.0);
+        col -= mean;
+    }
+
+    let mut cstrat = -1;
+    let mut denom = 0.0;
+    let mut keep = vec![false; nused];
+
+    let mut a = Array1::zeros(nvar);
+    let mut cmat = Array2::zeros((nvar, nvar));
+    let mut a2 = Array1::zeros(nvar);
+    let mut cmat2 = Array2::zeros((nvar, nvar));
+
+    let mut person = 0;
+    let mut indx1 = 0;
+    let mut indx2 = 0;
+    let mut nevent_counter = 0;
+
+    while person < nused {
+        let Some((dtime, timewt, death_index)) = find_next_death(&y, &gt, &sort2, person) else {
+            break;
+        };
+        let stratum = strata[sort2[death_index]];
+        if stratum != cstrat {
+            cstrat = stratum;
+            denom = 0.0;
+            a.fill(0.0);
+            cmat.fill(0.0);
+        }
+
+        update_risk_set(
+            &y,
+            &strata,
+            &sort1,
+            &sort2,
+            &mut keep,
+            &mut indx1,
+            &mut indx2,
+            cstrat,
+            dtime,
+            &mut denom,
+            &mut a,
+            &mut cmat,
+            &centered_covar,
+            &eta,
+            &weights,
+        );
+
+        let first_row = nevent_counter;
+        let (deadwt, denom2, ndead_current, next) = process_events(
+            &mut u,
+            &mut schoen,
+            &mut a2,
+            &mut cmat2,
+            &mut nevent_counter,
+            death_index,
+            &sort2,
+            &y,
+            &strata,
+            &centered_covar.view(),
+            &weights,
+            &eta,
+            timewt,
+        );
+
+        let xbar = match method {
+            0 => process_breslow(&mut u, &mut imat, deadwt, timewt, &a, &cmat, denom, nvar),
+            _ => process_efron(
+                &mut u,
+                &mut imat,
+                ndead_current,
+                deadwt,
+                timewt,
+                &a,
+                &a2,
+                &cmat,
+                &cmat2,
+                denom,
+                denom2,
+                nvar,
+            ),
+        };
+        for row in first_row..nevent_counter {
+            for i in 0..nvar {
+                schoen[(row, i)] -= xbar[i];
+            }
+        }
+        a2.fill(0.0);
+        cmat2.fill(0.0);
+
+        person = next;
+    }
+
+    ZphResult {
+        u,
+        imat,
+        schoen,
+        used,
+    }
+}
+
+/// Position in `sort2` of the next death at or after `start`, with its time
+/// and time weight.
+fn find_next_death(
+    y: &(ArrayView1<f64>, ArrayView1<f64>, ArrayView1<f64>),
+    gt: &ArrayView1<f64>,
+    sort2: &ArrayView1<usize>,
+    start: usize,
+) -> Option<(f64, f64, usize)> {
+    for i in start..sort2.len() {
+        let idx = sort2[i];
+        if y.2[idx] > 0.0 {
+            return Some((y.1[idx], gt[idx], i));
+        }
+    }
+    None
+}
+
+/// Brings the sums up to the risk set `start < dtime <= stop` of stratum
+/// `cstrat`, adding subjects in `sort1` order and removing those whose
+/// interval ended before `dtime` in `sort2` order. Strata are visited in
+/// increasing order.
+#[allow(clippy::too_many_arguments)]
+fn update_risk_set(
+    y: &(ArrayView1<f64>, ArrayView1<f64>, ArrayView1<f64>),
+    strata: &ArrayView1<i32>,
+    sort1: &ArrayView1<usize>,
+    sort2: &ArrayView1<usize>,
+    keep: &mut [bool],
+    indx1: &mut usize,
+    indx2: &mut usize,
+    cstrat: i32,
+    dtime: f64,
+    denom: &mut f64,
+    a: &mut Array1<f64>,
+    cmat: &mut Array2<f64>,
+    centered_covar: &Array2<f64>,
+    eta: &ArrayView1<f64>,
+    weights: &ArrayView1<f64>,
+) {
+    let mut update = |idx: usize, sign: f64| {
+        let risk = sign * eta[idx].exp() * weights[idx];
+        *denom += risk;
+        for i in 0..centered_covar.ncols() {
+            a[i] += risk * centered_covar[(idx, i)];
+            for j in 0..centered_covar.ncols() {
+                cmat[(i, j)] += risk * centered_covar[(idx, i)] * centered_covar[(idx, j)];
+            }
+        }
+    };
+
+    while *indx1 < sort1.len() {
+        let idx = sort1[*indx1];
+        if strata[idx] > cstrat || (strata[idx] == cstrat && y.0[idx] >= dtime) {
+            break;
+        }
+        if strata[idx] == cstrat {
+            keep[idx] = true;
+            update(idx, 1.0);
+        }
+        *indx1 += 1;
+    }
+    while *indx2 < sort2.len() {
+        let idx = sort2[*indx2];
+        if strata[idx] > cstrat || (strata[idx] == cstrat && y.1[idx] >= dtime) {
+            break;
+        }
+        if keep[idx] {
+            keep[idx] = false;
+            if strata[idx] == cstrat {
+                update(idx, -1.0);
+            }
+        }
+        *indx2 += 1;
+    }
+}
+
+/// Adds the deaths at the time of `sort2[death_index]` to the score and the
+/// Schoenfeld residuals. Returns their total weight, total risk, count and the
+/// position in `sort2` after the last observation ending at that time.
+#[allow(clippy::too_many_arguments)]
+fn process_events(
+    u: &mut Array1<f64>,
+    schoen: &mut Array2<f64>,
+    a2: &mut Array1<f64>,
+    cmat2: &mut Array2<f64>,
+    nevent_counter: &mut usize,
+    death_index: usize,
+    sort2: &ArrayView1<usize>,
+    y: &(ArrayView1<f64>, ArrayView1<f64>, ArrayView1<f64>),
+    strata: &ArrayView1<i32>,
+    centered_covar: &ArrayView2<f64>,
+    weights: &ArrayView1<f64>,
+    eta: &ArrayView1<f64>,
+    timewt: f64,
+) -> (f64, f64, usize, usize) {
+    let mut deadwt = 0.0;
+    let mut denom2 = 0.0;
+    let mut ndead_current = 0;
+    let dtime = y.1[sort2[death_index]];
+    let stratum = strata[sort2[death_index]];
+    let nvar = centered_covar.ncols();
+
+    let mut i = death_index;
+    while i < sort2.len() {
+        let idx = sort2[i];
+        if y.1[idx] != dtime || strata[idx] != stratum {
+            break;
+        }
+        if y.2[idx] > 0.0 {
+            ndead_current += 1;
+            deadwt += weights[idx];
+            let risk = eta[idx].exp() * weights[idx];
+            denom2 += risk;
+            for j in 0..nvar {
+                schoen[(*nevent_counter, j)] = centered_covar[(idx, j)];
+                u[j] += weights[idx] * centered_covar[(idx, j)];
+                u[j + nvar] += timewt * weights[idx] * centered_covar[(idx, j)];
+                a2[j] += risk * centered_covar[(idx, j)];
+                for k in 0..nvar {
+                    cmat2[(j, k)] += risk * centered_covar[(idx, j)] * centered_covar[(idx, k)];
+                }
+            }
+            *nevent_counter += 1;
+        }
+        i += 1;
+    }
+    (deadwt, denom2, ndead_current, i)
+}
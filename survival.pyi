@@ -1,32 +1,102 @@
-from typing import Optional, List, Tuple, Dict, Any
+from typing import Optional, List, Tuple, Dict, Any, Callable, Union
 from typing_extensions import Protocol
+from numpy.typing import ArrayLike, NDArray
+import numpy as np
 
 class AaregOptions:
     def __init__(
         self,
         formula: str,
-        data: List[List[float]],
+        data: ArrayLike,
         variable_names: List[str],
-        weights: Optional[List[float]] = None,
-        subset: Optional[List[int]] = None,
+        weights: Optional[ArrayLike] = None,
+        subset: Optional[ArrayLike] = None,
         na_action: Optional[str] = None,
         qrtol: float = 1e-8,
         nmin: Optional[int] = None,
         dfbeta: bool = False,
         taper: float = 0.0,
         test: List[str] = ...,
-        cluster: Optional[List[int]] = None,
+        cluster: Optional[ArrayLike] = None,
         model: bool = False,
         x: bool = False,
         y: bool = False,
     ) -> None: ...
 
+class SurvType:
+    Right: "SurvType"
+    Counting: "SurvType"
+    Interval: "SurvType"
+    Interval2: "SurvType"
+
+class PenalizedTerm:
+    name: str
+    first: int
+    columns: int
+    df: float
+
+class ModelFrame:
+    time: List[float]
+    start: Optional[List[float]]
+    status: Optional[List[float]]
+    surv_type: Optional[SurvType]
+    covariates: List[List[float]]
+    column_names: List[str]
+    penalized: List[PenalizedTerm]
+    intercept: bool
+    strata: Optional[List[int]]
+    cluster: Optional[List[int]]
+    offset: Optional[List[float]]
+
+class ConfidenceInterval:
+    lower_bound: float
+    upper_bound: float
+
+class FitDetails:
+    iterations: int
+    converged: bool
+    final_objective_value: float
+    convergence_threshold: float
+    change_in_objective: Optional[float]
+    max_iterations: Optional[int]
+    optimization_method: Optional[str]
+    warnings: List[str]
+
+class Diagnostics:
+    dfbetas: Optional[List[float]]
+    cooks_distance: Optional[List[float]]
+    leverage: Optional[List[float]]
+    deviance_residuals: Optional[List[float]]
+    martingale_residuals: Optional[List[float]]
+    schoenfeld_residuals: Optional[List[float]]
+    score_residuals: Optional[List[float]]
+    additional_measures: Optional[List[float]]
+
+class AaregResult:
+    coefficients: List[float]
+    standard_errors: List[float]
+    confidence_intervals: List[ConfidenceInterval]
+    p_values: List[float]
+    goodness_of_fit: float
+    fit_details: Optional[FitDetails]
+    residuals: Optional[List[float]]
+    diagnostics: Optional[Diagnostics]
+    def save(self, path: str, format: str = "json") -> None: ...
+    @staticmethod
+    def load(path: str) -> "AaregResult": ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> "AaregResult": ...
+    def to_bytes(self) -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes) -> "AaregResult": ...
+
 class PSpline:
     coefficients: Optional[List[float]]
     fitted: bool
     def __init__(
         self,
-        x: List[float],
+        x: ArrayLike,
         df: int,
         theta: float,
         eps: float,
@@ -36,7 +106,7 @@ class PSpline:
         penalty: bool,
     ) -> None: ...
     def fit(self) -> List[float]: ...
-    def predict(self, new_x: List[float]) -> List[float]: ...
+    def predict(self, new_x: ArrayLike) -> List[float]: ...
     @property
     def df(self) -> int: ...
     @property
@@ -58,59 +128,163 @@ class Subject:
     is_case: bool
     is_subcohort: bool
     stratum: int
+    time: float
+    entry_time: Optional[float]
     def __init__(
         self,
         id: int,
-        covariates: List[float],
+        covariates: ArrayLike,
         is_case: bool,
         is_subcohort: bool,
         stratum: int,
+        time: float = 0.0,
+        entry_time: Optional[float] = None,
     ) -> None: ...
 
+class StratumBaseline:
+    stratum: int
+    time: List[float]
+    cumulative_hazard: List[float]
+
+class CoefficientRow:
+    name: str
+    coef: float
+    exp_coef: float
+    se: float
+    z: float
+    p_value: float
+    lower: float
+    upper: float
+
+class SurvivalPrediction:
+    stratum: int
+    time: List[float]
+    survival: List[float]
+    cumulative_hazard: List[float]
+    std_err: Optional[List[float]]
+    lower: Optional[List[float]]
+    upper: Optional[List[float]]
+
+TimeTransform = Union[
+    str, List[float], Callable[[List[float], List[float]], List[float]]
+]
+
 class CoxPHModel:
     baseline_hazard: List[float]
+    baseline_hazards: List[StratumBaseline]
     risk_scores: List[float]
     event_times: List[float]
+    entry_times: Optional[List[float]]
     censoring: List[int]
+    strata: List[int]
+    weights: List[float]
+    offsets: List[float]
+    ties: str
+    cluster: Optional[List[int]]
+    @property
+    def source_rows(self) -> Optional[List[int]]: ...
+    @property
+    def penalized_terms(self) -> List[PenalizedTerm]: ...
+    @property
+    def penalty_theta(self) -> List[float]: ...
+    naive_var: NDArray[np.float64]
+    robust_var: NDArray[np.float64]
+    covariate_names: List[str]
+    iterations: Optional[int]
+    converged: bool
     def __init__(self) -> None: ...
     @staticmethod
+    def from_formula(
+        formula: str,
+        data: ArrayLike,
+        variable_names: List[str],
+        weights: Optional[ArrayLike] = None,
+        ties: str = "efron",
+    ) -> "CoxPHModel": ...
+    @staticmethod
     def new_with_data(
-        covariates: List[List[float]],
-        event_times: List[float],
-        censoring: List[int],
+        covariates: ArrayLike,
+        event_times: ArrayLike,
+        censoring: ArrayLike,
+        strata: Optional[ArrayLike] = None,
+        weights: Optional[ArrayLike] = None,
+        offsets: Optional[ArrayLike] = None,
+        ties: str = "efron",
+        entry_times: Optional[ArrayLike] = None,
+        cluster: Optional[ArrayLike] = None,
+        tt: Optional[List[Tuple[int, TimeTransform]]] = None,
+    ) -> "CoxPHModel": ...
+    def with_time_transforms(
+        self, tt: List[Tuple[int, TimeTransform]]
     ) -> "CoxPHModel": ...
     def fit(self, n_iters: int = 20) -> None: ...
-    def predict(self, covariates: List[List[float]]) -> List[float]: ...
+    def predict(self, covariates: ArrayLike) -> List[float]: ...
     def get_coefficients(self) -> List[List[float]]: ...
-    def brier_score(self) -> float: ...
+    def brier_score(
+        self, times: Optional[ArrayLike] = None
+    ) -> SurvivalBrierResult: ...
     def survival_curve(
         self,
-        covariates: List[List[float]],
-        time_points: Optional[List[float]] = None,
-    ) -> Tuple[List[float], List[List[float]]]: ...
+        covariates: ArrayLike,
+        time_points: Optional[ArrayLike] = None,
+        strata: Optional[ArrayLike] = None,
+    ) -> Tuple[NDArray[np.float64], NDArray[np.float64]]: ...
+    def predict_survival(
+        self,
+        covariates: ArrayLike,
+        times: Optional[ArrayLike] = None,
+        strata: Optional[ArrayLike] = None,
+        se: bool = True,
+        conf_type: str = "log",
+        conf_level: float = 0.95,
+    ) -> List[SurvivalPrediction]: ...
     def add_subject(self, subject: Subject) -> None: ...
     def hazard_ratios(self) -> List[float]: ...
     def hazard_ratios_with_ci(
-        self, confidence_level: float = 0.95
+        self, confidence_level: float = 0.95, robust: bool = False
     ) -> Tuple[List[float], List[float], List[float]]: ...
+    def standard_errors(self, robust: bool = False) -> List[float]: ...
+    def wald_test(self, robust: bool = False) -> TestResult: ...
+    def score_residuals(self) -> NDArray[np.float64]: ...
     def log_likelihood(self) -> float: ...
+    def null_log_likelihood(self) -> Optional[float]: ...
+    def likelihood_ratio_test(self) -> TestResult: ...
+    def score_test(self) -> TestResult: ...
+    def coefficient_table(
+        self, confidence_level: float = 0.95, robust: bool = False
+    ) -> List[CoefficientRow]: ...
     def aic(self) -> float: ...
     def bic(self) -> float: ...
     def cumulative_hazard(
-        self, covariates: List[List[float]]
-    ) -> Tuple[List[float], List[List[float]]]: ...
+        self, covariates: ArrayLike, strata: Optional[ArrayLike] = None
+    ) -> Tuple[NDArray[np.float64], NDArray[np.float64]]: ...
     def predicted_survival_time(
-        self, covariates: List[List[float]], percentile: float = 0.5
+        self,
+        covariates: ArrayLike,
+        percentile: float = 0.5,
+        strata: Optional[ArrayLike] = None,
     ) -> List[Optional[float]]: ...
     def restricted_mean_survival_time(
-        self, covariates: List[List[float]], tau: float
+        self,
+        covariates: ArrayLike,
+        tau: float,
+        strata: Optional[ArrayLike] = None,
     ) -> List[float]: ...
     def martingale_residuals(self) -> List[float]: ...
     def deviance_residuals(self) -> List[float]: ...
-    def dfbeta(self) -> List[List[float]]: ...
+    def dfbeta(self) -> NDArray[np.float64]: ...
     def n_events(self) -> int: ...
     def n_observations(self) -> int: ...
     def summary(self) -> str: ...
+    def save(self, path: str, format: str = "json") -> None: ...
+    @staticmethod
+    def load(path: str) -> "CoxPHModel": ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> "CoxPHModel": ...
+    def to_bytes(self) -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes) -> "CoxPHModel": ...
 
 class SurvFitKMOutput:
     time: List[float]
@@ -122,6 +296,20 @@ class SurvFitKMOutput:
     conf_lower: List[float]
     conf_upper: List[float]
 
+class TurnbullResult:
+    time: List[float]
+    interval_lower: List[float]
+    probability: List[float]
+    n_risk: List[float]
+    n_event: List[float]
+    estimate: List[float]
+    std_err: List[float]
+    conf_lower: List[float]
+    conf_upper: List[float]
+    log_likelihood: float
+    iterations: int
+    converged: bool
+
 class FineGrayOutput:
     row: List[int]
     start: List[float]
@@ -136,9 +324,42 @@ class SurvivalFit:
     log_likelihood: float
     convergence_flag: int
     score_vector: List[float]
+    distribution: DistributionType
+    scale: List[float]
+    shape: Optional[float]
+    def aic(self) -> float: ...
+    def predict_quantile(
+        self,
+        covariates: ArrayLike,
+        p: float = 0.5,
+        strata: Optional[ArrayLike] = None,
+    ) -> List[float]: ...
+    def predict_survival(
+        self,
+        covariates: ArrayLike,
+        times: ArrayLike,
+        strata: Optional[ArrayLike] = None,
+    ) -> NDArray[np.float64]: ...
+    def save(self, path: str, format: str = "json") -> None: ...
+    @staticmethod
+    def load(path: str) -> "SurvivalFit": ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> "SurvivalFit": ...
+    def to_bytes(self) -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes) -> "SurvivalFit": ...
 
 class DistributionType:
-    pass
+    extreme_value: "DistributionType"
+    logistic: "DistributionType"
+    gaussian: "DistributionType"
+    weibull: "DistributionType"
+    lognormal: "DistributionType"
+    exponential: "DistributionType"
+    log_logistic: "DistributionType"
+    generalized_gamma: "DistributionType"
+    gompertz: "DistributionType"
 
 class SurvDiffResult:
     observed: List[float]
@@ -155,8 +376,12 @@ class CchMethod:
     IIBorgan: "CchMethod"
 
 class CohortData:
+    cohort_size: Optional[int]
+    stratum_sizes: Optional[List[int]]
     @staticmethod
-    def new() -> "CohortData": ...
+    def new(
+        cohort_size: Optional[int] = None, stratum_sizes: Optional[ArrayLike] = None
+    ) -> "CohortData": ...
     def add_subject(self, subject: Subject) -> None: ...
     def get_subject(self, id: int) -> Subject: ...
     def fit(self, method: "CchMethod") -> CoxPHModel: ...
@@ -174,6 +399,61 @@ class SurvFitAJ:
     n_enter: Optional[List[List[float]]]
     n_transition: List[List[float]]
 
+class MultiStateCurve:
+    group: Optional[int]
+    time: List[float]
+    n_risk: List[List[float]]
+    n_event: List[List[float]]
+    n_censor: List[List[float]]
+    pstate: List[List[float]]
+    std_err: List[List[float]]
+    cumhaz: List[List[float]]
+    std_chaz: List[List[float]]
+
+class MultiStateFit:
+    states: List[str]
+    transitions: List[Tuple[str, str]]
+    curves: List[MultiStateCurve]
+    def probability(
+        self, state: str, group: Optional[int] = None
+    ) -> Tuple[List[float], List[float]]: ...
+    def hazard(
+        self, from_state: str, to_state: str, group: Optional[int] = None
+    ) -> Tuple[List[float], List[float]]: ...
+
+class FrailtyFit:
+    distribution: str
+    coefficients: List[float]
+    std_errors: List[float]
+    theta: float
+    groups: List[int]
+    frailties: List[float]
+    frailty_std_errors: List[float]
+    log_likelihood: float
+    penalized_log_likelihood: float
+    integrated_log_likelihood: Optional[float]
+    df: float
+    aic: float
+    iterations: int
+    converged: bool
+
+class MultiStateCoxModel:
+    states: List[str]
+    transitions: List[Tuple[str, str]]
+    coefficient_names: List[str]
+    coefficients: List[float]
+    std_errors: List[float]
+    robust_std_errors: List[float]
+    log_likelihood: float
+    transition_coefficients: List[List[float]]
+    baselines: List[StratumBaseline]
+    n_events: List[int]
+    iterations: int
+    converged: bool
+    def predict(
+        self, covariates: ArrayLike, initial_state: str
+    ) -> Tuple[List[float], List[List[float]]]: ...
+
 class SplitResult:
     row: List[int]
     interval: List[int]
@@ -183,11 +463,15 @@ class SplitResult:
 
 class ClogitDataSet:
     def __init__(self) -> None: ...
+    @staticmethod
+    def from_formula(
+        formula: str, data: ArrayLike, variable_names: List[str]
+    ) -> "ClogitDataSet": ...
     def add_observation(
         self,
         case_control_status: int,
         stratum: int,
-        covariates: List[float],
+        covariates: ArrayLike,
     ) -> None: ...
     def get_num_observations(self) -> int: ...
     def get_num_covariates(self) -> int: ...
@@ -198,22 +482,71 @@ class ConditionalLogisticRegression:
     tol: float
     iterations: int
     converged: bool
+    method: str
+    variance: List[List[float]]
+    log_likelihood: float
+    null_log_likelihood: float
     def __init__(
         self,
         data: ClogitDataSet,
         max_iter: int = 100,
         tol: float = 1e-6,
+        method: str = "exact",
     ) -> None: ...
     def fit(self) -> None: ...
-    def predict(self, covariates: List[float]) -> float: ...
+    def predict(self, covariates: ArrayLike) -> float: ...
     def odds_ratios(self) -> List[float]: ...
+    def std_errors(self) -> List[float]: ...
+    def wald_test(self) -> TestResult: ...
+    def likelihood_ratio_test(self) -> TestResult: ...
+    def score_test(self) -> TestResult: ...
+    def save(self, path: str, format: str = "json") -> None: ...
+    @staticmethod
+    def load(path: str) -> "ConditionalLogisticRegression": ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> "ConditionalLogisticRegression": ...
+    def to_bytes(self) -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes) -> "ConditionalLogisticRegression": ...
 
 class BootstrapResult:
     coefficients: List[float]
+    std_errors: List[float]
     ci_lower: List[float]
     ci_upper: List[float]
-    se: List[float]
-    n_bootstrap: int
+    bootstrap_samples: List[List[float]]
+    ci_method: str
+
+class CoxNetPath:
+    lambdas: List[float]
+    coefficients: List[List[float]]
+    df: List[int]
+    log_likelihood: List[float]
+    null_log_likelihood: float
+    alpha: float
+    def linear_predictors(
+        self, covariates: ArrayLike, index: int
+    ) -> List[float]: ...
+    def save(self, path: str, format: str = "json") -> None: ...
+    @staticmethod
+    def load(path: str) -> "CoxNetPath": ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> "CoxNetPath": ...
+    def to_bytes(self) -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes) -> "CoxNetPath": ...
+
+class CoxNetCVResult:
+    path: CoxNetPath
+    cv_mean: List[float]
+    cv_se: List[float]
+    lambda_min: float
+    lambda_1se: float
+    index_min: int
+    index_1se: int
+    fold_id: List[int]
 
 class CVResult:
     scores: List[float]
@@ -235,6 +568,19 @@ class ProportionalityTest:
     global_p_value: float
     global_df: int
 
+class CoxZphResult:
+    variable_names: List[str]
+    chi2_values: List[float]
+    df: List[int]
+    p_values: List[float]
+    global_chi2: float
+    global_df: int
+    global_p_value: float
+    transform: str
+    times: List[float]
+    transformed_times: List[float]
+    scaled_residuals: List[List[float]]
+
 class NelsonAalenResult:
     time: List[float]
     cumulative_hazard: List[float]
@@ -310,8 +656,26 @@ class RiskStratificationResult:
 class TdAUCResult:
     times: List[float]
     auc: List[float]
+    std_err: List[float]
+    conf_lower: List[float]
+    conf_upper: List[float]
     integrated_auc: float
 
+class ConcordanceComparison:
+    first: int
+    second: int
+    difference: float
+    std_err: float
+    z_statistic: float
+    p_value: float
+
+class ConcordanceIndexResult:
+    concordance: List[float]
+    std_err: List[float]
+    covariance: List[List[float]]
+    comparisons: List[ConcordanceComparison]
+    tau: Optional[float]
+
 class RMSTResult:
     rmst: float
     variance: float
@@ -397,68 +761,110 @@ class LifeTableResult:
     survival: List[float]
     se_survival: List[float]
 
-def aareg(options: AaregOptions) -> Dict[str, Any]: ...
+def aareg(options: AaregOptions) -> AaregResult: ...
 
 def survfitkm(
-    time: List[float],
-    status: List[float],
-    weights: Optional[List[float]] = None,
-    entry_times: Optional[List[float]] = None,
-    position: Optional[List[int]] = None,
+    time: ArrayLike,
+    status: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    entry_times: Optional[ArrayLike] = None,
+    position: Optional[ArrayLike] = None,
     reverse: Optional[bool] = None,
     computation_type: Optional[int] = None,
 ) -> SurvFitKMOutput: ...
 
+def turnbull(
+    lower: ArrayLike,
+    upper: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    n_bootstrap: int = 200,
+    confidence_level: float = 0.95,
+    seed: Optional[int] = None,
+    max_iter: int = 1000,
+    tol: float = 1e-9,
+) -> TurnbullResult: ...
+
 def survreg(
-    time: List[float],
-    status: List[float],
-    covariates: List[List[float]],
-    weights: Optional[List[float]] = None,
-    offsets: Optional[List[float]] = None,
-    initial_beta: Optional[List[float]] = None,
-    strata: Optional[List[int]] = None,
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    offsets: Optional[ArrayLike] = None,
+    initial_beta: Optional[ArrayLike] = None,
+    strata: Optional[ArrayLike] = None,
     distribution: Optional[str] = None,
     max_iter: Optional[int] = None,
     eps: Optional[float] = None,
     tol_chol: Optional[float] = None,
 ) -> SurvivalFit: ...
 
+def survreg_interval(
+    time: ArrayLike,
+    time2: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    offsets: Optional[ArrayLike] = None,
+    initial_beta: Optional[ArrayLike] = None,
+    strata: Optional[ArrayLike] = None,
+    distribution: Optional[str] = None,
+    max_iter: Optional[int] = None,
+    eps: Optional[float] = None,
+    tol_chol: Optional[float] = None,
+) -> SurvivalFit: ...
+
+def survreg_formula(
+    formula: str,
+    data: ArrayLike,
+    variable_names: List[str],
+    weights: Optional[ArrayLike] = None,
+    distribution: Optional[str] = None,
+    max_iter: Optional[int] = None,
+    eps: Optional[float] = None,
+    tol_chol: Optional[float] = None,
+) -> SurvivalFit: ...
+
+def model_frame(
+    formula: str, data: ArrayLike, variable_names: List[str]
+) -> ModelFrame: ...
+
 def survdiff2(
-    time: List[float],
-    status: List[int],
-    group: List[int],
-    strata: Optional[List[int]] = None,
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
+    strata: Optional[ArrayLike] = None,
     rho: Optional[float] = None,
 ) -> SurvDiffResult: ...
 
 def coxmart(
-    time: List[float],
-    status: List[int],
-    score: List[float],
-    weights: Optional[List[float]] = None,
-    strata: Optional[List[int]] = None,
+    time: ArrayLike,
+    status: ArrayLike,
+    score: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    strata: Optional[ArrayLike] = None,
     method: Optional[int] = None,
 ) -> List[float]: ...
 
 def finegray(
-    tstart: List[float],
-    tstop: List[float],
-    ctime: List[float],
-    cprob: List[float],
+    tstart: ArrayLike,
+    tstop: ArrayLike,
+    ctime: ArrayLike,
+    cprob: ArrayLike,
     extend: List[bool],
     keep: List[bool],
 ) -> FineGrayOutput: ...
 
 def perform_cox_regression_frailty(
-    time: List[float],
-    event: List[int],
-    covariates: List[List[float]],
-    offset: Optional[List[float]] = None,
-    weights: Optional[List[float]] = None,
-    strata: Optional[List[int]] = None,
-    frail: Optional[List[int]] = None,
+    time: ArrayLike,
+    event: ArrayLike,
+    covariates: ArrayLike,
+    offset: Optional[ArrayLike] = None,
+    weights: Optional[ArrayLike] = None,
+    strata: Optional[ArrayLike] = None,
+    frail: Optional[ArrayLike] = None,
     max_iter: Optional[int] = None,
     eps: Optional[float] = None,
+    start: Optional[ArrayLike] = None,
 ) -> Dict[str, Any]: ...
 
 def perform_pyears_calculation(
@@ -467,96 +873,103 @@ def perform_pyears_calculation(
     doevent: bool,
     doexpect: bool,
     edim: int,
-    efac: List[int],
-    edims: List[int],
-    ecut: List[float],
-    expect: List[float],
-    y: List[float],
-    wt: List[float],
-    data: List[float],
+    efac: ArrayLike,
+    edims: ArrayLike,
+    ecut: ArrayLike,
+    expect: ArrayLike,
+    y: ArrayLike,
+    wt: ArrayLike,
+    data: ArrayLike,
     odim: int,
-    ofac: List[int],
-    odims: List[int],
-    ocut: List[float],
+    ofac: ArrayLike,
+    odims: ArrayLike,
+    ocut: ArrayLike,
 ) -> Dict[str, Any]: ...
 
+def concordance_index(
+    time: ArrayLike,
+    status: ArrayLike,
+    risk_scores: ArrayLike,
+    tau: Optional[float] = None,
+) -> ConcordanceIndexResult: ...
+
 def perform_concordance1_calculation(
-    y: List[float],
-    wt: List[float],
-    indx: List[int],
+    y: ArrayLike,
+    wt: ArrayLike,
+    indx: ArrayLike,
     ntree: int,
-    sortstop: List[int],
-    sortstart: List[int],
+    sortstop: ArrayLike,
+    sortstart: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def perform_concordance3_calculation(
-    y: List[float],
-    wt: List[float],
-    indx: List[int],
+    y: ArrayLike,
+    wt: ArrayLike,
+    indx: ArrayLike,
     ntree: int,
-    sortstop: List[int],
-    sortstart: List[int],
+    sortstop: ArrayLike,
+    sortstart: ArrayLike,
     nvar: int,
-    covar: List[float],
+    covar: ArrayLike,
     need_residuals: bool,
 ) -> Dict[str, Any]: ...
 
 def perform_concordance_calculation(
-    y: List[float],
-    wt: List[float],
-    indx: List[int],
+    y: ArrayLike,
+    wt: ArrayLike,
+    indx: ArrayLike,
     ntree: int,
-    sortstop: List[int],
-    sortstart: Optional[List[int]] = None,
+    sortstop: ArrayLike,
+    sortstart: Optional[ArrayLike] = None,
     nvar: Optional[int] = None,
-    covar: Optional[List[float]] = None,
+    covar: Optional[ArrayLike] = None,
     need_residuals: bool = False,
 ) -> Dict[str, Any]: ...
 
 def perform_score_calculation(
-    time_data: List[float],
-    covariates: List[float],
-    strata: List[int],
-    score: List[float],
-    weights: List[float],
+    time_data: ArrayLike,
+    covariates: ArrayLike,
+    strata: ArrayLike,
+    score: ArrayLike,
+    weights: ArrayLike,
     method: int,
 ) -> Dict[str, Any]: ...
 
 def perform_agscore3_calculation(
-    time_data: List[float],
-    covariates: List[float],
-    strata: List[int],
-    score: List[float],
-    weights: List[float],
+    time_data: ArrayLike,
+    covariates: ArrayLike,
+    strata: ArrayLike,
+    score: ArrayLike,
+    weights: ArrayLike,
     method: int,
-    sort1: List[int],
+    sort1: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def perform_pystep_calculation(
     edim: int,
-    data: List[float],
-    efac: List[int],
-    edims: List[int],
-    ecut: List[List[float]],
+    data: ArrayLike,
+    efac: ArrayLike,
+    edims: ArrayLike,
+    ecut: ArrayLike,
     tmax: float,
 ) -> Dict[str, Any]: ...
 
 def perform_pystep_simple_calculation(
     odim: int,
-    data: List[float],
-    ofac: List[int],
-    odims: List[int],
-    ocut: List[List[float]],
+    data: ArrayLike,
+    ofac: ArrayLike,
+    odims: ArrayLike,
+    ocut: ArrayLike,
     timeleft: float,
 ) -> Dict[str, Any]: ...
 
 def collapse(
-    y: List[float],
-    x: List[int],
-    istate: List[int],
-    id: List[int],
-    wt: List[float],
-    order: List[int],
+    y: ArrayLike,
+    x: ArrayLike,
+    istate: ArrayLike,
+    id: ArrayLike,
+    wt: ArrayLike,
+    order: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def cox_callback(
@@ -576,30 +989,30 @@ def cox_callback(
 ) -> Dict[str, Any]: ...
 
 def coxcount1(
-    time1: List[float],
-    time2: List[float],
-    status: List[int],
-    strata: List[int],
-    sort1: List[int],
-    sort2: List[int],
+    time1: ArrayLike,
+    time2: ArrayLike,
+    status: ArrayLike,
+    strata: ArrayLike,
+    sort1: ArrayLike,
+    sort2: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def coxcount2(
-    time1: List[float],
-    time2: List[float],
-    status: List[int],
-    strata: List[int],
-    sort1: List[int],
-    sort2: List[int],
+    time1: ArrayLike,
+    time2: ArrayLike,
+    status: ArrayLike,
+    strata: ArrayLike,
+    sort1: ArrayLike,
+    sort2: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def norisk(
-    time1: List[float],
-    time2: List[float],
-    status: List[int],
-    sort1: List[int],
-    sort2: List[int],
-    strata: List[int],
+    time1: ArrayLike,
+    time2: ArrayLike,
+    status: ArrayLike,
+    sort1: ArrayLike,
+    sort2: ArrayLike,
+    strata: ArrayLike,
 ) -> List[int]: ...
 
 def cipoisson(k: int, time: float, p: float, method: str) -> Tuple[float, float]: ...
@@ -607,168 +1020,260 @@ def cipoisson_exact(k: int, time: float, p: float) -> Tuple[float, float]: ...
 def cipoisson_anscombe(k: int, time: float, p: float) -> Tuple[float, float]: ...
 
 def concordance(
-    y: List[float],
-    wt: List[float],
-    indx: List[int],
+    y: ArrayLike,
+    wt: ArrayLike,
+    indx: ArrayLike,
     ntree: int,
-    sortstop: List[int],
-    sortstart: List[int],
-    strata: List[int],
+    sortstop: ArrayLike,
+    sortstart: ArrayLike,
+    strata: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def agexact(
     maxiter: int,
     nused: int,
     nvar: int,
-    start: List[float],
-    stop: List[float],
-    event: List[int],
-    covar: List[float],
-    offset: List[float],
-    strata: List[int],
-    sort: List[int],
-    beta: List[float],
+    start: ArrayLike,
+    stop: ArrayLike,
+    event: ArrayLike,
+    covar: ArrayLike,
+    offset: ArrayLike,
+    strata: ArrayLike,
+    sort: ArrayLike,
+    beta: ArrayLike,
     eps: float,
     tol_chol: float,
 ) -> Dict[str, Any]: ...
 
 def agsurv4(
-    y: List[float],
-    wt: List[float],
-    surv: List[float],
-    varh: List[float],
-    nrisk: List[float],
-    nevent: List[float],
-    ncensor: List[float],
-    strata: List[int],
+    y: ArrayLike,
+    wt: ArrayLike,
+    surv: ArrayLike,
+    varh: ArrayLike,
+    nrisk: ArrayLike,
+    nevent: ArrayLike,
+    ncensor: ArrayLike,
+    strata: ArrayLike,
 ) -> Dict[str, Any]: ...
 
 def agsurv5(
-    y: List[float],
-    wt: List[float],
-    id: List[int],
-    cluster: List[int],
-    risk: List[float],
-    position: List[int],
-    strata: List[int],
+    y: ArrayLike,
+    wt: ArrayLike,
+    id: ArrayLike,
+    cluster: ArrayLike,
+    risk: ArrayLike,
+    position: ArrayLike,
+    strata: ArrayLike,
     se_type: int,
 ) -> Dict[str, Any]: ...
 
 def agmart(
-    time: List[float],
-    status: List[int],
-    score: List[float],
-    weights: List[float],
-    strata: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    score: ArrayLike,
+    weights: ArrayLike,
+    strata: ArrayLike,
     method: int,
 ) -> List[float]: ...
 
 def brier(
-    predictions: List[float],
-    outcomes: List[int],
-    weights: Optional[List[float]] = None,
+    predictions: ArrayLike,
+    outcomes: ArrayLike,
+    weights: Optional[ArrayLike] = None,
 ) -> float: ...
 
 def integrated_brier(
-    predictions: List[List[float]],
-    outcomes: List[int],
-    times: List[float],
-    weights: Optional[List[float]] = None,
+    predictions: ArrayLike,
+    outcomes: ArrayLike,
+    times: ArrayLike,
+    weights: Optional[ArrayLike] = None,
 ) -> float: ...
 
+class SurvivalBrierResult:
+    times: List[float]
+    brier: List[float]
+    reference_brier: List[float]
+    ipa: List[float]
+    integrated_brier: float
+    reference_integrated_brier: float
+    integrated_ipa: float
+
+def survival_brier(
+    time: ArrayLike,
+    status: ArrayLike,
+    survival_predictions: ArrayLike,
+    eval_times: ArrayLike,
+) -> SurvivalBrierResult: ...
+
 def tmerge(
-    id: List[int],
-    time1: List[float],
-    newx: List[float],
-    nid: List[int],
-    ntime: List[float],
-    x: List[float],
+    id: ArrayLike,
+    time1: ArrayLike,
+    newx: ArrayLike,
+    nid: ArrayLike,
+    ntime: ArrayLike,
+    x: ArrayLike,
 ) -> List[float]: ...
 
 def tmerge2(
-    id: List[int],
-    time1: List[float],
-    nid: List[int],
-    ntime: List[float],
+    id: ArrayLike,
+    time1: ArrayLike,
+    nid: ArrayLike,
+    ntime: ArrayLike,
 ) -> List[int]: ...
 
 def tmerge3(
-    id: List[int],
+    id: ArrayLike,
     miss: List[bool],
 ) -> List[int]: ...
 
 def survsplit(
-    tstart: List[float],
-    tstop: List[float],
-    cut: List[float],
+    tstart: ArrayLike,
+    tstop: ArrayLike,
+    cut: ArrayLike,
 ) -> SplitResult: ...
 
 def schoenfeld_residuals(
-    y: List[float],
-    score: List[float],
-    strata: List[int],
-    covar: List[float],
+    y: ArrayLike,
+    score: ArrayLike,
+    strata: ArrayLike,
+    covar: ArrayLike,
     nvar: int,
     method: int = 0,
 ) -> List[float]: ...
 
 def cox_score_residuals(
-    y: List[float],
-    strata: List[int],
-    covar: List[float],
-    score: List[float],
-    weights: List[float],
+    y: ArrayLike,
+    strata: ArrayLike,
+    covar: ArrayLike,
+    score: ArrayLike,
+    weights: ArrayLike,
     nvar: int,
     method: int = 0,
 ) -> List[float]: ...
 
 def survfitaj(
-    y: List[float],
-    sort1: List[int],
-    sort2: List[int],
-    utime: List[float],
-    cstate: List[int],
-    wt: List[float],
-    grp: List[int],
+    y: ArrayLike,
+    sort1: ArrayLike,
+    sort2: ArrayLike,
+    utime: ArrayLike,
+    cstate: ArrayLike,
+    wt: ArrayLike,
+    grp: ArrayLike,
     ngrp: int,
-    p0: List[float],
-    i0: List[float],
+    p0: ArrayLike,
+    i0: ArrayLike,
     sefit: int,
     entry: bool,
-    position: List[int],
+    position: ArrayLike,
     hindx: List[List[int]],
     trmat: List[List[int]],
     t0: float,
 ) -> SurvFitAJ: ...
 
+def multistate_fit(
+    id: ArrayLike,
+    tstart: ArrayLike,
+    tstop: ArrayLike,
+    from_state: List[str],
+    to_state: List[str],
+    weights: Optional[ArrayLike] = None,
+    group: Optional[ArrayLike] = None,
+) -> MultiStateFit: ...
+
+def multistate_coxph(
+    id: ArrayLike,
+    tstart: ArrayLike,
+    tstop: ArrayLike,
+    from_state: List[str],
+    to_state: List[str],
+    covariates: ArrayLike,
+    shared: Optional[List[bool]] = None,
+    weights: Optional[ArrayLike] = None,
+    ties: str = "efron",
+    max_iter: int = 20,
+) -> MultiStateCoxModel: ...
+
+def coxph_frailty(
+    covariates: ArrayLike,
+    times: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
+    strata: Optional[ArrayLike] = None,
+    weights: Optional[ArrayLike] = None,
+    distribution: str = "gamma",
+    theta: Optional[float] = None,
+    ties: str = "efron",
+    entry_times: Optional[ArrayLike] = None,
+) -> FrailtyFit: ...
+
 def bootstrap_cox_ci(
-    time: List[float],
-    status: List[int],
-    covariates: List[List[float]],
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
+    weights: Optional[ArrayLike] = None,
     n_bootstrap: int = 1000,
     confidence_level: float = 0.95,
+    seed: Optional[int] = None,
+    strata: Optional[ArrayLike] = None,
+    cluster: Optional[ArrayLike] = None,
+    ci_method: str = "percentile",
 ) -> BootstrapResult: ...
 
 def bootstrap_survreg_ci(
-    time: List[float],
-    status: List[int],
-    covariates: List[List[float]],
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
     distribution: str = "weibull",
     n_bootstrap: int = 1000,
     confidence_level: float = 0.95,
+    seed: Optional[int] = None,
+    strata: Optional[ArrayLike] = None,
+    cluster: Optional[ArrayLike] = None,
+    ci_method: str = "percentile",
 ) -> BootstrapResult: ...
 
+def coxnet(
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    alpha: float = 1.0,
+    n_lambda: int = 100,
+    lambda_min_ratio: Optional[float] = None,
+    lambdas: Optional[ArrayLike] = None,
+    standardize: bool = True,
+    max_iter: int = 10000,
+    tol: float = 1e-7,
+) -> CoxNetPath: ...
+
+def cv_coxnet(
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
+    weights: Optional[ArrayLike] = None,
+    alpha: float = 1.0,
+    n_lambda: int = 100,
+    lambda_min_ratio: Optional[float] = None,
+    lambdas: Optional[ArrayLike] = None,
+    standardize: bool = True,
+    n_folds: int = 10,
+    shuffle: bool = True,
+    seed: Optional[int] = None,
+    max_iter: int = 10000,
+    tol: float = 1e-7,
+) -> CoxNetCVResult: ...
+
 def cv_cox_concordance(
-    time: List[float],
-    status: List[int],
-    covariates: List[List[float]],
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
     n_folds: int = 5,
 ) -> CVResult: ...
 
 def cv_survreg_loglik(
-    time: List[float],
-    status: List[int],
-    covariates: List[List[float]],
+    time: ArrayLike,
+    status: ArrayLike,
+    covariates: ArrayLike,
     distribution: str = "weibull",
     n_folds: int = 5,
 ) -> CVResult: ...
@@ -780,56 +1285,61 @@ def lrt_test(
 ) -> TestResult: ...
 
 def wald_test_py(
-    coefficients: List[float],
-    variance_matrix: List[List[float]],
+    coefficients: ArrayLike,
+    variance_matrix: ArrayLike,
 ) -> TestResult: ...
 
 def score_test_py(
-    score: List[float],
-    information_matrix: List[List[float]],
+    score: ArrayLike,
+    information_matrix: ArrayLike,
 ) -> TestResult: ...
 
 def ph_test(
-    time: List[float],
-    status: List[int],
-    schoenfeld_residuals: List[List[float]],
+    time: ArrayLike,
+    status: ArrayLike,
+    schoenfeld_residuals: ArrayLike,
     variable_names: List[str],
 ) -> ProportionalityTest: ...
 
+def cox_zph(
+    model: CoxPHModel,
+    transform: Union[str, Callable[[List[float]], List[float]], None] = None,
+) -> CoxZphResult: ...
+
 def nelson_aalen_estimator(
-    time: List[float],
-    status: List[int],
-    weights: Optional[List[float]] = None,
+    time: ArrayLike,
+    status: ArrayLike,
+    weights: Optional[ArrayLike] = None,
     confidence_level: Optional[float] = None,
 ) -> NelsonAalenResult: ...
 
 def stratified_kaplan_meier(
-    time: List[float],
-    status: List[int],
-    strata: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    strata: ArrayLike,
     confidence_level: Optional[float] = None,
 ) -> StratifiedKMResult: ...
 
 def logrank_test(
-    time: List[float],
-    status: List[int],
-    group: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
     weight_type: Optional[str] = None,
 ) -> LogRankResult: ...
 
 def fleming_harrington_test(
-    time: List[float],
-    status: List[int],
-    group: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
     p: float,
     q: float,
 ) -> LogRankResult: ...
 
 def logrank_trend(
-    time: List[float],
-    status: List[int],
-    group: List[int],
-    scores: Optional[List[float]] = None,
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
+    scores: Optional[ArrayLike] = None,
 ) -> TrendTestResult: ...
 
 def sample_size_survival(
@@ -868,97 +1378,101 @@ def expected_events(
 ) -> AccrualResult: ...
 
 def calibration(
-    predicted_risk: List[float],
-    observed_event: List[int],
+    predicted_risk: ArrayLike,
+    observed_event: ArrayLike,
     n_groups: Optional[int] = None,
 ) -> CalibrationResult: ...
 
 def predict_cox(
-    coef: List[float],
-    x: List[List[float]],
-    baseline_hazard: List[float],
-    baseline_times: List[float],
-    pred_times: List[float],
+    coef: ArrayLike,
+    x: ArrayLike,
+    baseline_hazard: ArrayLike,
+    baseline_times: ArrayLike,
+    pred_times: ArrayLike,
 ) -> PredictionResult: ...
 
 def risk_stratification(
-    risk_scores: List[float],
-    events: List[int],
+    risk_scores: ArrayLike,
+    events: ArrayLike,
     n_groups: Optional[int] = None,
 ) -> RiskStratificationResult: ...
 
 def td_auc(
-    time: List[float],
-    status: List[int],
-    risk_score: List[float],
-    eval_times: List[float],
+    time: ArrayLike,
+    status: ArrayLike,
+    risk_score: ArrayLike,
+    eval_times: ArrayLike,
+    confidence_level: Optional[float] = None,
 ) -> TdAUCResult: ...
 
 def rmst(
-    time: List[float],
-    status: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
     tau: float,
     confidence_level: Optional[float] = None,
 ) -> RMSTResult: ...
 
 def rmst_comparison(
-    time: List[float],
-    status: List[int],
-    group: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
     tau: float,
     confidence_level: Optional[float] = None,
 ) -> RMSTComparisonResult: ...
 
 def survival_quantile(
-    time: List[float],
-    status: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
     quantile: Optional[float] = None,
     confidence_level: Optional[float] = None,
 ) -> MedianSurvivalResult: ...
 
 def cumulative_incidence(
-    time: List[float],
-    status: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
 ) -> CumulativeIncidenceResult: ...
 
 def number_needed_to_treat(
-    time: List[float],
-    status: List[int],
-    group: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
     time_horizon: float,
     confidence_level: Optional[float] = None,
 ) -> NNTResult: ...
 
 def landmark_analysis(
-    time: List[float],
-    status: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
     landmark_time: float,
 ) -> LandmarkResult: ...
 
 def conditional_survival(
-    time: List[float],
-    status: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
     given_time: float,
     target_time: float,
     confidence_level: Optional[float] = None,
 ) -> ConditionalSurvivalResult: ...
 
 def hazard_ratio(
-    time: List[float],
-    status: List[int],
-    group: List[int],
+    time: ArrayLike,
+    status: ArrayLike,
+    group: ArrayLike,
     confidence_level: Optional[float] = None,
 ) -> HazardRatioResult: ...
 
 def survival_at_times(
-    time: List[float],
-    status: List[int],
-    eval_times: List[float],
+    time: ArrayLike,
+    status: ArrayLike,
+    eval_times: ArrayLike,
     confidence_level: Optional[float] = None,
 ) -> List[SurvivalAtTimeResult]: ...
 
 def life_table(
-    time: List[float],
-    status: List[int],
-    breaks: List[float],
+    time: ArrayLike,
+    status: ArrayLike,
+    breaks: ArrayLike,
 ) -> LifeTableResult: ...
+
+def set_num_threads(num_threads: Optional[int] = None) -> None: ...
+def get_num_threads() -> int: ...